@@ -0,0 +1,323 @@
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    io::{self, IsTerminal, Read, Write},
+    path::PathBuf,
+    time::Duration,
+};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use client::{reqwest_transport::ReqwestTransport, Client, ClientError};
+use printing::format_brainfart;
+use shared::{
+    BrainfartFilter, BrainfartFilterMode, BrainfartInformation, BrainfartOrder,
+    CreateBrainfartRequest, ProveOwnsBrainRequest, ReactionKind, Uuid,
+};
+use token_cache::TokenCache;
+
+mod printing;
+#[cfg(test)]
+mod tests;
+mod token_cache;
+
+#[derive(Parser, Debug)]
+#[clap(name = "brains", about = "Post and read brainfarts from the terminal")]
+struct Opt {
+    /// where the server is
+    #[clap(
+        short = 's',
+        long = "server",
+        env = "BRAINS_SERVER",
+        default_value = "http://localhost:8080",
+        global = true
+    )]
+    server: String,
+
+    /// keep the token of the login in this file [default: brains/token in your configuration
+    /// directory]
+    #[clap(long = "token-file", env = "BRAINS_TOKEN_FILE", global = true)]
+    token_file: Option<PathBuf>,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Log in and remember the token for the other commands
+    Login {
+        brainname: String,
+        /// asked for when left out
+        #[clap(long, env = "BRAINS_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
+    },
+    /// Forget the remembered token
+    Logout,
+    /// Post a brainfart
+    Post {
+        title: String,
+        /// read from standard input when left out
+        content: Option<String>,
+    },
+    /// List the feed
+    List {
+        /// only the brainfarts you have bookmarked
+        #[clap(long)]
+        bookmarks: bool,
+        #[clap(long, value_enum, default_value_t = Order::Newest)]
+        order: Order,
+        /// show at most this many brainfarts
+        #[clap(short = 'n', long, default_value_t = 20)]
+        limit: u32,
+    },
+    /// React to a brainfart
+    React {
+        brainfart_id: Uuid,
+        #[clap(value_enum, default_value_t = Reaction::Explosion)]
+        reaction: Reaction,
+    },
+    /// Show the latest brainfarts and keep showing new ones as they are posted
+    Tail {
+        /// how many of the latest brainfarts to start with
+        #[clap(short = 'n', long, default_value_t = 10)]
+        limit: usize,
+        /// seconds between looking for new brainfarts
+        #[clap(long, default_value_t = 5)]
+        interval: u64,
+    },
+    /// Print everything you have made as JSON, for an admin to import on another server
+    Export,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Order {
+    Newest,
+    Hot,
+}
+
+impl From<Order> for BrainfartOrder {
+    fn from(order: Order) -> BrainfartOrder {
+        match order {
+            Order::Newest => BrainfartOrder::Newest,
+            Order::Hot => BrainfartOrder::Hot,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Reaction {
+    Explosion,
+    Implosion,
+}
+
+impl From<Reaction> for ReactionKind {
+    fn from(reaction: Reaction) -> ReactionKind {
+        match reaction {
+            Reaction::Explosion => ReactionKind::Explosion,
+            Reaction::Implosion => ReactionKind::Implosion,
+        }
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let opt = Opt::parse();
+    let cache = match opt.token_file.or_else(TokenCache::default_path) {
+        Some(path) => TokenCache::create(path),
+        None => exit_with_error("There is nowhere to keep the token, pass --token-file"),
+    };
+    let transport =
+        ReqwestTransport::create(&opt.server).unwrap_or_else(|err| exit_with_error(err));
+    let mut client = Client::new(transport);
+    match run(opt.command, &mut client, &cache).await {
+        Ok(output) => print!("{}", output),
+        Err(err) => exit_with_error(err),
+    }
+}
+
+fn exit_with_error(err: impl Display) -> ! {
+    eprintln!("error: {}", err);
+    std::process::exit(1)
+}
+
+/// Runs a command and returns what should be printed.
+async fn run(
+    command: Command,
+    client: &mut Client<ReqwestTransport>,
+    cache: &TokenCache,
+) -> Result<String, String> {
+    match command {
+        Command::Login {
+            brainname,
+            password,
+        } => {
+            let password = match password {
+                Some(password) => password,
+                None => ask_for_password()?,
+            };
+            if !ProveOwnsBrainRequest::validate(&brainname, &password) {
+                return Err("The brainname and the password must not be empty".to_string());
+            }
+            let token = client
+                .log_in(&ProveOwnsBrainRequest::create(brainname.clone(), password))
+                .await
+                .map_err(|err| err.to_string())?;
+            cache
+                .save(&token)
+                .map_err(|err| format!("Could not remember the token: {}", err))?;
+            Ok(format!("Logged in as {}\n", brainname))
+        }
+        Command::Logout => match cache.remove() {
+            Ok(true) => Ok("Logged out\n".to_string()),
+            Ok(false) => Ok("You were not logged in\n".to_string()),
+            Err(err) => Err(format!("Could not forget the token: {}", err)),
+        },
+        Command::Post { title, content } => {
+            use_cached_token(client, cache)?;
+            let content = match content {
+                Some(content) => content,
+                None => read_standard_input()?,
+            };
+            if !CreateBrainfartRequest::validate(&title, &content) {
+                return Err("The title and the content must not be empty".to_string());
+            }
+            let brainfart = client
+                .create_brainfart(&CreateBrainfartRequest::create(title, content))
+                .await
+                .map_err(explain)?;
+            Ok(format!("{}\n", brainfart.id))
+        }
+        Command::List {
+            bookmarks,
+            order,
+            limit,
+        } => {
+            use_cached_token(client, cache)?;
+            let filter = BrainfartFilter {
+                mode: if bookmarks {
+                    BrainfartFilterMode::Bookmarks
+                } else {
+                    BrainfartFilterMode::All
+                },
+                order: order.into(),
+                limit: Some(limit),
+                ..Default::default()
+            };
+            let brainfarts = client.get_brainfarts(&filter).await.map_err(explain)?;
+            Ok(brainfarts.iter().map(format_brainfart).collect())
+        }
+        Command::React {
+            brainfart_id,
+            reaction,
+        } => {
+            use_cached_token(client, cache)?;
+            let brainfart = client
+                .react(&brainfart_id, reaction.into())
+                .await
+                .map_err(explain)?;
+            Ok(format_brainfart(&brainfart))
+        }
+        Command::Tail { limit, interval } => {
+            use_cached_token(client, cache)?;
+            tail(client, limit, Duration::from_secs(interval.max(1))).await?;
+            Ok(String::new())
+        }
+        Command::Export => {
+            use_cached_token(client, cache)?;
+            let export = client.export_brain().await.map_err(explain)?;
+            serde_json::to_string_pretty(&export)
+                .map(|json| format!("{}\n", json))
+                .map_err(|err| format!("Could not write the export: {}", err))
+        }
+    }
+}
+
+fn use_cached_token(
+    client: &mut Client<ReqwestTransport>,
+    cache: &TokenCache,
+) -> Result<(), String> {
+    match cache.load() {
+        Some(token) => {
+            client.set_token(Some(token));
+            Ok(())
+        }
+        None => Err("You are not logged in, run `brains login <brainname>` first".to_string()),
+    }
+}
+
+/// The token is what usually goes stale, so saying so beats the bare status.
+fn explain(err: ClientError) -> String {
+    match err.status() {
+        Some(401) => format!(
+            "{}. The login may have run out, run `brains login <brainname>` again",
+            err
+        ),
+        _ => err.to_string(),
+    }
+}
+
+/// The password is not echoed when typed in a terminal. Piped in, it is read as a line.
+fn ask_for_password() -> Result<String, String> {
+    eprint!("Password: ");
+    io::stderr().flush().ok();
+    let password = if io::stdin().is_terminal() {
+        rpassword::read_password()
+    } else {
+        let mut password = String::new();
+        io::stdin().read_line(&mut password).map(|_| password)
+    }
+    .map_err(|err| format!("Could not read the password: {}", err))?;
+    Ok(password.trim_end_matches(['\r', '\n']).to_string())
+}
+
+fn read_standard_input() -> Result<String, String> {
+    let mut content = String::new();
+    io::stdin()
+        .read_to_string(&mut content)
+        .map_err(|err| format!("Could not read the content: {}", err))?;
+    Ok(content.trim_end().to_string())
+}
+
+/// Leaves out the brainfarts that were shown before and puts the rest oldest first, the order
+/// they should be printed in.
+fn unseen_brainfarts(
+    seen: &mut HashSet<Uuid>,
+    feed: Vec<BrainfartInformation>,
+) -> Vec<BrainfartInformation> {
+    let mut unseen: Vec<BrainfartInformation> = feed
+        .into_iter()
+        .filter(|brainfart| seen.insert(brainfart.id.clone()))
+        .collect();
+    unseen.reverse();
+    unseen
+}
+
+/// Looks for new brainfarts every `interval` until it is stopped. A server that cannot be reached
+/// is tried again, anything else ends the tail.
+async fn tail(
+    client: &Client<ReqwestTransport>,
+    limit: usize,
+    interval: Duration,
+) -> Result<(), String> {
+    let mut seen = HashSet::new();
+    let mut first_look = true;
+    loop {
+        match client.get_brainfarts(&BrainfartFilter::default()).await {
+            Ok(feed) => {
+                let mut unseen = unseen_brainfarts(&mut seen, feed);
+                if first_look {
+                    unseen.drain(..unseen.len().saturating_sub(limit));
+                    first_look = false;
+                }
+                let mut stdout = io::stdout().lock();
+                for brainfart in unseen.iter() {
+                    write!(stdout, "{}", format_brainfart(brainfart)).ok();
+                }
+                stdout.flush().ok();
+            }
+            Err(err @ ClientError::Transport(_)) => eprintln!("warning: {}", err),
+            Err(err) => return Err(explain(err)),
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
@@ -0,0 +1,57 @@
+use shared::BrainfartInformation;
+
+/// A brainfart as a few lines of text, ending with a blank line.
+pub fn format_brainfart(brainfart: &BrainfartInformation) -> String {
+    // A plain rebrainfart shows its original, the same as in the browser.
+    let (shown, rebrainfarted_by) = match &brainfart.original {
+        Some(original) if brainfart.is_plain_rebrainfart() => {
+            (original.as_ref(), Some(&brainfart.mastermind_name))
+        }
+        _ => (brainfart, None),
+    };
+    let mut text = String::new();
+    if let Some(brainname) = rebrainfarted_by {
+        text.push_str(&format!("{} rebrainfarted\n", brainname));
+    }
+    text.push_str(&format!(
+        "{}  {}  {}\n",
+        shown.birthdate.format("%Y/%m/%d %H:%M"),
+        shown.mastermind_name,
+        shown.id
+    ));
+    text.push_str(&format!("  {}\n", shown.title));
+    for line in shown.content.lines() {
+        text.push_str(&format!("  {}\n", line));
+    }
+    if let Some(original) = shown.original.as_ref() {
+        text.push_str(&format!(
+            "  > {}: {}\n",
+            original.mastermind_name, original.title
+        ));
+    }
+    if let Some(poll) = &shown.poll {
+        for option in poll.options.iter() {
+            text.push_str(&format!(
+                "  [{}] {} ({})\n",
+                if option.voted_by_me { "x" } else { " " },
+                option.text,
+                option.votes
+            ));
+        }
+    }
+    for attachment in shown.attachments.iter() {
+        text.push_str(&format!("  attached: {}\n", attachment.filename));
+    }
+    text.push_str(&format!(
+        "  {} blown, {} imploded, {} rebrainfarts{}\n\n",
+        shown.blew_minds.len(),
+        shown.imploded_minds.len(),
+        shown.rebrainfarts,
+        if shown.bookmarked_by_me {
+            ", bookmarked"
+        } else {
+            ""
+        }
+    ));
+    text
+}
@@ -0,0 +1,102 @@
+use std::collections::HashSet;
+
+use shared::BrainfartInformation;
+
+use crate::{printing::format_brainfart, token_cache::TokenCache, unseen_brainfarts};
+
+fn brainfart(id: &str, mastermind_name: &str, title: &str) -> BrainfartInformation {
+    BrainfartInformation {
+        id: id.to_string(),
+        title: title.to_string(),
+        content: "content".to_string(),
+        mastermind_name: mastermind_name.to_string(),
+        ..BrainfartInformation::empty()
+    }
+}
+
+#[test]
+fn the_token_survives_until_logging_out() {
+    let path = std::env::temp_dir()
+        .join(format!("brains-cli-{}", std::process::id()))
+        .join("token");
+    let cache = TokenCache::create(path.clone());
+    assert_eq!(cache.load(), None);
+
+    cache.save("token").unwrap();
+    assert_eq!(
+        TokenCache::create(path.clone()).load().as_deref(),
+        Some("token")
+    );
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    assert!(cache.remove().unwrap());
+    assert_eq!(cache.load(), None);
+    assert!(!cache.remove().unwrap());
+    std::fs::remove_dir(path.parent().unwrap()).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn a_token_file_from_before_is_made_private() {
+    use std::os::unix::fs::PermissionsExt;
+    let directory = std::env::temp_dir().join(format!("brains-cli-old-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("token");
+    std::fs::write(&path, "old token").unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+    TokenCache::create(path.clone()).save("token").unwrap();
+    let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+    std::fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn tailing_shows_each_brainfart_once_oldest_first() {
+    let mut seen = HashSet::new();
+    let first = unseen_brainfarts(
+        &mut seen,
+        vec![brainfart("2", "anna", "b"), brainfart("1", "anna", "a")],
+    );
+    assert_eq!(
+        first
+            .iter()
+            .map(|item| item.id.as_str())
+            .collect::<Vec<_>>(),
+        ["1", "2"]
+    );
+
+    let second = unseen_brainfarts(
+        &mut seen,
+        vec![
+            brainfart("4", "anna", "d"),
+            brainfart("3", "anna", "c"),
+            brainfart("2", "anna", "b"),
+        ],
+    );
+    assert_eq!(
+        second
+            .iter()
+            .map(|item| item.id.as_str())
+            .collect::<Vec<_>>(),
+        ["3", "4"]
+    );
+}
+
+#[test]
+fn a_plain_rebrainfart_is_printed_as_its_original() {
+    let original = brainfart("1", "anna", "idea");
+    let rebrainfart = BrainfartInformation {
+        content: String::new(),
+        original: Some(Box::new(original)),
+        ..brainfart("2", "bertil", "")
+    };
+    let text = format_brainfart(&rebrainfart);
+    assert!(text.starts_with("bertil rebrainfarted\n"));
+    assert!(text.contains("anna  1\n  idea\n  content\n"));
+}
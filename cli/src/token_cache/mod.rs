@@ -0,0 +1,56 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+/// Where the token of the last login is kept between runs.
+pub struct TokenCache {
+    path: PathBuf,
+}
+
+impl TokenCache {
+    pub fn create(path: PathBuf) -> TokenCache {
+        TokenCache { path }
+    }
+
+    /// `brains/token` in the configuration directory of the user, e.g. `~/.config/brains/token`.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|directory| directory.join("brains").join("token"))
+    }
+
+    pub fn load(&self) -> Option<String> {
+        let token = fs::read_to_string(&self.path).ok()?;
+        let token = token.trim();
+        if token.is_empty() {
+            None
+        } else {
+            Some(token.to_string())
+        }
+    }
+
+    /// The file is only readable by its owner, since the token is as good as the password. A file
+    /// from before is made so too, before the token is written to it.
+    pub fn save(&self, token: &str) -> io::Result<()> {
+        if let Some(directory) = self.path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(&self.path)?;
+        #[cfg(unix)]
+        file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+        file.write_all(token.as_bytes())
+    }
+
+    /// Answers with whether there was a token to forget.
+    pub fn remove(&self) -> io::Result<bool> {
+        match fs::remove_file(&self.path) {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+}
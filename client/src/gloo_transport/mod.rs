@@ -0,0 +1,81 @@
+use async_trait::async_trait;
+use gloo_net::http::{Method as GlooMethod, Request};
+use js_sys::{Array, Uint8Array};
+use web_sys::{Blob, BlobPropertyBag, FormData};
+
+use crate::{
+    ClientError, HttpRequest, HttpResponse, Method, MultipartField, RequestBody, Transport,
+};
+
+/// Sends the requests with the browser's fetch.
+#[derive(Default, Clone)]
+pub struct GlooTransport {
+    base: String,
+}
+
+impl GlooTransport {
+    /// `base` goes in front of every path. Leave it empty to talk to the server the page came
+    /// from.
+    pub fn new(base: impl Into<String>) -> GlooTransport {
+        GlooTransport { base: base.into() }
+    }
+}
+
+fn form_data(fields: Vec<(String, MultipartField)>) -> Result<FormData, ClientError> {
+    let form = FormData::new().map_err(js_error)?;
+    for (name, field) in fields {
+        match field {
+            MultipartField::Text(text) => form.append_with_str(&name, &text),
+            MultipartField::File(upload) => {
+                let parts = Array::of1(&Uint8Array::from(upload.data.as_slice()));
+                let mut properties = BlobPropertyBag::new();
+                properties.type_(&upload.content_type);
+                let blob = Blob::new_with_u8_array_sequence_and_options(&parts, &properties)
+                    .map_err(js_error)?;
+                form.append_with_blob_and_filename(&name, &blob, &upload.filename)
+            }
+        }
+        .map_err(js_error)?;
+    }
+    Ok(form)
+}
+
+fn js_error(err: wasm_bindgen::JsValue) -> ClientError {
+    ClientError::Transport(format!("{:?}", err))
+}
+
+#[async_trait(?Send)]
+impl Transport for GlooTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, ClientError> {
+        let method = match request.method {
+            Method::Get => GlooMethod::GET,
+            Method::Post => GlooMethod::POST,
+            Method::Put => GlooMethod::PUT,
+            Method::Delete => GlooMethod::DELETE,
+        };
+        let mut builder = Request::new(&format!("{}{}", self.base, request.path)).method(method);
+        if let Some(token) = &request.token {
+            builder = builder.header("Authorization", &format!("Bearer {}", token));
+        }
+        builder = match request.body {
+            RequestBody::Empty => builder,
+            RequestBody::Json(json) => builder
+                .header("Content-Type", "application/json")
+                .body(json),
+            // The browser sets the content type, with the boundary it picked.
+            RequestBody::Multipart(fields) => builder.body(form_data(fields)?),
+        };
+        let response = builder
+            .send()
+            .await
+            .map_err(|err| ClientError::Transport(err.to_string()))?;
+        let body = response
+            .binary()
+            .await
+            .map_err(|err| ClientError::Transport(err.to_string()))?;
+        Ok(HttpResponse {
+            status: response.status(),
+            body,
+        })
+    }
+}
@@ -0,0 +1,537 @@
+//! A typed client of the Brains API, for the frontend and for tools.
+//!
+//! Every endpoint of `/api/v1` has a method on [`Client`] that takes and returns the types of
+//! `shared`. How the requests travel is up to the [`Transport`]: enable the `gloo` feature for
+//! the browser or the `native` feature for everything else, or bring your own.
+
+use std::fmt::Display;
+
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use shared::{
+    routes, BrainDeletion, BrainExport, BrainRelationInformation, BrainRelationRequest, Brainfart,
+    BrainfartDraft, BrainfartFilter, BrainfartInformation, CreateBrainfartRequest,
+    CreateWebhookRequest, DeleteBrainRequest, FeedToken, Leaderboard, LeaderboardWindow,
+    ProveOwnsBrainRequest, ReactionKind, ReactionRequest, RebrainfartRequest, RegisterBrainRequest,
+    ReportBrainfartRequest, ReportInformation, ReportedBrainfart, Role, SaveDraftRequest,
+    SetRoleRequest, Uuid, VoteRequest, WebhookDeliveryInformation, WebhookInformation,
+};
+
+#[cfg(feature = "gloo")]
+pub mod gloo_transport;
+#[cfg(feature = "native")]
+pub mod reqwest_transport;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Post,
+    Put,
+    Delete,
+}
+
+impl Display for Method {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Method::Get => write!(f, "GET"),
+            Method::Post => write!(f, "POST"),
+            Method::Put => write!(f, "PUT"),
+            Method::Delete => write!(f, "DELETE"),
+        }
+    }
+}
+
+/// A file to upload.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Upload {
+    pub filename: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MultipartField {
+    Text(String),
+    File(Upload),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RequestBody {
+    Empty,
+    Json(String),
+    /// The fields in the order they are sent, by name.
+    Multipart(Vec<(String, MultipartField)>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpRequest {
+    pub method: Method,
+    /// The path and query of the request, e.g. `/api/v1/brainfarts?order=hot`.
+    pub path: String,
+    /// Sent as a bearer token.
+    pub token: Option<String>,
+    pub body: RequestBody,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+/// Sends a request to the server and hands back its answer, whatever the status.
+///
+/// The futures are not `Send`, because those of the browser are not.
+#[async_trait(?Send)]
+pub trait Transport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, ClientError>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientError {
+    /// The request did not reach the server, or its answer did not make it back.
+    Transport(String),
+    /// The server turned the request down, with the reason it gave.
+    Api { status: u16, message: String },
+    /// The server answered with something else than the endpoint promises.
+    InvalidResponse(String),
+}
+
+impl ClientError {
+    /// The status the server answered with, if it answered.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ClientError::Api { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientError::Transport(reason) => write!(f, "Could not reach the server: {}", reason),
+            ClientError::Api { message, .. } => write!(f, "{}", message),
+            ClientError::InvalidResponse(reason) => {
+                write!(f, "The server gave an unexpected answer: {}", reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+pub struct Client<T: Transport> {
+    transport: T,
+    token: Option<String>,
+}
+
+impl<T: Transport> Client<T> {
+    pub fn new(transport: T) -> Client<T> {
+        Client {
+            transport,
+            token: None,
+        }
+    }
+
+    /// Sends the token with every request from now on.
+    pub fn with_token(mut self, token: impl Into<String>) -> Client<T> {
+        self.token = Some(token.into());
+        self
+    }
+
+    pub fn set_token(&mut self, token: Option<String>) {
+        self.token = token;
+    }
+
+    pub fn get_token(&self) -> Option<&str> {
+        self.token.as_deref()
+    }
+
+    async fn send_body(
+        &self,
+        method: Method,
+        path: String,
+        body: RequestBody,
+    ) -> Result<Vec<u8>, ClientError> {
+        let response = self
+            .transport
+            .send(HttpRequest {
+                method,
+                path,
+                token: self.token.clone(),
+                body,
+            })
+            .await?;
+        if (200..300).contains(&response.status) {
+            Ok(response.body)
+        } else {
+            Err(ClientError::Api {
+                status: response.status,
+                message: error_message(&response.body),
+            })
+        }
+    }
+
+    async fn send<R: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: String,
+        body: Option<&impl Serialize>,
+    ) -> Result<R, ClientError> {
+        let body = match body {
+            Some(body) => RequestBody::Json(
+                serde_json::to_string(body)
+                    .map_err(|err| ClientError::Transport(err.to_string()))?,
+            ),
+            None => RequestBody::Empty,
+        };
+        let answer = self.send_body(method, path, body).await?;
+        serde_json::from_slice(&answer).map_err(|err| ClientError::InvalidResponse(err.to_string()))
+    }
+
+    async fn get<R: DeserializeOwned>(&self, path: String) -> Result<R, ClientError> {
+        self.send(Method::Get, path, None::<&()>).await
+    }
+
+    // Brains
+
+    /// Answers with a token for the new brain.
+    pub async fn register(&self, request: &RegisterBrainRequest) -> Result<String, ClientError> {
+        self.send(Method::Post, routes::BRAINS.to_string(), Some(request))
+            .await
+    }
+
+    /// Answers with a token for the brain.
+    pub async fn log_in(&self, request: &ProveOwnsBrainRequest) -> Result<String, ClientError> {
+        self.send(Method::Post, routes::SESSIONS.to_string(), Some(request))
+            .await
+    }
+
+    // Brainfarts
+
+    pub async fn get_brainfarts(
+        &self,
+        filter: &BrainfartFilter,
+    ) -> Result<Vec<BrainfartInformation>, ClientError> {
+        let mut path = format!(
+            "{}?mode={}&order={}",
+            routes::BRAINFARTS,
+            query_value(&filter.mode),
+            query_value(&filter.order)
+        );
+        if let Some(brainname) = &filter.brainname {
+            path.push_str(&format!("&brainname={}", routes::encode(brainname)));
+        }
+        if let Some(tag) = &filter.tag {
+            path.push_str(&format!("&tag={}", routes::encode(tag)));
+        }
+        if let Some(limit) = filter.limit {
+            path.push_str(&format!("&limit={}", limit));
+        }
+        self.get(path).await
+    }
+
+    /// Opens the brainfart, which counts as viewing it.
+    pub async fn get_brainfart(
+        &self,
+        brainfart_id: &Uuid,
+    ) -> Result<BrainfartInformation, ClientError> {
+        self.get(routes::path(routes::BRAINFART, &[brainfart_id]))
+            .await
+    }
+
+    pub async fn create_brainfart(
+        &self,
+        request: &CreateBrainfartRequest,
+    ) -> Result<Brainfart, ClientError> {
+        self.send(Method::Post, routes::BRAINFARTS.to_string(), Some(request))
+            .await
+    }
+
+    /// Answers with the brainfart as it is after the reaction.
+    pub async fn react(
+        &self,
+        brainfart_id: &Uuid,
+        kind: ReactionKind,
+    ) -> Result<BrainfartInformation, ClientError> {
+        let path = routes::path(routes::BRAINFART_REACTIONS, &[brainfart_id]);
+        self.send(Method::Post, path, Some(&ReactionRequest { kind }))
+            .await
+    }
+
+    pub async fn add_bookmark(
+        &self,
+        brainfart_id: &Uuid,
+    ) -> Result<BrainfartInformation, ClientError> {
+        let path = routes::path(routes::BRAINFART_BOOKMARK, &[brainfart_id]);
+        self.send(Method::Put, path, None::<&()>).await
+    }
+
+    pub async fn remove_bookmark(
+        &self,
+        brainfart_id: &Uuid,
+    ) -> Result<BrainfartInformation, ClientError> {
+        let path = routes::path(routes::BRAINFART_BOOKMARK, &[brainfart_id]);
+        self.send(Method::Delete, path, None::<&()>).await
+    }
+
+    pub async fn report(
+        &self,
+        brainfart_id: &Uuid,
+        reason: &str,
+    ) -> Result<ReportInformation, ClientError> {
+        let path = routes::path(routes::BRAINFART_REPORTS, &[brainfart_id]);
+        let request = ReportBrainfartRequest {
+            brainfart_id: brainfart_id.clone(),
+            reason: reason.to_string(),
+        };
+        self.send(Method::Post, path, Some(&request)).await
+    }
+
+    /// Votes for the options at the given positions of the brainfart's poll.
+    pub async fn vote(
+        &self,
+        brainfart_id: &Uuid,
+        options: Vec<usize>,
+    ) -> Result<BrainfartInformation, ClientError> {
+        let path = routes::path(routes::BRAINFART_VOTES, &[brainfart_id]);
+        let request = VoteRequest {
+            brainfart_id: brainfart_id.clone(),
+            options,
+        };
+        self.send(Method::Post, path, Some(&request)).await
+    }
+
+    /// Without commentary this is a plain rebrainfart, with it a quote.
+    pub async fn rebrainfart(
+        &self,
+        brainfart_id: &Uuid,
+        commentary: &str,
+    ) -> Result<BrainfartInformation, ClientError> {
+        let path = routes::path(routes::BRAINFART_REBRAINFARTS, &[brainfart_id]);
+        let request = RebrainfartRequest {
+            brainfart_id: brainfart_id.clone(),
+            commentary: commentary.to_string(),
+        };
+        self.send(Method::Post, path, Some(&request)).await
+    }
+
+    // Relations
+
+    pub async fn get_relations(&self) -> Result<Vec<BrainRelationInformation>, ClientError> {
+        self.get(routes::RELATIONS.to_string()).await
+    }
+
+    /// Answers with all the relations, the new one included.
+    pub async fn add_relation(
+        &self,
+        request: &BrainRelationRequest,
+    ) -> Result<Vec<BrainRelationInformation>, ClientError> {
+        self.send(Method::Post, routes::RELATIONS.to_string(), Some(request))
+            .await
+    }
+
+    /// Answers with the relations that are left.
+    pub async fn remove_relation(
+        &self,
+        request: &BrainRelationRequest,
+    ) -> Result<Vec<BrainRelationInformation>, ClientError> {
+        let path = routes::path(
+            routes::RELATION,
+            &[&request.relation.to_string(), &request.brainname],
+        );
+        self.send(Method::Delete, path, None::<&()>).await
+    }
+
+    // Attachments
+
+    /// Answers with the brainfart and all its attachments.
+    pub async fn add_attachments(
+        &self,
+        brainfart_id: &Uuid,
+        uploads: Vec<Upload>,
+    ) -> Result<BrainfartInformation, ClientError> {
+        let mut fields = vec![(
+            "brainfart_id".to_string(),
+            MultipartField::Text(brainfart_id.clone()),
+        )];
+        fields.extend(
+            uploads
+                .into_iter()
+                .map(|upload| ("file".to_string(), MultipartField::File(upload))),
+        );
+        let answer = self
+            .send_body(
+                Method::Post,
+                routes::ATTACHMENTS.to_string(),
+                RequestBody::Multipart(fields),
+            )
+            .await?;
+        serde_json::from_slice(&answer).map_err(|err| ClientError::InvalidResponse(err.to_string()))
+    }
+
+    // Leaderboards
+
+    pub async fn get_leaderboard(
+        &self,
+        window: LeaderboardWindow,
+    ) -> Result<Leaderboard, ClientError> {
+        self.get(format!(
+            "{}?window={}",
+            routes::LEADERBOARD,
+            query_value(&window)
+        ))
+        .await
+    }
+
+    // Drafts
+
+    /// A brain without a draft gets an error with status 404.
+    pub async fn get_draft(&self) -> Result<BrainfartDraft, ClientError> {
+        self.get(routes::DRAFT.to_string()).await
+    }
+
+    pub async fn save_draft(
+        &self,
+        request: &SaveDraftRequest,
+    ) -> Result<BrainfartDraft, ClientError> {
+        self.send(Method::Put, routes::DRAFT.to_string(), Some(request))
+            .await
+    }
+
+    pub async fn delete_draft(&self) -> Result<(), ClientError> {
+        self.send(Method::Delete, routes::DRAFT.to_string(), None::<&()>)
+            .await
+    }
+
+    /// Everything the logged in brain has made, for `brains import` on another instance.
+    pub async fn export_brain(&self) -> Result<BrainExport, ClientError> {
+        self.get(routes::EXPORT.to_string()).await
+    }
+
+    // Deletion
+
+    /// A brain that has not asked to be deleted gets an error with status 404.
+    pub async fn get_brain_deletion(&self) -> Result<BrainDeletion, ClientError> {
+        self.get(routes::ACCOUNT_DELETION.to_string()).await
+    }
+
+    /// The brain is deleted once the grace period is over, unless the deletion is called off.
+    pub async fn request_brain_deletion(
+        &self,
+        request: &DeleteBrainRequest,
+    ) -> Result<BrainDeletion, ClientError> {
+        self.send(
+            Method::Put,
+            routes::ACCOUNT_DELETION.to_string(),
+            Some(request),
+        )
+        .await
+    }
+
+    pub async fn cancel_brain_deletion(&self) -> Result<(), ClientError> {
+        self.send(
+            Method::Delete,
+            routes::ACCOUNT_DELETION.to_string(),
+            None::<&()>,
+        )
+        .await
+    }
+
+    // Feeds
+
+    /// A token for reading the Atom and RSS feeds with `?token=`, e.g. in a feed reader.
+    pub async fn create_feed_token(&self) -> Result<FeedToken, ClientError> {
+        self.send(Method::Post, routes::FEED_TOKENS.to_string(), None::<&()>)
+            .await
+    }
+
+    /// Revokes every feed token of the logged in brain.
+    pub async fn revoke_feed_tokens(&self) -> Result<(), ClientError> {
+        self.send(Method::Delete, routes::FEED_TOKENS.to_string(), None::<&()>)
+            .await
+    }
+
+    // Webhooks
+
+    pub async fn get_webhooks(&self) -> Result<Vec<WebhookInformation>, ClientError> {
+        self.get(routes::WEBHOOKS.to_string()).await
+    }
+
+    pub async fn create_webhook(
+        &self,
+        request: &CreateWebhookRequest,
+    ) -> Result<WebhookInformation, ClientError> {
+        self.send(Method::Post, routes::WEBHOOKS.to_string(), Some(request))
+            .await
+    }
+
+    pub async fn delete_webhook(&self, webhook_id: &Uuid) -> Result<(), ClientError> {
+        let path = routes::path(routes::WEBHOOK, &[webhook_id]);
+        self.send(Method::Delete, path, None::<&()>).await
+    }
+
+    pub async fn get_webhook_deliveries(
+        &self,
+        webhook_id: &Uuid,
+    ) -> Result<Vec<WebhookDeliveryInformation>, ClientError> {
+        self.get(routes::path(routes::WEBHOOK_DELIVERIES, &[webhook_id]))
+            .await
+    }
+
+    // Moderation
+
+    pub async fn get_reports(&self) -> Result<Vec<ReportedBrainfart>, ClientError> {
+        self.get(routes::ADMIN_REPORTS.to_string()).await
+    }
+
+    pub async fn hide_brainfart(
+        &self,
+        brainfart_id: &Uuid,
+    ) -> Result<BrainfartInformation, ClientError> {
+        let path = routes::path(routes::ADMIN_HIDDEN_BRAINFART, &[brainfart_id]);
+        self.send(Method::Put, path, None::<&()>).await
+    }
+
+    pub async fn restore_brainfart(
+        &self,
+        brainfart_id: &Uuid,
+    ) -> Result<BrainfartInformation, ClientError> {
+        let path = routes::path(routes::ADMIN_HIDDEN_BRAINFART, &[brainfart_id]);
+        self.send(Method::Delete, path, None::<&()>).await
+    }
+
+    pub async fn suspend_brain(&self, brainname: &str) -> Result<String, ClientError> {
+        let path = routes::path(routes::ADMIN_BRAIN_SUSPENSION, &[brainname]);
+        self.send(Method::Put, path, None::<&()>).await
+    }
+
+    pub async fn unsuspend_brain(&self, brainname: &str) -> Result<String, ClientError> {
+        let path = routes::path(routes::ADMIN_BRAIN_SUSPENSION, &[brainname]);
+        self.send(Method::Delete, path, None::<&()>).await
+    }
+
+    pub async fn set_role(&self, brainname: &str, role: Role) -> Result<String, ClientError> {
+        let path = routes::path(routes::ADMIN_BRAIN_ROLE, &[brainname]);
+        let request = SetRoleRequest {
+            brainname: brainname.to_string(),
+            role,
+        };
+        self.send(Method::Put, path, Some(&request)).await
+    }
+}
+
+/// How a unit enum of `shared` is written in a query string.
+fn query_value(value: &impl Serialize) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// The server explains errors in plain text, or in a JSON string.
+fn error_message(body: &[u8]) -> String {
+    serde_json::from_slice::<String>(body)
+        .unwrap_or_else(|_| String::from_utf8_lossy(body).into_owned())
+}
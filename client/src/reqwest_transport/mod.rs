@@ -0,0 +1,98 @@
+use async_trait::async_trait;
+use reqwest::{
+    multipart::{Form, Part},
+    Client, Method as ReqwestMethod,
+};
+use url::Url;
+
+use crate::{
+    ClientError, HttpRequest, HttpResponse, Method, MultipartField, RequestBody, Transport,
+};
+
+/// Sends the requests with reqwest, over HTTP or HTTPS depending on the base url.
+pub struct ReqwestTransport {
+    base: String,
+    client: Client,
+}
+
+impl ReqwestTransport {
+    /// `base` is where the server is, e.g. `https://brains.example.com` or
+    /// `https://example.com/brains` when it is served under a path.
+    pub fn create(base: &str) -> Result<ReqwestTransport, ClientError> {
+        let url = Url::parse(base).map_err(transport_error)?;
+        if url.scheme() != "http" && url.scheme() != "https" {
+            return Err(ClientError::Transport(format!(
+                "{} is not an http or https url",
+                url
+            )));
+        }
+        if url.query().is_some() || url.fragment().is_some() {
+            return Err(ClientError::Transport(format!(
+                "{} has a query or a fragment",
+                url
+            )));
+        }
+        let client = Client::builder()
+            .user_agent("Brains client")
+            .build()
+            .map_err(transport_error)?;
+        Ok(ReqwestTransport {
+            base: url.as_str().trim_end_matches('/').to_string(),
+            client,
+        })
+    }
+}
+
+fn transport_error(err: impl ToString) -> ClientError {
+    ClientError::Transport(err.to_string())
+}
+
+fn form(fields: Vec<(String, MultipartField)>) -> Result<Form, ClientError> {
+    let mut form = Form::new();
+    for (name, field) in fields {
+        form = match field {
+            MultipartField::Text(text) => form.text(name, text),
+            MultipartField::File(upload) => form.part(
+                name,
+                Part::bytes(upload.data)
+                    .file_name(upload.filename)
+                    .mime_str(&upload.content_type)
+                    .map_err(transport_error)?,
+            ),
+        };
+    }
+    Ok(form)
+}
+
+#[async_trait(?Send)]
+impl Transport for ReqwestTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, ClientError> {
+        let method = match request.method {
+            Method::Get => ReqwestMethod::GET,
+            Method::Post => ReqwestMethod::POST,
+            Method::Put => ReqwestMethod::PUT,
+            Method::Delete => ReqwestMethod::DELETE,
+        };
+        // The path is added to the base rather than joined with it, which would drop the path
+        // the server is served under.
+        let url = format!("{}{}", self.base, request.path);
+        let mut builder = self.client.request(method, url);
+        if let Some(token) = &request.token {
+            builder = builder.bearer_auth(token);
+        }
+        builder = match request.body {
+            RequestBody::Empty => builder,
+            RequestBody::Json(json) => builder
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(json),
+            RequestBody::Multipart(fields) => builder.multipart(form(fields)?),
+        };
+        let response = builder.send().await.map_err(transport_error)?;
+        let status = response.status().as_u16();
+        let body = response.bytes().await.map_err(transport_error)?;
+        Ok(HttpResponse {
+            status,
+            body: body.to_vec(),
+        })
+    }
+}
@@ -1,4 +1,3 @@
-use gloo_net::http::Request;
 use shared::RegisterBrainRequest;
 use web_sys::HtmlInputElement;
 use web_sys::KeyboardEvent;
@@ -9,7 +8,7 @@ use yew::{html, Component, Html, InputEvent, MouseEvent, NodeRef, Properties};
 
 use yew_router::prelude::Link;
 
-use crate::Route;
+use crate::{HelperService, Route};
 
 #[derive(Properties, Clone, PartialEq)]
 pub struct RegisterFormProps {
@@ -114,22 +113,17 @@ impl Component for RegisterFormComponent {
 
                 let fields = self.get_input_fields_content();
                 let on_succesfull_registration = ctx.props().clone().on_succesfull_registration;
+                let client = HelperService::client();
                 ctx.link().send_future(async move {
-                    let resp = Request::post("/api/registerbrain")
-                        .json(&RegisterBrainRequest::create(fields.0, fields.1, fields.2))
-                        .unwrap()
-                        .send()
-                        .await
-                        .unwrap();
-
-                    let response_text = resp.text().await.unwrap().replace("\"", "");
-                    if !resp.ok() {
-                        Message::AfterApiResponse(AfterApiAction {
-                            error_text: response_text,
-                        })
-                    } else {
-                        on_succesfull_registration.emit(response_text);
-                        Message::DoNothing
+                    let request = RegisterBrainRequest::create(fields.0, fields.1, fields.2);
+                    match client.register(&request).await {
+                        Ok(token) => {
+                            on_succesfull_registration.emit(token);
+                            Message::DoNothing
+                        }
+                        Err(err) => Message::AfterApiResponse(AfterApiAction {
+                            error_text: err.to_string(),
+                        }),
                     }
                 });
                 true
@@ -1,4 +1,5 @@
-use shared::BrainfartInformation;
+use chrono::Local;
+use shared::{BrainfartInformation, PollInformation};
 use web_sys::MouseEvent;
 use yew::{classes, html, Callback, Component, Html, Properties};
 
@@ -9,23 +10,120 @@ pub struct BrainfartProps {
     pub brainfart: BrainfartInformation,
     pub on_explosion: Callback<MouseEvent>,
     pub on_implosion: Callback<MouseEvent>,
+    pub on_report: Callback<MouseEvent>,
+    pub on_bookmark: Callback<MouseEvent>,
+    pub on_vote: Callback<Vec<usize>>,
+    pub on_rebrainfart: Callback<MouseEvent>,
+    /// Who rebrainfarted the brainfart, when it is shown because of a plain rebrainfart.
+    #[prop_or_default]
+    pub rebrainfarted_by: Option<String>,
 }
 
-pub enum Message {}
+pub enum Message {
+    ToggleOption(usize),
+    Vote,
+}
 
-pub struct BrainfartComponent {}
+pub struct BrainfartComponent {
+    chosen_options: Vec<usize>,
+}
 
-impl BrainfartComponent {}
+impl BrainfartComponent {
+    /// Once the brain has voted, or the poll has closed, the results are shown instead of the
+    /// options.
+    fn view_poll(&self, ctx: &yew::Context<Self>, poll: &PollInformation) -> Html {
+        let is_closed = poll.is_closed(Local::now());
+        let status = match poll.closes_at {
+            _ if is_closed => "Closed".to_string(),
+            Some(closes_at) => format!("Closes {}", closes_at.format("%Y/%m/%d %H:%M")),
+            None => "Open".to_string(),
+        };
+        let footer = html! {
+            <p class={classes!("text-xs", "text-gray-500")}>
+                {format!("{} voted · {}", poll.voters, status)}
+            </p>
+        };
+        if is_closed || poll.has_voted() {
+            let most_votes = poll
+                .options
+                .iter()
+                .map(|option| option.votes)
+                .max()
+                .unwrap_or(0);
+            return html! {
+                <div class={classes!("space-y-1")}>
+                    { for poll.options.iter().map(|option| {
+                        let percent = if poll.voters > 0 { option.votes * 100 / poll.voters } else { 0 };
+                        html! {
+                            <div class={classes!("relative", "border", "border-gray-300", "rounded", "bg-white", "overflow-hidden")}>
+                                <div class={classes!("absolute", "inset-y-0", "left-0", if option.votes == most_votes && most_votes > 0 {"bg-blue-200"} else {"bg-gray-200"})} style={format!("width: {}%", percent)}></div>
+                                <div class={classes!("relative", "flex", "justify-between", "px-2", "py-1", "text-sm")}>
+                                    <span class={classes!(option.voted_by_me.then_some("font-bold"))}>{&option.text}</span>
+                                    <span>{format!("{}% ({})", percent, option.votes)}</span>
+                                </div>
+                            </div>
+                        }
+                    }) }
+                    {footer}
+                </div>
+            };
+        }
+        let input_type = if poll.multiple_choice {
+            "checkbox"
+        } else {
+            "radio"
+        };
+        let on_vote = ctx.link().callback(|_: MouseEvent| Message::Vote);
+        html! {
+            <div class={classes!("space-y-1")}>
+                { for poll.options.iter().enumerate().map(|(position, option)| {
+                    let on_toggle = ctx.link().callback(move |_: MouseEvent| Message::ToggleOption(position));
+                    html! {
+                        <label class={classes!("flex", "items-center", "px-2", "py-1", "border", "border-gray-300", "rounded", "bg-white", "text-sm")}>
+                            <input type={input_type} checked={self.chosen_options.contains(&position)} onclick={on_toggle} class={classes!("mr-2")}/>
+                            {&option.text}
+                        </label>
+                    }
+                }) }
+                <button disabled={self.chosen_options.is_empty()} onclick={on_vote} class={classes!("px-4", "py-1", "text-sm", "text-white", "rounded-lg", if self.chosen_options.is_empty() {"bg-gray-300"} else {"bg-blue-600"})}>{"Vote"}</button>
+                {footer}
+            </div>
+        }
+    }
+}
 
 impl Component for BrainfartComponent {
     type Message = Message;
     type Properties = BrainfartProps;
     fn create(_ctx: &yew::Context<Self>) -> Self {
-        Self {}
+        Self {
+            chosen_options: vec![],
+        }
     }
 
-    fn update(&mut self, _ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
-        match msg {}
+    fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Message::ToggleOption(position) => {
+                let multiple_choice = ctx
+                    .props()
+                    .brainfart
+                    .poll
+                    .as_ref()
+                    .is_some_and(|poll| poll.multiple_choice);
+                if self.chosen_options.contains(&position) {
+                    self.chosen_options.retain(|chosen| *chosen != position);
+                } else if multiple_choice {
+                    self.chosen_options.push(position);
+                } else {
+                    self.chosen_options = vec![position];
+                }
+                true
+            }
+            Message::Vote => {
+                ctx.props().on_vote.emit(self.chosen_options.clone());
+                false
+            }
+        }
     }
 
     fn view(&self, ctx: &yew::Context<Self>) -> Html {
@@ -33,6 +131,9 @@ impl Component for BrainfartComponent {
         let dag = brainfart.birthdate.format("%Y/%m/%d %H:%M").to_string();
         let on_explosion = &ctx.props().on_explosion;
         let on_implosion = &ctx.props().on_implosion;
+        let on_report = &ctx.props().on_report;
+        let on_bookmark = &ctx.props().on_bookmark;
+        let on_rebrainfart = &ctx.props().on_rebrainfart;
 
         let has_exploded = if let Some(_) = &brainfart
             .blew_minds
@@ -56,6 +157,11 @@ impl Component for BrainfartComponent {
 
         html! {
             <div key={brainfart.id.to_string()} class={classes!("block", "xl:w-2/5", "md:w-2/3", "sm:w-4/5", "xs:w-full", "border", "border-gray-300", "rounded-lg", "shadow-md", "bg-gray-50", "mt-2")}>
+                if let Some(rebrainfarted_by) = &ctx.props().rebrainfarted_by {
+                    <p class={classes!("px-3", "pt-2", "text-xs", "text-gray-500")}>
+                        {format!("{} rebrainfarted", rebrainfarted_by)}
+                    </p>
+                }
                 <div class={classes!("p-2", "border-b", "rounded-t", "dark:border-gray-600", "items-center", "justify-center")}>
                     <h1 class={classes!( "font-bold", "tracking-tight", "text-center")}>
                     {&brainfart.title}
@@ -65,6 +171,50 @@ impl Component for BrainfartComponent {
                     <p class={classes!("font-normal", "text-gray-600",  "dark:text-gray-400", "text-base", "leading-relaxed")}>
                     {&brainfart.content}
                     </p>
+                    if !brainfart.attachments.is_empty() {
+                        <div class={classes!("flex", "flex-wrap", "gap-2")}>
+                            { for brainfart.attachments.iter().map(|attachment| match attachment.thumbnail_url() {
+                                Some(thumbnail_url) => html! {
+                                    <a key={attachment.id.clone()} href={attachment.url()} target="_blank" title={attachment.filename.clone()}>
+                                        <img src={thumbnail_url} alt={attachment.filename.clone()} class={classes!("max-h-32", "rounded", "border", "border-gray-300")}/>
+                                    </a>
+                                },
+                                None => html! {
+                                    <a key={attachment.id.clone()} href={attachment.url()} target="_blank" class={classes!("text-sm", "text-blue-600", "hover:underline")}>
+                                        {format!("{} ({} kB)", attachment.filename, (attachment.size + 1023) / 1024)}
+                                    </a>
+                                },
+                            }) }
+                        </div>
+                    }
+                    { for brainfart.link_previews.iter().map(|preview| html! {
+                        <a key={preview.url.clone()} href={preview.url.clone()} target="_blank" rel="noopener noreferrer nofollow" class={classes!("flex", "border", "border-gray-300", "rounded-lg", "overflow-hidden", "bg-white", "hover:bg-gray-50")}>
+                            if let Some(image_url) = &preview.image_url {
+                                <img src={image_url.clone()} alt="" class={classes!("w-24", "h-24", "object-cover", "flex-none")}/>
+                            }
+                            <div class={classes!("p-2", "min-w-0")}>
+                                if let Some(site_name) = &preview.site_name {
+                                    <p class={classes!("text-xs", "text-gray-500", "truncate")}>{site_name}</p>
+                                }
+                                <p class={classes!("font-medium", "text-gray-800", "truncate")}>{&preview.title}</p>
+                                if let Some(description) = &preview.description {
+                                    <p class={classes!("text-sm", "text-gray-600", "line-clamp-2")}>{description}</p>
+                                }
+                            </div>
+                        </a>
+                    }) }
+                    if let Some(poll) = &brainfart.poll {
+                        {self.view_poll(ctx, poll)}
+                    }
+                    if let Some(original) = &brainfart.original {
+                        <div class={classes!("border", "border-gray-300", "rounded-lg", "bg-white", "p-2")}>
+                            <p class={classes!("text-xs", "text-gray-500")}>
+                                {format!("{} · {}", original.mastermind_name, original.birthdate.format("%Y/%m/%d %H:%M"))}
+                            </p>
+                            <p class={classes!("font-medium", "text-gray-800")}>{&original.title}</p>
+                            <p class={classes!("text-sm", "text-gray-600")}>{&original.content}</p>
+                        </div>
+                    }
                 </div>
                 <div class={classes!("flex","justify-between", "border-t", "border-gray-200", "rounded-b", "space-x-2")}>
                     <p class="inline-flex items-center px-3 py-2 text-sm font-medium text-center divide-x shadow-md">
@@ -108,6 +258,22 @@ impl Component for BrainfartComponent {
                             <path d="M12.075,10.812c1.358-0.853,2.242-2.507,2.242-4.037c0-2.181-1.795-4.618-4.198-4.618S5.921,4.594,5.921,6.775c0,1.53,0.884,3.185,2.242,4.037c-3.222,0.865-5.6,3.807-5.6,7.298c0,0.23,0.189,0.42,0.42,0.42h14.273c0.23,0,0.42-0.189,0.42-0.42C17.676,14.619,15.297,11.677,12.075,10.812 M6.761,6.775c0-2.162,1.773-3.778,3.358-3.778s3.359,1.616,3.359,3.778c0,2.162-1.774,3.778-3.359,3.778S6.761,8.937,6.761,6.775 M3.415,17.69c0.218-3.51,3.142-6.297,6.704-6.297c3.562,0,6.486,2.787,6.705,6.297H3.415z"></path>
                         </svg>
                         {&brainfart.mastermind_name}
+                        <button onclick={on_rebrainfart} title="Rebrainfart or quote" class={classes!("ml-3", "inline-flex", "items-center", "hover:text-green-600", if brainfart.rebrainfarted_by_me {"text-green-600"} else {"text-gray-400"})}>
+                            <svg class="w-4 h-4 mr-1" fill="none" stroke="currentColor" stroke-width="1.5" viewBox="0 0 24 24" xmlns="http://www.w3.org/2000/svg">
+                                <path d="M19.5 12c0-1.232-.046-2.453-.138-3.662a4.006 4.006 0 00-3.7-3.7 48.678 48.678 0 00-7.324 0 4.006 4.006 0 00-3.7 3.7c-.017.22-.032.441-.046.662M19.5 12l3-3m-3 3l-3-3m-12 3c0 1.232.046 2.453.138 3.662a4.006 4.006 0 003.7 3.7 48.656 48.656 0 007.324 0 4.006 4.006 0 003.7-3.7c.017-.22.032-.441.046-.662M4.5 12l3 3m-3-3l-3 3" stroke-linecap="round" stroke-linejoin="round"></path>
+                            </svg>
+                            {brainfart.rebrainfarts}
+                        </button>
+                        <button onclick={on_bookmark} title={if brainfart.bookmarked_by_me {"Remove bookmark"} else {"Bookmark"}} class="ml-3 text-gray-400 hover:text-blue-500">
+                            <svg class={classes!("w-4", "h-4", brainfart.bookmarked_by_me.then_some("fill-blue-500 stroke-blue-500"))} fill="none" stroke="currentColor" stroke-width="1.5" viewBox="0 0 24 24" xmlns="http://www.w3.org/2000/svg">
+                                <path d="M17.593 3.322c1.1.128 1.907 1.077 1.907 2.185V21L12 17.25 4.5 21V5.507c0-1.108.806-2.057 1.907-2.185a48.507 48.507 0 0111.186 0z" stroke-linecap="round" stroke-linejoin="round"></path>
+                            </svg>
+                        </button>
+                        <button onclick={on_report} title="Report" class="ml-3 text-gray-400 hover:text-red-500">
+                            <svg class="w-4 h-4" fill="none" stroke="currentColor" stroke-width="1.5" viewBox="0 0 24 24" xmlns="http://www.w3.org/2000/svg">
+                                <path d="M3 3v1.5M3 21v-6m0 0l2.77-.693a9 9 0 016.208.682l.108.054a9 9 0 006.086.71l3.114-.732a48.524 48.524 0 01-.005-10.499l-3.11.732a9 9 0 01-6.085-.711l-.108-.054a9 9 0 00-6.208-.682L3 4.5M3 15V4.5" stroke-linecap="round" stroke-linejoin="round"></path>
+                            </svg>
+                        </button>
                     </p>
                 </div>
             </div>
@@ -1,6 +1,12 @@
-use gloo_net::http::Request;
-use shared::CreateBrainfartRequest;
-use web_sys::{HtmlInputElement, InputEvent, MouseEvent};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use client::{gloo_transport::GlooTransport, Client, Upload};
+use js_sys::Uint8Array;
+use shared::{
+    BrainfartDraft, CreateBrainfartRequest, PollRequest, SaveDraftRequest, Uuid,
+    ALLOWED_ATTACHMENT_TYPES, MAX_POLL_OPTIONS,
+};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{File, HtmlInputElement, InputEvent, MouseEvent};
 use yew::{classes, html, Callback, Classes, Component, Html, NodeRef, Properties};
 
 use crate::HelperService;
@@ -18,17 +24,33 @@ pub struct AfterApiAction {
 pub enum Message {
     Submit,
     AfterCreation,
+    AttachmentsFailed(String),
     SetField,
+    PollChanged,
     AfterApiResponse(AfterApiAction),
+    DraftLoaded(BrainfartDraft),
+    AfterDraftSaved,
+    DiscardDraft,
+    None,
 }
 
+/// The format of an `<input type="datetime-local">`.
+const PUBLISH_AT_FORMAT: &str = "%Y-%m-%dT%H:%M";
+
 pub struct NewBrainfartComponent {
     title_ref: NodeRef,
     innehal_ref: NodeRef,
+    publish_at_ref: NodeRef,
+    attachments_ref: NodeRef,
+    poll_options_ref: NodeRef,
+    poll_closes_at_ref: NodeRef,
+    poll_multiple_choice_ref: NodeRef,
     show_warning: bool,
     error_text: String,
     button_disabled: bool,
     is_busy: bool,
+    is_saving_draft: bool,
+    draft_changed_while_saving: bool,
 }
 
 impl NewBrainfartComponent {
@@ -61,11 +83,11 @@ impl NewBrainfartComponent {
     fn update_button_status(&mut self) -> bool {
         let fields = self.get_input_fields_content();
 
-        let should_be_disabled = if CreateBrainfartRequest::validate(&fields.0, &fields.1) {
-            false
-        } else {
-            true
-        };
+        let should_be_disabled = !CreateBrainfartRequest::validate(&fields.0, &fields.1)
+            || !CreateBrainfartRequest::validate_publish_at(self.get_publish_at(), Local::now())
+            || !self
+                .get_poll()
+                .is_none_or(|poll| poll.validate(self.get_publish_at().unwrap_or_else(Local::now)));
         let will_value_change = should_be_disabled != self.button_disabled;
         self.button_disabled = should_be_disabled;
         will_value_change
@@ -78,36 +100,223 @@ impl NewBrainfartComponent {
         let innehal = innehal_element.value();
         (title, innehal)
     }
+
+    fn get_publish_at(&self) -> Option<DateTime<Local>> {
+        Self::get_datetime(&self.publish_at_ref)
+    }
+
+    fn get_datetime(input_ref: &NodeRef) -> Option<DateTime<Local>> {
+        let value = input_ref.cast::<HtmlInputElement>()?.value();
+        let naive = NaiveDateTime::parse_from_str(&value, PUBLISH_AT_FORMAT).ok()?;
+        Local.from_local_datetime(&naive).single()
+    }
+
+    /// The poll options are written one per line. Without any there is no poll.
+    fn get_poll(&self) -> Option<PollRequest> {
+        let options = self
+            .poll_options_ref
+            .cast::<HtmlInputElement>()?
+            .value()
+            .lines()
+            .map(str::trim)
+            .filter(|option| !option.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        if options.is_empty() {
+            return None;
+        }
+        Some(PollRequest {
+            options,
+            closes_at: Self::get_datetime(&self.poll_closes_at_ref),
+            multiple_choice: self
+                .poll_multiple_choice_ref
+                .cast::<HtmlInputElement>()
+                .map(|input| input.checked())
+                .unwrap_or_default(),
+        })
+    }
+
+    fn get_selected_files(&self) -> Vec<File> {
+        let mut files = vec![];
+        if let Some(file_list) = self
+            .attachments_ref
+            .cast::<HtmlInputElement>()
+            .and_then(|input| input.files())
+        {
+            for index in 0..file_list.length() {
+                if let Some(file) = file_list.get(index) {
+                    files.push(file);
+                }
+            }
+        }
+        files
+    }
+
+    /// Attachments can only be uploaded once the brainfart exists. Returns the error text if the
+    /// upload failed.
+    async fn upload_attachments(
+        client: &Client<GlooTransport>,
+        brainfart_id: &Uuid,
+        files: Vec<File>,
+    ) -> Option<String> {
+        let mut uploads = vec![];
+        for file in files {
+            let data = match JsFuture::from(file.array_buffer()).await {
+                Ok(buffer) => Uint8Array::new(&buffer).to_vec(),
+                Err(_) => return Some(format!("Could not read {}", file.name())),
+            };
+            uploads.push(Upload {
+                filename: file.name(),
+                content_type: file.type_(),
+                data,
+            });
+        }
+        client
+            .add_attachments(brainfart_id, uploads)
+            .await
+            .err()
+            .map(|err| err.to_string())
+    }
+
+    fn set_fields(&self, title: &str, content: &str, publish_at: Option<DateTime<Local>>) {
+        self.title_ref
+            .cast::<HtmlInputElement>()
+            .unwrap()
+            .set_value(title);
+        self.innehal_ref
+            .cast::<HtmlInputElement>()
+            .unwrap()
+            .set_value(content);
+        self.publish_at_ref
+            .cast::<HtmlInputElement>()
+            .unwrap()
+            .set_value(
+                &publish_at
+                    .map(|publish_at| publish_at.format(PUBLISH_AT_FORMAT).to_string())
+                    .unwrap_or_default(),
+            );
+        for input_ref in [
+            &self.attachments_ref,
+            &self.poll_options_ref,
+            &self.poll_closes_at_ref,
+        ] {
+            if let Some(input) = input_ref.cast::<HtmlInputElement>() {
+                input.set_value("");
+            }
+        }
+        if let Some(multiple_choice) = self.poll_multiple_choice_ref.cast::<HtmlInputElement>() {
+            multiple_choice.set_checked(false);
+        }
+    }
+
+    fn load_draft(ctx: &yew::Context<Self>) {
+        let client = HelperService::client();
+        ctx.link().send_future(async move {
+            match client.get_draft().await {
+                Ok(draft) => Message::DraftLoaded(draft),
+                Err(_) => Message::None,
+            }
+        });
+    }
+
+    /// Saves what is in the editor. Changes made while a save is underway are saved right after
+    /// it, so at most one request is in flight.
+    fn save_draft(&mut self, ctx: &yew::Context<Self>) {
+        if self.is_saving_draft {
+            self.draft_changed_while_saving = true;
+            return;
+        }
+        self.is_saving_draft = true;
+        let fields = self.get_input_fields_content();
+        let request = SaveDraftRequest {
+            title: fields.0,
+            content: fields.1,
+            publish_at: self.get_publish_at(),
+        };
+        let client = HelperService::client();
+        ctx.link().send_future(async move {
+            if let Err(err) = client.save_draft(&request).await {
+                log::debug!("Saving the draft failed: {}", err);
+            }
+            Message::AfterDraftSaved
+        });
+    }
+
+    fn delete_draft(ctx: &yew::Context<Self>) {
+        let client = HelperService::client();
+        ctx.link().send_future(async move {
+            if let Err(err) = client.delete_draft().await {
+                log::debug!("Deleting the draft failed: {}", err);
+            }
+            Message::None
+        });
+    }
 }
 
 impl Component for NewBrainfartComponent {
     type Message = Message;
     type Properties = NewBrainfartProps;
-    fn create(_ctx: &yew::Context<Self>) -> Self {
+    fn create(ctx: &yew::Context<Self>) -> Self {
+        Self::load_draft(ctx);
         Self {
             title_ref: NodeRef::default(),
             innehal_ref: NodeRef::default(),
+            publish_at_ref: NodeRef::default(),
+            attachments_ref: NodeRef::default(),
+            poll_options_ref: NodeRef::default(),
+            poll_closes_at_ref: NodeRef::default(),
+            poll_multiple_choice_ref: NodeRef::default(),
             button_disabled: true,
             show_warning: false,
             error_text: String::default(),
             is_busy: false,
+            is_saving_draft: false,
+            draft_changed_while_saving: false,
         }
     }
 
     fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
         match msg {
-            Message::SetField => self.update_button_status(),
+            Message::None => false,
+            Message::SetField => {
+                self.save_draft(ctx);
+                self.update_button_status()
+            }
+            Message::PollChanged => self.update_button_status(),
+            Message::DraftLoaded(draft) => {
+                self.set_fields(&draft.title, &draft.content, draft.publish_at);
+                self.update_button_status();
+                true
+            }
+            Message::AfterDraftSaved => {
+                self.is_saving_draft = false;
+                if self.draft_changed_while_saving {
+                    self.draft_changed_while_saving = false;
+                    self.save_draft(ctx);
+                }
+                false
+            }
+            Message::DiscardDraft => {
+                self.set_fields("", "", None);
+                Self::delete_draft(ctx);
+                self.update_button_status();
+                true
+            }
             Message::AfterCreation => {
                 self.is_busy = false;
                 self.show_warning = false;
-                self.title_ref
-                    .cast::<HtmlInputElement>()
-                    .unwrap()
-                    .set_value("");
-                self.innehal_ref
-                    .cast::<HtmlInputElement>()
-                    .unwrap()
-                    .set_value("");
+                self.set_fields("", "", None);
+                Self::delete_draft(ctx);
+                self.update_button_status();
+                true
+            }
+            Message::AttachmentsFailed(error_text) => {
+                self.update(ctx, Message::AfterCreation);
+                self.show_warning = true;
+                self.error_text = format!(
+                    "The brainfart was created without its attachments: {}",
+                    error_text
+                );
                 true
             }
             Message::Submit => {
@@ -115,27 +324,34 @@ impl Component for NewBrainfartComponent {
                 self.show_warning = false;
 
                 let fields = self.get_input_fields_content();
+                let publish_at = self.get_publish_at();
+                let files = self.get_selected_files();
+                let mut request =
+                    CreateBrainfartRequest::create_scheduled(fields.0, fields.1, publish_at);
+                if let Some(poll) = self.get_poll() {
+                    request = request.with_poll(poll);
+                }
                 let on_creation = ctx.props().clone().on_creation;
+                let client = HelperService::client();
 
                 ctx.link().send_future(async move {
-                    let resp = HelperService::add_authorization_header(Request::post(
-                        "/api/createbrainfart",
-                    ))
-                    .json(&CreateBrainfartRequest::create(fields.0, fields.1))
-                    .unwrap()
-                    .send()
-                    .await
-                    .unwrap();
-
-                    let response_text = resp.text().await.unwrap().replace("\"", "");
-
-                    if !resp.ok() {
-                        Message::AfterApiResponse(AfterApiAction {
-                            error_text: response_text,
-                        })
+                    let brainfart = match client.create_brainfart(&request).await {
+                        Ok(brainfart) => brainfart,
+                        Err(err) => {
+                            return Message::AfterApiResponse(AfterApiAction {
+                                error_text: err.to_string(),
+                            })
+                        }
+                    };
+                    let upload_error = if files.is_empty() {
+                        None
                     } else {
-                        on_creation.emit(response_text);
-                        Message::AfterCreation
+                        Self::upload_attachments(&client, &brainfart.id, files).await
+                    };
+                    on_creation.emit(brainfart.id);
+                    match upload_error {
+                        Some(error_text) => Message::AttachmentsFailed(error_text),
+                        None => Message::AfterCreation,
                     }
                 });
                 true
@@ -155,6 +371,20 @@ impl Component for NewBrainfartComponent {
         let button_is_enabled = self.button_is_enabled();
         let on_click = ctx.link().callback(move |_e: MouseEvent| Message::Submit);
         let on_input = ctx.link().callback(move |_e: InputEvent| Message::SetField);
+        let on_poll_input = ctx
+            .link()
+            .callback(move |_e: InputEvent| Message::PollChanged);
+        let on_multiple_choice = ctx
+            .link()
+            .callback(move |_e: MouseEvent| Message::PollChanged);
+        let on_discard = ctx
+            .link()
+            .callback(move |_e: MouseEvent| Message::DiscardDraft);
+        let submit_text = if self.get_publish_at().is_some() {
+            "Schedule your brainwaves"
+        } else {
+            "Fart your brainwaves"
+        };
         let on_close = ctx.props().clone().on_close;
         let on_mouse_enters_content_area = ctx.props().clone().on_mouse_enters_content_area;
         let on_mouse_leaves_content_area = ctx.props().clone().on_mouse_leaves_content_area;
@@ -183,11 +413,35 @@ impl Component for NewBrainfartComponent {
                 <div>
                     <label class={classes!("block")} for="innehal">{"Content"}</label>
                     <textarea ref={self.innehal_ref.clone()} id="innehal" oninput={on_input.clone()}
-                    class={title_classes}>
+                    class={title_classes.clone()}>
+                    </textarea>
+                </div>
+                <div>
+                    <label class={classes!("block")} for="publish_at">{"Publish at (leave empty to publish now)"}</label>
+                    <input ref={self.publish_at_ref.clone()} id="publish_at" type="datetime-local" oninput={on_input.clone()}
+                    class={title_classes.clone()}/>
+                </div>
+                <div>
+                    <label class={classes!("block")} for="attachments">{"Attachments (images, PDF or text)"}</label>
+                    <input ref={self.attachments_ref.clone()} id="attachments" type="file" multiple=true accept={ALLOWED_ATTACHMENT_TYPES.join(",")}
+                    class={title_classes.clone()}/>
+                </div>
+                <div>
+                    <label class={classes!("block")} for="poll_options">{format!("Poll options, one per line (up to {})", MAX_POLL_OPTIONS)}</label>
+                    <textarea ref={self.poll_options_ref.clone()} id="poll_options" oninput={on_poll_input.clone()}
+                    class={title_classes.clone()}>
                     </textarea>
+                    <label class={classes!("inline-flex", "items-center", "mt-2")}>
+                        <input ref={self.poll_multiple_choice_ref.clone()} type="checkbox" onclick={on_multiple_choice} class={classes!("mr-2")}/>
+                        {"Several options can be chosen"}
+                    </label>
+                    <label class={classes!("block", "mt-2")} for="poll_closes_at">{"Poll closes at (leave empty to keep it open)"}</label>
+                    <input ref={self.poll_closes_at_ref.clone()} id="poll_closes_at" type="datetime-local" oninput={on_poll_input}
+                    class={title_classes}/>
                 </div>
-                <div class="flex justify-end">
-                    <button disabled={!button_is_enabled} onclick={on_click} class={self.get_classes()}>{"Fart your brainwaves"}</button>
+                <div class="flex justify-end space-x-2">
+                    <button onclick={on_discard} class={classes!("px-6", "py-2", "mt-4", "text-gray-700", "bg-gray-200", "rounded-lg", "hover:bg-gray-300")}>{"Discard draft"}</button>
+                    <button disabled={!button_is_enabled} onclick={on_click} class={self.get_classes()}>{submit_text}</button>
                 </div>
             </div>
         }
@@ -1,3 +1,4 @@
+use shared::Permission;
 use yew::{classes, html, Component, Html, Properties};
 
 use crate::HelperService;
@@ -27,7 +28,7 @@ impl Component for NavbarComponent {
     }
 
     fn view(&self, _ctx: &yew::Context<Self>) -> Html {
-        let _jwt = HelperService::get_jwt_information().unwrap();
+        let jwt = HelperService::get_jwt_information().unwrap();
 
         html! {<nav class="bg-white border-gray-200 shadow px-2 sm:px-4 py-2.5 rounded dark:bg-gray-900 mb-3">
         <div class="container flex flex-wrap items-center justify-between mx-auto">
@@ -44,6 +45,20 @@ impl Component for NavbarComponent {
               <li>
                 <NavLinkComponent to={Route::Home} text={"Home"}/>
               </li>
+              <li>
+                <NavLinkComponent to={Route::Bookmarks} text={"Bookmarks"}/>
+              </li>
+              <li>
+                <NavLinkComponent to={Route::Leaderboard} text={"Leaderboard"}/>
+              </li>
+              <li>
+                <NavLinkComponent to={Route::Settings} text={"Settings"}/>
+              </li>
+              if jwt.has_permission(Permission::ReviewReports) {
+                <li>
+                  <NavLinkComponent to={Route::Admin} text={"Admin"}/>
+                </li>
+              }
               <li>
                 <NavLinkComponent to={Route::Logout} text={"Logout"}/>
               </li>
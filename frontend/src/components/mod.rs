@@ -1,3 +1,4 @@
 pub mod authentication;
 pub mod brainfarts;
 pub mod general;
+pub mod moderation;
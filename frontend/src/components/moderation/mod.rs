@@ -0,0 +1 @@
+pub mod reported_brainfart_component;
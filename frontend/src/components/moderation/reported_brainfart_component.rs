@@ -0,0 +1,81 @@
+use shared::ReportedBrainfart;
+use web_sys::MouseEvent;
+use yew::{classes, html, Callback, Component, Html, Properties};
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct ReportedBrainfartProps {
+    pub reported_brainfart: ReportedBrainfart,
+    pub on_hide: Callback<MouseEvent>,
+    pub on_restore: Callback<MouseEvent>,
+    pub on_suspend: Callback<MouseEvent>,
+}
+
+pub enum Message {}
+
+pub struct ReportedBrainfartComponent {}
+
+impl ReportedBrainfartComponent {}
+
+impl Component for ReportedBrainfartComponent {
+    type Message = Message;
+    type Properties = ReportedBrainfartProps;
+    fn create(_ctx: &yew::Context<Self>) -> Self {
+        Self {}
+    }
+
+    fn update(&mut self, _ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+        match msg {}
+    }
+
+    fn view(&self, ctx: &yew::Context<Self>) -> Html {
+        let reported_brainfart = &ctx.props().reported_brainfart;
+        let brainfart = &reported_brainfart.brainfart;
+        let dag = brainfart.birthdate.format("%Y/%m/%d %H:%M").to_string();
+        let reports = reported_brainfart
+            .reports
+            .iter()
+            .map(|report| {
+                html! {
+                    <li class="text-sm text-gray-600">
+                        <span class="font-medium">{&report.reporter_name}</span>
+                        {format!(" ({}): ", report.reportdate.format("%Y/%m/%d %H:%M"))}
+                        {&report.reason}
+                    </li>
+                }
+            })
+            .collect::<Html>();
+
+        html! {
+            <div key={brainfart.id.to_string()} class={classes!("block", "xl:w-2/5", "md:w-2/3", "sm:w-4/5", "xs:w-full", "border", "border-red-300", "rounded-lg", "shadow-md", "bg-gray-50", "mt-2")}>
+                <div class={classes!("p-2", "border-b", "rounded-t", "items-center", "justify-center")}>
+                    <h1 class={classes!("font-bold", "tracking-tight", "text-center")}>
+                    {&brainfart.title}
+                    if reported_brainfart.hidden {
+                        <span class="ml-2 text-xs font-normal text-red-500">{"hidden"}</span>
+                    }
+                    </h1>
+                    <p class="text-xs text-center text-gray-500">{format!("{} by {}", dag, brainfart.mastermind_name)}</p>
+                </div>
+                <div class={classes!("p-3", "space-y-3", "bg-gray-100")}>
+                    <p class={classes!("font-normal", "text-gray-600", "text-base", "leading-relaxed")}>
+                    {&brainfart.content}
+                    </p>
+                </div>
+                <ul class="p-3 space-y-1 border-t border-gray-200">
+                    {reports}
+                </ul>
+                <div class="flex justify-end p-2 space-x-2 border-t border-gray-200 rounded-b">
+                    <button onclick={&ctx.props().on_restore} class="px-3 py-1 text-sm text-gray-700 bg-white border border-gray-300 rounded-lg hover:bg-gray-100">
+                        if reported_brainfart.hidden {{"Restore"}} else {{"Dismiss"}}
+                    </button>
+                    <button onclick={&ctx.props().on_hide} class="px-3 py-1 text-sm text-white bg-red-500 rounded-lg hover:bg-red-600">
+                        {"Hide"}
+                    </button>
+                    <button onclick={&ctx.props().on_suspend} class="px-3 py-1 text-sm text-white bg-gray-700 rounded-lg hover:bg-gray-800">
+                        {format!("Suspend {}", brainfart.mastermind_name)}
+                    </button>
+                </div>
+            </div>
+        }
+    }
+}
@@ -1,12 +1,26 @@
-use gloo_net::http::Request;
+// Lints the frontend has had since before the workspace was checked with clippy, left for a
+// cleanup of their own.
+#![allow(
+    unused_variables,
+    clippy::clone_on_copy,
+    clippy::len_zero,
+    clippy::match_like_matches_macro,
+    clippy::needless_bool,
+    clippy::needless_return,
+    clippy::redundant_pattern_matching
+)]
+use client::{gloo_transport::GlooTransport, Client};
 use jwt_simple::prelude::*;
 use once_cell::sync::Lazy;
+use pages::adminpage::AdminPage;
+use pages::bookmarkspage::BookmarksPage;
 use pages::homepage::HomePage;
+use pages::leaderboardpage::LeaderboardPage;
 use pages::loginpage::LoginPage;
 use pages::logoutpage::LogoutPage;
 use pages::registerpage::RegisterPage;
+use pages::settingspage::SettingsPage;
 use shared::JwtInformation;
-use wasm_bindgen_futures::spawn_local;
 use web_sys::Storage;
 use yew::prelude::*;
 use yew_router::prelude::*;
@@ -36,17 +50,11 @@ impl HelperService {
         web_sys::window().unwrap().local_storage().unwrap().unwrap()
     }
 
-    pub fn add_authorization_header(request: Request) -> Request {
-        request.header(
-            "Authorization",
-            &format!(
-                "Bearer {}",
-                HelperService::get_storage()
-                    .get_item("token")
-                    .unwrap()
-                    .unwrap()
-            ),
-        )
+    /// A client of the API that sends the stored token, if there is one.
+    pub fn client() -> Client<GlooTransport> {
+        let mut client = Client::new(GlooTransport::default());
+        client.set_token(Self::get_storage().get_item("token").ok().flatten());
+        client
     }
     pub fn get_jwt_information() -> Option<JwtInformation> {
         let storage = Self::get_storage();
@@ -62,55 +70,6 @@ impl HelperService {
     }
 }
 
-#[function_component(HelloServer)]
-fn hello_server() -> Html {
-    let data = use_state(|| None);
-
-    // Request `/api/hello` once
-    {
-        let data = data.clone();
-        use_effect(move || {
-            if data.is_none() {
-                spawn_local(async move {
-                    let resp = Request::get("/api/hello").send().await.unwrap();
-                    let result = {
-                        if !resp.ok() {
-                            Err(format!(
-                                "Error fetching data {} ({})",
-                                resp.status(),
-                                resp.status_text()
-                            ))
-                        } else {
-                            resp.text().await.map_err(|err| err.to_string())
-                        }
-                    };
-                    data.set(Some(result));
-                });
-            }
-
-            || {}
-        });
-    }
-
-    match data.as_ref() {
-        None => {
-            html! {
-                <div>{"No server response"}</div>
-            }
-        }
-        Some(Ok(data)) => {
-            html! {
-                <div>{"Got server response: "}{data}<br/><a href="/">{"Back"}</a></div>
-            }
-        }
-        Some(Err(err)) => {
-            html! {
-                <div>{"Error requesting data from server: "}{err}</div>
-            }
-        }
-    }
-}
-
 #[derive(Clone, Routable, PartialEq)]
 pub enum Route {
     #[at("/")]
@@ -121,6 +80,14 @@ pub enum Route {
     Register,
     #[at("/logout")]
     Logout,
+    #[at("/admin")]
+    Admin,
+    #[at("/settings")]
+    Settings,
+    #[at("/bookmarks")]
+    Bookmarks,
+    #[at("/leaderboard")]
+    Leaderboard,
 }
 
 fn switch(routes: Route) -> Html {
@@ -131,6 +98,10 @@ fn switch(routes: Route) -> Html {
         Route::Login => html! {<LoginPage />},
         Route::Register => html! {<RegisterPage/>},
         Route::Logout => html! {<LogoutPage/>},
+        Route::Admin => html! {<AdminPage/>},
+        Route::Settings => html! {<SettingsPage/>},
+        Route::Bookmarks => html! {<BookmarksPage/>},
+        Route::Leaderboard => html! {<LeaderboardPage/>},
     }
 }
 
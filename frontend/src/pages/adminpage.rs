@@ -0,0 +1,51 @@
+use shared::Permission;
+use yew::{html, Component, Html, Properties};
+use yew_router::scope_ext::RouterScopeExt;
+
+use crate::{
+    components::general::{loading_component::LoadingComponent, navbar_component::NavbarComponent},
+    views::reports_view::ReportsView,
+    HelperService, Route,
+};
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct AdminPageProps {}
+
+pub enum Message {}
+
+pub struct AdminPage {}
+
+impl AdminPage {}
+
+impl Component for AdminPage {
+    type Message = Message;
+    type Properties = AdminPageProps;
+    fn create(_ctx: &yew::Context<Self>) -> Self {
+        Self {}
+    }
+
+    fn update(&mut self, _ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+        match msg {}
+    }
+
+    fn view(&self, ctx: &yew::Context<Self>) -> Html {
+        let navigator = ctx.link().navigator().unwrap();
+        match HelperService::get_jwt_information() {
+            Some(information) if information.has_permission(Permission::ReviewReports) => html! {
+            <div>
+                <NavbarComponent/>
+                <div>
+                <ReportsView/>
+                </div>
+            </div> },
+            Some(_) => {
+                navigator.push(&Route::Home);
+                html! {<LoadingComponent/>}
+            }
+            None => {
+                navigator.push(&Route::Login);
+                html! {<LoadingComponent/>}
+            }
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use shared::BrainfartFilterMode;
+use yew::{html, Component, Html, Properties};
+use yew_router::scope_ext::RouterScopeExt;
+
+use crate::{
+    components::general::{loading_component::LoadingComponent, navbar_component::NavbarComponent},
+    views::brainfarts_view::BrainfartsView,
+    HelperService, Route,
+};
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct BookmarksPageProps {}
+
+pub enum Message {}
+
+pub struct BookmarksPage {}
+
+impl BookmarksPage {}
+
+impl Component for BookmarksPage {
+    type Message = Message;
+    type Properties = BookmarksPageProps;
+    fn create(_ctx: &yew::Context<Self>) -> Self {
+        Self {}
+    }
+
+    fn update(&mut self, _ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+        match msg {}
+    }
+
+    fn view(&self, ctx: &yew::Context<Self>) -> Html {
+        let navigator = ctx.link().navigator().unwrap();
+        if HelperService::get_jwt_information().is_some() {
+            html! {
+            <div>
+                <NavbarComponent/>
+                <div>
+                <BrainfartsView counter={0} mode={BrainfartFilterMode::Bookmarks}/>
+                </div>
+            </div> }
+        } else {
+            navigator.push(&Route::Login);
+            html! {<LoadingComponent/>}
+        }
+    }
+}
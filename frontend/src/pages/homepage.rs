@@ -1,4 +1,6 @@
-use yew::{html, Component, Html, Properties};
+use shared::BrainfartOrder;
+use web_sys::MouseEvent;
+use yew::{classes, html, Component, Html, Properties};
 use yew_router::scope_ext::RouterScopeExt;
 
 use crate::{
@@ -12,10 +14,12 @@ pub struct HomePageProps {}
 
 pub enum Message {
     ReRender,
+    SetOrder(BrainfartOrder),
 }
 
 pub struct HomePage {
     counter: u8,
+    order: BrainfartOrder,
 }
 
 impl HomePage {}
@@ -24,7 +28,10 @@ impl Component for HomePage {
     type Message = Message;
     type Properties = HomePageProps;
     fn create(_ctx: &yew::Context<Self>) -> Self {
-        Self { counter: 0 }
+        Self {
+            counter: 0,
+            order: BrainfartOrder::default(),
+        }
     }
 
     fn update(&mut self, _ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
@@ -33,12 +40,30 @@ impl Component for HomePage {
                 self.counter += 1;
                 true
             }
+            Message::SetOrder(order) => {
+                let changed = self.order != order;
+                self.order = order;
+                changed
+            }
         }
     }
 
     fn view(&self, ctx: &yew::Context<Self>) -> Html {
         let navigator = ctx.link().navigator().unwrap();
         let on_new_brainfart = ctx.link().callback(move |_s: String| Message::ReRender);
+        let order_button = |order: BrainfartOrder, text: &str| {
+            let on_click = ctx
+                .link()
+                .callback(move |_e: MouseEvent| Message::SetOrder(order));
+            let colors = if self.order == order {
+                classes!("bg-blue-600", "text-white")
+            } else {
+                classes!("bg-gray-200", "text-gray-700", "hover:bg-gray-300")
+            };
+            html! {
+                <button onclick={on_click} class={classes!("px-4", "py-1", "text-sm", "rounded-lg", colors)}>{text}</button>
+            }
+        };
         if let Some(_) = HelperService::get_jwt_information() {
             html! {
             <div>
@@ -46,8 +71,12 @@ impl Component for HomePage {
                 <div>
                 <NewBrainfartView on_new_brainfart={on_new_brainfart}/>
                 </div>
+                <div class={classes!("flex", "justify-center", "space-x-2", "mt-2")}>
+                {order_button(BrainfartOrder::Newest, "Newest")}
+                {order_button(BrainfartOrder::Hot, "Hot")}
+                </div>
                 <div>
-                <BrainfartsView counter={self.counter}/>
+                <BrainfartsView counter={self.counter} order={self.order}/>
                 </div>
 
 
@@ -0,0 +1,45 @@
+use yew::{html, Component, Html, Properties};
+use yew_router::scope_ext::RouterScopeExt;
+
+use crate::{
+    components::general::{loading_component::LoadingComponent, navbar_component::NavbarComponent},
+    views::leaderboard_view::LeaderboardView,
+    HelperService, Route,
+};
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct LeaderboardPageProps {}
+
+pub enum Message {}
+
+pub struct LeaderboardPage {}
+
+impl LeaderboardPage {}
+
+impl Component for LeaderboardPage {
+    type Message = Message;
+    type Properties = LeaderboardPageProps;
+    fn create(_ctx: &yew::Context<Self>) -> Self {
+        Self {}
+    }
+
+    fn update(&mut self, _ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+        match msg {}
+    }
+
+    fn view(&self, ctx: &yew::Context<Self>) -> Html {
+        let navigator = ctx.link().navigator().unwrap();
+        if HelperService::get_jwt_information().is_some() {
+            html! {
+            <div>
+                <NavbarComponent/>
+                <div>
+                <LeaderboardView/>
+                </div>
+            </div> }
+        } else {
+            navigator.push(&Route::Login);
+            html! {<LoadingComponent/>}
+        }
+    }
+}
@@ -1,4 +1,8 @@
+pub mod adminpage;
+pub mod bookmarkspage;
 pub mod homepage;
+pub mod leaderboardpage;
 pub mod loginpage;
 pub mod logoutpage;
 pub mod registerpage;
+pub mod settingspage;
@@ -1,6 +1,6 @@
-use gloo_net::http::Request;
 use shared::{
-    BrainfartInformation, NotifyAboutMindExplosionRequest, NotifyAboutMindImplosionRequest, Uuid,
+    BrainfartFilter, BrainfartFilterMode, BrainfartInformation, BrainfartOrder, ReactionKind,
+    ReportBrainfartRequest, Uuid,
 };
 use web_sys::{HtmlDivElement, MouseEvent, WheelEvent};
 use yew::{classes, html, html::onscroll::Event, Component, Html, NodeRef, Properties};
@@ -16,6 +16,10 @@ use crate::{
 #[derive(Properties, Clone, PartialEq)]
 pub struct BrainfartsProps {
     pub counter: u8,
+    #[prop_or_default]
+    pub mode: BrainfartFilterMode,
+    #[prop_or_default]
+    pub order: BrainfartOrder,
 }
 
 pub enum Message {
@@ -23,95 +27,132 @@ pub enum Message {
     Brainfart(Vec<BrainfartInformation>),
     NewExplosion(Uuid),
     NewImplosion(Uuid),
-    UpdatedBrainfart(BrainfartInformation),
+    UpdatedBrainfart(Box<BrainfartInformation>),
+    Report(Uuid),
+    ToggleBookmark(Uuid),
+    Vote(Uuid, Vec<usize>),
+    Rebrainfart(Uuid),
+    Reload,
 }
 
 pub struct BrainfartsView {
     brainfarts: Vec<BrainfartInformation>,
     brainfarts_div: NodeRef,
+    loaded: bool,
+}
+
+/// Copies what reacting, bookmarking, voting and rebrainfarting can change.
+fn apply_changes(item: &mut BrainfartInformation, changed: &BrainfartInformation) {
+    item.blew_minds = changed.blew_minds.clone();
+    item.imploded_minds = changed.imploded_minds.clone();
+    item.bookmarked_by_me = changed.bookmarked_by_me;
+    item.poll = changed.poll.clone();
+    item.rebrainfarts = changed.rebrainfarts;
+    item.rebrainfarted_by_me = changed.rebrainfarted_by_me;
 }
 
 impl BrainfartsView {
     fn get_brainfarts(ctx: &yew::Context<Self>) {
+        let filter = BrainfartFilter {
+            mode: ctx.props().mode,
+            order: ctx.props().order,
+            ..Default::default()
+        };
+        let client = HelperService::client();
         ctx.link().send_future(async move {
-            let resp = HelperService::add_authorization_header(Request::get("/api/getbrainfarts"))
-                .send()
-                .await
-                .unwrap();
-
-            let response_text = resp.text().await.unwrap();
-
-            if !resp.ok() {
-                Message::None
-            } else {
-                let json = serde_json::from_str(&response_text);
-                if let Ok(brainfarts) = json {
-                    Message::Brainfart(brainfarts)
-                } else {
-                    if let Err(_) = json {
-                        Message::None
-                    } else {
-                        Message::None
-                    }
+            match client.get_brainfarts(&filter).await {
+                Ok(brainfarts) => Message::Brainfart(brainfarts),
+                Err(err) => {
+                    log::debug!("Getting the brainfarts failed: {}", err);
+                    Message::None
                 }
             }
         });
     }
-    fn send_new_implosion(ctx: &yew::Context<Self>, brainfart_id: Uuid) {
+
+    fn send_reaction(ctx: &yew::Context<Self>, brainfart_id: Uuid, kind: ReactionKind) {
+        let client = HelperService::client();
         ctx.link().send_future(async move {
-            let resp = HelperService::add_authorization_header(Request::post(
-                "/api/registermindimplosion",
-            ))
-            .json(&NotifyAboutMindImplosionRequest { brainfart_id })
-            .unwrap()
-            .send()
-            .await
-            .unwrap();
+            match client.react(&brainfart_id, kind).await {
+                Ok(brainfart) => Message::UpdatedBrainfart(Box::new(brainfart)),
+                Err(err) => {
+                    log::debug!("Reacting failed: {}", err);
+                    Message::None
+                }
+            }
+        });
+    }
 
-            let response_text = resp.text().await.unwrap();
+    fn send_report(ctx: &yew::Context<Self>, brainfart_id: Uuid) {
+        let reason = web_sys::window()
+            .unwrap()
+            .prompt_with_message("Why are you reporting this brainfart?")
+            .ok()
+            .flatten();
+        let reason = match reason {
+            Some(reason) if ReportBrainfartRequest::validate(&reason) => reason,
+            _ => return,
+        };
+        let client = HelperService::client();
+        ctx.link().send_future(async move {
+            if let Err(err) = client.report(&brainfart_id, &reason).await {
+                log::debug!("Reporting failed: {}", err);
+            }
+            Message::None
+        });
+    }
 
-            if !resp.ok() {
-                Message::None
+    fn send_bookmark(ctx: &yew::Context<Self>, brainfart_id: Uuid, bookmarked: bool) {
+        let client = HelperService::client();
+        ctx.link().send_future(async move {
+            let result = if bookmarked {
+                client.remove_bookmark(&brainfart_id).await
             } else {
-                let json = serde_json::from_str(&response_text);
-                if let Ok(brainfart) = json {
-                    Message::UpdatedBrainfart(brainfart)
-                } else {
-                    if let Err(_) = json {
-                        Message::None
-                    } else {
-                        Message::None
-                    }
+                client.add_bookmark(&brainfart_id).await
+            };
+            match result {
+                Ok(brainfart) => Message::UpdatedBrainfart(Box::new(brainfart)),
+                Err(err) => {
+                    log::debug!("Bookmarking failed: {}", err);
+                    Message::None
                 }
             }
         });
     }
 
-    fn send_new_explosion(ctx: &yew::Context<Self>, brainfart_id: Uuid) {
-        ctx.link().send_future(async move {
-            let resp = HelperService::add_authorization_header(Request::post(
-                "/api/registermindexplosion",
-            ))
-            .json(&NotifyAboutMindExplosionRequest { brainfart_id })
+    /// An empty answer to the prompt makes a plain rebrainfart, anything else a quote.
+    fn send_rebrainfart(ctx: &yew::Context<Self>, brainfart_id: Uuid) {
+        let commentary = web_sys::window()
             .unwrap()
-            .send()
-            .await
-            .unwrap();
-
-            let response_text = resp.text().await.unwrap();
+            .prompt_with_message(
+                "Add a comment to quote the brainfart, or leave it empty to rebrainfart it",
+            )
+            .ok()
+            .flatten();
+        let commentary = match commentary {
+            Some(commentary) => commentary,
+            None => return,
+        };
+        let client = HelperService::client();
+        ctx.link().send_future(async move {
+            match client.rebrainfart(&brainfart_id, &commentary).await {
+                Ok(_) => Message::Reload,
+                Err(err) => {
+                    log::debug!("Rebrainfarting failed: {}", err);
+                    Message::None
+                }
+            }
+        });
+    }
 
-            if !resp.ok() {
-                Message::None
-            } else {
-                let json = serde_json::from_str(&response_text);
-                if let Ok(brainfart) = json {
-                    Message::UpdatedBrainfart(brainfart)
-                } else {
-                    if let Err(_) = json {
-                        Message::None
-                    } else {
-                        Message::None
-                    }
+    fn send_vote(ctx: &yew::Context<Self>, brainfart_id: Uuid, options: Vec<usize>) {
+        let client = HelperService::client();
+        ctx.link().send_future(async move {
+            match client.vote(&brainfart_id, options).await {
+                Ok(brainfart) => Message::UpdatedBrainfart(Box::new(brainfart)),
+                Err(err) => {
+                    log::debug!("Voting failed: {}", err);
+                    Message::None
                 }
             }
         });
@@ -126,6 +167,7 @@ impl Component for BrainfartsView {
         Self {
             brainfarts: vec![],
             brainfarts_div: NodeRef::default(),
+            loaded: false,
         }
     }
 
@@ -134,27 +176,65 @@ impl Component for BrainfartsView {
             Message::None => false,
             Message::Brainfart(brainfarts) => {
                 self.brainfarts = brainfarts;
+                self.loaded = true;
                 true
             }
             Message::NewExplosion(fartid) => {
-                Self::send_new_explosion(ctx, fartid);
+                Self::send_reaction(ctx, fartid, ReactionKind::Explosion);
                 false
             }
             Message::NewImplosion(fartid) => {
-                Self::send_new_implosion(ctx, fartid);
+                Self::send_reaction(ctx, fartid, ReactionKind::Implosion);
                 false
             }
-            Message::UpdatedBrainfart(changed_fart) => {
-                if let Some(index) = self
+            Message::Report(fartid) => {
+                Self::send_report(ctx, fartid);
+                false
+            }
+            Message::ToggleBookmark(fartid) => {
+                let bookmarked = self
                     .brainfarts
-                    .iter_mut()
-                    .find(|item| item.id == changed_fart.id)
+                    .iter()
+                    .any(|item| item.id == fartid && item.bookmarked_by_me);
+                Self::send_bookmark(ctx, fartid, bookmarked);
+                false
+            }
+            Message::Vote(fartid, options) => {
+                Self::send_vote(ctx, fartid, options);
+                false
+            }
+            Message::Rebrainfart(fartid) => {
+                Self::send_rebrainfart(ctx, fartid);
+                false
+            }
+            Message::Reload => {
+                Self::get_brainfarts(ctx);
+                false
+            }
+            Message::UpdatedBrainfart(changed_fart) => {
+                if ctx.props().mode == BrainfartFilterMode::Bookmarks
+                    && !changed_fart.bookmarked_by_me
                 {
-                    index.blew_minds = changed_fart.blew_minds;
-                    index.imploded_minds = changed_fart.imploded_minds;
+                    self.brainfarts.retain(|item| item.id != changed_fart.id);
                     true
                 } else {
-                    false
+                    // The brainfart can be in the feed itself and embedded in rebrainfarts of it.
+                    let mut found = false;
+                    for item in self.brainfarts.iter_mut() {
+                        if item.id == changed_fart.id {
+                            apply_changes(item, &changed_fart);
+                            found = true;
+                        }
+                        if let Some(original) = item
+                            .original
+                            .as_mut()
+                            .filter(|original| original.id == changed_fart.id)
+                        {
+                            apply_changes(original, &changed_fart);
+                            found = true;
+                        }
+                    }
+                    found
                 }
             }
         }
@@ -177,16 +257,39 @@ impl Component for BrainfartsView {
             let brainfart = self
                 .brainfarts
                 .iter()
-                .map(|brainfart| {
+                .map(|item| {
+                    // A plain rebrainfart shows its original, which is what gets reacted to.
+                    let (brainfart, rebrainfarted_by) = match &item.original {
+                        Some(original) if item.is_plain_rebrainfart() => {
+                            (original.as_ref(), Some(item.mastermind_name.clone()))
+                        }
+                        _ => (item, None),
+                    };
                     let brain_id = brainfart.id.clone();
                     let brain_id_two = brainfart.id.clone();
+                    let brain_id_three = brainfart.id.clone();
+                    let brain_id_four = brainfart.id.clone();
+                    let brain_id_five = brainfart.id.clone();
+                    let brain_id_six = brainfart.id.clone();
                     let on_explosion = ctx
                         .link()
                         .callback(move |_: MouseEvent| Message::NewExplosion(brain_id.clone()));
                     let on_implosion = ctx
                         .link()
                         .callback(move |_: MouseEvent| Message::NewImplosion(brain_id_two.clone()));
-                    html! {<BrainfartComponent brainfart={brainfart.clone()} on_explosion={on_explosion} on_implosion={on_implosion} />}
+                    let on_report = ctx
+                        .link()
+                        .callback(move |_: MouseEvent| Message::Report(brain_id_three.clone()));
+                    let on_bookmark = ctx.link().callback(move |_: MouseEvent| {
+                        Message::ToggleBookmark(brain_id_four.clone())
+                    });
+                    let on_vote = ctx.link().callback(move |options: Vec<usize>| {
+                        Message::Vote(brain_id_five.clone(), options)
+                    });
+                    let on_rebrainfart = ctx.link().callback(move |_: MouseEvent| {
+                        Message::Rebrainfart(brain_id_six.clone())
+                    });
+                    html! {<BrainfartComponent key={item.id.clone()} brainfart={brainfart.clone()} rebrainfarted_by={rebrainfarted_by} on_explosion={on_explosion} on_implosion={on_implosion} on_report={on_report} on_bookmark={on_bookmark} on_vote={on_vote} on_rebrainfart={on_rebrainfart} />}
                 })
                 .collect::<Html>();
             html! {
@@ -195,8 +298,15 @@ impl Component for BrainfartsView {
                     <div ref={self.brainfarts_div.clone()} class={classes!("flex","items-center","justify-between","flex-col")} onscroll={handle_scroll.clone()} onwheel={handle_wheel.clone()}>
                     {brainfart}
                     </div>
-                }else{
+                }else if !self.loaded{
                     <LoadingComponent/>
+                }else{
+                    <p class={classes!("text-center", "text-gray-500", "mt-4")}>
+                    {match ctx.props().mode {
+                        BrainfartFilterMode::All => "There are no brainfarts yet",
+                        BrainfartFilterMode::Bookmarks => "You have not bookmarked any brainfarts yet",
+                    }}
+                    </p>
                 }
                 </div>
             }
@@ -0,0 +1,144 @@
+use shared::{Leaderboard, LeaderboardWindow};
+use web_sys::MouseEvent;
+use yew::{classes, html, Component, Html, Properties};
+
+use crate::{components::general::loading_component::LoadingComponent, HelperService};
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct LeaderboardProps {}
+
+pub enum Message {
+    None,
+    Leaderboard(Leaderboard),
+    SetWindow(LeaderboardWindow),
+}
+
+pub struct LeaderboardView {
+    window: LeaderboardWindow,
+    leaderboard: Option<Leaderboard>,
+}
+
+impl LeaderboardView {
+    fn get_leaderboard(ctx: &yew::Context<Self>, window: LeaderboardWindow) {
+        let client = HelperService::client();
+        ctx.link().send_future(async move {
+            match client.get_leaderboard(window).await {
+                Ok(leaderboard) => Message::Leaderboard(leaderboard),
+                Err(err) => {
+                    log::debug!("Getting the leaderboard failed: {}", err);
+                    Message::None
+                }
+            }
+        });
+    }
+}
+
+impl Component for LeaderboardView {
+    type Message = Message;
+    type Properties = LeaderboardProps;
+    fn create(ctx: &yew::Context<Self>) -> Self {
+        let window = LeaderboardWindow::default();
+        Self::get_leaderboard(ctx, window);
+        Self {
+            window,
+            leaderboard: None,
+        }
+    }
+
+    fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Message::None => false,
+            Message::Leaderboard(leaderboard) => {
+                // An answer for a window that is no longer chosen is left out.
+                if leaderboard.window == self.window {
+                    self.leaderboard = Some(leaderboard);
+                }
+                true
+            }
+            Message::SetWindow(window) => {
+                if self.window == window {
+                    return false;
+                }
+                self.window = window;
+                self.leaderboard = None;
+                Self::get_leaderboard(ctx, window);
+                true
+            }
+        }
+    }
+
+    fn view(&self, ctx: &yew::Context<Self>) -> Html {
+        let window_button = |window: LeaderboardWindow, text: &str| {
+            let on_click = ctx
+                .link()
+                .callback(move |_e: MouseEvent| Message::SetWindow(window));
+            let colors = if self.window == window {
+                classes!("bg-blue-600", "text-white")
+            } else {
+                classes!("bg-gray-200", "text-gray-700", "hover:bg-gray-300")
+            };
+            html! {
+                <button onclick={on_click} class={classes!("px-4", "py-1", "text-sm", "rounded-lg", colors)}>{text}</button>
+            }
+        };
+        let content = match &self.leaderboard {
+            None => html! {<LoadingComponent/>},
+            Some(leaderboard) => {
+                let brainfarts = leaderboard
+                    .brainfarts
+                    .iter()
+                    .map(|brainfart| {
+                        html! {
+                            <li class={classes!("flex", "justify-between", "py-1")}>
+                                <span>{&brainfart.title}<span class="text-gray-500">{format!(" by {}", brainfart.mastermind_name)}</span></span>
+                                <span>{format!("🤯 {}", brainfart.explosions)}</span>
+                            </li>
+                        }
+                    })
+                    .collect::<Html>();
+                let masterminds = leaderboard
+                    .masterminds
+                    .iter()
+                    .map(|mastermind| {
+                        html! {
+                            <li class={classes!("flex", "justify-between", "py-1")}>
+                                <span>{&mastermind.brainname}</span>
+                                <span>{format!("🤯 {}", mastermind.explosions)}</span>
+                            </li>
+                        }
+                    })
+                    .collect::<Html>();
+                html! {
+                    <div class={classes!("grid", "md:grid-cols-2", "gap-6", "w-full", "max-w-3xl")}>
+                        <div>
+                            <h2 class={classes!("text-xl", "font-bold", "mb-2")}>{"Most mind blowing brainfarts"}</h2>
+                            if leaderboard.brainfarts.is_empty() {
+                                <p class="text-gray-500">{"No minds have been blown yet."}</p>
+                            } else {
+                                <ol class="divide-y">{brainfarts}</ol>
+                            }
+                        </div>
+                        <div>
+                            <h2 class={classes!("text-xl", "font-bold", "mb-2")}>{"Most mind blowing masterminds"}</h2>
+                            if leaderboard.masterminds.is_empty() {
+                                <p class="text-gray-500">{"No minds have been blown yet."}</p>
+                            } else {
+                                <ol class="divide-y">{masterminds}</ol>
+                            }
+                        </div>
+                    </div>
+                }
+            }
+        };
+        html! {
+            <div class={classes!("flex", "items-center", "flex-col", "mt-2", "space-y-4")}>
+                <div class={classes!("flex", "justify-center", "space-x-2")}>
+                {window_button(LeaderboardWindow::Week, "This week")}
+                {window_button(LeaderboardWindow::Month, "This month")}
+                {window_button(LeaderboardWindow::All, "All time")}
+                </div>
+                {content}
+            </div>
+        }
+    }
+}
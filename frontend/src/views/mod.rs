@@ -1,2 +1,5 @@
 pub mod brainfarts_view;
+pub mod leaderboard_view;
 pub mod new_brainfart_view;
+pub mod relations_view;
+pub mod reports_view;
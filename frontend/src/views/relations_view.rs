@@ -0,0 +1,169 @@
+use std::future::Future;
+
+use client::ClientError;
+use shared::{BrainRelation, BrainRelationInformation, BrainRelationRequest};
+use web_sys::{HtmlInputElement, MouseEvent};
+use yew::{classes, html, Component, Html, NodeRef, Properties};
+
+use crate::{components::general::loading_component::LoadingComponent, HelperService};
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct RelationsProps {}
+
+pub enum Message {
+    Relations(Vec<BrainRelationInformation>),
+    Failed(String),
+    Add(BrainRelation),
+    Remove(BrainRelationRequest),
+}
+
+pub struct RelationsView {
+    relations: Option<Vec<BrainRelationInformation>>,
+    name_ref: NodeRef,
+    error_text: String,
+}
+
+impl RelationsView {
+    fn send(
+        ctx: &yew::Context<Self>,
+        request: impl Future<Output = Result<Vec<BrainRelationInformation>, ClientError>> + 'static,
+    ) {
+        ctx.link().send_future(async move {
+            match request.await {
+                Ok(relations) => Message::Relations(relations),
+                Err(err) => Message::Failed(err.to_string()),
+            }
+        });
+    }
+
+    fn get_relations(ctx: &yew::Context<Self>) {
+        let client = HelperService::client();
+        Self::send(ctx, async move { client.get_relations().await });
+    }
+
+    fn add_relation(ctx: &yew::Context<Self>, request: BrainRelationRequest) {
+        let client = HelperService::client();
+        Self::send(ctx, async move { client.add_relation(&request).await });
+    }
+
+    fn remove_relation(ctx: &yew::Context<Self>, request: BrainRelationRequest) {
+        let client = HelperService::client();
+        Self::send(ctx, async move { client.remove_relation(&request).await });
+    }
+
+    fn view_relations(&self, ctx: &yew::Context<Self>, relation: BrainRelation) -> Html {
+        let relations = self
+            .relations
+            .iter()
+            .flatten()
+            .filter(|information| information.relation == relation)
+            .map(|information| {
+                let brainname = information.brainname.clone();
+                let on_remove = ctx.link().callback(move |_: MouseEvent| {
+                    Message::Remove(BrainRelationRequest {
+                        brainname: brainname.clone(),
+                        relation,
+                    })
+                });
+                html! {
+                    <li class="flex justify-between items-center py-1">
+                        <span>{&information.brainname}</span>
+                        <button onclick={on_remove} class="px-3 py-1 text-sm text-gray-700 bg-white border border-gray-300 rounded-lg hover:bg-gray-100">
+                            {match relation {
+                                BrainRelation::Mute => "Unmute",
+                                BrainRelation::Block => "Unblock",
+                            }}
+                        </button>
+                    </li>
+                }
+            })
+            .collect::<Html>();
+        html! {
+            <ul class="divide-y divide-gray-200">{relations}</ul>
+        }
+    }
+}
+
+impl Component for RelationsView {
+    type Message = Message;
+    type Properties = RelationsProps;
+    fn create(ctx: &yew::Context<Self>) -> Self {
+        Self::get_relations(ctx);
+        Self {
+            relations: None,
+            name_ref: NodeRef::default(),
+            error_text: String::default(),
+        }
+    }
+
+    fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Message::Relations(relations) => {
+                self.relations = Some(relations);
+                self.error_text = String::default();
+                true
+            }
+            Message::Failed(error_text) => {
+                self.error_text = error_text;
+                true
+            }
+            Message::Add(relation) => {
+                let name_element = self.name_ref.cast::<HtmlInputElement>().unwrap();
+                let brainname = name_element.value();
+                if BrainRelationRequest::validate(&brainname) {
+                    name_element.set_value("");
+                    Self::add_relation(
+                        ctx,
+                        BrainRelationRequest {
+                            brainname,
+                            relation,
+                        },
+                    );
+                }
+                false
+            }
+            Message::Remove(request) => {
+                Self::remove_relation(ctx, request);
+                false
+            }
+        }
+    }
+
+    fn view(&self, ctx: &yew::Context<Self>) -> Html {
+        if self.relations.is_none() && self.error_text.is_empty() {
+            return html! {<LoadingComponent/>};
+        }
+        let on_mute = ctx
+            .link()
+            .callback(|_: MouseEvent| Message::Add(BrainRelation::Mute));
+        let on_block = ctx
+            .link()
+            .callback(|_: MouseEvent| Message::Add(BrainRelation::Block));
+        html! {
+            <div class={classes!("flex", "items-center", "justify-between", "flex-col")}>
+                <div class={classes!("block", "xl:w-2/5", "md:w-2/3", "sm:w-4/5", "xs:w-full", "p-4", "border", "border-gray-300", "rounded-lg", "shadow-md", "bg-gray-50", "space-y-4")}>
+                    <div>
+                        <label class="block text-sm font-medium text-gray-700">{"Brainname"}</label>
+                        <div class="flex mt-1 space-x-2">
+                            <input ref={self.name_ref.clone()} type="text" placeholder="Brainname" class="w-full px-4 py-2 border rounded-md focus:outline-none focus:ring-1 focus:ring-blue-600"/>
+                            <button onclick={on_mute} class="px-4 py-2 text-white bg-blue-600 rounded-lg hover:bg-blue-900">{"Mute"}</button>
+                            <button onclick={on_block} class="px-4 py-2 text-white bg-red-500 rounded-lg hover:bg-red-600">{"Block"}</button>
+                        </div>
+                        if !self.error_text.is_empty() {
+                            <p class="mt-1 text-sm text-red-500">{&self.error_text}</p>
+                        }
+                        <p class="mt-1 text-xs text-gray-500">{"Muted brains' brainfarts are left out of your feed. Blocked brains cannot react to your brainfarts."}</p>
+                    </div>
+                    <div>
+                        <h2 class="font-bold">{"Muted"}</h2>
+                        {self.view_relations(ctx, BrainRelation::Mute)}
+                    </div>
+                    <div>
+                        <h2 class="font-bold">{"Blocked"}</h2>
+                        {self.view_relations(ctx, BrainRelation::Block)}
+                    </div>
+                </div>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use shared::{ReportedBrainfart, Uuid};
+use web_sys::MouseEvent;
+use yew::{classes, html, Component, Html, Properties};
+
+use crate::{
+    components::{
+        general::loading_component::LoadingComponent,
+        moderation::reported_brainfart_component::ReportedBrainfartComponent,
+    },
+    HelperService,
+};
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct ReportsProps {}
+
+pub enum Message {
+    None,
+    Reports(Vec<ReportedBrainfart>),
+    Hide(Uuid),
+    Restore(Uuid),
+    Suspend(String),
+    Moderated,
+}
+
+pub struct ReportsView {
+    reports: Option<Vec<ReportedBrainfart>>,
+}
+
+impl ReportsView {
+    fn get_reports(ctx: &yew::Context<Self>) {
+        let client = HelperService::client();
+        ctx.link().send_future(async move {
+            match client.get_reports().await {
+                Ok(reports) => Message::Reports(reports),
+                Err(err) => {
+                    log::debug!("Getting the reports failed: {}", err);
+                    Message::None
+                }
+            }
+        });
+    }
+
+    /// Hides the brainfart, or restores it when `hidden` is false.
+    fn send_brainfart_moderation(ctx: &yew::Context<Self>, brainfart_id: Uuid, hidden: bool) {
+        let client = HelperService::client();
+        ctx.link().send_future(async move {
+            let result = if hidden {
+                client.hide_brainfart(&brainfart_id).await
+            } else {
+                client.restore_brainfart(&brainfart_id).await
+            };
+            match result {
+                Ok(_) => Message::Moderated,
+                Err(err) => {
+                    log::debug!("Moderating the brainfart failed: {}", err);
+                    Message::None
+                }
+            }
+        });
+    }
+
+    fn send_suspension(ctx: &yew::Context<Self>, brainname: String) {
+        let client = HelperService::client();
+        ctx.link().send_future(async move {
+            match client.suspend_brain(&brainname).await {
+                Ok(_) => Message::Moderated,
+                Err(err) => {
+                    log::debug!("Suspending the brain failed: {}", err);
+                    Message::None
+                }
+            }
+        });
+    }
+}
+
+impl Component for ReportsView {
+    type Message = Message;
+    type Properties = ReportsProps;
+    fn create(ctx: &yew::Context<Self>) -> Self {
+        Self::get_reports(ctx);
+        Self { reports: None }
+    }
+
+    fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Message::None => false,
+            Message::Reports(reports) => {
+                self.reports = Some(reports);
+                true
+            }
+            Message::Hide(brainfart_id) => {
+                Self::send_brainfart_moderation(ctx, brainfart_id, true);
+                false
+            }
+            Message::Restore(brainfart_id) => {
+                Self::send_brainfart_moderation(ctx, brainfart_id, false);
+                false
+            }
+            Message::Suspend(brainname) => {
+                Self::send_suspension(ctx, brainname);
+                false
+            }
+            Message::Moderated => {
+                Self::get_reports(ctx);
+                false
+            }
+        }
+    }
+
+    fn view(&self, ctx: &yew::Context<Self>) -> Html {
+        match &self.reports {
+            None => html! {<LoadingComponent/>},
+            Some(reports) if reports.is_empty() => html! {
+                <p class="text-center text-gray-500">{"Nothing has been reported."}</p>
+            },
+            Some(reports) => {
+                let reports = reports
+                    .iter()
+                    .map(|reported_brainfart| {
+                        let hide_id = reported_brainfart.brainfart.id.clone();
+                        let restore_id = reported_brainfart.brainfart.id.clone();
+                        let mastermind_name = reported_brainfart.brainfart.mastermind_name.clone();
+                        let on_hide = ctx
+                            .link()
+                            .callback(move |_: MouseEvent| Message::Hide(hide_id.clone()));
+                        let on_restore = ctx
+                            .link()
+                            .callback(move |_: MouseEvent| Message::Restore(restore_id.clone()));
+                        let on_suspend = ctx.link().callback(move |_: MouseEvent| {
+                            Message::Suspend(mastermind_name.clone())
+                        });
+                        html! {<ReportedBrainfartComponent reported_brainfart={reported_brainfart.clone()} on_hide={on_hide} on_restore={on_restore} on_suspend={on_suspend} />}
+                    })
+                    .collect::<Html>();
+                html! {
+                    <div class={classes!("flex","items-center","justify-between","flex-col")}>
+                    {reports}
+                    </div>
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,33 @@
+-- The schema the first release created from schema.sql. Databases that already have it are left
+-- as they are.
+create table if not exists brains(
+	id UUID DEFAULT gen_random_uuid() PRIMARY KEY,
+	brainname VARCHAR(255) NOT NULL UNIQUE,
+	password VARCHAR(255) NOT NULL,
+	birthdate TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+	lastupdatedate TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+create table if not exists brainfarts(
+	id UUID DEFAULT gen_random_uuid() PRIMARY KEY,
+	title VARCHAR(255) NOT NULL,
+	content TEXT NOT NULL,
+	birthdate TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+	mastermind UUID,
+	CONSTRAINT fk_brains FOREIGN KEY(mastermind) REFERENCES brains(id)
+);
+create table if not exists mindsblownbyfarts(
+	id UUID DEFAULT gen_random_uuid() PRIMARY KEY,
+	brainfartid UUID,
+	brainid UUID,
+	explosion bool,
+	CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id),
+	CONSTRAINT fk_brainfart FOREIGN KEY(brainfartid) REFERENCES brainfarts(id)
+);
+create table if not exists hallucinatedfarts(
+	id UUID DEFAULT gen_random_uuid() PRIMARY KEY,
+	brainfartid UUID,
+	brainid UUID,
+	hallucinationdate TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+	CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id),
+	CONSTRAINT fk_brainfart FOREIGN KEY(brainfartid) REFERENCES brainfarts(id)
+);
@@ -0,0 +1 @@
+alter table brains add column if not exists disabled BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,13 @@
+alter table brains add column if not exists role VARCHAR(32) NOT NULL DEFAULT 'brain' CHECK (role in ('brain', 'admin'));
+alter table brainfarts add column if not exists hidden BOOLEAN NOT NULL DEFAULT FALSE;
+create table if not exists reportedfarts(
+	id UUID DEFAULT gen_random_uuid() PRIMARY KEY,
+	brainfartid UUID NOT NULL,
+	brainid UUID NOT NULL,
+	reason TEXT NOT NULL,
+	reportdate TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+	resolved BOOLEAN NOT NULL DEFAULT FALSE,
+	CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id),
+	CONSTRAINT fk_brainfart FOREIGN KEY(brainfartid) REFERENCES brainfarts(id),
+	CONSTRAINT one_report_per_brain UNIQUE(brainfartid, brainid)
+);
@@ -0,0 +1,10 @@
+create table if not exists brainrelations(
+	id UUID DEFAULT gen_random_uuid() PRIMARY KEY,
+	brainid UUID NOT NULL,
+	otherbrainid UUID NOT NULL,
+	relation VARCHAR(16) NOT NULL CHECK (relation in ('mute', 'block')),
+	createdate TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+	CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id),
+	CONSTRAINT fk_other_brain FOREIGN KEY(otherbrainid) REFERENCES brains(id),
+	CONSTRAINT one_relation_of_each_kind UNIQUE(brainid, otherbrainid, relation)
+);
@@ -0,0 +1,9 @@
+create table if not exists bookmarkedfarts(
+	id UUID DEFAULT gen_random_uuid() PRIMARY KEY,
+	brainfartid UUID NOT NULL,
+	brainid UUID NOT NULL,
+	bookmarkdate TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+	CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id),
+	CONSTRAINT fk_brainfart FOREIGN KEY(brainfartid) REFERENCES brainfarts(id),
+	CONSTRAINT one_bookmark_per_brain UNIQUE(brainfartid, brainid)
+);
@@ -0,0 +1,11 @@
+alter table brainfarts add column if not exists publishdate TIMESTAMPTZ;
+create table if not exists brainfartdrafts(
+	id UUID DEFAULT gen_random_uuid() PRIMARY KEY,
+	brainid UUID NOT NULL,
+	title VARCHAR(255) NOT NULL,
+	content TEXT NOT NULL,
+	publishdate TIMESTAMPTZ,
+	lastupdatedate TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+	CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id),
+	CONSTRAINT one_draft_per_brain UNIQUE(brainid)
+);
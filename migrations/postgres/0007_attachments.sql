@@ -0,0 +1,10 @@
+create table if not exists brainfartattachments(
+	id UUID DEFAULT gen_random_uuid() PRIMARY KEY,
+	brainfartid UUID NOT NULL,
+	filename VARCHAR(255) NOT NULL,
+	contenttype VARCHAR(255) NOT NULL,
+	size BIGINT NOT NULL,
+	hasthumbnail BOOLEAN NOT NULL DEFAULT FALSE,
+	uploaddate TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+	CONSTRAINT fk_brainfart FOREIGN KEY(brainfartid) REFERENCES brainfarts(id)
+);
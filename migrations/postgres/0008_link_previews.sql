@@ -0,0 +1,8 @@
+create table if not exists linkpreviews(
+	url VARCHAR(2048) PRIMARY KEY,
+	title TEXT,
+	description TEXT,
+	imageurl TEXT,
+	sitename TEXT,
+	fetchdate TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
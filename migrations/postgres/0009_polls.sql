@@ -0,0 +1,30 @@
+create table if not exists polls(
+	brainfartid UUID PRIMARY KEY,
+	closedate TIMESTAMPTZ,
+	multiplechoice BOOLEAN NOT NULL DEFAULT FALSE,
+	CONSTRAINT fk_brainfart FOREIGN KEY(brainfartid) REFERENCES brainfarts(id)
+);
+create table if not exists polloptions(
+	brainfartid UUID NOT NULL,
+	position INTEGER NOT NULL,
+	text VARCHAR(100) NOT NULL,
+	PRIMARY KEY(brainfartid, position),
+	CONSTRAINT fk_poll FOREIGN KEY(brainfartid) REFERENCES polls(brainfartid)
+);
+create table if not exists pollballots(
+	id UUID DEFAULT gen_random_uuid() PRIMARY KEY,
+	brainfartid UUID NOT NULL,
+	brainid UUID NOT NULL,
+	votedate TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+	CONSTRAINT fk_poll FOREIGN KEY(brainfartid) REFERENCES polls(brainfartid),
+	CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id),
+	CONSTRAINT one_ballot_per_brain UNIQUE(brainfartid, brainid)
+);
+create table if not exists pollvotes(
+	ballotid UUID NOT NULL,
+	brainfartid UUID NOT NULL,
+	position INTEGER NOT NULL,
+	PRIMARY KEY(ballotid, position),
+	CONSTRAINT fk_ballot FOREIGN KEY(ballotid) REFERENCES pollballots(id),
+	CONSTRAINT fk_option FOREIGN KEY(brainfartid, position) REFERENCES polloptions(brainfartid, position)
+);
@@ -0,0 +1,2 @@
+alter table brainfarts add column if not exists originalid UUID CONSTRAINT fk_original REFERENCES brainfarts(id);
+create unique index if not exists one_rebrainfart_per_brain ON brainfarts(originalid, mastermind) WHERE content = '';
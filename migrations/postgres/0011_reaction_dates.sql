@@ -0,0 +1,2 @@
+-- Reactions from before this have no date of their own, so they count as made now.
+alter table mindsblownbyfarts add column if not exists reactiondate TIMESTAMPTZ NOT NULL DEFAULT NOW();
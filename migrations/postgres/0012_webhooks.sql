@@ -0,0 +1,25 @@
+create table if not exists webhooks(
+	id UUID DEFAULT gen_random_uuid() PRIMARY KEY,
+	ownerid UUID NOT NULL,
+	url VARCHAR(2048) NOT NULL,
+	events TEXT NOT NULL,
+	secret TEXT NOT NULL,
+	global BOOLEAN NOT NULL DEFAULT FALSE,
+	createdate TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+	CONSTRAINT fk_owner FOREIGN KEY(ownerid) REFERENCES brains(id)
+);
+create table if not exists webhookdeliveries(
+	id UUID DEFAULT gen_random_uuid() PRIMARY KEY,
+	webhookid UUID NOT NULL,
+	event TEXT NOT NULL,
+	payload TEXT NOT NULL,
+	status TEXT NOT NULL DEFAULT 'pending',
+	attempts INTEGER NOT NULL DEFAULT 0,
+	responsestatus INTEGER,
+	lasterror TEXT,
+	createdate TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+	lastattemptdate TIMESTAMPTZ,
+	nextattemptdate TIMESTAMPTZ,
+	CONSTRAINT fk_webhook FOREIGN KEY(webhookid) REFERENCES webhooks(id)
+);
+create index if not exists due_webhook_deliveries ON webhookdeliveries(nextattemptdate) WHERE status = 'pending';
@@ -0,0 +1,27 @@
+alter table brains add column if not exists deletiondate TIMESTAMPTZ;
+alter table brains add column if not exists deletionmode VARCHAR(16) CHECK (deletionmode in ('delete', 'anonymize'));
+-- Deleting a brain takes what is only its own along, and keeps the rest without it.
+alter table brainfarts drop constraint if exists fk_brains,
+	add CONSTRAINT fk_brains FOREIGN KEY(mastermind) REFERENCES brains(id) ON DELETE SET NULL;
+alter table mindsblownbyfarts drop constraint if exists fk_brain,
+	add CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id) ON DELETE SET NULL;
+alter table hallucinatedfarts drop constraint if exists fk_brain,
+	add CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id) ON DELETE CASCADE;
+alter table reportedfarts drop constraint if exists fk_brain,
+	add CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id) ON DELETE CASCADE;
+alter table brainrelations drop constraint if exists fk_brain,
+	add CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id) ON DELETE CASCADE,
+	drop constraint if exists fk_other_brain,
+	add CONSTRAINT fk_other_brain FOREIGN KEY(otherbrainid) REFERENCES brains(id) ON DELETE CASCADE;
+alter table bookmarkedfarts drop constraint if exists fk_brain,
+	add CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id) ON DELETE CASCADE;
+alter table brainfartdrafts drop constraint if exists fk_brain,
+	add CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id) ON DELETE CASCADE;
+alter table pollballots drop constraint if exists fk_brain,
+	add CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id) ON DELETE CASCADE;
+alter table pollvotes drop constraint if exists fk_ballot,
+	add CONSTRAINT fk_ballot FOREIGN KEY(ballotid) REFERENCES pollballots(id) ON DELETE CASCADE;
+alter table webhooks drop constraint if exists fk_owner,
+	add CONSTRAINT fk_owner FOREIGN KEY(ownerid) REFERENCES brains(id) ON DELETE CASCADE;
+alter table webhookdeliveries drop constraint if exists fk_webhook,
+	add CONSTRAINT fk_webhook FOREIGN KEY(webhookid) REFERENCES webhooks(id) ON DELETE CASCADE;
@@ -0,0 +1 @@
+alter table brains add column disabled BOOLEAN NOT NULL DEFAULT FALSE;
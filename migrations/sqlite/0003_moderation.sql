@@ -0,0 +1,13 @@
+alter table brains add column role VARCHAR(32) NOT NULL DEFAULT 'brain' CHECK (role in ('brain', 'admin'));
+alter table brainfarts add column hidden BOOLEAN NOT NULL DEFAULT FALSE;
+create table reportedfarts(
+	id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' || substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + (abs(random()) % 4), 1) || substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6)))),
+	brainfartid TEXT NOT NULL,
+	brainid TEXT NOT NULL,
+	reason TEXT NOT NULL,
+	reportdate TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+	resolved BOOLEAN NOT NULL DEFAULT FALSE,
+	CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id),
+	CONSTRAINT fk_brainfart FOREIGN KEY(brainfartid) REFERENCES brainfarts(id),
+	CONSTRAINT one_report_per_brain UNIQUE(brainfartid, brainid)
+);
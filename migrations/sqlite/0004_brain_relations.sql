@@ -0,0 +1,10 @@
+create table brainrelations(
+	id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' || substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + (abs(random()) % 4), 1) || substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6)))),
+	brainid TEXT NOT NULL,
+	otherbrainid TEXT NOT NULL,
+	relation VARCHAR(16) NOT NULL CHECK (relation in ('mute', 'block')),
+	createdate TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+	CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id),
+	CONSTRAINT fk_other_brain FOREIGN KEY(otherbrainid) REFERENCES brains(id),
+	CONSTRAINT one_relation_of_each_kind UNIQUE(brainid, otherbrainid, relation)
+);
@@ -0,0 +1,10 @@
+create table brainfartattachments(
+	id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' || substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + (abs(random()) % 4), 1) || substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6)))),
+	brainfartid TEXT NOT NULL,
+	filename VARCHAR(255) NOT NULL,
+	contenttype VARCHAR(255) NOT NULL,
+	size BIGINT NOT NULL,
+	hasthumbnail BOOLEAN NOT NULL DEFAULT FALSE,
+	uploaddate TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+	CONSTRAINT fk_brainfart FOREIGN KEY(brainfartid) REFERENCES brainfarts(id)
+);
@@ -0,0 +1,8 @@
+create table linkpreviews(
+	url VARCHAR(2048) PRIMARY KEY,
+	title TEXT,
+	description TEXT,
+	imageurl TEXT,
+	sitename TEXT,
+	fetchdate TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
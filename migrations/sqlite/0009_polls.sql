@@ -0,0 +1,30 @@
+create table polls(
+	brainfartid TEXT PRIMARY KEY,
+	closedate TEXT,
+	multiplechoice BOOLEAN NOT NULL DEFAULT FALSE,
+	CONSTRAINT fk_brainfart FOREIGN KEY(brainfartid) REFERENCES brainfarts(id)
+);
+create table polloptions(
+	brainfartid TEXT NOT NULL,
+	position INTEGER NOT NULL,
+	text VARCHAR(100) NOT NULL,
+	PRIMARY KEY(brainfartid, position),
+	CONSTRAINT fk_poll FOREIGN KEY(brainfartid) REFERENCES polls(brainfartid)
+);
+create table pollballots(
+	id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' || substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + (abs(random()) % 4), 1) || substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6)))),
+	brainfartid TEXT NOT NULL,
+	brainid TEXT NOT NULL,
+	votedate TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+	CONSTRAINT fk_poll FOREIGN KEY(brainfartid) REFERENCES polls(brainfartid),
+	CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id),
+	CONSTRAINT one_ballot_per_brain UNIQUE(brainfartid, brainid)
+);
+create table pollvotes(
+	ballotid TEXT NOT NULL,
+	brainfartid TEXT NOT NULL,
+	position INTEGER NOT NULL,
+	PRIMARY KEY(ballotid, position),
+	CONSTRAINT fk_ballot FOREIGN KEY(ballotid) REFERENCES pollballots(id),
+	CONSTRAINT fk_option FOREIGN KEY(brainfartid, position) REFERENCES polloptions(brainfartid, position)
+);
@@ -0,0 +1,2 @@
+alter table brainfarts add column originalid TEXT CONSTRAINT fk_original REFERENCES brainfarts(id);
+create unique index one_rebrainfart_per_brain ON brainfarts(originalid, mastermind) WHERE content = '';
@@ -0,0 +1,14 @@
+-- SQLite cannot add a column with a default that is not a constant, so the table is rebuilt.
+-- Reactions from before this have no date of their own, so they count as made now.
+create table mindsblownbyfarts_new(
+	id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' || substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + (abs(random()) % 4), 1) || substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6)))),
+	brainfartid TEXT,
+	brainid TEXT,
+	explosion BOOLEAN,
+	reactiondate TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+	CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id),
+	CONSTRAINT fk_brainfart FOREIGN KEY(brainfartid) REFERENCES brainfarts(id)
+);
+insert into mindsblownbyfarts_new(rowid, id, brainfartid, brainid, explosion) select rowid, id, brainfartid, brainid, explosion from mindsblownbyfarts;
+drop table mindsblownbyfarts;
+alter table mindsblownbyfarts_new rename to mindsblownbyfarts;
@@ -0,0 +1,25 @@
+create table webhooks(
+	id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' || substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + (abs(random()) % 4), 1) || substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6)))),
+	ownerid TEXT NOT NULL,
+	url VARCHAR(2048) NOT NULL,
+	events TEXT NOT NULL,
+	secret TEXT NOT NULL,
+	global BOOLEAN NOT NULL DEFAULT FALSE,
+	createdate TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+	CONSTRAINT fk_owner FOREIGN KEY(ownerid) REFERENCES brains(id)
+);
+create table webhookdeliveries(
+	id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' || substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + (abs(random()) % 4), 1) || substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6)))),
+	webhookid TEXT NOT NULL,
+	event TEXT NOT NULL,
+	payload TEXT NOT NULL,
+	status TEXT NOT NULL DEFAULT 'pending',
+	attempts INTEGER NOT NULL DEFAULT 0,
+	responsestatus INTEGER,
+	lasterror TEXT,
+	createdate TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+	lastattemptdate TEXT,
+	nextattemptdate TEXT,
+	CONSTRAINT fk_webhook FOREIGN KEY(webhookid) REFERENCES webhooks(id)
+);
+create index due_webhook_deliveries ON webhookdeliveries(nextattemptdate) WHERE status = 'pending';
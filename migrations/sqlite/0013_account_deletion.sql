@@ -0,0 +1,149 @@
+alter table brains add column deletiondate TEXT;
+alter table brains add column deletionmode VARCHAR(16) CHECK (deletionmode in ('delete', 'anonymize'));
+-- Deleting a brain takes what is only its own along, and keeps the rest without it. SQLite cannot
+-- change a foreign key, so the tables are rebuilt; the foreign keys are off while migrating.
+create table brainfarts_new(
+	id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' || substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + (abs(random()) % 4), 1) || substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6)))),
+	title VARCHAR(255) NOT NULL,
+	content TEXT NOT NULL,
+	birthdate TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+	mastermind TEXT,
+	hidden BOOLEAN NOT NULL DEFAULT FALSE,
+	publishdate TEXT,
+	originalid TEXT,
+	CONSTRAINT fk_brains FOREIGN KEY(mastermind) REFERENCES brains(id) ON DELETE SET NULL,
+	CONSTRAINT fk_original FOREIGN KEY(originalid) REFERENCES brainfarts(id)
+);
+insert into brainfarts_new(rowid, id, title, content, birthdate, mastermind, hidden, publishdate, originalid) select rowid, id, title, content, birthdate, mastermind, hidden, publishdate, originalid from brainfarts;
+drop table brainfarts;
+alter table brainfarts_new rename to brainfarts;
+create unique index one_rebrainfart_per_brain ON brainfarts(originalid, mastermind) WHERE content = '';
+create table mindsblownbyfarts_new(
+	id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' || substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + (abs(random()) % 4), 1) || substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6)))),
+	brainfartid TEXT,
+	brainid TEXT,
+	explosion BOOLEAN,
+	reactiondate TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+	CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id) ON DELETE SET NULL,
+	CONSTRAINT fk_brainfart FOREIGN KEY(brainfartid) REFERENCES brainfarts(id)
+);
+insert into mindsblownbyfarts_new(rowid, id, brainfartid, brainid, explosion, reactiondate) select rowid, id, brainfartid, brainid, explosion, reactiondate from mindsblownbyfarts;
+drop table mindsblownbyfarts;
+alter table mindsblownbyfarts_new rename to mindsblownbyfarts;
+create table hallucinatedfarts_new(
+	id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' || substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + (abs(random()) % 4), 1) || substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6)))),
+	brainfartid TEXT,
+	brainid TEXT,
+	hallucinationdate TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+	CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id) ON DELETE CASCADE,
+	CONSTRAINT fk_brainfart FOREIGN KEY(brainfartid) REFERENCES brainfarts(id)
+);
+insert into hallucinatedfarts_new(rowid, id, brainfartid, brainid, hallucinationdate) select rowid, id, brainfartid, brainid, hallucinationdate from hallucinatedfarts;
+drop table hallucinatedfarts;
+alter table hallucinatedfarts_new rename to hallucinatedfarts;
+create table reportedfarts_new(
+	id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' || substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + (abs(random()) % 4), 1) || substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6)))),
+	brainfartid TEXT NOT NULL,
+	brainid TEXT NOT NULL,
+	reason TEXT NOT NULL,
+	reportdate TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+	resolved BOOLEAN NOT NULL DEFAULT FALSE,
+	CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id) ON DELETE CASCADE,
+	CONSTRAINT fk_brainfart FOREIGN KEY(brainfartid) REFERENCES brainfarts(id),
+	CONSTRAINT one_report_per_brain UNIQUE(brainfartid, brainid)
+);
+insert into reportedfarts_new(rowid, id, brainfartid, brainid, reason, reportdate, resolved) select rowid, id, brainfartid, brainid, reason, reportdate, resolved from reportedfarts;
+drop table reportedfarts;
+alter table reportedfarts_new rename to reportedfarts;
+create table brainrelations_new(
+	id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' || substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + (abs(random()) % 4), 1) || substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6)))),
+	brainid TEXT NOT NULL,
+	otherbrainid TEXT NOT NULL,
+	relation VARCHAR(16) NOT NULL CHECK (relation in ('mute', 'block')),
+	createdate TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+	CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id) ON DELETE CASCADE,
+	CONSTRAINT fk_other_brain FOREIGN KEY(otherbrainid) REFERENCES brains(id) ON DELETE CASCADE,
+	CONSTRAINT one_relation_of_each_kind UNIQUE(brainid, otherbrainid, relation)
+);
+insert into brainrelations_new(rowid, id, brainid, otherbrainid, relation, createdate) select rowid, id, brainid, otherbrainid, relation, createdate from brainrelations;
+drop table brainrelations;
+alter table brainrelations_new rename to brainrelations;
+create table bookmarkedfarts_new(
+	id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' || substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + (abs(random()) % 4), 1) || substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6)))),
+	brainfartid TEXT NOT NULL,
+	brainid TEXT NOT NULL,
+	bookmarkdate TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+	CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id) ON DELETE CASCADE,
+	CONSTRAINT fk_brainfart FOREIGN KEY(brainfartid) REFERENCES brainfarts(id),
+	CONSTRAINT one_bookmark_per_brain UNIQUE(brainfartid, brainid)
+);
+insert into bookmarkedfarts_new(rowid, id, brainfartid, brainid, bookmarkdate) select rowid, id, brainfartid, brainid, bookmarkdate from bookmarkedfarts;
+drop table bookmarkedfarts;
+alter table bookmarkedfarts_new rename to bookmarkedfarts;
+create table brainfartdrafts_new(
+	id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' || substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + (abs(random()) % 4), 1) || substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6)))),
+	brainid TEXT NOT NULL,
+	title VARCHAR(255) NOT NULL,
+	content TEXT NOT NULL,
+	publishdate TEXT,
+	lastupdatedate TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+	CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id) ON DELETE CASCADE,
+	CONSTRAINT one_draft_per_brain UNIQUE(brainid)
+);
+insert into brainfartdrafts_new(rowid, id, brainid, title, content, publishdate, lastupdatedate) select rowid, id, brainid, title, content, publishdate, lastupdatedate from brainfartdrafts;
+drop table brainfartdrafts;
+alter table brainfartdrafts_new rename to brainfartdrafts;
+create table pollballots_new(
+	id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' || substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + (abs(random()) % 4), 1) || substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6)))),
+	brainfartid TEXT NOT NULL,
+	brainid TEXT NOT NULL,
+	votedate TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+	CONSTRAINT fk_poll FOREIGN KEY(brainfartid) REFERENCES polls(brainfartid),
+	CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id) ON DELETE CASCADE,
+	CONSTRAINT one_ballot_per_brain UNIQUE(brainfartid, brainid)
+);
+insert into pollballots_new(rowid, id, brainfartid, brainid, votedate) select rowid, id, brainfartid, brainid, votedate from pollballots;
+drop table pollballots;
+alter table pollballots_new rename to pollballots;
+create table pollvotes_new(
+	ballotid TEXT NOT NULL,
+	brainfartid TEXT NOT NULL,
+	position INTEGER NOT NULL,
+	PRIMARY KEY(ballotid, position),
+	CONSTRAINT fk_ballot FOREIGN KEY(ballotid) REFERENCES pollballots(id) ON DELETE CASCADE,
+	CONSTRAINT fk_option FOREIGN KEY(brainfartid, position) REFERENCES polloptions(brainfartid, position)
+);
+insert into pollvotes_new(rowid, ballotid, brainfartid, position) select rowid, ballotid, brainfartid, position from pollvotes;
+drop table pollvotes;
+alter table pollvotes_new rename to pollvotes;
+create table webhooks_new(
+	id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' || substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + (abs(random()) % 4), 1) || substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6)))),
+	ownerid TEXT NOT NULL,
+	url VARCHAR(2048) NOT NULL,
+	events TEXT NOT NULL,
+	secret TEXT NOT NULL,
+	global BOOLEAN NOT NULL DEFAULT FALSE,
+	createdate TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+	CONSTRAINT fk_owner FOREIGN KEY(ownerid) REFERENCES brains(id) ON DELETE CASCADE
+);
+insert into webhooks_new(rowid, id, ownerid, url, events, secret, global, createdate) select rowid, id, ownerid, url, events, secret, global, createdate from webhooks;
+drop table webhooks;
+alter table webhooks_new rename to webhooks;
+create table webhookdeliveries_new(
+	id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' || substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + (abs(random()) % 4), 1) || substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6)))),
+	webhookid TEXT NOT NULL,
+	event TEXT NOT NULL,
+	payload TEXT NOT NULL,
+	status TEXT NOT NULL DEFAULT 'pending',
+	attempts INTEGER NOT NULL DEFAULT 0,
+	responsestatus INTEGER,
+	lasterror TEXT,
+	createdate TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+	lastattemptdate TEXT,
+	nextattemptdate TEXT,
+	CONSTRAINT fk_webhook FOREIGN KEY(webhookid) REFERENCES webhooks(id) ON DELETE CASCADE
+);
+insert into webhookdeliveries_new(rowid, id, webhookid, event, payload, status, attempts, responsestatus, lasterror, createdate, lastattemptdate, nextattemptdate) select rowid, id, webhookid, event, payload, status, attempts, responsestatus, lasterror, createdate, lastattemptdate, nextattemptdate from webhookdeliveries;
+drop table webhookdeliveries;
+alter table webhookdeliveries_new rename to webhookdeliveries;
+create index due_webhook_deliveries ON webhookdeliveries(nextattemptdate) WHERE status = 'pending';
@@ -0,0 +1,3 @@
+-- The tags of each brainfart, separated by spaces, so the tag feeds can be read with a query.
+-- The server fills them in on startup for the brainfarts from before this.
+alter table brainfarts add column tags TEXT;
@@ -0,0 +1,3 @@
+-- Feed tokens carry the generation of the brain's feed tokens they were handed out in, so a
+-- brain revokes all of them by moving on to the next generation.
+alter table brains add column feedtokengeneration INTEGER NOT NULL DEFAULT 0;
@@ -1,7 +1,14 @@
+-- Creates a new database. Older databases are brought up to date by migrations/postgres, which
+-- the server runs when it connects, so every change here needs a migration there too.
 create table brains(
 	id UUID DEFAULT gen_random_uuid() PRIMARY KEY,
 	brainname VARCHAR(255) NOT NULL UNIQUE,
 	password VARCHAR(255) NOT NULL,
+	disabled BOOLEAN NOT NULL DEFAULT FALSE,
+	role VARCHAR(32) NOT NULL DEFAULT 'brain' CHECK (role in ('brain', 'admin')),
+	deletiondate TIMESTAMPTZ,
+	deletionmode VARCHAR(16) CHECK (deletionmode in ('delete', 'anonymize')),
+	feedtokengeneration BIGINT NOT NULL DEFAULT 0,
 	birthdate TIMESTAMPTZ NOT NULL DEFAULT NOW(),
 	lastupdatedate TIMESTAMPTZ NOT NULL DEFAULT NOW()
 );
@@ -11,7 +18,12 @@ create table brainfarts(
 	content TEXT NOT NULL,
 	birthdate TIMESTAMPTZ NOT NULL DEFAULT NOW(),
 	mastermind UUID,
-	CONSTRAINT fk_brains FOREIGN KEY(mastermind) REFERENCES brains(id)
+	hidden BOOLEAN NOT NULL DEFAULT FALSE,
+	publishdate TIMESTAMPTZ,
+	originalid UUID,
+	tags TEXT,
+	CONSTRAINT fk_brains FOREIGN KEY(mastermind) REFERENCES brains(id) ON DELETE SET NULL,
+	CONSTRAINT fk_original FOREIGN KEY(originalid) REFERENCES brainfarts(id)
 	);
 
 create table mindsblownbyfarts(
@@ -19,7 +31,8 @@ create table mindsblownbyfarts(
 	brainfartid UUID,
 	brainid UUID,
 	explosion bool,
-	CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id),
+	reactiondate TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+	CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id) ON DELETE SET NULL,
 	CONSTRAINT fk_brainfart FOREIGN KEY(brainfartid) REFERENCES brainfarts(id)
 );
 create table hallucinatedfarts(
@@ -27,6 +40,120 @@ create table hallucinatedfarts(
 	brainfartid UUID,
 	brainid UUID,
 	hallucinationdate TIMESTAMPTZ NOT NULL DEFAULT NOW(),
-	CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id),
+	CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id) ON DELETE CASCADE,
 	CONSTRAINT fk_brainfart FOREIGN KEY(brainfartid) REFERENCES brainfarts(id)
-);
\ No newline at end of file
+);
+create table reportedfarts(
+	id UUID DEFAULT gen_random_uuid() PRIMARY KEY,
+	brainfartid UUID NOT NULL,
+	brainid UUID NOT NULL,
+	reason TEXT NOT NULL,
+	reportdate TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+	resolved BOOLEAN NOT NULL DEFAULT FALSE,
+	CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id) ON DELETE CASCADE,
+	CONSTRAINT fk_brainfart FOREIGN KEY(brainfartid) REFERENCES brainfarts(id),
+	CONSTRAINT one_report_per_brain UNIQUE(brainfartid, brainid)
+);
+create table brainrelations(
+	id UUID DEFAULT gen_random_uuid() PRIMARY KEY,
+	brainid UUID NOT NULL,
+	otherbrainid UUID NOT NULL,
+	relation VARCHAR(16) NOT NULL CHECK (relation in ('mute', 'block')),
+	createdate TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+	CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id) ON DELETE CASCADE,
+	CONSTRAINT fk_other_brain FOREIGN KEY(otherbrainid) REFERENCES brains(id) ON DELETE CASCADE,
+	CONSTRAINT one_relation_of_each_kind UNIQUE(brainid, otherbrainid, relation)
+);
+create table bookmarkedfarts(
+	id UUID DEFAULT gen_random_uuid() PRIMARY KEY,
+	brainfartid UUID NOT NULL,
+	brainid UUID NOT NULL,
+	bookmarkdate TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+	CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id) ON DELETE CASCADE,
+	CONSTRAINT fk_brainfart FOREIGN KEY(brainfartid) REFERENCES brainfarts(id),
+	CONSTRAINT one_bookmark_per_brain UNIQUE(brainfartid, brainid)
+);
+create table brainfartdrafts(
+	id UUID DEFAULT gen_random_uuid() PRIMARY KEY,
+	brainid UUID NOT NULL,
+	title VARCHAR(255) NOT NULL,
+	content TEXT NOT NULL,
+	publishdate TIMESTAMPTZ,
+	lastupdatedate TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+	CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id) ON DELETE CASCADE,
+	CONSTRAINT one_draft_per_brain UNIQUE(brainid)
+);
+create table brainfartattachments(
+	id UUID DEFAULT gen_random_uuid() PRIMARY KEY,
+	brainfartid UUID NOT NULL,
+	filename VARCHAR(255) NOT NULL,
+	contenttype VARCHAR(255) NOT NULL,
+	size BIGINT NOT NULL,
+	hasthumbnail BOOLEAN NOT NULL DEFAULT FALSE,
+	uploaddate TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+	CONSTRAINT fk_brainfart FOREIGN KEY(brainfartid) REFERENCES brainfarts(id)
+);
+create table linkpreviews(
+	url VARCHAR(2048) PRIMARY KEY,
+	title TEXT,
+	description TEXT,
+	imageurl TEXT,
+	sitename TEXT,
+	fetchdate TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+create table polls(
+	brainfartid UUID PRIMARY KEY,
+	closedate TIMESTAMPTZ,
+	multiplechoice BOOLEAN NOT NULL DEFAULT FALSE,
+	CONSTRAINT fk_brainfart FOREIGN KEY(brainfartid) REFERENCES brainfarts(id)
+);
+create table polloptions(
+	brainfartid UUID NOT NULL,
+	position INTEGER NOT NULL,
+	text VARCHAR(100) NOT NULL,
+	PRIMARY KEY(brainfartid, position),
+	CONSTRAINT fk_poll FOREIGN KEY(brainfartid) REFERENCES polls(brainfartid)
+);
+create table pollballots(
+	id UUID DEFAULT gen_random_uuid() PRIMARY KEY,
+	brainfartid UUID NOT NULL,
+	brainid UUID NOT NULL,
+	votedate TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+	CONSTRAINT fk_poll FOREIGN KEY(brainfartid) REFERENCES polls(brainfartid),
+	CONSTRAINT fk_brain FOREIGN KEY(brainid) REFERENCES brains(id) ON DELETE CASCADE,
+	CONSTRAINT one_ballot_per_brain UNIQUE(brainfartid, brainid)
+);
+create table pollvotes(
+	ballotid UUID NOT NULL,
+	brainfartid UUID NOT NULL,
+	position INTEGER NOT NULL,
+	PRIMARY KEY(ballotid, position),
+	CONSTRAINT fk_ballot FOREIGN KEY(ballotid) REFERENCES pollballots(id) ON DELETE CASCADE,
+	CONSTRAINT fk_option FOREIGN KEY(brainfartid, position) REFERENCES polloptions(brainfartid, position)
+);
+create unique index one_rebrainfart_per_brain ON brainfarts(originalid, mastermind) WHERE content = '';
+create table webhooks(
+	id UUID DEFAULT gen_random_uuid() PRIMARY KEY,
+	ownerid UUID NOT NULL,
+	url VARCHAR(2048) NOT NULL,
+	events TEXT NOT NULL,
+	secret TEXT NOT NULL,
+	global BOOLEAN NOT NULL DEFAULT FALSE,
+	createdate TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+	CONSTRAINT fk_owner FOREIGN KEY(ownerid) REFERENCES brains(id) ON DELETE CASCADE
+);
+create table webhookdeliveries(
+	id UUID DEFAULT gen_random_uuid() PRIMARY KEY,
+	webhookid UUID NOT NULL,
+	event TEXT NOT NULL,
+	payload TEXT NOT NULL,
+	status TEXT NOT NULL DEFAULT 'pending',
+	attempts INTEGER NOT NULL DEFAULT 0,
+	responsestatus INTEGER,
+	lasterror TEXT,
+	createdate TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+	lastattemptdate TIMESTAMPTZ,
+	nextattemptdate TIMESTAMPTZ,
+	CONSTRAINT fk_webhook FOREIGN KEY(webhookid) REFERENCES webhooks(id) ON DELETE CASCADE
+);
+create index due_webhook_deliveries ON webhookdeliveries(nextattemptdate) WHERE status = 'pending';
\ No newline at end of file
@@ -0,0 +1,4 @@
+// The migrations are embedded by `sqlx::migrate!`, which does not notice new files by itself.
+fn main() {
+    println!("cargo:rerun-if-changed=../migrations");
+}
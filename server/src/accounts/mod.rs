@@ -0,0 +1,167 @@
+//! Brains deleting themselves. A deletion waits out a grace period, during which it can be
+//! called off, before the deleter removes the brain for good.
+
+use std::time::Duration;
+
+use axum::{
+    extract::{rejection::JsonRejection, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::Local;
+use shared::{BrainDeletion, DeleteBrainRequest, ProveOwnsBrainRequest};
+use tokio::task::JoinHandle;
+
+use crate::{
+    attachments,
+    authentication::JwtDataHolder,
+    database::repository::SharedRepository,
+    error_responders,
+    storage::{AttachmentStorage, SharedStorage},
+};
+
+/// How often the deleter looks for brains whose grace period has run out.
+pub const DELETION_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Clone)]
+pub struct AccountSettings {
+    /// How long a brain has to change its mind after asking to be deleted.
+    pub deletion_grace: chrono::Duration,
+}
+
+/// Deletes the brains that are due, along with the files attached to their deleted brainfarts.
+/// Returns how many were deleted.
+pub async fn delete_due_brains(
+    repository: &SharedRepository,
+    storage: &dyn AttachmentStorage,
+    now: chrono::DateTime<Local>,
+) -> usize {
+    let Some(due) = repository.get_due_brain_deletions(now).await else {
+        log::warn!("Reading the brains due for deletion failed");
+        return 0;
+    };
+    let mut deleted = 0;
+    for brain_id in due.iter() {
+        let Some(deletion) = repository.get_brain_deletion(brain_id).await else {
+            continue;
+        };
+        match repository.delete_brain(brain_id, deletion.mode).await {
+            Some(attachments) => {
+                attachments::delete_files(storage, &attachments).await;
+                deleted += 1;
+            }
+            None => log::warn!("Deleting the brain {} failed", brain_id),
+        }
+    }
+    deleted
+}
+
+/// Runs [`delete_due_brains`] every `interval` for as long as the server does.
+pub fn spawn_deleter(
+    repository: SharedRepository,
+    storage: SharedStorage,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let deleted = delete_due_brains(&repository, storage.as_ref(), Local::now()).await;
+            if deleted > 0 {
+                log::info!("Deleted {} brains", deleted);
+            }
+        }
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/account/deletion",
+    tag = "brains",
+    responses(
+        (status = 200, description = "Your pending deletion", body = BrainDeletion),
+        (status = 404, description = "Your brain is not about to be deleted", body = String)
+    ),
+    security(("bearer" = []))
+)]
+pub async fn get_deletion(
+    State(repository): State<SharedRepository>,
+    claims: JwtDataHolder,
+) -> impl IntoResponse {
+    match repository.get_brain_deletion(&claims.get_id()).await {
+        Some(deletion) => Ok((StatusCode::OK, Json(deletion))),
+        None => Err((
+            StatusCode::NOT_FOUND,
+            "Your brain is not about to be deleted".to_string(),
+        )),
+    }
+}
+
+/// Asking again replaces the pending deletion, and starts the grace period over.
+#[utoipa::path(
+    put,
+    path = "/api/v1/account/deletion",
+    tag = "brains",
+    request_body = DeleteBrainRequest,
+    responses(
+        (status = 202, description = "Your brain will be deleted", body = BrainDeletion),
+        (status = 403, description = "The password is wrong", body = String)
+    ),
+    security(("bearer" = []))
+)]
+pub async fn request_deletion(
+    State(repository): State<SharedRepository>,
+    State(settings): State<AccountSettings>,
+    claims: JwtDataHolder,
+    result: Result<Json<DeleteBrainRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    let payload = match result {
+        Ok(Json(payload)) => payload,
+        Err(err) => return Err(error_responders::post_error_responder(err)),
+    };
+    let brain_id = claims.get_id();
+    let proof = ProveOwnsBrainRequest::create(claims.information.brainname, payload.password);
+    if repository.verify_password(&proof).await != Some(brain_id) {
+        return Err((StatusCode::FORBIDDEN, "The password is wrong".to_string()));
+    }
+    let deletion = BrainDeletion {
+        mode: payload.mode,
+        delete_at: Local::now() + settings.deletion_grace,
+    };
+    if repository
+        .request_brain_deletion(&brain_id, &deletion)
+        .await
+    {
+        Ok((StatusCode::ACCEPTED, Json(deletion)))
+    } else {
+        Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Something went wrong scheduling the deletion".to_string(),
+        ))
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/account/deletion",
+    tag = "brains",
+    responses(
+        (status = 200, description = "The deletion was called off"),
+        (status = 404, description = "Your brain is not about to be deleted", body = String)
+    ),
+    security(("bearer" = []))
+)]
+pub async fn cancel_deletion(
+    State(repository): State<SharedRepository>,
+    claims: JwtDataHolder,
+) -> impl IntoResponse {
+    if repository.cancel_brain_deletion(&claims.get_id()).await {
+        Ok((StatusCode::OK, Json(())))
+    } else {
+        Err((
+            StatusCode::NOT_FOUND,
+            "Your brain is not about to be deleted".to_string(),
+        ))
+    }
+}
@@ -0,0 +1,195 @@
+use std::path::PathBuf;
+
+use clap::Subcommand;
+use shared::{BrainExport, RegisterBrainRequest, Role, EXPORT_VERSION};
+use sqlx::types::Uuid;
+
+use crate::{attachments, database::repository::Repository, storage::AttachmentStorage};
+
+#[derive(Subcommand, Debug)]
+pub enum BrainsCommand {
+    /// List every brain
+    List,
+    /// Register a new brain
+    Create {
+        brainname: String,
+        #[clap(long, env = "BRAINS_PASSWORD", hide_env_values = true)]
+        password: String,
+    },
+    /// Stop a brain from logging in or using its tokens
+    Disable { brainname: String },
+    /// Allow a disabled brain to log in again
+    Enable { brainname: String },
+    /// Give a brain a new password
+    ResetPassword {
+        brainname: String,
+        #[clap(long, env = "BRAINS_PASSWORD", hide_env_values = true)]
+        password: String,
+    },
+    /// Make a brain an admin, or an ordinary brain again
+    SetRole {
+        brainname: String,
+        /// brain or admin
+        role: Role,
+    },
+    /// Restore a brain from an export, keeping its ids and birthdates
+    Import {
+        /// The JSON file downloaded from /api/v1/export
+        path: PathBuf,
+        #[clap(long, env = "BRAINS_PASSWORD", hide_env_values = true)]
+        password: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BrainfartsCommand {
+    /// Delete a brainfart together with its reactions, views and attachments
+    Delete { brainfart_id: String },
+}
+
+#[derive(Debug)]
+pub enum AdminCommand {
+    Brains(BrainsCommand),
+    Brainfarts(BrainfartsCommand),
+    Stats,
+}
+
+/// Runs an administration command and returns what should be printed to the operator.
+pub async fn run(
+    command: AdminCommand,
+    repository: &dyn Repository,
+    storage: &dyn AttachmentStorage,
+) -> Result<String, String> {
+    match command {
+        AdminCommand::Brains(BrainsCommand::List) => {
+            let brains = repository
+                .list_brains()
+                .await
+                .ok_or("Could not list the brains")?;
+            Ok(brains
+                .iter()
+                .map(|brain| {
+                    format!(
+                        "{}  {}  {:<8}  {:<5}  {}\n",
+                        brain.id,
+                        brain.birthdate.format("%Y/%m/%d %H:%M"),
+                        if brain.disabled { "disabled" } else { "active" },
+                        brain.role,
+                        brain.brainname
+                    )
+                })
+                .collect())
+        }
+        AdminCommand::Brains(BrainsCommand::Create {
+            brainname,
+            password,
+        }) => {
+            if !RegisterBrainRequest::validate(&brainname, &password, &password) {
+                return Err("The brainname and the password must not be empty".to_string());
+            }
+            let request =
+                RegisterBrainRequest::create(brainname.clone(), password.clone(), password);
+            let response = repository
+                .create_brain(&request)
+                .await
+                .ok_or(format!("Could not create the brain {}", brainname))?;
+            Ok(format!(
+                "Created the brain {} ({})\n",
+                brainname, response.uuid
+            ))
+        }
+        AdminCommand::Brains(BrainsCommand::Disable { brainname }) => {
+            if repository.set_brain_disabled(&brainname, true).await {
+                Ok(format!("Disabled the brain {}\n", brainname))
+            } else {
+                Err(format!("There is no brain called {}", brainname))
+            }
+        }
+        AdminCommand::Brains(BrainsCommand::Enable { brainname }) => {
+            if repository.set_brain_disabled(&brainname, false).await {
+                Ok(format!("Enabled the brain {}\n", brainname))
+            } else {
+                Err(format!("There is no brain called {}", brainname))
+            }
+        }
+        AdminCommand::Brains(BrainsCommand::ResetPassword {
+            brainname,
+            password,
+        }) => {
+            if password.is_empty() {
+                return Err("The password must not be empty".to_string());
+            }
+            if repository.reset_password(&brainname, &password).await {
+                Ok(format!("Reset the password of {}\n", brainname))
+            } else {
+                Err(format!("There is no brain called {}", brainname))
+            }
+        }
+        AdminCommand::Brains(BrainsCommand::SetRole { brainname, role }) => {
+            if repository.set_brain_role(&brainname, role).await {
+                Ok(format!("{} now has the role {}\n", brainname, role))
+            } else {
+                Err(format!("There is no brain called {}", brainname))
+            }
+        }
+        AdminCommand::Brains(BrainsCommand::Import { path, password }) => {
+            if password.is_empty() {
+                return Err("The password must not be empty".to_string());
+            }
+            let text = std::fs::read_to_string(&path)
+                .map_err(|err| format!("Could not read {}: {}", path.display(), err))?;
+            let archive: BrainExport = serde_json::from_str(&text)
+                .map_err(|err| format!("{} is not an export: {}", path.display(), err))?;
+            if archive.version != EXPORT_VERSION {
+                return Err(format!(
+                    "The export has version {}, but only version {} can be imported",
+                    archive.version, EXPORT_VERSION
+                ));
+            }
+            let summary = repository
+                .import_brain(&archive, &password)
+                .await
+                .ok_or(format!(
+                    "Could not import {}. The brain or some of its brainfarts may already exist",
+                    archive.brain.brainname
+                ))?;
+            Ok(format!(
+                "Imported the brain {} ({}) with {} brainfarts, {} reactions and {} views\n",
+                archive.brain.brainname,
+                archive.brain.id,
+                summary.brainfarts,
+                summary.reactions,
+                summary.views
+            ))
+        }
+        AdminCommand::Brainfarts(BrainfartsCommand::Delete { brainfart_id }) => {
+            let id = Uuid::parse_str(&brainfart_id)
+                .map_err(|_| format!("{} is not a valid brainfart id", brainfart_id))?;
+            let attachments = repository.get_attachments(&id).await.unwrap_or_default();
+            if repository.delete_brainfart(&id).await {
+                attachments::delete_files(storage, &attachments).await;
+                Ok(format!("Deleted the brainfart {}\n", brainfart_id))
+            } else {
+                Err(format!(
+                    "There is no brainfart with the id {}",
+                    brainfart_id
+                ))
+            }
+        }
+        AdminCommand::Stats => {
+            let statistics = repository
+                .get_statistics()
+                .await
+                .ok_or("Could not read the statistics")?;
+            Ok(format!(
+                "brains:      {} ({} disabled)\nbrainfarts:  {}\nexplosions:  {}\nimplosions:  {}\nviews:       {}\n",
+                statistics.brains,
+                statistics.disabled_brains,
+                statistics.brainfarts,
+                statistics.explosions,
+                statistics.implosions,
+                statistics.views
+            ))
+        }
+    }
+}
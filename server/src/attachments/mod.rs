@@ -0,0 +1,405 @@
+use std::io::Cursor;
+
+use axum::{
+    extract::{Multipart, Path, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use image::{ImageFormat, ImageOutputFormat};
+use shared::{AttachmentInformation, MAX_ATTACHMENTS_PER_BRAINFART};
+use sqlx::types::Uuid;
+
+use crate::{
+    authentication::JwtDataHolder,
+    database::repository::{AttachedFile, NewAttachment, SharedRepository},
+    storage::{AttachmentStorage, SharedStorage},
+};
+
+const THUMBNAIL_SIZE: u32 = 256;
+
+#[derive(Clone)]
+pub struct AttachmentSettings {
+    pub storage: SharedStorage,
+    /// The largest file that can be attached.
+    pub max_bytes: usize,
+}
+
+impl AttachmentSettings {
+    /// Room for the most files a single upload may carry, plus the rest of the form.
+    pub fn body_limit(&self) -> usize {
+        self.max_bytes * MAX_ATTACHMENTS_PER_BRAINFART + 64 * 1024
+    }
+}
+
+pub fn thumbnail_key(attachment_id: &str) -> String {
+    format!("{}-thumbnail", attachment_id)
+}
+
+/// Removes the files of the attachments from the storage, for brainfarts that are deleted.
+pub async fn delete_files(storage: &dyn AttachmentStorage, attachments: &[AttachmentInformation]) {
+    for attachment in attachments {
+        if !storage.delete(&attachment.id).await {
+            log::warn!("Deleting the attachment {} failed", attachment.id);
+        }
+        if attachment.has_thumbnail {
+            storage.delete(&thumbnail_key(&attachment.id)).await;
+        }
+    }
+}
+
+struct Upload {
+    filename: String,
+    content_type: String,
+    content: Vec<u8>,
+    thumbnail: Option<Vec<u8>>,
+}
+
+/// Keeps the last path component of what the browser sent, since some send the whole path.
+fn clean_filename(filename: Option<&str>) -> String {
+    let filename = filename
+        .unwrap_or_default()
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .trim();
+    if filename.is_empty() {
+        "attachment".to_string()
+    } else {
+        filename.chars().take(255).collect()
+    }
+}
+
+/// `text/plain; charset=utf-8` counts as `text/plain`.
+fn clean_content_type(content_type: Option<&str>) -> String {
+    content_type
+        .unwrap_or("application/octet-stream")
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+/// Scales the image down to fit a `THUMBNAIL_SIZE` square and encodes it as PNG. None if the file
+/// is not an image of the type it claims to be.
+fn create_thumbnail(content: &[u8], content_type: &str) -> Option<Vec<u8>> {
+    let format = ImageFormat::from_mime_type(content_type)?;
+    let image = image::load_from_memory_with_format(content, format).ok()?;
+    let mut thumbnail = Cursor::new(vec![]);
+    image
+        .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+        .write_to(&mut thumbnail, ImageOutputFormat::Png)
+        .ok()?;
+    Some(thumbnail.into_inner())
+}
+
+async fn read_upload(
+    mut multipart: Multipart,
+    max_bytes: usize,
+) -> Result<(Option<String>, Vec<Upload>), (StatusCode, String)> {
+    let read_error = |_| {
+        (
+            StatusCode::BAD_REQUEST,
+            "Failed to read the upload".to_string(),
+        )
+    };
+    let mut brainfart_id = None;
+    let mut uploads = vec![];
+    while let Some(mut field) = multipart.next_field().await.map_err(read_error)? {
+        match field.name() {
+            Some("brainfart_id") => brainfart_id = Some(field.text().await.map_err(read_error)?),
+            Some("file") => {
+                let filename = clean_filename(field.file_name());
+                let content_type = clean_content_type(field.content_type());
+                let mut content = vec![];
+                while let Some(chunk) = field.chunk().await.map_err(read_error)? {
+                    if content.len() + chunk.len() > max_bytes {
+                        return Err((
+                            StatusCode::PAYLOAD_TOO_LARGE,
+                            format!("{} is larger than {} bytes", filename, max_bytes),
+                        ));
+                    }
+                    content.extend_from_slice(&chunk);
+                }
+                uploads.push(Upload {
+                    filename,
+                    content_type,
+                    content,
+                    thumbnail: None,
+                });
+            }
+            _ => {}
+        }
+    }
+    Ok((brainfart_id, uploads))
+}
+
+/// Checks the type of every file and makes the thumbnails of the images, before anything is
+/// stored.
+async fn prepare_uploads(uploads: Vec<Upload>) -> Result<Vec<Upload>, (StatusCode, String)> {
+    let mut prepared = vec![];
+    for mut upload in uploads {
+        if !AttachmentInformation::is_allowed_type(&upload.content_type) {
+            return Err((
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                format!("{} files cannot be attached", upload.content_type),
+            ));
+        }
+        if upload.content_type.starts_with("image/") {
+            let (content, content_type) = (upload.content, upload.content_type);
+            let (content, content_type, thumbnail) = tokio::task::spawn_blocking(move || {
+                let thumbnail = create_thumbnail(&content, &content_type);
+                (content, content_type, thumbnail)
+            })
+            .await
+            .map_err(|_| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Something went wrong reading the image".to_string(),
+                )
+            })?;
+            if thumbnail.is_none() {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    format!("{} is not a valid image", upload.filename),
+                ));
+            }
+            upload.content = content;
+            upload.content_type = content_type;
+            upload.thumbnail = thumbnail;
+        }
+        prepared.push(upload);
+    }
+    Ok(prepared)
+}
+
+/// Records the attachment and stores its files. Nothing is left behind if storing fails.
+async fn store_upload(
+    repository: &SharedRepository,
+    storage: &dyn AttachmentStorage,
+    brainfart_id: &Uuid,
+    upload: &Upload,
+) -> bool {
+    let attachment = NewAttachment {
+        filename: upload.filename.clone(),
+        content_type: upload.content_type.clone(),
+        size: upload.content.len() as i64,
+        has_thumbnail: upload.thumbnail.is_some(),
+    };
+    let attachment_id = match repository.add_attachment(&attachment, brainfart_id).await {
+        Some(response) => response.uuid,
+        None => return false,
+    };
+    let key = attachment_id.to_string();
+    let stored = storage.store(&key, &upload.content).await
+        && match &upload.thumbnail {
+            Some(thumbnail) => storage.store(&thumbnail_key(&key), thumbnail).await,
+            None => true,
+        };
+    if !stored {
+        storage.delete(&key).await;
+        repository.remove_attachment(&attachment_id).await;
+    }
+    stored
+}
+
+/// Takes a `multipart/form-data` upload with a `brainfart_id` field and one or more `file` fields.
+/// Only the mastermind can attach files. Answers with the brainfart including its attachments.
+#[utoipa::path(
+    post,
+    path = "/api/v1/attachments",
+    tag = "attachments",
+    request_body(content = String, description = "A `brainfart_id` field and one `file` field for each file", content_type = "multipart/form-data"),
+    responses(
+        (status = 201, description = "The brainfart with its attachments", body = BrainfartInformation),
+        (status = 400, description = "Invalid brainfart id or no files", body = String),
+        (status = 403, description = "The brainfart is not yours", body = String),
+        (status = 413, description = "A file is too large", body = String),
+        (status = 415, description = "A file has a type that is not allowed", body = String)
+    ),
+    security(("bearer" = []))
+)]
+pub async fn add_attachments(
+    State(repository): State<SharedRepository>,
+    State(settings): State<AttachmentSettings>,
+    claims: JwtDataHolder,
+    multipart: Multipart,
+) -> impl IntoResponse {
+    let (brainfart_id, uploads) = read_upload(multipart, settings.max_bytes).await?;
+    let brainfart_id = brainfart_id
+        .and_then(|brainfart_id| Uuid::parse_str(brainfart_id.trim()).ok())
+        .ok_or((StatusCode::BAD_REQUEST, "Invalid brainfart id".to_string()))?;
+    if uploads.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "There are no files in the upload".to_string(),
+        ));
+    }
+    let brain_id = claims.get_id();
+    let brainfart = repository
+        .get_brainfart(&brainfart_id, &brain_id)
+        .await
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            "There is no such brainfart".to_string(),
+        ))?;
+    if !repository.is_mastermind(&brainfart_id, &brain_id).await {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only the mastermind can attach files to a brainfart".to_string(),
+        ));
+    }
+    if brainfart.attachments.len() + uploads.len() > MAX_ATTACHMENTS_PER_BRAINFART {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "A brainfart can have at most {} attachments",
+                MAX_ATTACHMENTS_PER_BRAINFART
+            ),
+        ));
+    }
+
+    for upload in prepare_uploads(uploads).await?.iter() {
+        if !store_upload(
+            &repository,
+            settings.storage.as_ref(),
+            &brainfart_id,
+            upload,
+        )
+        .await
+        {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Something went wrong storing {}", upload.filename),
+            ));
+        }
+    }
+
+    match repository.get_brainfart(&brainfart_id, &brain_id).await {
+        Some(brainfart) => Ok((StatusCode::CREATED, Json(brainfart))),
+        None => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Something went wrong reading the brainfart".to_string(),
+        )),
+    }
+}
+
+/// Quotes are not allowed in the header, and not every browser decodes anything but ASCII.
+fn content_disposition(attachment: &AttachmentInformation) -> String {
+    let filename: String = attachment
+        .filename
+        .chars()
+        .map(|character| match character {
+            ' '..='~' if character != '"' && character != '\\' => character,
+            _ => '_',
+        })
+        .collect();
+    let disposition = if attachment.is_image() {
+        "inline"
+    } else {
+        "attachment"
+    };
+    format!("{}; filename=\"{}\"", disposition, filename)
+}
+
+/// The file is only served to those who get to see its brainfart, and anyone without a token sees
+/// it like a brain nobody has muted or blocked.
+async fn serve_file(
+    repository: SharedRepository,
+    storage: SharedStorage,
+    viewer: Option<JwtDataHolder>,
+    attachment_id: String,
+    thumbnail: bool,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let not_found = || {
+        (
+            StatusCode::NOT_FOUND,
+            "There is no such attachment".to_string(),
+        )
+    };
+    let attachment_id = Uuid::parse_str(&attachment_id).map_err(|_| not_found())?;
+    let AttachedFile {
+        brainfart_id,
+        attachment,
+    } = repository
+        .get_attachment(&attachment_id)
+        .await
+        .ok_or_else(not_found)?;
+    let viewer_id = viewer.map_or_else(Uuid::nil, |viewer| viewer.get_id());
+    repository
+        .get_brainfart(&brainfart_id, &viewer_id)
+        .await
+        .ok_or_else(not_found)?;
+    if thumbnail && !attachment.has_thumbnail {
+        return Err(not_found());
+    }
+    let key = if thumbnail {
+        thumbnail_key(&attachment.id)
+    } else {
+        attachment.id.clone()
+    };
+    let content = storage.load(&key).await.ok_or_else(not_found)?;
+    let content_type = if thumbnail {
+        "image/png".to_string()
+    } else {
+        attachment.content_type.clone()
+    };
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type),
+            (
+                header::CONTENT_DISPOSITION,
+                content_disposition(&attachment),
+            ),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        ],
+        content,
+    ))
+}
+
+/// Needs no token, so that browsers can show the images directly, but then the files of hidden and
+/// scheduled brainfarts are not found.
+#[utoipa::path(
+    get,
+    path = "/api/v1/attachments/{attachment_id}",
+    tag = "attachments",
+    params(
+        ("attachment_id" = String, Path, description = "The id of the attachment")
+    ),
+    responses(
+        (status = 200, description = "The file"),
+        (status = 404, description = "There is no such attachment", body = String)
+    ),
+    security((), ("bearer" = []))
+)]
+pub async fn get_attachment(
+    State(repository): State<SharedRepository>,
+    State(settings): State<AttachmentSettings>,
+    viewer: Option<JwtDataHolder>,
+    Path(attachment_id): Path<String>,
+) -> impl IntoResponse {
+    serve_file(repository, settings.storage, viewer, attachment_id, false).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/attachments/{attachment_id}/thumbnail",
+    tag = "attachments",
+    params(
+        ("attachment_id" = String, Path, description = "The id of the attachment")
+    ),
+    responses(
+        (status = 200, description = "A small PNG of the image"),
+        (status = 404, description = "There is no such thumbnail", body = String)
+    ),
+    security((), ("bearer" = []))
+)]
+pub async fn get_thumbnail(
+    State(repository): State<SharedRepository>,
+    State(settings): State<AttachmentSettings>,
+    viewer: Option<JwtDataHolder>,
+    Path(attachment_id): Path<String>,
+) -> impl IntoResponse {
+    serve_file(repository, settings.storage, viewer, attachment_id, true).await
+}
@@ -1,15 +1,23 @@
+use std::{marker::PhantomData, sync::Arc};
+
 use axum::{
     async_trait,
-    extract::FromRequestParts,
+    extract::{FromRef, FromRequestParts, Query},
     headers::{authorization::Bearer, Authorization},
     http::{request::Parts, StatusCode},
+    response::{IntoResponse, Response},
     TypedHeader,
 };
 use jwt_simple::prelude::*;
-use serde::{Deserialize, Serialize};
-use shared::JwtInformation;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use shared::{JwtInformation, Permission, Role};
 use sqlx::types::Uuid;
 
+use crate::{
+    configuration::{Configuration, ConfigurationError},
+    database::repository::SharedRepository,
+};
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct JwtDataHolder {
     pub information: JwtInformation,
@@ -19,14 +27,17 @@ pub struct JwtDataHolder {
 impl<B> FromRequestParts<B> for JwtDataHolder
 where
     B: Send + Sync,
+    Arc<JwtKeys>: FromRef<B>,
+    SharedRepository: FromRef<B>,
 {
     type Rejection = StatusCode;
     async fn from_request_parts(parts: &mut Parts, state: &B) -> Result<Self, Self::Rejection> {
         if let Ok(TypedHeader(Authorization(bearer))) =
             TypedHeader::<Authorization<Bearer>>::from_request_parts(parts, state).await
         {
-            if let Some(information) = konvertera_jwt(bearer.token()) {
-                Ok(JwtDataHolder { information })
+            let keys = Arc::<JwtKeys>::from_ref(state);
+            if let Some(information) = keys.konvertera_jwt(bearer.token()) {
+                still_active(information, state).await
             } else {
                 Err(StatusCode::UNPROCESSABLE_ENTITY)
             }
@@ -36,6 +47,26 @@ where
     }
 }
 
+/// A token outlives a suspension or a deletion of its brain, and a change of its role, so the
+/// brain is looked up again on every request and the role in the token replaced by its current one.
+async fn still_active<B>(
+    mut information: JwtInformation,
+    state: &B,
+) -> Result<JwtDataHolder, StatusCode>
+where
+    SharedRepository: FromRef<B>,
+{
+    let id = Uuid::parse_str(&information.id).map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?;
+    let repository = SharedRepository::from_ref(state);
+    match repository.get_brain_role(&id).await {
+        Some(role) => {
+            information.role = role;
+            Ok(JwtDataHolder { information })
+        }
+        None => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
 impl JwtDataHolder {
     fn get_audience() -> String {
         "HjärnorFörening".to_string()
@@ -43,58 +74,323 @@ impl JwtDataHolder {
     fn get_issuer() -> String {
         "Brainn".to_string()
     }
+    fn get_feed_audience() -> String {
+        "HjärnorFlöde".to_string()
+    }
     pub fn get_id(&self) -> Uuid {
         Uuid::parse_str(&self.information.id).unwrap()
     }
 }
 
-fn get_hemlighet() -> RS384KeyPair {
-    RS384KeyPair::from_pem(&std::fs::read_to_string("private.pem").unwrap()).unwrap()
+#[derive(Deserialize)]
+struct FeedTokenQuery {
+    token: Option<String>,
+}
+
+/// The claims of a feed token: whose it is, and the generation of the brain's feed tokens it was
+/// handed out in.
+#[derive(Serialize, Deserialize)]
+pub struct FeedTokenInformation {
+    #[serde(flatten)]
+    pub information: JwtInformation,
+    pub generation: i64,
+}
+
+/// Lets feed readers in with a feed token in `?token=`, since most of them cannot send an
+/// `Authorization` header. Without one it answers like `JwtDataHolder`. A feed token of an earlier
+/// generation than the brain's current one was revoked.
+pub struct FeedReader(pub JwtDataHolder);
+
+#[async_trait]
+impl<B> FromRequestParts<B> for FeedReader
+where
+    B: Send + Sync,
+    Arc<JwtKeys>: FromRef<B>,
+    SharedRepository: FromRef<B>,
+{
+    type Rejection = StatusCode;
+    async fn from_request_parts(parts: &mut Parts, state: &B) -> Result<Self, Self::Rejection> {
+        let token = Query::<FeedTokenQuery>::from_request_parts(parts, state)
+            .await
+            .ok()
+            .and_then(|Query(query)| query.token);
+        match token {
+            Some(token) => {
+                let keys = Arc::<JwtKeys>::from_ref(state);
+                let FeedTokenInformation {
+                    information,
+                    generation,
+                } = keys
+                    .konvertera_feed_token(&token)
+                    .ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+                let claims = still_active(information, state).await?;
+                let repository = SharedRepository::from_ref(state);
+                if repository.get_feed_token_generation(&claims.get_id()).await != Some(generation)
+                {
+                    return Err(StatusCode::UNAUTHORIZED);
+                }
+                Ok(FeedReader(claims))
+            }
+            None => JwtDataHolder::from_request_parts(parts, state)
+                .await
+                .map(FeedReader),
+        }
+    }
 }
 
-fn get_hemlighet_public() -> RS384PublicKey {
-    RS384PublicKey::from_pem(&std::fs::read_to_string("public.pem").unwrap()).unwrap()
+/// Why an authorization guard turned a request away.
+#[derive(Debug, PartialEq, Eq)]
+pub enum AuthorizationRejection {
+    /// There was no valid token, answered the same way `JwtDataHolder` answers it.
+    Unauthenticated(StatusCode),
+    /// The token is valid but the brain's role does not allow the request.
+    Forbidden(String),
 }
 
-pub fn create_jwt(id: Uuid, brainname: String) -> String {
-    create_jwt_från_information(
-        id,
-        JwtInformation {
-            id: id.to_string(),
-            brainname: brainname,
-        },
-    )
+impl IntoResponse for AuthorizationRejection {
+    fn into_response(self) -> Response {
+        match self {
+            AuthorizationRejection::Unauthenticated(status) => status.into_response(),
+            AuthorizationRejection::Forbidden(reason) => {
+                (StatusCode::FORBIDDEN, reason).into_response()
+            }
+        }
+    }
 }
 
-fn create_jwt_från_information(id: Uuid, information: JwtInformation) -> String {
-    let claims = Claims::with_custom_claims(
-        information,
-        Duration::from_days(
-            std::env::var("TOKEN_DURATION_DAYS")
-                .expect("TOKEN_DURATION_DAYS environmental variable not set")
-                .parse()
-                .expect("TOKEN_DURATION_DAYS is not an integer (i64)"),
-        ),
-    )
-    .with_audience(JwtDataHolder::get_audience())
-    .with_issuer(JwtDataHolder::get_issuer())
-    .with_subject(id);
-    let token = get_hemlighet().sign(claims);
-    token.unwrap()
+async fn authenticate<B>(
+    parts: &mut Parts,
+    state: &B,
+) -> Result<JwtDataHolder, AuthorizationRejection>
+where
+    B: Send + Sync,
+    Arc<JwtKeys>: FromRef<B>,
+    SharedRepository: FromRef<B>,
+{
+    JwtDataHolder::from_request_parts(parts, state)
+        .await
+        .map_err(AuthorizationRejection::Unauthenticated)
 }
 
-pub fn konvertera_jwt(raw_token: &str) -> Option<JwtInformation> {
-    let mut options = VerificationOptions::default();
-    // reject tokens if they don't include an issuer from that list
-    options.allowed_issuers = Some(HashSet::from_strings(&[JwtDataHolder::get_issuer()]));
-    options.allowed_audiences = Some(HashSet::from_strings(&[JwtDataHolder::get_audience()]));
-    // see the documentation for the full list of available options
+/// Only lets brains with the admin role through.
+pub struct RequiresAdmin(pub JwtDataHolder);
 
-    if let Ok(claims) =
-        get_hemlighet_public().verify_token::<JwtInformation>(raw_token, Some(options))
-    {
-        Some(claims.custom)
-    } else {
-        None
+#[async_trait]
+impl<B> FromRequestParts<B> for RequiresAdmin
+where
+    B: Send + Sync,
+    Arc<JwtKeys>: FromRef<B>,
+    SharedRepository: FromRef<B>,
+{
+    type Rejection = AuthorizationRejection;
+    async fn from_request_parts(parts: &mut Parts, state: &B) -> Result<Self, Self::Rejection> {
+        let claims = authenticate(parts, state).await?;
+        if claims.information.is_admin() {
+            Ok(RequiresAdmin(claims))
+        } else {
+            Err(AuthorizationRejection::Forbidden(
+                "Only admins may do this".to_string(),
+            ))
+        }
     }
 }
+
+/// Names a `Permission` as a type, so it can be asked for with `RequiresPermission`.
+pub trait PermissionMarker: Send + Sync {
+    const PERMISSION: Permission;
+}
+
+pub struct CanReviewReports;
+impl PermissionMarker for CanReviewReports {
+    const PERMISSION: Permission = Permission::ReviewReports;
+}
+
+pub struct CanModerateBrainfarts;
+impl PermissionMarker for CanModerateBrainfarts {
+    const PERMISSION: Permission = Permission::ModerateBrainfarts;
+}
+
+pub struct CanSuspendBrains;
+impl PermissionMarker for CanSuspendBrains {
+    const PERMISSION: Permission = Permission::SuspendBrains;
+}
+
+/// Only lets brains whose role has the permission `P` through, e.g.
+/// `RequiresPermission<CanModerateBrainfarts>`.
+pub struct RequiresPermission<P: PermissionMarker> {
+    pub claims: JwtDataHolder,
+    permission: PhantomData<P>,
+}
+
+#[async_trait]
+impl<B, P> FromRequestParts<B> for RequiresPermission<P>
+where
+    B: Send + Sync,
+    Arc<JwtKeys>: FromRef<B>,
+    SharedRepository: FromRef<B>,
+    P: PermissionMarker,
+{
+    type Rejection = AuthorizationRejection;
+    async fn from_request_parts(parts: &mut Parts, state: &B) -> Result<Self, Self::Rejection> {
+        let claims = authenticate(parts, state).await?;
+        if claims.information.has_permission(P::PERMISSION) {
+            Ok(RequiresPermission {
+                claims,
+                permission: PhantomData,
+            })
+        } else {
+            Err(AuthorizationRejection::Forbidden(format!(
+                "You are not allowed to {}",
+                P::PERMISSION
+            )))
+        }
+    }
+}
+
+/// Feed readers are set up once and then left alone, so feed tokens last a year.
+const FEED_TOKEN_DURATION_DAYS: u64 = 365;
+
+/// The key pair used to sign and verify tokens, together with how long a token stays valid.
+pub struct JwtKeys {
+    hemlighet: RS384KeyPair,
+    hemlighet_public: RS384PublicKey,
+    token_duration_days: u64,
+}
+
+impl JwtKeys {
+    pub fn create(
+        hemlighet: RS384KeyPair,
+        hemlighet_public: RS384PublicKey,
+        token_duration_days: u64,
+    ) -> JwtKeys {
+        JwtKeys {
+            hemlighet,
+            hemlighet_public,
+            token_duration_days,
+        }
+    }
+
+    pub fn load(configuration: &Configuration) -> Result<JwtKeys, ConfigurationError> {
+        let hemlighet = RS384KeyPair::from_pem(&read_key(&configuration.private_key_path)?)
+            .map_err(|err| {
+                ConfigurationError::create(format!(
+                    "The private key {} is not a valid RS384 PEM key: {}",
+                    configuration.private_key_path.display(),
+                    err
+                ))
+            })?;
+        let hemlighet_public = RS384PublicKey::from_pem(&read_key(&configuration.public_key_path)?)
+            .map_err(|err| {
+                ConfigurationError::create(format!(
+                    "The public key {} is not a valid RS384 PEM key: {}",
+                    configuration.public_key_path.display(),
+                    err
+                ))
+            })?;
+        Ok(JwtKeys::create(
+            hemlighet,
+            hemlighet_public,
+            configuration.token_duration_days,
+        ))
+    }
+
+    pub fn create_jwt(&self, id: Uuid, brainname: String, role: Role) -> String {
+        self.create_jwt_från_information(
+            id,
+            JwtInformation {
+                id: id.to_string(),
+                brainname,
+                role,
+            },
+        )
+    }
+
+    /// A feed token is signed like a session token but for another audience, so it cannot be
+    /// used for anything but reading the feeds.
+    pub fn create_feed_token(
+        &self,
+        id: Uuid,
+        brainname: String,
+        role: Role,
+        generation: i64,
+    ) -> String {
+        self.sign(
+            id,
+            FeedTokenInformation {
+                information: JwtInformation {
+                    id: id.to_string(),
+                    brainname,
+                    role,
+                },
+                generation,
+            },
+            JwtDataHolder::get_feed_audience(),
+            FEED_TOKEN_DURATION_DAYS,
+        )
+    }
+
+    fn create_jwt_från_information(&self, id: Uuid, information: JwtInformation) -> String {
+        self.sign(
+            id,
+            information,
+            JwtDataHolder::get_audience(),
+            self.token_duration_days,
+        )
+    }
+
+    fn sign<C: Serialize + DeserializeOwned>(
+        &self,
+        id: Uuid,
+        information: C,
+        audience: String,
+        duration_days: u64,
+    ) -> String {
+        let claims = Claims::with_custom_claims(information, Duration::from_days(duration_days))
+            .with_audience(audience)
+            .with_issuer(JwtDataHolder::get_issuer())
+            .with_subject(id);
+        let token = self.hemlighet.sign(claims);
+        token.unwrap()
+    }
+
+    pub fn konvertera_jwt(&self, raw_token: &str) -> Option<JwtInformation> {
+        self.verify(raw_token, JwtDataHolder::get_audience())
+    }
+
+    pub fn konvertera_feed_token(&self, raw_token: &str) -> Option<FeedTokenInformation> {
+        self.verify(raw_token, JwtDataHolder::get_feed_audience())
+    }
+
+    fn verify<C: Serialize + DeserializeOwned>(
+        &self,
+        raw_token: &str,
+        audience: String,
+    ) -> Option<C> {
+        let options = VerificationOptions {
+            // reject tokens if they don't include an issuer from that list
+            allowed_issuers: Some(HashSet::from_strings(&[JwtDataHolder::get_issuer()])),
+            allowed_audiences: Some(HashSet::from_strings(&[audience])),
+            // see the documentation for the full list of available options
+            ..Default::default()
+        };
+
+        if let Ok(claims) = self
+            .hemlighet_public
+            .verify_token::<C>(raw_token, Some(options))
+        {
+            Some(claims.custom)
+        } else {
+            None
+        }
+    }
+}
+
+fn read_key(path: &std::path::Path) -> Result<String, ConfigurationError> {
+    std::fs::read_to_string(path).map_err(|err| {
+        ConfigurationError::create(format!(
+            "Could not read the key file {}: {}",
+            path.display(),
+            err
+        ))
+    })
+}
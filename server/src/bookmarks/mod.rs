@@ -0,0 +1,79 @@
+use axum::{
+    extract::{rejection::JsonRejection, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use shared::{BookmarkRequest, BrainfartInformation};
+use sqlx::types::Uuid;
+
+use crate::{
+    authentication::JwtDataHolder, database::repository::SharedRepository, error_responders,
+};
+
+fn parse_brainfart_id(brainfart_id: &str) -> Result<Uuid, (StatusCode, String)> {
+    Uuid::parse_str(brainfart_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid brainfart id".to_string()))
+}
+
+async fn bookmarked_brainfart(
+    repository: &SharedRepository,
+    brainfart_id: &Uuid,
+    brain_id: &Uuid,
+) -> Result<Json<BrainfartInformation>, (StatusCode, String)> {
+    repository
+        .get_brainfart(brainfart_id, brain_id)
+        .await
+        .map(Json)
+        .ok_or((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Something went wrong reading the brainfart".to_string(),
+        ))
+}
+
+/// Answers with the brainfart as the requester now sees it, so the frontend can update its icon.
+pub async fn add_bookmark(
+    State(repository): State<SharedRepository>,
+    claims: JwtDataHolder,
+    result: Result<Json<BookmarkRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    let payload = match result {
+        Ok(Json(payload)) => payload,
+        Err(err) => return Err(error_responders::post_error_responder(err)),
+    };
+    let brainfart_id = parse_brainfart_id(&payload.brainfart_id)?;
+    let brain_id = claims.get_id();
+    if repository.add_bookmark(&payload, &brain_id).await.is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            "There is no such brainfart to bookmark".to_string(),
+        ));
+    }
+    Ok((
+        StatusCode::CREATED,
+        bookmarked_brainfart(&repository, &brainfart_id, &brain_id).await?,
+    ))
+}
+
+pub async fn remove_bookmark(
+    State(repository): State<SharedRepository>,
+    claims: JwtDataHolder,
+    result: Result<Json<BookmarkRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    let payload = match result {
+        Ok(Json(payload)) => payload,
+        Err(err) => return Err(error_responders::post_error_responder(err)),
+    };
+    let brainfart_id = parse_brainfart_id(&payload.brainfart_id)?;
+    let brain_id = claims.get_id();
+    if !repository.remove_bookmark(&payload, &brain_id).await {
+        return Err((
+            StatusCode::NOT_FOUND,
+            "You have not bookmarked this brainfart".to_string(),
+        ));
+    }
+    Ok((
+        StatusCode::OK,
+        bookmarked_brainfart(&repository, &brainfart_id, &brain_id).await?,
+    ))
+}
@@ -0,0 +1,406 @@
+use std::{
+    fmt::{self, Display},
+    net::IpAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use clap::{Args, ValueEnum};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+const DEFAULT_CONFIGURATION_FILE: &str = "brains.toml";
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ConfigurationError(String);
+
+impl Display for ConfigurationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl ConfigurationError {
+    pub fn create(message: String) -> ConfigurationError {
+        ConfigurationError(message)
+    }
+}
+
+/// How the log lines are written.
+#[derive(ValueEnum, Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// For people reading the terminal.
+    #[default]
+    Text,
+    /// One JSON object per line, for log collectors.
+    Json,
+}
+
+/// One source of settings. The configuration file, the environment and the command line each
+/// fill in a layer; the command line wins over the environment, which wins over the file.
+#[derive(Args, Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigurationLayer {
+    ///set the log level [default: debug]
+    #[clap(short = 'l', long = "log", env = "BRAINS_LOG", global = true)]
+    pub log_level: Option<String>,
+
+    /// set how the log lines are written [default: text]
+    #[clap(
+        long = "log-format",
+        env = "BRAINS_LOG_FORMAT",
+        global = true,
+        value_enum
+    )]
+    pub log_format: Option<LogFormat>,
+
+    /// send the spans to the OpenTelemetry collector at this OTLP url, e.g. http://localhost:4317,
+    /// in a server built with the `otel` feature
+    #[clap(long = "otel-endpoint", env = "BRAINS_OTEL_ENDPOINT", global = true)]
+    pub otel_endpoint: Option<String>,
+
+    /// set the listen addr [default: ::1]
+    #[clap(short = 'a', long = "addr", env = "BRAINS_ADDR", global = true)]
+    pub addr: Option<String>,
+
+    /// set the listen port [default: 8080]
+    #[clap(short = 'p', long = "port", env = "BRAINS_PORT", global = true)]
+    pub port: Option<u16>,
+
+    /// set the directory where static files are to be found [default: ./dist]
+    #[clap(long = "static-dir", env = "BRAINS_STATIC_DIR", global = true)]
+    pub static_dir: Option<String>,
+
+    /// set the database url, `postgres://…` or `sqlite:…`
+    #[clap(long = "database-url", env = "DATABASE_URL", global = true)]
+    pub database_url: Option<String>,
+
+    /// set the maximum number of database connections [default: 5]
+    #[clap(
+        long = "max-connections",
+        env = "BRAINS_MAX_CONNECTIONS",
+        global = true
+    )]
+    pub max_connections: Option<u32>,
+
+    /// set how many days a login token stays valid [default: 7]
+    #[clap(
+        long = "token-duration-days",
+        env = "TOKEN_DURATION_DAYS",
+        global = true
+    )]
+    pub token_duration_days: Option<u64>,
+
+    /// set the path of the PEM file with the private key used to sign tokens [default: private.pem]
+    #[clap(long = "private-key", env = "BRAINS_PRIVATE_KEY", global = true)]
+    pub private_key_path: Option<PathBuf>,
+
+    /// set the path of the PEM file with the public key used to verify tokens [default: public.pem]
+    #[clap(long = "public-key", env = "BRAINS_PUBLIC_KEY", global = true)]
+    pub public_key_path: Option<PathBuf>,
+
+    /// set how many seconds pass between checks for scheduled brainfarts to publish [default: 30]
+    #[clap(
+        long = "publish-interval-seconds",
+        env = "BRAINS_PUBLISH_INTERVAL_SECONDS",
+        global = true
+    )]
+    pub publish_interval_seconds: Option<u64>,
+
+    /// set the directory where attached files are stored [default: ./attachments]
+    #[clap(
+        long = "attachments-dir",
+        env = "BRAINS_ATTACHMENTS_DIR",
+        global = true
+    )]
+    pub attachments_dir: Option<PathBuf>,
+
+    /// set the largest file, in bytes, that can be attached to a brainfart [default: 5242880]
+    #[clap(
+        long = "max-attachment-bytes",
+        env = "BRAINS_MAX_ATTACHMENT_BYTES",
+        global = true
+    )]
+    pub max_attachment_bytes: Option<usize>,
+
+    /// set how many seconds pass between checks for webhook deliveries to send [default: 10]
+    #[clap(
+        long = "webhook-interval-seconds",
+        env = "BRAINS_WEBHOOK_INTERVAL_SECONDS",
+        global = true
+    )]
+    pub webhook_interval_seconds: Option<u64>,
+
+    /// let webhooks post to private and local addresses [default: false]
+    #[clap(
+        long = "webhooks-allow-private-addresses",
+        env = "BRAINS_WEBHOOKS_ALLOW_PRIVATE_ADDRESSES",
+        global = true
+    )]
+    pub webhooks_allow_private_addresses: Option<bool>,
+
+    /// set how many days a brain has to call off its deletion before it is carried out [default: 14]
+    #[clap(
+        long = "deletion-grace-days",
+        env = "BRAINS_DELETION_GRACE_DAYS",
+        global = true
+    )]
+    pub deletion_grace_days: Option<u64>,
+
+    /// set the url the site is reached at, e.g. https://brains.example.com, for the links in the
+    /// feeds [default: the host the request was sent to]
+    #[clap(long = "public-url", env = "BRAINS_PUBLIC_URL", global = true)]
+    pub public_url: Option<String>,
+
+    /// set the bearer token Prometheus sends for /metrics [default: none, the metrics are not
+    /// served]
+    #[clap(long = "metrics-token", env = "BRAINS_METRICS_TOKEN", global = true)]
+    pub metrics_token: Option<String>,
+}
+
+impl ConfigurationLayer {
+    /// Fills every setting missing from this layer with the one from `lower`.
+    pub fn over(self, lower: ConfigurationLayer) -> ConfigurationLayer {
+        ConfigurationLayer {
+            log_level: self.log_level.or(lower.log_level),
+            log_format: self.log_format.or(lower.log_format),
+            otel_endpoint: self.otel_endpoint.or(lower.otel_endpoint),
+            addr: self.addr.or(lower.addr),
+            port: self.port.or(lower.port),
+            static_dir: self.static_dir.or(lower.static_dir),
+            database_url: self.database_url.or(lower.database_url),
+            max_connections: self.max_connections.or(lower.max_connections),
+            token_duration_days: self.token_duration_days.or(lower.token_duration_days),
+            private_key_path: self.private_key_path.or(lower.private_key_path),
+            public_key_path: self.public_key_path.or(lower.public_key_path),
+            publish_interval_seconds: self
+                .publish_interval_seconds
+                .or(lower.publish_interval_seconds),
+            attachments_dir: self.attachments_dir.or(lower.attachments_dir),
+            max_attachment_bytes: self.max_attachment_bytes.or(lower.max_attachment_bytes),
+            webhook_interval_seconds: self
+                .webhook_interval_seconds
+                .or(lower.webhook_interval_seconds),
+            webhooks_allow_private_addresses: self
+                .webhooks_allow_private_addresses
+                .or(lower.webhooks_allow_private_addresses),
+            deletion_grace_days: self.deletion_grace_days.or(lower.deletion_grace_days),
+            public_url: self.public_url.or(lower.public_url),
+            metrics_token: self.metrics_token.or(lower.metrics_token),
+        }
+    }
+
+    pub fn from_file(path: &Path) -> Result<ConfigurationLayer, ConfigurationError> {
+        let content = std::fs::read_to_string(path).map_err(|err| {
+            ConfigurationError(format!(
+                "Could not read the configuration file {}: {}",
+                path.display(),
+                err
+            ))
+        })?;
+        toml::from_str(&content).map_err(|err| {
+            ConfigurationError(format!(
+                "The configuration file {} is invalid: {}",
+                path.display(),
+                err
+            ))
+        })
+    }
+}
+
+/// The effective, validated settings of the server.
+#[derive(Serialize, Debug, Clone)]
+pub struct Configuration {
+    pub log_level: String,
+    pub log_format: LogFormat,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub otel_endpoint: Option<String>,
+    pub addr: IpAddr,
+    pub port: u16,
+    pub static_dir: String,
+    pub database_url: String,
+    pub max_connections: u32,
+    pub token_duration_days: u64,
+    pub private_key_path: PathBuf,
+    pub public_key_path: PathBuf,
+    pub publish_interval_seconds: u64,
+    pub attachments_dir: PathBuf,
+    pub max_attachment_bytes: usize,
+    pub webhook_interval_seconds: u64,
+    pub webhooks_allow_private_addresses: bool,
+    pub deletion_grace_days: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metrics_token: Option<String>,
+}
+
+impl Configuration {
+    /// Reads the configuration file (if any) and puts `overrides` from the environment and the
+    /// command line on top of it. An explicitly given file must exist, the default one may not.
+    pub fn load(
+        configuration_file: Option<&Path>,
+        overrides: ConfigurationLayer,
+    ) -> Result<Configuration, ConfigurationError> {
+        let file_layer = match configuration_file {
+            Some(path) => ConfigurationLayer::from_file(path)?,
+            None if Path::new(DEFAULT_CONFIGURATION_FILE).exists() => {
+                ConfigurationLayer::from_file(Path::new(DEFAULT_CONFIGURATION_FILE))?
+            }
+            None => ConfigurationLayer::default(),
+        };
+        Configuration::from_layer(overrides.over(file_layer))
+    }
+
+    pub fn from_layer(layer: ConfigurationLayer) -> Result<Configuration, ConfigurationError> {
+        let addr = layer.addr.unwrap_or_else(|| "::1".to_string());
+        let addr = IpAddr::from_str(&addr).map_err(|_| {
+            ConfigurationError(format!("addr `{}` is not a valid IP address", addr))
+        })?;
+
+        let database_url = layer.database_url.ok_or_else(|| {
+            ConfigurationError(
+                "database_url is not set; pass --database-url, set DATABASE_URL or add it to the configuration file"
+                    .to_string(),
+            )
+        })?;
+        if !["postgres://", "postgresql://", "sqlite:"]
+            .iter()
+            .any(|scheme| database_url.starts_with(scheme))
+        {
+            return Err(ConfigurationError(format!(
+                "database_url `{}` must start with postgres://, postgresql:// or sqlite:",
+                redact_database_url(&database_url)
+            )));
+        }
+
+        let max_connections = layer.max_connections.unwrap_or(5);
+        if max_connections == 0 {
+            return Err(ConfigurationError(
+                "max_connections must be at least 1".to_string(),
+            ));
+        }
+
+        let token_duration_days = layer.token_duration_days.unwrap_or(7);
+        if token_duration_days == 0 {
+            return Err(ConfigurationError(
+                "token_duration_days must be at least 1".to_string(),
+            ));
+        }
+
+        let publish_interval_seconds = layer.publish_interval_seconds.unwrap_or(30);
+        if publish_interval_seconds == 0 {
+            return Err(ConfigurationError(
+                "publish_interval_seconds must be at least 1".to_string(),
+            ));
+        }
+
+        let max_attachment_bytes = layer.max_attachment_bytes.unwrap_or(5 * 1024 * 1024);
+        if max_attachment_bytes == 0 {
+            return Err(ConfigurationError(
+                "max_attachment_bytes must be at least 1".to_string(),
+            ));
+        }
+
+        if layer.otel_endpoint.is_some() && !cfg!(feature = "otel") {
+            return Err(ConfigurationError(
+                "otel_endpoint is set, but the server was built without the otel feature"
+                    .to_string(),
+            ));
+        }
+
+        let webhook_interval_seconds = layer.webhook_interval_seconds.unwrap_or(10);
+        if webhook_interval_seconds == 0 {
+            return Err(ConfigurationError(
+                "webhook_interval_seconds must be at least 1".to_string(),
+            ));
+        }
+
+        let public_url = match layer.public_url {
+            Some(public_url) => {
+                let valid = Url::parse(&public_url).is_ok_and(|url| {
+                    matches!(url.scheme(), "http" | "https")
+                        && url.has_host()
+                        && url.query().is_none()
+                        && url.fragment().is_none()
+                });
+                if !valid {
+                    return Err(ConfigurationError(format!(
+                        "public_url `{}` must be an http or https url without a query",
+                        public_url
+                    )));
+                }
+                Some(public_url.trim_end_matches('/').to_string())
+            }
+            None => None,
+        };
+
+        if layer
+            .metrics_token
+            .as_ref()
+            .is_some_and(|metrics_token| metrics_token.trim().is_empty())
+        {
+            return Err(ConfigurationError(
+                "metrics_token must not be empty".to_string(),
+            ));
+        }
+
+        Ok(Configuration {
+            log_level: layer.log_level.unwrap_or_else(|| "debug".to_string()),
+            log_format: layer.log_format.unwrap_or_default(),
+            otel_endpoint: layer.otel_endpoint,
+            addr,
+            port: layer.port.unwrap_or(8080),
+            static_dir: layer.static_dir.unwrap_or_else(|| "./dist".to_string()),
+            database_url,
+            max_connections,
+            token_duration_days,
+            private_key_path: layer
+                .private_key_path
+                .unwrap_or_else(|| PathBuf::from("private.pem")),
+            public_key_path: layer
+                .public_key_path
+                .unwrap_or_else(|| PathBuf::from("public.pem")),
+            publish_interval_seconds,
+            attachments_dir: layer
+                .attachments_dir
+                .unwrap_or_else(|| PathBuf::from("./attachments")),
+            max_attachment_bytes,
+            webhook_interval_seconds,
+            webhooks_allow_private_addresses: layer
+                .webhooks_allow_private_addresses
+                .unwrap_or(false),
+            deletion_grace_days: layer.deletion_grace_days.unwrap_or(14),
+            public_url,
+            metrics_token: layer.metrics_token,
+        })
+    }
+
+    /// The configuration as TOML, with the database password and the metrics token hidden.
+    pub fn to_printable(&self) -> String {
+        let mut printable = self.clone();
+        printable.database_url = redact_database_url(&self.database_url);
+        if printable.metrics_token.is_some() {
+            printable.metrics_token = Some("***".to_string());
+        }
+        toml::to_string(&printable).unwrap()
+    }
+}
+
+fn redact_database_url(database_url: &str) -> String {
+    if let Some(scheme_end) = database_url.find("://") {
+        let rest = &database_url[scheme_end + 3..];
+        if let Some(at) = rest.find('@') {
+            if let Some(colon) = rest[..at].find(':') {
+                return format!(
+                    "{}{}:***{}",
+                    &database_url[..scheme_end + 3],
+                    &rest[..colon],
+                    &rest[at..]
+                );
+            }
+        }
+    }
+    database_url.to_string()
+}
@@ -1,15 +1,20 @@
-use argon2::{
-    password_hash::{rand_core::OsRng, PasswordHasher, SaltString},
-    Argon2,
-};
 use axum::async_trait;
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Local, Utc};
 use shared::{
-    CreateBrainfartRequest, NotifyAboutMindExplosionRequest, NotifyAboutMindImplosionRequest,
-    RegisterBrainRequest,
+    BookmarkRequest, BrainExport, BrainRelationRequest, CreateBrainfartRequest,
+    CreateWebhookRequest, NotifyAboutMindExplosionRequest, NotifyAboutMindImplosionRequest,
+    ReactionKind, RebrainfartRequest, RegisterBrainRequest, ReportBrainfartRequest,
+    SaveDraftRequest, WebhookEvent,
 };
 use sqlx::{types::Uuid, Pool, Postgres};
 
+use super::{
+    get_models::{is_blocked_by_mastermind, is_in_feed},
+    password_verification::hash_password,
+    repository::{events_to_text, tags_to_text, ImportSummary, NewAttachment},
+    LogError,
+};
+
 pub struct CreateResponse {
     pub uuid: Uuid,
     pub birthdate: DateTime<Local>,
@@ -33,31 +38,35 @@ pub trait CreateFromRequest {
 impl CreateFromRequest for NotifyAboutMindExplosionRequest {
     async fn create(&self, pool: &Pool<Postgres>, foreign_id: &Uuid) -> Option<CreateResponse> {
         let brainfart_uuid = Uuid::parse_str(&self.brainfart_id).unwrap();
+        if !is_in_feed(pool, &brainfart_uuid).await
+            || is_blocked_by_mastermind(pool, &brainfart_uuid, foreign_id).await
+        {
+            return None;
+        }
         let check_existence_query = sqlx::query!(
             "SELECT id from mindsblownbyfarts where brainfartid=$1 and brainid=$2 LIMIT 1",
             &brainfart_uuid,
             foreign_id
         )
         .fetch_one(pool)
-        .await;
+        .await
+        .log_error("create_models::NotifyAboutMindExplosionRequest::create");
         if let Ok(change_result) = match check_existence_query {
-            Ok(result) => {
-                sqlx::query!(
-                    "update mindsblownbyfarts set explosion=TRUE where id=$1",
-                    result.id
-                )
-                .execute(pool)
-                .await
-            }
-            Err(_) => {
-                sqlx::query!(
+            Ok(result) => sqlx::query!(
+                "update mindsblownbyfarts set explosion=TRUE, reactiondate=NOW() where id=$1",
+                result.id
+            )
+            .execute(pool)
+            .await
+            .log_error("create_models::NotifyAboutMindExplosionRequest::create"),
+            Err(_) => sqlx::query!(
                 "insert into mindsblownbyfarts(brainfartid, brainid, explosion) VALUES($1,$2,TRUE)",
                 &brainfart_uuid,
                 foreign_id
             )
-                .execute(pool)
-                .await
-            }
+            .execute(pool)
+            .await
+            .log_error("create_models::NotifyAboutMindExplosionRequest::create"),
         } {
             if change_result.rows_affected() > 0 {
                 return Some(CreateResponse::empty());
@@ -71,21 +80,28 @@ impl CreateFromRequest for NotifyAboutMindExplosionRequest {
 impl CreateFromRequest for NotifyAboutMindImplosionRequest {
     async fn create(&self, pool: &Pool<Postgres>, foreign_id: &Uuid) -> Option<CreateResponse> {
         let brainfart_uuid = Uuid::parse_str(&self.brainfart_id).unwrap();
+        if !is_in_feed(pool, &brainfart_uuid).await
+            || is_blocked_by_mastermind(pool, &brainfart_uuid, foreign_id).await
+        {
+            return None;
+        }
         let check_existence_query = sqlx::query!(
             "SELECT id from mindsblownbyfarts where brainfartid=$1 and brainid=$2 LIMIT 1",
             &brainfart_uuid,
             foreign_id
         )
         .fetch_one(pool)
-        .await;
+        .await
+        .log_error("create_models::NotifyAboutMindImplosionRequest::create");
         if let Ok(change_result) = match check_existence_query {
             Ok(result) => {
                 sqlx::query!(
-                    "update mindsblownbyfarts set explosion=FALSE where id=$1",
+                    "update mindsblownbyfarts set explosion=FALSE, reactiondate=NOW() where id=$1",
                     result.id
                 )
                 .execute(pool)
                 .await
+                .log_error("create_models::NotifyAboutMindImplosionRequest::create")
             }
             Err(_) => {
                 sqlx::query!(
@@ -95,6 +111,7 @@ impl CreateFromRequest for NotifyAboutMindImplosionRequest {
             )
                 .execute(pool)
                 .await
+                .log_error("create_models::NotifyAboutMindImplosionRequest::create")
             }
         } {
             if change_result.rows_affected() > 0 {
@@ -108,26 +125,215 @@ impl CreateFromRequest for NotifyAboutMindImplosionRequest {
 #[async_trait]
 impl CreateFromRequest for CreateBrainfartRequest {
     async fn create(&self, pool: &Pool<Postgres>, foreign_id: &Uuid) -> Option<CreateResponse> {
+        let mut transaction = pool
+            .begin()
+            .await
+            .log_error("create_models::CreateBrainfartRequest::create")
+            .ok()?;
         let create_query = sqlx::query!(
             "INSERT INTO
                 brainfarts
-                (title,content,birthdate,mastermind)
+                (title,content,birthdate,mastermind,publishdate,tags)
                 VALUES(
                 $1,
                 $2,
                 NOW(),
-                $3)
+                $3,
+                $4,
+                $5)
                 RETURNING id, birthdate",
             &self.get_title(),
             &self.get_content(),
+            foreign_id,
+            self.get_publish_at()
+                .map(|publish_at| publish_at.with_timezone(&Utc)),
+            tags_to_text(self.get_title(), self.get_content())
+        )
+        .fetch_one(&mut transaction)
+        .await
+        .log_error("create_models::CreateBrainfartRequest::create");
+        let result = create_query.ok()?;
+        if let Some(poll) = self.get_poll() {
+            sqlx::query!(
+                "INSERT INTO polls (brainfartid, closedate, multiplechoice) VALUES($1, $2, $3)",
+                result.id,
+                poll.closes_at
+                    .map(|closes_at| closes_at.with_timezone(&Utc)),
+                poll.multiple_choice
+            )
+            .execute(&mut transaction)
+            .await
+            .log_error("create_models::CreateBrainfartRequest::create")
+            .ok()?;
+            for (position, text) in poll.options.iter().enumerate() {
+                sqlx::query!(
+                    "INSERT INTO polloptions (brainfartid, position, text) VALUES($1, $2, $3)",
+                    result.id,
+                    position as i32,
+                    text.trim()
+                )
+                .execute(&mut transaction)
+                .await
+                .log_error("create_models::CreateBrainfartRequest::create")
+                .ok()?;
+            }
+        }
+        transaction
+            .commit()
+            .await
+            .log_error("create_models::CreateBrainfartRequest::create")
+            .ok()?;
+        Some(CreateResponse {
+            uuid: result.id,
+            birthdate: result.birthdate.into(),
+            extra_information: None,
+        })
+    }
+}
+
+#[async_trait]
+impl CreateFromRequest for ReportBrainfartRequest {
+    async fn create(&self, pool: &Pool<Postgres>, foreign_id: &Uuid) -> Option<CreateResponse> {
+        let brainfart_uuid = Uuid::parse_str(&self.brainfart_id).ok()?;
+        // Reporting the same brainfart again replaces the earlier reason and reopens the report.
+        let create_query = sqlx::query!(
+            "INSERT INTO
+                reportedfarts
+                (brainfartid, brainid, reason)
+                VALUES($1, $2, $3)
+                ON CONFLICT (brainfartid, brainid)
+                DO UPDATE SET reason=EXCLUDED.reason, reportdate=NOW(), resolved=FALSE
+                RETURNING id, reportdate",
+            &brainfart_uuid,
+            foreign_id,
+            &self.reason
+        )
+        .fetch_one(pool)
+        .await
+        .log_error("create_models::ReportBrainfartRequest::create");
+        match create_query {
+            Ok(result) => Some(CreateResponse {
+                uuid: result.id,
+                birthdate: result.reportdate.into(),
+                extra_information: None,
+            }),
+            Err(_) => None,
+        }
+    }
+}
+
+#[async_trait]
+impl CreateFromRequest for BrainRelationRequest {
+    async fn create(&self, pool: &Pool<Postgres>, foreign_id: &Uuid) -> Option<CreateResponse> {
+        let create_query = sqlx::query!(
+            "INSERT INTO
+                brainrelations
+                (brainid, otherbrainid, relation)
+                SELECT $1, id, $2 FROM brains where brainname=$3
+                ON CONFLICT (brainid, otherbrainid, relation)
+                DO UPDATE SET relation=EXCLUDED.relation
+                RETURNING id, createdate",
+            foreign_id,
+            self.relation.to_string(),
+            &self.brainname
+        )
+        .fetch_one(pool)
+        .await
+        .log_error("create_models::BrainRelationRequest::create");
+        match create_query {
+            Ok(result) => Some(CreateResponse {
+                uuid: result.id,
+                birthdate: result.createdate.into(),
+                extra_information: None,
+            }),
+            Err(_) => None,
+        }
+    }
+}
+
+#[async_trait]
+impl CreateFromRequest for BookmarkRequest {
+    async fn create(&self, pool: &Pool<Postgres>, foreign_id: &Uuid) -> Option<CreateResponse> {
+        let brainfart_uuid = Uuid::parse_str(&self.brainfart_id).ok()?;
+        let create_query = sqlx::query!(
+            "INSERT INTO
+                bookmarkedfarts
+                (brainfartid, brainid)
+                SELECT id, $2 FROM brainfarts where id=$1 and not hidden and publishdate is null
+                ON CONFLICT (brainfartid, brainid)
+                DO UPDATE SET brainid=EXCLUDED.brainid
+                RETURNING id, bookmarkdate",
+            &brainfart_uuid,
             foreign_id
         )
         .fetch_one(pool)
-        .await;
+        .await
+        .log_error("create_models::BookmarkRequest::create");
         match create_query {
             Ok(result) => Some(CreateResponse {
                 uuid: result.id,
-                birthdate: result.birthdate.into(),
+                birthdate: result.bookmarkdate.into(),
+                extra_information: None,
+            }),
+            Err(_) => None,
+        }
+    }
+}
+
+#[async_trait]
+impl CreateFromRequest for NewAttachment {
+    async fn create(&self, pool: &Pool<Postgres>, foreign_id: &Uuid) -> Option<CreateResponse> {
+        let create_query = sqlx::query!(
+            "INSERT INTO
+                brainfartattachments
+                (brainfartid, filename, contenttype, size, hasthumbnail)
+                VALUES($1, $2, $3, $4, $5)
+                RETURNING id, uploaddate",
+            foreign_id,
+            &self.filename,
+            &self.content_type,
+            self.size,
+            self.has_thumbnail
+        )
+        .fetch_one(pool)
+        .await
+        .log_error("create_models::NewAttachment::create");
+        match create_query {
+            Ok(result) => Some(CreateResponse {
+                uuid: result.id,
+                birthdate: result.uploaddate.into(),
+                extra_information: None,
+            }),
+            Err(_) => None,
+        }
+    }
+}
+
+#[async_trait]
+impl CreateFromRequest for SaveDraftRequest {
+    async fn create(&self, pool: &Pool<Postgres>, foreign_id: &Uuid) -> Option<CreateResponse> {
+        let create_query = sqlx::query!(
+            "INSERT INTO
+                brainfartdrafts
+                (brainid, title, content, publishdate)
+                VALUES($1, $2, $3, $4)
+                ON CONFLICT (brainid)
+                DO UPDATE SET title=EXCLUDED.title, content=EXCLUDED.content,
+                    publishdate=EXCLUDED.publishdate, lastupdatedate=NOW()
+                RETURNING id, lastupdatedate",
+            foreign_id,
+            &self.title,
+            &self.content,
+            self.publish_at
+                .map(|publish_at| publish_at.with_timezone(&Utc))
+        )
+        .fetch_one(pool)
+        .await
+        .log_error("create_models::SaveDraftRequest::create");
+        match create_query {
+            Ok(result) => Some(CreateResponse {
+                uuid: result.id,
+                birthdate: result.lastupdatedate.into(),
                 extra_information: None,
             }),
             Err(_) => None,
@@ -138,9 +344,7 @@ impl CreateFromRequest for CreateBrainfartRequest {
 #[async_trait]
 impl CreateFromRequest for RegisterBrainRequest {
     async fn create(&self, pool: &Pool<Postgres>, _foreign_id: &Uuid) -> Option<CreateResponse> {
-        let salt = SaltString::generate(&mut OsRng);
-        let argon2 = Argon2::default();
-        if let Ok(password_hash) = argon2.hash_password(&self.get_password().as_bytes(), &salt) {
+        if let Some(password_hash) = hash_password(self.get_password()) {
             let create_query = sqlx::query!(
                 "INSERT INTO
                 brains
@@ -150,15 +354,16 @@ impl CreateFromRequest for RegisterBrainRequest {
                 $2)
                 RETURNING id, birthdate",
                 &self.get_name(),
-                password_hash.to_string()
+                &password_hash
             )
             .fetch_one(pool)
-            .await;
+            .await
+            .log_error("create_models::RegisterBrainRequest::create");
             match create_query {
                 Ok(result) => Some(CreateResponse {
                     uuid: result.id,
                     birthdate: result.birthdate.into(),
-                    extra_information: Some(password_hash.to_string()),
+                    extra_information: Some(password_hash),
                 }),
                 Err(_) => None,
             }
@@ -167,3 +372,238 @@ impl CreateFromRequest for RegisterBrainRequest {
         }
     }
 }
+
+/// The ballot and its votes go in together, so a ballot is never left without votes.
+pub async fn add_poll_vote(
+    pool: &Pool<Postgres>,
+    brainfart_id: &Uuid,
+    brain_id: &Uuid,
+    positions: &[usize],
+) -> Option<CreateResponse> {
+    let mut transaction = pool
+        .begin()
+        .await
+        .log_error("create_models::add_poll_vote")
+        .ok()?;
+    let ballot = sqlx::query!(
+        "INSERT INTO pollballots (brainfartid, brainid)
+            SELECT id, $2 FROM brainfarts where id=$1 and not hidden and publishdate is null
+            RETURNING id, votedate",
+        brainfart_id,
+        brain_id
+    )
+    .fetch_one(&mut transaction)
+    .await
+    .log_error("create_models::add_poll_vote")
+    .ok()?;
+    for position in positions {
+        sqlx::query!(
+            "INSERT INTO pollvotes (ballotid, brainfartid, position) VALUES($1, $2, $3)",
+            ballot.id,
+            brainfart_id,
+            *position as i32
+        )
+        .execute(&mut transaction)
+        .await
+        .log_error("create_models::add_poll_vote")
+        .ok()?;
+    }
+    transaction
+        .commit()
+        .await
+        .log_error("create_models::add_poll_vote")
+        .ok()?;
+    Some(CreateResponse {
+        uuid: ballot.id,
+        birthdate: ballot.votedate.into(),
+        extra_information: None,
+    })
+}
+
+/// Only brainfarts that are in the feed can be rebrainfarted.
+pub async fn create_rebrainfart(
+    pool: &Pool<Postgres>,
+    request: &RebrainfartRequest,
+    original_id: &Uuid,
+    mastermind_id: &Uuid,
+) -> Option<CreateResponse> {
+    let result = sqlx::query!(
+        "INSERT INTO brainfarts (title, content, mastermind, originalid, tags)
+            SELECT '', $1, $2, id, $4 FROM brainfarts where id=$3 and not hidden and publishdate is null
+            RETURNING id, birthdate",
+        request.commentary.trim(),
+        mastermind_id,
+        original_id,
+        tags_to_text("", &request.commentary)
+    )
+    .fetch_one(pool)
+    .await
+    .log_error("create_models::create_rebrainfart")
+    .ok()?;
+    Some(CreateResponse {
+        uuid: result.id,
+        birthdate: result.birthdate.into(),
+        extra_information: None,
+    })
+}
+
+#[async_trait]
+impl CreateFromRequest for CreateWebhookRequest {
+    async fn create(&self, pool: &Pool<Postgres>, foreign_id: &Uuid) -> Option<CreateResponse> {
+        let create_query = sqlx::query!(
+            "INSERT INTO
+                webhooks
+                (ownerid, url, events, secret, global)
+                VALUES($1, $2, $3, $4, $5)
+                RETURNING id, createdate",
+            foreign_id,
+            &self.url,
+            events_to_text(&self.events),
+            &self.secret,
+            self.global
+        )
+        .fetch_one(pool)
+        .await
+        .log_error("create_models::CreateWebhookRequest::create");
+        match create_query {
+            Ok(result) => Some(CreateResponse {
+                uuid: result.id,
+                birthdate: result.createdate.into(),
+                extra_information: None,
+            }),
+            Err(_) => None,
+        }
+    }
+}
+
+pub async fn add_webhook_delivery(
+    pool: &Pool<Postgres>,
+    webhook_id: &Uuid,
+    event: WebhookEvent,
+    payload: &str,
+) -> Option<CreateResponse> {
+    let create_query = sqlx::query!(
+        "INSERT INTO
+            webhookdeliveries
+            (webhookid, event, payload, nextattemptdate)
+            VALUES($1, $2, $3, NOW())
+            RETURNING id, createdate",
+        webhook_id,
+        event.to_string(),
+        payload
+    )
+    .fetch_one(pool)
+    .await
+    .log_error("create_models::CreateWebhookRequest::add_webhook_delivery");
+    match create_query {
+        Ok(result) => Some(CreateResponse {
+            uuid: result.id,
+            birthdate: result.createdate.into(),
+            extra_information: None,
+        }),
+        Err(_) => None,
+    }
+}
+
+/// Brainfarts are restored oldest first, so rebrainfarts of the brain's own brainfarts find their
+/// originals.
+pub async fn import_brain(
+    pool: &Pool<Postgres>,
+    archive: &BrainExport,
+    password: &str,
+) -> Option<ImportSummary> {
+    let password_hash = hash_password(password)?;
+    let mut transaction = pool
+        .begin()
+        .await
+        .log_error("create_models::import_brain")
+        .ok()?;
+    let brain_id = Uuid::parse_str(&archive.brain.id).ok()?;
+    sqlx::query!(
+        "INSERT INTO brains (id, brainname, password, birthdate) VALUES($1, $2, $3, $4)",
+        brain_id,
+        &archive.brain.brainname,
+        password_hash,
+        archive.brain.birthdate.with_timezone(&Utc)
+    )
+    .execute(&mut transaction)
+    .await
+    .log_error("create_models::import_brain")
+    .ok()?;
+    let mut summary = ImportSummary::default();
+    let mut brainfarts = archive.brainfarts.iter().collect::<Vec<_>>();
+    brainfarts.sort_by_key(|brainfart| brainfart.birthdate);
+    for brainfart in brainfarts {
+        let original_id = match &brainfart.original_id {
+            Some(original_id) => {
+                let original_id = Uuid::parse_str(original_id).ok()?;
+                sqlx::query!("select id from brainfarts where id=$1", original_id)
+                    .fetch_optional(&mut transaction)
+                    .await
+                    .log_error("create_models::import_brain")
+                    .ok()?
+                    .map(|original| original.id)
+            }
+            None => None,
+        };
+        if brainfart.original_id.is_some() && original_id.is_none() && brainfart.content.is_empty()
+        {
+            continue;
+        }
+        sqlx::query!(
+            "INSERT INTO brainfarts (id, title, content, birthdate, mastermind, hidden, publishdate, originalid, tags)
+                VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+            Uuid::parse_str(&brainfart.id).ok()?,
+            &brainfart.title,
+            &brainfart.content,
+            brainfart.birthdate.with_timezone(&Utc),
+            brain_id,
+            brainfart.hidden,
+            brainfart
+                .publish_at
+                .map(|publish_at| publish_at.with_timezone(&Utc)),
+            original_id,
+            tags_to_text(&brainfart.title, &brainfart.content)
+        )
+        .execute(&mut transaction)
+        .await
+        .log_error("create_models::import_brain")
+        .ok()?;
+        summary.brainfarts += 1;
+    }
+    for reaction in archive.reactions.iter() {
+        let inserted = sqlx::query!(
+            "INSERT INTO mindsblownbyfarts (brainfartid, brainid, explosion, reactiondate)
+                select id, $2, $3, $4 from brainfarts where id=$1",
+            Uuid::parse_str(&reaction.brainfart_id).ok()?,
+            brain_id,
+            reaction.kind == ReactionKind::Explosion,
+            reaction.reactiondate.with_timezone(&Utc)
+        )
+        .execute(&mut transaction)
+        .await
+        .log_error("create_models::import_brain")
+        .ok()?;
+        summary.reactions += inserted.rows_affected() as usize;
+    }
+    for view in archive.views.iter() {
+        let inserted = sqlx::query!(
+            "INSERT INTO hallucinatedfarts (brainfartid, brainid, hallucinationdate)
+                select id, $2, $3 from brainfarts where id=$1",
+            Uuid::parse_str(&view.brainfart_id).ok()?,
+            brain_id,
+            view.viewdate.with_timezone(&Utc)
+        )
+        .execute(&mut transaction)
+        .await
+        .log_error("create_models::import_brain")
+        .ok()?;
+        summary.views += inserted.rows_affected() as usize;
+    }
+    transaction
+        .commit()
+        .await
+        .log_error("create_models::import_brain")
+        .ok()?;
+    Some(summary)
+}
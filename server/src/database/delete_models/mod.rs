@@ -0,0 +1,241 @@
+use shared::{AttachmentInformation, BookmarkRequest, BrainRelationRequest, DeletionMode};
+use sqlx::{types::Uuid, Pool, Postgres};
+
+use super::{get_models::get_attachments, LogError};
+
+/// Plain rebrainfarts of the brainfart go with it, while quotes of it lose their original.
+pub async fn delete_brainfart(pool: &Pool<Postgres>, brainfart_id: &Uuid) -> bool {
+    let mut transaction = match pool
+        .begin()
+        .await
+        .log_error("delete_models::delete_brainfart")
+    {
+        Ok(transaction) => transaction,
+        Err(_) => return false,
+    };
+    let mut ids = match sqlx::query!(
+        "select id from brainfarts where originalid=$1 and content=''",
+        brainfart_id
+    )
+    .fetch_all(&mut transaction)
+    .await
+    .log_error("delete_models::delete_brainfart")
+    {
+        Ok(rebrainfarts) => rebrainfarts
+            .into_iter()
+            .map(|rebrainfart| rebrainfart.id)
+            .collect::<Vec<_>>(),
+        Err(_) => return false,
+    };
+    ids.push(*brainfart_id);
+    let deleted = sqlx::query!(
+        "delete from mindsblownbyfarts where brainfartid=ANY($1)",
+        &ids
+    )
+    .execute(&mut transaction)
+    .await
+    .log_error("delete_models::delete_brainfart")
+    .is_ok()
+        && sqlx::query!(
+            "delete from hallucinatedfarts where brainfartid=ANY($1)",
+            &ids
+        )
+        .execute(&mut transaction)
+        .await
+        .log_error("delete_models::delete_brainfart")
+        .is_ok()
+        && sqlx::query!("delete from reportedfarts where brainfartid=ANY($1)", &ids)
+            .execute(&mut transaction)
+            .await
+            .log_error("delete_models::delete_brainfart")
+            .is_ok()
+        && sqlx::query!(
+            "delete from bookmarkedfarts where brainfartid=ANY($1)",
+            &ids
+        )
+        .execute(&mut transaction)
+        .await
+        .log_error("delete_models::delete_brainfart")
+        .is_ok()
+        && sqlx::query!(
+            "delete from brainfartattachments where brainfartid=ANY($1)",
+            &ids
+        )
+        .execute(&mut transaction)
+        .await
+        .log_error("delete_models::delete_brainfart")
+        .is_ok()
+        && sqlx::query!("delete from pollvotes where brainfartid=ANY($1)", &ids)
+            .execute(&mut transaction)
+            .await
+            .log_error("delete_models::delete_brainfart")
+            .is_ok()
+        && sqlx::query!("delete from pollballots where brainfartid=ANY($1)", &ids)
+            .execute(&mut transaction)
+            .await
+            .log_error("delete_models::delete_brainfart")
+            .is_ok()
+        && sqlx::query!("delete from polloptions where brainfartid=ANY($1)", &ids)
+            .execute(&mut transaction)
+            .await
+            .log_error("delete_models::delete_brainfart")
+            .is_ok()
+        && sqlx::query!("delete from polls where brainfartid=ANY($1)", &ids)
+            .execute(&mut transaction)
+            .await
+            .log_error("delete_models::delete_brainfart")
+            .is_ok()
+        && sqlx::query!(
+            "update brainfarts set originalid=NULL where originalid=$1",
+            brainfart_id
+        )
+        .execute(&mut transaction)
+        .await
+        .log_error("delete_models::delete_brainfart")
+        .is_ok()
+        && matches!(
+            sqlx::query!("delete from brainfarts where id=ANY($1)", &ids)
+                .execute(&mut transaction)
+                .await
+                .log_error("delete_models::delete_brainfart"),
+            Ok(result) if result.rows_affected() > 0
+        );
+    deleted
+        && transaction
+            .commit()
+            .await
+            .log_error("delete_models::delete_brainfart")
+            .is_ok()
+}
+
+pub async fn remove_brain_relation(
+    pool: &Pool<Postgres>,
+    request: &BrainRelationRequest,
+    brain_id: &Uuid,
+) -> bool {
+    let query = sqlx::query!(
+        "delete from brainrelations where brainid=$1 and relation=$2
+            and otherbrainid=(select id from brains where brainname=$3)",
+        brain_id,
+        request.relation.to_string(),
+        &request.brainname
+    )
+    .execute(pool)
+    .await
+    .log_error("delete_models::remove_brain_relation");
+    matches!(query, Ok(result) if result.rows_affected() > 0)
+}
+
+pub async fn remove_bookmark(
+    pool: &Pool<Postgres>,
+    request: &BookmarkRequest,
+    brain_id: &Uuid,
+) -> bool {
+    let brainfart_id = match Uuid::parse_str(&request.brainfart_id) {
+        Ok(brainfart_id) => brainfart_id,
+        Err(_) => return false,
+    };
+    let query = sqlx::query!(
+        "delete from bookmarkedfarts where brainfartid=$1 and brainid=$2",
+        brainfart_id,
+        brain_id
+    )
+    .execute(pool)
+    .await
+    .log_error("delete_models::remove_bookmark");
+    matches!(query, Ok(result) if result.rows_affected() > 0)
+}
+
+pub async fn delete_draft(pool: &Pool<Postgres>, brain_id: &Uuid) -> bool {
+    let query = sqlx::query!("delete from brainfartdrafts where brainid=$1", brain_id)
+        .execute(pool)
+        .await
+        .log_error("delete_models::delete_draft");
+    matches!(query, Ok(result) if result.rows_affected() > 0)
+}
+
+pub async fn remove_attachment(pool: &Pool<Postgres>, attachment_id: &Uuid) -> bool {
+    let query = sqlx::query!(
+        "delete from brainfartattachments where id=$1",
+        attachment_id
+    )
+    .execute(pool)
+    .await
+    .log_error("delete_models::remove_attachment");
+    matches!(query, Ok(result) if result.rows_affected() > 0)
+}
+
+pub async fn delete_webhook(pool: &Pool<Postgres>, webhook_id: &Uuid) -> bool {
+    let mut transaction = match pool
+        .begin()
+        .await
+        .log_error("delete_models::delete_webhook")
+    {
+        Ok(transaction) => transaction,
+        Err(_) => return false,
+    };
+    let deliveries_deleted = sqlx::query!(
+        "delete from webhookdeliveries where webhookid=$1",
+        webhook_id
+    )
+    .execute(&mut transaction)
+    .await
+    .log_error("delete_models::delete_webhook")
+    .is_ok();
+    let deleted = matches!(
+        sqlx::query!("delete from webhooks where id=$1", webhook_id)
+            .execute(&mut transaction)
+            .await
+            .log_error("delete_models::delete_webhook"),
+        Ok(result) if result.rows_affected() > 0
+    );
+    deliveries_deleted
+        && deleted
+        && transaction
+            .commit()
+            .await
+            .log_error("delete_models::delete_webhook")
+            .is_ok()
+}
+
+/// The brainfarts go one at a time, like when they are deleted on their own, since a rebrainfart
+/// among them may go with its original. The foreign keys take care of the rest.
+pub async fn delete_brain(
+    pool: &Pool<Postgres>,
+    brain_id: &Uuid,
+    mode: DeletionMode,
+) -> Option<Vec<AttachmentInformation>> {
+    let mut attachments = vec![];
+    while let Some(brainfart) = sqlx::query!(
+        "select id from brainfarts where mastermind=$1 and ($2 or publishdate is not null) LIMIT 1",
+        brain_id,
+        mode == DeletionMode::Delete
+    )
+    .fetch_optional(pool)
+    .await
+    .log_error("delete_models::delete_brain")
+    .ok()?
+    {
+        attachments.extend(
+            get_attachments(pool, &brainfart.id)
+                .await
+                .unwrap_or_default(),
+        );
+        if !delete_brainfart(pool, &brainfart.id).await {
+            return None;
+        }
+    }
+    if mode == DeletionMode::Delete {
+        sqlx::query!("delete from mindsblownbyfarts where brainid=$1", brain_id)
+            .execute(pool)
+            .await
+            .log_error("delete_models::delete_brain")
+            .ok()?;
+    }
+    let query = sqlx::query!("delete from brains where id=$1", brain_id)
+        .execute(pool)
+        .await
+        .log_error("delete_models::delete_brain")
+        .ok()?;
+    (query.rows_affected() > 0).then_some(attachments)
+}
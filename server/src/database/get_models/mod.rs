@@ -1,7 +1,23 @@
-use shared::{BrainInformation, BrainfartFilter, BrainfartInformation};
+use chrono::{DateTime, Local, Utc};
+use shared::{
+    find_urls, AttachmentInformation, BrainDeletion, BrainExport, BrainInformation,
+    BrainRelationInformation, BrainfartDraft, BrainfartFilter, BrainfartFilterMode,
+    BrainfartInformation, BrainfartOrder, ExportedBrain, ExportedBrainfart, ExportedReaction,
+    ExportedView, LeaderboardBrainfart, LeaderboardMastermind, LinkPreview, PollInformation,
+    PollOptionResult, ReactionKind, ReportInformation, ReportedBrainfart, Role,
+    WebhookDeliveryInformation, WebhookEvent, EXPORT_VERSION, REMOVED_BRAIN_NAME,
+};
 use sqlx::{types::Uuid, Pool, Postgres};
 
-async fn set_brainfart_as_viewed(
+use super::{
+    repository::{
+        events_from_text, AttachedFile, BrainAccount, DueDelivery, UsageStatistics, Webhook,
+    },
+    LogError,
+};
+use crate::ranking;
+
+pub async fn set_brainfart_as_viewed(
     pool: &Pool<Postgres>,
     brainfart_id: &Uuid,
     brain_id: &Uuid,
@@ -13,31 +29,33 @@ async fn set_brainfart_as_viewed(
         brainfart_id
     )
     .fetch_one(pool)
-    .await;
-    if let Err(_) = query {
+    .await
+    .log_error("get_models::set_brainfart_as_viewed");
+    if query.is_err() {
         let query = sqlx::query!(
             "insert into hallucinatedfarts(brainid,brainfartid) VALUES($1,$2)",
             brain_id,
             brainfart_id
         )
         .execute(pool)
-        .await;
-        if let Ok(_) = query {
-            true
-        } else {
-            false
-        }
+        .await
+        .log_error("get_models::set_brainfart_as_viewed");
+        query.is_ok()
     } else {
         false
     }
 }
-async fn get_brain_information(pool: &Pool<Postgres>, brain_id: &Uuid) -> Option<BrainInformation> {
+pub async fn get_brain_information(
+    pool: &Pool<Postgres>,
+    brain_id: &Uuid,
+) -> Option<BrainInformation> {
     let query = sqlx::query!(
         "select brainname, birthdate from brains where id=$1 LIMIT 1",
         brain_id
     )
     .fetch_one(pool)
-    .await;
+    .await
+    .log_error("get_models::get_brain_information");
     if let Ok(result) = query {
         Some(BrainInformation::create(
             brain_id.to_string(),
@@ -48,16 +66,58 @@ async fn get_brain_information(pool: &Pool<Postgres>, brain_id: &Uuid) -> Option
         None
     }
 }
+/// The reactions of deleted brains are kept without the brain.
+async fn get_reacting_brain(
+    pool: &Pool<Postgres>,
+    brain_id: Option<Uuid>,
+) -> Option<BrainInformation> {
+    match brain_id {
+        Some(brain_id) => get_brain_information(pool, &brain_id).await,
+        None => Some(BrainInformation::removed()),
+    }
+}
+
 pub async fn get_brainfarts_using_filter(
     pool: &Pool<Postgres>,
-    _filter: BrainfartFilter,
+    filter: BrainfartFilter,
+    viewer_id: &Uuid,
 ) -> Option<Vec<BrainfartInformation>> {
     let select_query = sqlx::query!(
-        "select id, title, content, birthdate, (select brainname from brains where id=mastermind LIMIT 1) as mastermind_name FROM
-            brainfarts ORDER BY birthdate DESC"
+        r#"select id, title, content, birthdate, originalid, (select brainname from brains where id=mastermind LIMIT 1) as mastermind_name,
+            exists(select 1 from bookmarkedfarts where brainfartid=brainfarts.id and brainid=$1) as "bookmarked_by_me!",
+            (select count(*) from brainfarts as shares where shares.originalid=brainfarts.id
+                and not shares.hidden and shares.publishdate is null) as "rebrainfarts!",
+            exists(select 1 from brainfarts as shares where shares.originalid=brainfarts.id
+                and shares.mastermind=$1 and shares.content='') as "rebrainfarted_by_me!" FROM
+            brainfarts where not hidden and not exists
+                (select 1 from brainrelations where brainid=$1 and otherbrainid=mastermind and relation='mute')
+            and publishdate is null
+            and not (content='' and originalid is not null and not exists(select 1 from brainfarts as originals
+                where originals.id=brainfarts.originalid and not originals.hidden and originals.publishdate is null))
+            and (not $2 or exists(select 1 from bookmarkedfarts where brainfartid=brainfarts.id and brainid=$1))
+            and ($7::text is null or mastermind=(select id from brains where brainname=$7))
+            and ($8::text is null or strpos(' ' || coalesce(case when content='' and originalid is not null
+                then (select originals.tags from brainfarts as originals where originals.id=brainfarts.originalid)
+                else tags end, '') || ' ', ' ' || $8 || ' ') > 0)
+            ORDER BY (case when $3 then
+                ((select count(*) from mindsblownbyfarts where brainfartid=brainfarts.id and explosion)
+                - (select count(*) from mindsblownbyfarts where brainfartid=brainfarts.id and not explosion)
+                + $4::float8 * (select count(*) from hallucinatedfarts where brainfartid=brainfarts.id))
+                / power(greatest(extract(epoch from now() - birthdate)::float8 / 3600, 0) + $5::float8, $6::float8)
+                else 0 end) DESC, birthdate DESC LIMIT $9"#,
+        viewer_id,
+        filter.mode == BrainfartFilterMode::Bookmarks,
+        filter.order == BrainfartOrder::Hot,
+        ranking::VIEW_WEIGHT,
+        ranking::AGE_OFFSET_HOURS,
+        ranking::GRAVITY,
+        filter.brainname,
+        filter.tag,
+        filter.limit.map(i64::from)
     )
     .fetch_all(pool)
-    .await;
+    .await
+    .log_error("get_models::get_brainfarts_using_filter");
 
     if let Ok(result) = select_query {
         let mut final_result = vec![];
@@ -67,14 +127,13 @@ pub async fn get_brainfarts_using_filter(
                 a.id
             )
             .fetch_all(pool)
-            .await;
+            .await
+            .log_error("get_models::get_brainfarts_using_filter");
             if let Ok(minds_blown_result) = minds_blown_query {
                 let mut minds_blown = vec![];
                 let mut minds_imploded = vec![];
                 for record in minds_blown_result.iter() {
-                    if let Some(brain_info) =
-                        get_brain_information(pool, &record.brainid.unwrap()).await
-                    {
+                    if let Some(brain_info) = get_reacting_brain(pool, record.brainid).await {
                         if record.explosion.unwrap() {
                             minds_blown.push(brain_info);
                         } else {
@@ -88,9 +147,19 @@ pub async fn get_brainfarts_using_filter(
                     title: a.title.clone(),
                     content: a.content.clone(),
                     birthdate: a.birthdate.into(),
-                    mastermind_name: a.mastermind_name.clone().unwrap(),
+                    mastermind_name: a
+                        .mastermind_name
+                        .clone()
+                        .unwrap_or_else(|| REMOVED_BRAIN_NAME.to_string()),
                     blew_minds: minds_blown,
                     imploded_minds: minds_imploded,
+                    bookmarked_by_me: a.bookmarked_by_me,
+                    attachments: get_attachments(pool, &a.id).await.unwrap_or_default(),
+                    link_previews: get_link_previews(pool, &find_urls(&a.content)).await,
+                    poll: get_poll(pool, &a.id, viewer_id).await,
+                    original: get_original(pool, a.originalid, viewer_id).await,
+                    rebrainfarts: a.rebrainfarts,
+                    rebrainfarted_by_me: a.rebrainfarted_by_me,
                 });
             }
         }
@@ -102,14 +171,82 @@ pub async fn get_brainfarts_using_filter(
 pub async fn get_brainfart(
     pool: &Pool<Postgres>,
     brainfart_id: &Uuid,
+    viewer_id: &Uuid,
+) -> Option<BrainfartInformation> {
+    sqlx::query!(
+        "select id from brainfarts where id=$1
+            and ((not hidden and publishdate is null) or mastermind=$2)",
+        brainfart_id,
+        viewer_id
+    )
+    .fetch_optional(pool)
+    .await
+    .log_error("get_models::get_brainfart")
+    .ok()??;
+    get_brainfart_information(pool, brainfart_id, viewer_id, true).await
+}
+pub async fn get_any_brainfart(
+    pool: &Pool<Postgres>,
+    brainfart_id: &Uuid,
+) -> Option<BrainfartInformation> {
+    get_brainfart_information(pool, brainfart_id, &Uuid::nil(), true).await
+}
+/// Whether the brainfart is in the feed, i.e. neither hidden nor scheduled.
+pub async fn is_in_feed(pool: &Pool<Postgres>, brainfart_id: &Uuid) -> bool {
+    let query = sqlx::query!(
+        r#"select exists(select 1 from brainfarts where id=$1 and not hidden and publishdate is null) as "in_feed!""#,
+        brainfart_id
+    )
+    .fetch_one(pool)
+    .await
+    .log_error("get_models::is_in_feed");
+    matches!(query, Ok(result) if result.in_feed)
+}
+/// The original of a rebrainfart, as long as it can be seen in the feed. Its own original is
+/// left out, so quotes of quotes do not nest any deeper.
+async fn get_original(
+    pool: &Pool<Postgres>,
+    original_id: Option<Uuid>,
+    viewer_id: &Uuid,
+) -> Option<Box<BrainfartInformation>> {
+    let original_id = original_id?;
+    sqlx::query!(
+        "select id from brainfarts where id=$1 and not hidden and publishdate is null",
+        original_id
+    )
+    .fetch_optional(pool)
+    .await
+    .log_error("get_models::get_original")
+    .ok()??;
+    Box::pin(get_brainfart_information(
+        pool,
+        &original_id,
+        viewer_id,
+        false,
+    ))
+    .await
+    .map(Box::new)
+}
+async fn get_brainfart_information(
+    pool: &Pool<Postgres>,
+    brainfart_id: &Uuid,
+    viewer_id: &Uuid,
+    with_original: bool,
 ) -> Option<BrainfartInformation> {
     let select_query = sqlx::query!(
-        "select title, content, birthdate, (select brainname from brains where id=mastermind LIMIT 1) as mastermind_name FROM
-            brainfarts where id=$1 LIMIT 1",
-            &brainfart_id
+        r#"select title, content, birthdate, originalid, (select brainname from brains where id=mastermind LIMIT 1) as mastermind_name,
+            exists(select 1 from bookmarkedfarts where brainfartid=$1 and brainid=$2) as "bookmarked_by_me!",
+            (select count(*) from brainfarts as shares where shares.originalid=$1
+                and not shares.hidden and shares.publishdate is null) as "rebrainfarts!",
+            exists(select 1 from brainfarts as shares where shares.originalid=$1
+                and shares.mastermind=$2 and shares.content='') as "rebrainfarted_by_me!" FROM
+            brainfarts where id=$1 LIMIT 1"#,
+            &brainfart_id,
+            viewer_id
     )
     .fetch_one(pool)
-    .await;
+    .await
+    .log_error("get_models::get_brainfart_information");
 
     if let Ok(a) = select_query {
         let minds_blown_query = sqlx::query!(
@@ -117,14 +254,13 @@ pub async fn get_brainfart(
             &brainfart_id
         )
         .fetch_all(pool)
-        .await;
+        .await
+        .log_error("get_models::get_brainfart_information");
         if let Ok(minds_blown_result) = minds_blown_query {
             let mut minds_blown = vec![];
             let mut minds_imploded = vec![];
             for record in minds_blown_result.iter() {
-                if let Some(brain_info) =
-                    get_brain_information(&pool, &record.brainid.unwrap()).await
-                {
+                if let Some(brain_info) = get_reacting_brain(pool, record.brainid).await {
                     if record.explosion.unwrap() {
                         minds_blown.push(brain_info);
                     } else {
@@ -138,11 +274,686 @@ pub async fn get_brainfart(
                 title: a.title.clone(),
                 content: a.content.clone(),
                 birthdate: a.birthdate.into(),
-                mastermind_name: a.mastermind_name.clone().unwrap(),
+                mastermind_name: a
+                    .mastermind_name
+                    .clone()
+                    .unwrap_or_else(|| REMOVED_BRAIN_NAME.to_string()),
                 blew_minds: minds_blown,
                 imploded_minds: minds_imploded,
+                bookmarked_by_me: a.bookmarked_by_me,
+                attachments: get_attachments(pool, brainfart_id)
+                    .await
+                    .unwrap_or_default(),
+                link_previews: get_link_previews(pool, &find_urls(&a.content)).await,
+                poll: get_poll(pool, brainfart_id, viewer_id).await,
+                original: if with_original {
+                    get_original(pool, a.originalid, viewer_id).await
+                } else {
+                    None
+                },
+                rebrainfarts: a.rebrainfarts,
+                rebrainfarted_by_me: a.rebrainfarted_by_me,
             });
         }
     }
     None
 }
+pub async fn list_brains(pool: &Pool<Postgres>) -> Option<Vec<BrainAccount>> {
+    let query = sqlx::query!(
+        "select id, brainname, birthdate, disabled, role from brains ORDER BY birthdate"
+    )
+    .fetch_all(pool)
+    .await
+    .log_error("get_models::list_brains");
+    if let Ok(result) = query {
+        Some(
+            result
+                .into_iter()
+                .map(|record| BrainAccount {
+                    id: record.id,
+                    brainname: record.brainname,
+                    birthdate: record.birthdate.into(),
+                    disabled: record.disabled,
+                    role: record.role.parse().unwrap_or_default(),
+                })
+                .collect(),
+        )
+    } else {
+        None
+    }
+}
+pub async fn is_ready(pool: &Pool<Postgres>) -> bool {
+    sqlx::query!("select 1 as one")
+        .fetch_one(pool)
+        .await
+        .log_error("get_models::is_ready")
+        .is_ok()
+}
+
+pub async fn get_statistics(pool: &Pool<Postgres>) -> Option<UsageStatistics> {
+    let query = sqlx::query!(
+        r#"select
+            (select count(*) from brains) as "brains!",
+            (select count(*) from brains where disabled) as "disabled_brains!",
+            (select count(*) from brainfarts) as "brainfarts!",
+            (select count(*) from mindsblownbyfarts where explosion) as "explosions!",
+            (select count(*) from mindsblownbyfarts where not explosion) as "implosions!",
+            (select count(*) from hallucinatedfarts) as "views!""#
+    )
+    .fetch_one(pool)
+    .await
+    .log_error("get_models::get_statistics");
+    if let Ok(result) = query {
+        Some(UsageStatistics {
+            brains: result.brains,
+            disabled_brains: result.disabled_brains,
+            brainfarts: result.brainfarts,
+            explosions: result.explosions,
+            implosions: result.implosions,
+            views: result.views,
+        })
+    } else {
+        None
+    }
+}
+pub async fn get_top_brainfarts(
+    pool: &Pool<Postgres>,
+    since: Option<DateTime<Local>>,
+    limit: i64,
+) -> Option<Vec<LeaderboardBrainfart>> {
+    let query = sqlx::query!(
+        r#"select brainfarts.id, brainfarts.title, brains.brainname as "brainname?", count(*) as "explosions!"
+            from mindsblownbyfarts
+            join brainfarts on brainfarts.id=mindsblownbyfarts.brainfartid
+            left join brains on brains.id=brainfarts.mastermind
+            where mindsblownbyfarts.explosion and not brainfarts.hidden and brainfarts.publishdate is null
+            and ($1::timestamptz is null or mindsblownbyfarts.reactiondate >= $1)
+            group by brainfarts.id, brains.brainname
+            ORDER BY "explosions!" DESC, brainfarts.birthdate DESC LIMIT $2"#,
+        since.map(|since| since.with_timezone(&Utc)),
+        limit
+    )
+    .fetch_all(pool)
+    .await
+    .log_error("get_models::get_top_brainfarts");
+    if let Ok(result) = query {
+        Some(
+            result
+                .into_iter()
+                .map(|row| LeaderboardBrainfart {
+                    brainfart_id: row.id.to_string(),
+                    title: row.title,
+                    mastermind_name: row
+                        .brainname
+                        .unwrap_or_else(|| REMOVED_BRAIN_NAME.to_string()),
+                    explosions: row.explosions,
+                })
+                .collect(),
+        )
+    } else {
+        None
+    }
+}
+pub async fn get_top_masterminds(
+    pool: &Pool<Postgres>,
+    since: Option<DateTime<Local>>,
+    limit: i64,
+) -> Option<Vec<LeaderboardMastermind>> {
+    let query = sqlx::query!(
+        r#"select brains.brainname, count(*) as "explosions!"
+            from mindsblownbyfarts
+            join brainfarts on brainfarts.id=mindsblownbyfarts.brainfartid
+            join brains on brains.id=brainfarts.mastermind
+            where mindsblownbyfarts.explosion and not brainfarts.hidden and brainfarts.publishdate is null
+            and ($1::timestamptz is null or mindsblownbyfarts.reactiondate >= $1)
+            group by brains.id, brains.brainname
+            ORDER BY "explosions!" DESC, brains.brainname LIMIT $2"#,
+        since.map(|since| since.with_timezone(&Utc)),
+        limit
+    )
+    .fetch_all(pool)
+    .await
+    .log_error("get_models::get_top_masterminds");
+    if let Ok(result) = query {
+        Some(
+            result
+                .into_iter()
+                .map(|row| LeaderboardMastermind {
+                    brainname: row.brainname,
+                    explosions: row.explosions,
+                })
+                .collect(),
+        )
+    } else {
+        None
+    }
+}
+pub async fn get_brain_role(pool: &Pool<Postgres>, brain_id: &Uuid) -> Option<Role> {
+    let query = sqlx::query!(
+        "select role from brains where id=$1 and not disabled LIMIT 1",
+        brain_id
+    )
+    .fetch_optional(pool)
+    .await
+    .log_error("get_models::get_brain_role");
+    if let Ok(Some(result)) = query {
+        result.role.parse().ok()
+    } else {
+        None
+    }
+}
+pub async fn get_feed_token_generation(pool: &Pool<Postgres>, brain_id: &Uuid) -> Option<i64> {
+    let query = sqlx::query!(
+        "select feedtokengeneration from brains where id=$1 LIMIT 1",
+        brain_id
+    )
+    .fetch_optional(pool)
+    .await
+    .log_error("get_models::get_feed_token_generation");
+    if let Ok(Some(result)) = query {
+        Some(result.feedtokengeneration)
+    } else {
+        None
+    }
+}
+pub async fn get_reported_brainfarts(pool: &Pool<Postgres>) -> Option<Vec<ReportedBrainfart>> {
+    let select_query = sqlx::query!(
+        "select id, hidden from brainfarts where id in
+            (select brainfartid from reportedfarts where not resolved) ORDER BY birthdate DESC"
+    )
+    .fetch_all(pool)
+    .await
+    .log_error("get_models::get_reported_brainfarts");
+
+    if let Ok(result) = select_query {
+        let mut final_result = vec![];
+        for a in result.iter() {
+            let reports_query = sqlx::query!(
+                "select reason, reportdate, (select brainname from brains where id=brainid LIMIT 1) as reporter_name FROM
+                    reportedfarts where brainfartid=$1 and not resolved ORDER BY reportdate",
+                a.id
+            )
+            .fetch_all(pool)
+            .await
+            .log_error("get_models::get_reported_brainfarts");
+            if let (Some(brainfart), Ok(reports_result)) = (
+                get_brainfart(pool, &a.id, &Uuid::nil()).await,
+                reports_query,
+            ) {
+                final_result.push(ReportedBrainfart {
+                    brainfart,
+                    hidden: a.hidden,
+                    reports: reports_result
+                        .into_iter()
+                        .map(|record| ReportInformation {
+                            reporter_name: record.reporter_name.unwrap_or_default(),
+                            reason: record.reason,
+                            reportdate: record.reportdate.into(),
+                        })
+                        .collect(),
+                });
+            }
+        }
+        Some(final_result)
+    } else {
+        None
+    }
+}
+pub async fn is_blocked_by_mastermind(
+    pool: &Pool<Postgres>,
+    brainfart_id: &Uuid,
+    brain_id: &Uuid,
+) -> bool {
+    let query = sqlx::query!(
+        r#"select exists(select 1 from brainrelations join brainfarts on brainfarts.mastermind=brainrelations.brainid
+            where brainfarts.id=$1 and brainrelations.otherbrainid=$2 and brainrelations.relation='block') as "blocked!""#,
+        brainfart_id,
+        brain_id
+    )
+    .fetch_one(pool)
+    .await
+    .log_error("get_models::is_blocked_by_mastermind");
+    matches!(query, Ok(result) if result.blocked)
+}
+pub async fn is_mastermind(pool: &Pool<Postgres>, brainfart_id: &Uuid, brain_id: &Uuid) -> bool {
+    let query = sqlx::query!(
+        r#"select exists(select 1 from brainfarts where id=$1 and mastermind=$2) as "mastermind!""#,
+        brainfart_id,
+        brain_id
+    )
+    .fetch_one(pool)
+    .await
+    .log_error("get_models::is_mastermind");
+    matches!(query, Ok(result) if result.mastermind)
+}
+pub async fn get_brain_relations(
+    pool: &Pool<Postgres>,
+    brain_id: &Uuid,
+) -> Option<Vec<BrainRelationInformation>> {
+    let query = sqlx::query!(
+        r#"select relation, (select brainname from brains where id=otherbrainid LIMIT 1) as "brainname!" FROM
+            brainrelations where brainid=$1 ORDER BY createdate"#,
+        brain_id
+    )
+    .fetch_all(pool)
+    .await
+    .log_error("get_models::get_brain_relations");
+    if let Ok(result) = query {
+        Some(
+            result
+                .into_iter()
+                .filter_map(|record| {
+                    Some(BrainRelationInformation {
+                        brainname: record.brainname,
+                        relation: record.relation.parse().ok()?,
+                    })
+                })
+                .collect(),
+        )
+    } else {
+        None
+    }
+}
+pub async fn export_brain(
+    pool: &Pool<Postgres>,
+    brain_id: &Uuid,
+    now: DateTime<Local>,
+) -> Option<BrainExport> {
+    let brain = sqlx::query!(
+        "select id, brainname, role, birthdate from brains where id=$1 LIMIT 1",
+        brain_id
+    )
+    .fetch_one(pool)
+    .await
+    .log_error("get_models::export_brain")
+    .ok()?;
+    let brainfarts = sqlx::query!(
+        "select id, title, content, birthdate, hidden, publishdate, originalid from brainfarts
+            where mastermind=$1 ORDER BY birthdate",
+        brain_id
+    )
+    .fetch_all(pool)
+    .await
+    .log_error("get_models::export_brain")
+    .ok()?;
+    let reactions = sqlx::query!(
+        r#"select brainfartid as "brainfartid!", explosion as "explosion!", reactiondate
+            from mindsblownbyfarts where brainid=$1 ORDER BY reactiondate"#,
+        brain_id
+    )
+    .fetch_all(pool)
+    .await
+    .log_error("get_models::export_brain")
+    .ok()?;
+    let views = sqlx::query!(
+        r#"select brainfartid as "brainfartid!", hallucinationdate from hallucinatedfarts
+            where brainid=$1 ORDER BY hallucinationdate"#,
+        brain_id
+    )
+    .fetch_all(pool)
+    .await
+    .log_error("get_models::export_brain")
+    .ok()?;
+    Some(BrainExport {
+        version: EXPORT_VERSION,
+        exported_at: now,
+        brain: ExportedBrain {
+            id: brain.id.to_string(),
+            brainname: brain.brainname,
+            role: brain.role.parse().unwrap_or_default(),
+            birthdate: brain.birthdate.into(),
+        },
+        brainfarts: brainfarts
+            .into_iter()
+            .map(|brainfart| ExportedBrainfart {
+                id: brainfart.id.to_string(),
+                title: brainfart.title,
+                content: brainfart.content,
+                birthdate: brainfart.birthdate.into(),
+                hidden: brainfart.hidden,
+                publish_at: brainfart.publishdate.map(Into::into),
+                original_id: brainfart.originalid.map(|id| id.to_string()),
+            })
+            .collect(),
+        reactions: reactions
+            .into_iter()
+            .map(|reaction| ExportedReaction {
+                brainfart_id: reaction.brainfartid.to_string(),
+                kind: if reaction.explosion {
+                    ReactionKind::Explosion
+                } else {
+                    ReactionKind::Implosion
+                },
+                reactiondate: reaction.reactiondate.into(),
+            })
+            .collect(),
+        views: views
+            .into_iter()
+            .map(|view| ExportedView {
+                brainfart_id: view.brainfartid.to_string(),
+                viewdate: view.hallucinationdate.into(),
+            })
+            .collect(),
+    })
+}
+
+pub async fn get_brain_deletion(pool: &Pool<Postgres>, brain_id: &Uuid) -> Option<BrainDeletion> {
+    let result = sqlx::query!(
+        "select deletiondate, deletionmode from brains where id=$1 LIMIT 1",
+        brain_id
+    )
+    .fetch_one(pool)
+    .await
+    .log_error("get_models::get_brain_deletion")
+    .ok()?;
+    Some(BrainDeletion {
+        mode: result.deletionmode?.parse().ok()?,
+        delete_at: result.deletiondate?.into(),
+    })
+}
+
+pub async fn get_due_brain_deletions(
+    pool: &Pool<Postgres>,
+    now: DateTime<Local>,
+) -> Option<Vec<Uuid>> {
+    let result = sqlx::query!(
+        "select id from brains where deletiondate <= $1 ORDER BY deletiondate",
+        now.with_timezone(&Utc)
+    )
+    .fetch_all(pool)
+    .await
+    .log_error("get_models::get_due_brain_deletions")
+    .ok()?;
+    Some(result.into_iter().map(|row| row.id).collect())
+}
+
+pub async fn get_draft(pool: &Pool<Postgres>, brain_id: &Uuid) -> Option<BrainfartDraft> {
+    let query = sqlx::query!(
+        "select title, content, publishdate, lastupdatedate from brainfartdrafts where brainid=$1 LIMIT 1",
+        brain_id
+    )
+    .fetch_one(pool)
+    .await
+    .log_error("get_models::get_draft");
+    if let Ok(result) = query {
+        Some(BrainfartDraft {
+            title: result.title,
+            content: result.content,
+            publish_at: result.publishdate.map(Into::into),
+            lastupdatedate: result.lastupdatedate.into(),
+        })
+    } else {
+        None
+    }
+}
+
+pub async fn get_attachment(pool: &Pool<Postgres>, attachment_id: &Uuid) -> Option<AttachedFile> {
+    let query = sqlx::query!(
+        "select id, brainfartid, filename, contenttype, size, hasthumbnail from brainfartattachments where id=$1 LIMIT 1",
+        attachment_id
+    )
+    .fetch_one(pool)
+    .await
+    .log_error("get_models::get_attachment");
+    if let Ok(result) = query {
+        Some(AttachedFile {
+            brainfart_id: result.brainfartid,
+            attachment: AttachmentInformation {
+                id: result.id.to_string(),
+                filename: result.filename,
+                content_type: result.contenttype,
+                size: result.size,
+                has_thumbnail: result.hasthumbnail,
+            },
+        })
+    } else {
+        None
+    }
+}
+pub async fn get_attachments(
+    pool: &Pool<Postgres>,
+    brainfart_id: &Uuid,
+) -> Option<Vec<AttachmentInformation>> {
+    let query = sqlx::query!(
+        "select id, filename, contenttype, size, hasthumbnail from brainfartattachments where brainfartid=$1 ORDER BY uploaddate",
+        brainfart_id
+    )
+    .fetch_all(pool)
+    .await
+    .log_error("get_models::get_attachments");
+    if let Ok(result) = query {
+        Some(
+            result
+                .into_iter()
+                .map(|row| AttachmentInformation {
+                    id: row.id.to_string(),
+                    filename: row.filename,
+                    content_type: row.contenttype,
+                    size: row.size,
+                    has_thumbnail: row.hasthumbnail,
+                })
+                .collect(),
+        )
+    } else {
+        None
+    }
+}
+
+pub async fn has_link_preview(pool: &Pool<Postgres>, url: &str) -> bool {
+    let query = sqlx::query!("select url from linkpreviews where url=$1 LIMIT 1", url)
+        .fetch_optional(pool)
+        .await
+        .log_error("get_models::has_link_preview");
+    matches!(query, Ok(Some(_)))
+}
+/// The previews of those urls that have one, in the order of `urls`.
+pub async fn get_link_previews(pool: &Pool<Postgres>, urls: &[String]) -> Vec<LinkPreview> {
+    if urls.is_empty() {
+        return vec![];
+    }
+    let query = sqlx::query!(
+        r#"select url, title as "title!", description, imageurl, sitename from linkpreviews
+            where url = ANY($1) and title is not null"#,
+        urls
+    )
+    .fetch_all(pool)
+    .await
+    .log_error("get_models::get_link_previews");
+    let mut previews: Vec<LinkPreview> = match query {
+        Ok(result) => result
+            .into_iter()
+            .map(|row| LinkPreview {
+                url: row.url,
+                title: row.title,
+                description: row.description,
+                image_url: row.imageurl,
+                site_name: row.sitename,
+            })
+            .collect(),
+        Err(_) => vec![],
+    };
+    previews.sort_by_key(|preview| urls.iter().position(|url| url == &preview.url));
+    previews
+}
+
+pub async fn get_poll(
+    pool: &Pool<Postgres>,
+    brainfart_id: &Uuid,
+    viewer_id: &Uuid,
+) -> Option<PollInformation> {
+    let poll = sqlx::query!(
+        r#"select closedate, multiplechoice,
+            (select count(*) from pollballots where brainfartid=$1) as "voters!"
+            from polls where brainfartid=$1 LIMIT 1"#,
+        brainfart_id
+    )
+    .fetch_optional(pool)
+    .await
+    .log_error("get_models::get_poll")
+    .ok()??;
+    let options = sqlx::query!(
+        r#"select text,
+            (select count(*) from pollvotes where brainfartid=$1 and position=polloptions.position) as "votes!",
+            exists(select 1 from pollvotes join pollballots on pollballots.id=pollvotes.ballotid
+                where pollvotes.brainfartid=$1 and pollvotes.position=polloptions.position
+                and pollballots.brainid=$2) as "voted_by_me!"
+            from polloptions where brainfartid=$1 ORDER BY position"#,
+        brainfart_id,
+        viewer_id
+    )
+    .fetch_all(pool)
+    .await
+    .log_error("get_models::get_poll")
+    .ok()?;
+    Some(PollInformation {
+        options: options
+            .into_iter()
+            .map(|option| PollOptionResult {
+                text: option.text,
+                votes: option.votes,
+                voted_by_me: option.voted_by_me,
+            })
+            .collect(),
+        closes_at: poll.closedate.map(Into::into),
+        multiple_choice: poll.multiplechoice,
+        voters: poll.voters,
+    })
+}
+
+pub async fn get_webhook(pool: &Pool<Postgres>, webhook_id: &Uuid) -> Option<Webhook> {
+    let query = sqlx::query!(
+        "select id, ownerid, url, events, secret, global, createdate from webhooks where id=$1",
+        webhook_id
+    )
+    .fetch_one(pool)
+    .await
+    .log_error("get_models::get_webhook");
+    query.ok().map(|row| Webhook {
+        id: row.id,
+        owner_id: row.ownerid,
+        url: row.url,
+        events: events_from_text(&row.events),
+        secret: row.secret,
+        global: row.global,
+        createdate: row.createdate.into(),
+    })
+}
+
+pub async fn get_webhooks(pool: &Pool<Postgres>, owner_id: &Uuid) -> Option<Vec<Webhook>> {
+    let query = sqlx::query!(
+        "select id, ownerid, url, events, secret, global, createdate from webhooks where ownerid=$1 ORDER BY createdate",
+        owner_id
+    )
+    .fetch_all(pool)
+    .await
+    .log_error("get_models::get_webhooks");
+    query.ok().map(|rows| {
+        rows.into_iter()
+            .map(|row| Webhook {
+                id: row.id,
+                owner_id: row.ownerid,
+                url: row.url,
+                events: events_from_text(&row.events),
+                secret: row.secret,
+                global: row.global,
+                createdate: row.createdate.into(),
+            })
+            .collect()
+    })
+}
+
+pub async fn get_subscribed_webhooks(
+    pool: &Pool<Postgres>,
+    event: WebhookEvent,
+    mastermind_name: &str,
+) -> Option<Vec<Webhook>> {
+    let query = sqlx::query!(
+        "select webhooks.id, ownerid, url, events, secret, global, createdate from webhooks
+            where global OR ownerid=(select id from brains where brainname=$1)
+            ORDER BY createdate",
+        mastermind_name
+    )
+    .fetch_all(pool)
+    .await
+    .log_error("get_models::get_subscribed_webhooks");
+    query.ok().map(|rows| {
+        rows.into_iter()
+            .map(|row| Webhook {
+                id: row.id,
+                owner_id: row.ownerid,
+                url: row.url,
+                events: events_from_text(&row.events),
+                secret: row.secret,
+                global: row.global,
+                createdate: row.createdate.into(),
+            })
+            .filter(|webhook| webhook.events.contains(&event))
+            .collect()
+    })
+}
+
+pub async fn get_due_webhook_deliveries(
+    pool: &Pool<Postgres>,
+    now: DateTime<Local>,
+    limit: i64,
+) -> Option<Vec<DueDelivery>> {
+    let query = sqlx::query!(
+        "select webhookdeliveries.id, webhookid, url, secret, event, payload, attempts
+            from webhookdeliveries JOIN webhooks ON webhooks.id=webhookdeliveries.webhookid
+            where status='pending' and nextattemptdate <= $1
+            ORDER BY webhookdeliveries.createdate LIMIT $2",
+        now.with_timezone(&Utc),
+        limit
+    )
+    .fetch_all(pool)
+    .await
+    .log_error("get_models::get_due_webhook_deliveries");
+    query.ok().map(|rows| {
+        rows.into_iter()
+            .filter_map(|row| {
+                Some(DueDelivery {
+                    id: row.id,
+                    webhook_id: row.webhookid,
+                    url: row.url,
+                    secret: row.secret,
+                    event: row.event.parse().ok()?,
+                    payload: row.payload,
+                    attempts: row.attempts,
+                })
+            })
+            .collect()
+    })
+}
+
+pub async fn get_webhook_deliveries(
+    pool: &Pool<Postgres>,
+    webhook_id: &Uuid,
+    limit: i64,
+) -> Option<Vec<WebhookDeliveryInformation>> {
+    let query = sqlx::query!(
+        "select id, event, status, attempts, responsestatus, lasterror, createdate, lastattemptdate, nextattemptdate
+            from webhookdeliveries where webhookid=$1 ORDER BY createdate DESC LIMIT $2",
+        webhook_id,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+    .log_error("get_models::get_webhook_deliveries");
+    query.ok().map(|rows| {
+        rows.into_iter()
+            .filter_map(|row| {
+                Some(WebhookDeliveryInformation {
+                    id: row.id.to_string(),
+                    event: row.event.parse().ok()?,
+                    status: row.status.parse().ok()?,
+                    attempts: row.attempts,
+                    response_status: row.responsestatus,
+                    error: row.lasterror,
+                    createdate: row.createdate.into(),
+                    last_attempt_at: row.lastattemptdate.map(Into::into),
+                    next_attempt_at: row.nextattemptdate.map(Into::into),
+                })
+            })
+            .collect()
+    })
+}
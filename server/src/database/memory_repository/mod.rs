@@ -0,0 +1,1689 @@
+use std::sync::Mutex;
+
+use axum::async_trait;
+use chrono::{DateTime, Local};
+use shared::{
+    find_tags, find_urls, AttachmentInformation, BookmarkRequest, BrainDeletion, BrainExport,
+    BrainInformation, BrainRelation, BrainRelationInformation, BrainRelationRequest,
+    BrainfartDraft, BrainfartFilter, BrainfartFilterMode, BrainfartInformation, BrainfartOrder,
+    CreateBrainfartRequest, CreateWebhookRequest, DeletionMode, DeliveryStatus, ExportedBrain,
+    ExportedBrainfart, ExportedReaction, ExportedView, LeaderboardBrainfart, LeaderboardMastermind,
+    LinkPreview, NotifyAboutMindExplosionRequest, NotifyAboutMindImplosionRequest, PollInformation,
+    PollOptionResult, ProveOwnsBrainRequest, ReactionKind, RebrainfartRequest,
+    RegisterBrainRequest, ReportBrainfartRequest, ReportInformation, ReportedBrainfart, Role,
+    SaveDraftRequest, WebhookDeliveryInformation, WebhookEvent, EXPORT_VERSION,
+};
+use sqlx::types::Uuid;
+
+use crate::ranking;
+
+use super::{
+    create_models::CreateResponse,
+    password_verification::{hash_password, password_matches},
+    repository::{
+        AttachedFile, BrainAccount, DeliveryAttempt, DueDelivery, ImportSummary, NewAttachment,
+        PoolStatus, Repository, UsageStatistics, Webhook,
+    },
+};
+
+struct StoredBrain {
+    id: Uuid,
+    brainname: String,
+    password: String,
+    birthdate: DateTime<Local>,
+    disabled: bool,
+    role: Role,
+    deletion: Option<BrainDeletion>,
+    feed_token_generation: i64,
+}
+
+struct StoredBrainfart {
+    id: Uuid,
+    title: String,
+    content: String,
+    birthdate: DateTime<Local>,
+    mastermind: Uuid,
+    hidden: bool,
+    publish_at: Option<DateTime<Local>>,
+    original_id: Option<Uuid>,
+}
+
+struct StoredReaction {
+    brainfart_id: Uuid,
+    brain_id: Uuid,
+    explosion: bool,
+    /// When the brain last changed its mind about the brainfart.
+    reactiondate: DateTime<Local>,
+}
+
+struct StoredView {
+    brainfart_id: Uuid,
+    brain_id: Uuid,
+    viewdate: DateTime<Local>,
+}
+
+struct StoredRelation {
+    brain_id: Uuid,
+    other_brain_id: Uuid,
+    relation: BrainRelation,
+}
+
+struct StoredBookmark {
+    brainfart_id: Uuid,
+    brain_id: Uuid,
+}
+
+struct StoredAttachment {
+    brainfart_id: Uuid,
+    attachment: AttachmentInformation,
+}
+
+struct StoredPoll {
+    brainfart_id: Uuid,
+    options: Vec<String>,
+    closes_at: Option<DateTime<Local>>,
+    multiple_choice: bool,
+}
+
+struct StoredBallot {
+    brainfart_id: Uuid,
+    brain_id: Uuid,
+    positions: Vec<usize>,
+}
+
+struct StoredDraft {
+    brain_id: Uuid,
+    draft: BrainfartDraft,
+}
+
+struct StoredReport {
+    brainfart_id: Uuid,
+    brain_id: Uuid,
+    reason: String,
+    reportdate: DateTime<Local>,
+    resolved: bool,
+}
+
+struct StoredDelivery {
+    id: Uuid,
+    webhook_id: Uuid,
+    payload: String,
+    delivery: WebhookDeliveryInformation,
+}
+
+#[derive(Default)]
+struct Store {
+    brains: Vec<StoredBrain>,
+    brainfarts: Vec<StoredBrainfart>,
+    reactions: Vec<StoredReaction>,
+    views: Vec<StoredView>,
+    reports: Vec<StoredReport>,
+    relations: Vec<StoredRelation>,
+    bookmarks: Vec<StoredBookmark>,
+    attachments: Vec<StoredAttachment>,
+    polls: Vec<StoredPoll>,
+    ballots: Vec<StoredBallot>,
+    /// Urls without a preview are kept as `None`.
+    link_previews: Vec<(String, Option<LinkPreview>)>,
+    drafts: Vec<StoredDraft>,
+    webhooks: Vec<Webhook>,
+    deliveries: Vec<StoredDelivery>,
+}
+
+impl StoredBrainfart {
+    fn is_visible(&self) -> bool {
+        !self.hidden && self.publish_at.is_none()
+    }
+
+    /// A plain rebrainfart has nothing to show once its original is out of the feed.
+    fn is_orphaned_rebrainfart(&self, brainfarts: &[StoredBrainfart]) -> bool {
+        self.content.is_empty()
+            && self.original_id.is_some_and(|original_id| {
+                !brainfarts
+                    .iter()
+                    .any(|original| original.id == original_id && original.is_visible())
+            })
+    }
+}
+
+impl Store {
+    fn delete_brainfart(&mut self, brainfart_id: &Uuid) {
+        self.brainfarts
+            .retain(|brainfart| &brainfart.id != brainfart_id);
+        self.reactions
+            .retain(|reaction| &reaction.brainfart_id != brainfart_id);
+        self.views.retain(|view| &view.brainfart_id != brainfart_id);
+        self.reports
+            .retain(|report| &report.brainfart_id != brainfart_id);
+        self.bookmarks
+            .retain(|bookmark| &bookmark.brainfart_id != brainfart_id);
+        self.attachments
+            .retain(|stored| &stored.brainfart_id != brainfart_id);
+        self.ballots
+            .retain(|ballot| &ballot.brainfart_id != brainfart_id);
+        self.polls.retain(|poll| &poll.brainfart_id != brainfart_id);
+    }
+
+    /// Plain rebrainfarts go with their original, quotes are kept without it.
+    fn delete_brainfart_with_rebrainfarts(&mut self, brainfart_id: &Uuid) -> bool {
+        if !self
+            .brainfarts
+            .iter()
+            .any(|brainfart| &brainfart.id == brainfart_id)
+        {
+            return false;
+        }
+        let mut ids = self
+            .brainfarts
+            .iter()
+            .filter(|share| {
+                share.original_id.as_ref() == Some(brainfart_id) && share.content.is_empty()
+            })
+            .map(|share| share.id)
+            .collect::<Vec<_>>();
+        ids.push(*brainfart_id);
+        for brainfart in self.brainfarts.iter_mut() {
+            if brainfart.original_id.as_ref() == Some(brainfart_id) {
+                brainfart.original_id = None;
+            }
+        }
+        for brainfart_id in ids.iter() {
+            self.delete_brainfart(brainfart_id);
+        }
+        true
+    }
+
+    /// A brain that has been deleted shows as [`BrainInformation::removed`] on what it left behind.
+    fn get_brain_information_or_removed(&self, brain_id: &Uuid) -> BrainInformation {
+        self.get_brain_information(brain_id)
+            .unwrap_or_else(BrainInformation::removed)
+    }
+
+    fn get_brain_information(&self, brain_id: &Uuid) -> Option<BrainInformation> {
+        self.brains
+            .iter()
+            .find(|brain| &brain.id == brain_id)
+            .map(|brain| {
+                BrainInformation::create(
+                    brain.id.to_string(),
+                    brain.brainname.clone(),
+                    brain.birthdate,
+                )
+            })
+    }
+
+    fn to_information(
+        &self,
+        brainfart: &StoredBrainfart,
+        viewer_id: &Uuid,
+    ) -> Option<BrainfartInformation> {
+        let mastermind = self.get_brain_information_or_removed(&brainfart.mastermind);
+        let mut minds_blown = vec![];
+        let mut minds_imploded = vec![];
+        for reaction in self
+            .reactions
+            .iter()
+            .filter(|reaction| reaction.brainfart_id == brainfart.id)
+        {
+            let brain_info = self.get_brain_information_or_removed(&reaction.brain_id);
+            if reaction.explosion {
+                minds_blown.push(brain_info);
+            } else {
+                minds_imploded.push(brain_info);
+            }
+        }
+        Some(BrainfartInformation {
+            id: brainfart.id.to_string(),
+            title: brainfart.title.clone(),
+            content: brainfart.content.clone(),
+            birthdate: brainfart.birthdate,
+            mastermind_name: mastermind.get_name().to_string(),
+            blew_minds: minds_blown,
+            imploded_minds: minds_imploded,
+            bookmarked_by_me: self.has_bookmark(&brainfart.id, viewer_id),
+            attachments: self.get_attachments(&brainfart.id),
+            link_previews: find_urls(&brainfart.content)
+                .iter()
+                .filter_map(|url| {
+                    self.link_previews
+                        .iter()
+                        .find(|(cached_url, _)| cached_url == url)
+                        .and_then(|(_, preview)| preview.clone())
+                })
+                .collect(),
+            poll: self.get_poll(&brainfart.id, viewer_id),
+            original: brainfart
+                .original_id
+                .and_then(|original_id| self.get_original(&original_id, viewer_id))
+                .map(Box::new),
+            rebrainfarts: self
+                .brainfarts
+                .iter()
+                .filter(|share| share.original_id == Some(brainfart.id) && share.is_visible())
+                .count() as i64,
+            rebrainfarted_by_me: self.has_plain_rebrainfart(&brainfart.id, viewer_id),
+        })
+    }
+
+    /// Only one level deep, so quotes of quotes do not nest any further.
+    fn get_original(&self, original_id: &Uuid, viewer_id: &Uuid) -> Option<BrainfartInformation> {
+        let original = self
+            .brainfarts
+            .iter()
+            .find(|original| &original.id == original_id && original.is_visible())?;
+        let mut information = self.to_information(original, viewer_id)?;
+        information.original = None;
+        Some(information)
+    }
+
+    /// The visible brainfarts with at least one explosion since `since`, with how many they got.
+    fn explosions_since(&self, since: Option<DateTime<Local>>) -> Vec<(&StoredBrainfart, i64)> {
+        self.brainfarts
+            .iter()
+            .filter(|brainfart| brainfart.is_visible())
+            .map(|brainfart| {
+                let explosions = self
+                    .reactions
+                    .iter()
+                    .filter(|reaction| {
+                        reaction.brainfart_id == brainfart.id
+                            && reaction.explosion
+                            && since.is_none_or(|since| reaction.reactiondate >= since)
+                    })
+                    .count();
+                (brainfart, explosions as i64)
+            })
+            .filter(|(_, explosions)| *explosions > 0)
+            .collect()
+    }
+
+    /// A plain rebrainfart has the tags of its original.
+    fn tags(&self, brainfart: &StoredBrainfart) -> Vec<String> {
+        let tagged = match brainfart.original_id {
+            Some(original_id) if brainfart.content.is_empty() => self
+                .brainfarts
+                .iter()
+                .find(|original| original.id == original_id)
+                .unwrap_or(brainfart),
+            _ => brainfart,
+        };
+        find_tags(&format!("{} {}", tagged.title, tagged.content))
+    }
+
+    fn hot_score(&self, brainfart: &StoredBrainfart, now: DateTime<Local>) -> f64 {
+        let count_reactions = |explosion: bool| {
+            self.reactions
+                .iter()
+                .filter(|reaction| {
+                    reaction.brainfart_id == brainfart.id && reaction.explosion == explosion
+                })
+                .count() as i64
+        };
+        let views = self
+            .views
+            .iter()
+            .filter(|view| view.brainfart_id == brainfart.id)
+            .count() as i64;
+        ranking::hot_score(
+            count_reactions(true),
+            count_reactions(false),
+            views,
+            ranking::age_in_hours(brainfart.birthdate, now),
+        )
+    }
+
+    fn has_plain_rebrainfart(&self, original_id: &Uuid, brain_id: &Uuid) -> bool {
+        self.brainfarts.iter().any(|share| {
+            share.original_id.as_ref() == Some(original_id)
+                && &share.mastermind == brain_id
+                && share.content.is_empty()
+        })
+    }
+
+    fn get_poll(&self, brainfart_id: &Uuid, viewer_id: &Uuid) -> Option<PollInformation> {
+        let poll = self
+            .polls
+            .iter()
+            .find(|poll| &poll.brainfart_id == brainfart_id)?;
+        let ballots = self
+            .ballots
+            .iter()
+            .filter(|ballot| &ballot.brainfart_id == brainfart_id)
+            .collect::<Vec<_>>();
+        Some(PollInformation {
+            options: poll
+                .options
+                .iter()
+                .enumerate()
+                .map(|(position, text)| PollOptionResult {
+                    text: text.clone(),
+                    votes: ballots
+                        .iter()
+                        .filter(|ballot| ballot.positions.contains(&position))
+                        .count() as i64,
+                    voted_by_me: ballots.iter().any(|ballot| {
+                        &ballot.brain_id == viewer_id && ballot.positions.contains(&position)
+                    }),
+                })
+                .collect(),
+            closes_at: poll.closes_at,
+            multiple_choice: poll.multiple_choice,
+            voters: ballots.len() as i64,
+        })
+    }
+
+    fn get_attachments(&self, brainfart_id: &Uuid) -> Vec<AttachmentInformation> {
+        self.attachments
+            .iter()
+            .filter(|stored| &stored.brainfart_id == brainfart_id)
+            .map(|stored| stored.attachment.clone())
+            .collect()
+    }
+
+    fn has_bookmark(&self, brainfart_id: &Uuid, brain_id: &Uuid) -> bool {
+        self.bookmarks.iter().any(|bookmark| {
+            &bookmark.brainfart_id == brainfart_id && &bookmark.brain_id == brain_id
+        })
+    }
+
+    fn has_relation(
+        &self,
+        brain_id: &Uuid,
+        other_brain_id: &Uuid,
+        relation: BrainRelation,
+    ) -> bool {
+        self.relations.iter().any(|stored| {
+            &stored.brain_id == brain_id
+                && &stored.other_brain_id == other_brain_id
+                && stored.relation == relation
+        })
+    }
+
+    fn is_blocked_by_mastermind(&self, brainfart_id: &Uuid, brain_id: &Uuid) -> bool {
+        self.brainfarts.iter().any(|brainfart| {
+            &brainfart.id == brainfart_id
+                && self.has_relation(&brainfart.mastermind, brain_id, BrainRelation::Block)
+        })
+    }
+
+    fn find_brain_id(&self, brainname: &str) -> Option<Uuid> {
+        self.brains
+            .iter()
+            .find(|brain| brain.brainname == brainname)
+            .map(|brain| brain.id)
+    }
+
+    fn react(&mut self, brainfart_id: &str, brain_id: &Uuid, explosion: bool) -> Option<()> {
+        let brainfart_id = Uuid::parse_str(brainfart_id).ok()?;
+        if !self
+            .brainfarts
+            .iter()
+            .any(|fart| fart.id == brainfart_id && fart.is_visible())
+            || !self.brains.iter().any(|brain| &brain.id == brain_id)
+            || self.is_blocked_by_mastermind(&brainfart_id, brain_id)
+        {
+            return None;
+        }
+        if let Some(reaction) = self.reactions.iter_mut().find(|reaction| {
+            reaction.brainfart_id == brainfart_id && &reaction.brain_id == brain_id
+        }) {
+            reaction.explosion = explosion;
+            reaction.reactiondate = Local::now();
+        } else {
+            self.reactions.push(StoredReaction {
+                brainfart_id,
+                brain_id: *brain_id,
+                explosion,
+                reactiondate: Local::now(),
+            });
+        }
+        Some(())
+    }
+}
+
+/// Keeps everything in process memory. Used by the handler tests so they can run without Postgres.
+#[derive(Default)]
+pub struct MemoryRepository {
+    store: Mutex<Store>,
+}
+
+impl MemoryRepository {
+    pub fn create() -> MemoryRepository {
+        MemoryRepository::default()
+    }
+}
+
+#[async_trait]
+impl Repository for MemoryRepository {
+    async fn create_brain(&self, request: &RegisterBrainRequest) -> Option<CreateResponse> {
+        let password_hash = hash_password(request.get_password())?;
+        let mut store = self.store.lock().unwrap();
+        if store
+            .brains
+            .iter()
+            .any(|brain| brain.brainname == request.get_name())
+        {
+            return None;
+        }
+        let brain = StoredBrain {
+            id: Uuid::new_v4(),
+            brainname: request.get_name().to_string(),
+            password: password_hash.clone(),
+            birthdate: Local::now(),
+            disabled: false,
+            role: Role::Brain,
+            deletion: None,
+            feed_token_generation: 0,
+        };
+        let response = CreateResponse {
+            uuid: brain.id,
+            birthdate: brain.birthdate,
+            extra_information: Some(password_hash),
+        };
+        store.brains.push(brain);
+        Some(response)
+    }
+
+    async fn verify_password(&self, request: &ProveOwnsBrainRequest) -> Option<Uuid> {
+        let store = self.store.lock().unwrap();
+        store
+            .brains
+            .iter()
+            .find(|brain| brain.brainname == request.get_name() && !brain.disabled)
+            .filter(|brain| password_matches(&brain.password, request.get_password()))
+            .map(|brain| brain.id)
+    }
+
+    async fn get_brain_information(&self, brain_id: &Uuid) -> Option<BrainInformation> {
+        self.store.lock().unwrap().get_brain_information(brain_id)
+    }
+
+    async fn list_brains(&self) -> Option<Vec<BrainAccount>> {
+        let store = self.store.lock().unwrap();
+        Some(
+            store
+                .brains
+                .iter()
+                .map(|brain| BrainAccount {
+                    id: brain.id,
+                    brainname: brain.brainname.clone(),
+                    birthdate: brain.birthdate,
+                    disabled: brain.disabled,
+                    role: brain.role,
+                })
+                .collect(),
+        )
+    }
+
+    async fn set_brain_disabled(&self, brainname: &str, disabled: bool) -> bool {
+        let mut store = self.store.lock().unwrap();
+        if let Some(brain) = store
+            .brains
+            .iter_mut()
+            .find(|brain| brain.brainname == brainname)
+        {
+            brain.disabled = disabled;
+            true
+        } else {
+            false
+        }
+    }
+
+    async fn reset_password(&self, brainname: &str, password: &str) -> bool {
+        let password_hash = match hash_password(password) {
+            Some(password_hash) => password_hash,
+            None => return false,
+        };
+        let mut store = self.store.lock().unwrap();
+        if let Some(brain) = store
+            .brains
+            .iter_mut()
+            .find(|brain| brain.brainname == brainname)
+        {
+            brain.password = password_hash;
+            true
+        } else {
+            false
+        }
+    }
+
+    async fn get_brain_role(&self, brain_id: &Uuid) -> Option<Role> {
+        let store = self.store.lock().unwrap();
+        store
+            .brains
+            .iter()
+            .find(|brain| &brain.id == brain_id && !brain.disabled)
+            .map(|brain| brain.role)
+    }
+
+    async fn set_brain_role(&self, brainname: &str, role: Role) -> bool {
+        let mut store = self.store.lock().unwrap();
+        if let Some(brain) = store
+            .brains
+            .iter_mut()
+            .find(|brain| brain.brainname == brainname)
+        {
+            brain.role = role;
+            true
+        } else {
+            false
+        }
+    }
+
+    async fn get_feed_token_generation(&self, brain_id: &Uuid) -> Option<i64> {
+        let store = self.store.lock().unwrap();
+        store
+            .brains
+            .iter()
+            .find(|brain| &brain.id == brain_id)
+            .map(|brain| brain.feed_token_generation)
+    }
+
+    async fn revoke_feed_tokens(&self, brain_id: &Uuid) -> bool {
+        let mut store = self.store.lock().unwrap();
+        if let Some(brain) = store.brains.iter_mut().find(|brain| &brain.id == brain_id) {
+            brain.feed_token_generation += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    async fn request_brain_deletion(&self, brain_id: &Uuid, deletion: &BrainDeletion) -> bool {
+        let mut store = self.store.lock().unwrap();
+        if let Some(brain) = store.brains.iter_mut().find(|brain| &brain.id == brain_id) {
+            brain.deletion = Some(deletion.clone());
+            true
+        } else {
+            false
+        }
+    }
+
+    async fn get_brain_deletion(&self, brain_id: &Uuid) -> Option<BrainDeletion> {
+        let store = self.store.lock().unwrap();
+        store
+            .brains
+            .iter()
+            .find(|brain| &brain.id == brain_id)?
+            .deletion
+            .clone()
+    }
+
+    async fn cancel_brain_deletion(&self, brain_id: &Uuid) -> bool {
+        let mut store = self.store.lock().unwrap();
+        store
+            .brains
+            .iter_mut()
+            .find(|brain| &brain.id == brain_id)
+            .and_then(|brain| brain.deletion.take())
+            .is_some()
+    }
+
+    async fn get_due_brain_deletions(&self, now: DateTime<Local>) -> Option<Vec<Uuid>> {
+        let store = self.store.lock().unwrap();
+        Some(
+            store
+                .brains
+                .iter()
+                .filter(|brain| {
+                    brain
+                        .deletion
+                        .as_ref()
+                        .is_some_and(|deletion| deletion.delete_at <= now)
+                })
+                .map(|brain| brain.id)
+                .collect(),
+        )
+    }
+
+    async fn delete_brain(
+        &self,
+        brain_id: &Uuid,
+        mode: DeletionMode,
+    ) -> Option<Vec<AttachmentInformation>> {
+        let mut store = self.store.lock().unwrap();
+        if !store.brains.iter().any(|brain| &brain.id == brain_id) {
+            return None;
+        }
+        let mut attachments = vec![];
+        while let Some(brainfart_id) = store
+            .brainfarts
+            .iter()
+            .find(|brainfart| {
+                &brainfart.mastermind == brain_id
+                    && (mode == DeletionMode::Delete || brainfart.publish_at.is_some())
+            })
+            .map(|brainfart| brainfart.id)
+        {
+            attachments.extend(store.get_attachments(&brainfart_id));
+            store.delete_brainfart_with_rebrainfarts(&brainfart_id);
+        }
+        if mode == DeletionMode::Delete {
+            store
+                .reactions
+                .retain(|reaction| &reaction.brain_id != brain_id);
+        }
+        store.views.retain(|view| &view.brain_id != brain_id);
+        store.reports.retain(|report| &report.brain_id != brain_id);
+        store.relations.retain(|relation| {
+            &relation.brain_id != brain_id && &relation.other_brain_id != brain_id
+        });
+        store
+            .bookmarks
+            .retain(|bookmark| &bookmark.brain_id != brain_id);
+        store.ballots.retain(|ballot| &ballot.brain_id != brain_id);
+        store.drafts.retain(|draft| &draft.brain_id != brain_id);
+        let webhook_ids = store
+            .webhooks
+            .iter()
+            .filter(|webhook| &webhook.owner_id == brain_id)
+            .map(|webhook| webhook.id)
+            .collect::<Vec<_>>();
+        store
+            .deliveries
+            .retain(|delivery| !webhook_ids.contains(&delivery.webhook_id));
+        store
+            .webhooks
+            .retain(|webhook| &webhook.owner_id != brain_id);
+        store.brains.retain(|brain| &brain.id != brain_id);
+        Some(attachments)
+    }
+
+    async fn create_brainfart(
+        &self,
+        request: &CreateBrainfartRequest,
+        mastermind_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        let mut store = self.store.lock().unwrap();
+        if !store.brains.iter().any(|brain| &brain.id == mastermind_id) {
+            return None;
+        }
+        let brainfart = StoredBrainfart {
+            id: Uuid::new_v4(),
+            title: request.get_title().to_string(),
+            content: request.get_content().to_string(),
+            birthdate: Local::now(),
+            mastermind: *mastermind_id,
+            hidden: false,
+            publish_at: request.get_publish_at(),
+            original_id: None,
+        };
+        let response = CreateResponse {
+            uuid: brainfart.id,
+            birthdate: brainfart.birthdate,
+            extra_information: None,
+        };
+        if let Some(poll) = request.get_poll() {
+            store.polls.push(StoredPoll {
+                brainfart_id: brainfart.id,
+                options: poll
+                    .options
+                    .iter()
+                    .map(|option| option.trim().to_string())
+                    .collect(),
+                closes_at: poll.closes_at,
+                multiple_choice: poll.multiple_choice,
+            });
+        }
+        store.brainfarts.push(brainfart);
+        Some(response)
+    }
+
+    async fn get_brainfart(
+        &self,
+        brainfart_id: &Uuid,
+        viewer_id: &Uuid,
+    ) -> Option<BrainfartInformation> {
+        let store = self.store.lock().unwrap();
+        store
+            .brainfarts
+            .iter()
+            .find(|brainfart| {
+                &brainfart.id == brainfart_id
+                    && (brainfart.is_visible() || &brainfart.mastermind == viewer_id)
+            })
+            .and_then(|brainfart| store.to_information(brainfart, viewer_id))
+    }
+
+    async fn get_any_brainfart(&self, brainfart_id: &Uuid) -> Option<BrainfartInformation> {
+        let store = self.store.lock().unwrap();
+        store
+            .brainfarts
+            .iter()
+            .find(|brainfart| &brainfart.id == brainfart_id)
+            .and_then(|brainfart| store.to_information(brainfart, &Uuid::nil()))
+    }
+
+    async fn get_brainfarts_using_filter(
+        &self,
+        filter: BrainfartFilter,
+        viewer_id: &Uuid,
+    ) -> Option<Vec<BrainfartInformation>> {
+        let store = self.store.lock().unwrap();
+        let mut brainfarts = store
+            .brainfarts
+            .iter()
+            .rev()
+            .filter(|brainfart| {
+                brainfart.is_visible()
+                    && !brainfart.is_orphaned_rebrainfart(&store.brainfarts)
+                    && !store.has_relation(viewer_id, &brainfart.mastermind, BrainRelation::Mute)
+                    && (filter.mode != BrainfartFilterMode::Bookmarks
+                        || store.has_bookmark(&brainfart.id, viewer_id))
+                    && filter.brainname.as_ref().is_none_or(|brainname| {
+                        store.brains.iter().any(|brain| {
+                            brain.id == brainfart.mastermind && &brain.brainname == brainname
+                        })
+                    })
+                    && filter
+                        .tag
+                        .as_ref()
+                        .is_none_or(|tag| store.tags(brainfart).contains(tag))
+            })
+            .collect::<Vec<_>>();
+        brainfarts.sort_by_key(|brainfart| std::cmp::Reverse(brainfart.birthdate));
+        if filter.order == BrainfartOrder::Hot {
+            let now = Local::now();
+            ranking::sort_by_hotness(&mut brainfarts, |brainfart| {
+                (store.hot_score(brainfart, now), brainfart.birthdate)
+            });
+        }
+        if let Some(limit) = filter.limit {
+            brainfarts.truncate(limit as usize);
+        }
+        Some(
+            brainfarts
+                .into_iter()
+                .filter_map(|brainfart| store.to_information(brainfart, viewer_id))
+                .collect(),
+        )
+    }
+
+    async fn delete_brainfart(&self, brainfart_id: &Uuid) -> bool {
+        self.store
+            .lock()
+            .unwrap()
+            .delete_brainfart_with_rebrainfarts(brainfart_id)
+    }
+
+    async fn create_rebrainfart(
+        &self,
+        request: &RebrainfartRequest,
+        original_id: &Uuid,
+        mastermind_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        let mut store = self.store.lock().unwrap();
+        let commentary = request.commentary.trim();
+        if !store
+            .brainfarts
+            .iter()
+            .any(|original| &original.id == original_id && original.is_visible())
+            || !store.brains.iter().any(|brain| &brain.id == mastermind_id)
+            || (commentary.is_empty() && store.has_plain_rebrainfart(original_id, mastermind_id))
+        {
+            return None;
+        }
+        let rebrainfart = StoredBrainfart {
+            id: Uuid::new_v4(),
+            title: String::new(),
+            content: commentary.to_string(),
+            birthdate: Local::now(),
+            mastermind: *mastermind_id,
+            hidden: false,
+            publish_at: None,
+            original_id: Some(*original_id),
+        };
+        let response = CreateResponse {
+            uuid: rebrainfart.id,
+            birthdate: rebrainfart.birthdate,
+            extra_information: None,
+        };
+        store.brainfarts.push(rebrainfart);
+        Some(response)
+    }
+
+    async fn register_mind_explosion(
+        &self,
+        request: &NotifyAboutMindExplosionRequest,
+        brain_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        let mut store = self.store.lock().unwrap();
+        store
+            .react(&request.brainfart_id, brain_id, true)
+            .map(|_| CreateResponse::empty())
+    }
+
+    async fn register_mind_implosion(
+        &self,
+        request: &NotifyAboutMindImplosionRequest,
+        brain_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        let mut store = self.store.lock().unwrap();
+        store
+            .react(&request.brainfart_id, brain_id, false)
+            .map(|_| CreateResponse::empty())
+    }
+
+    async fn is_blocked_by_mastermind(&self, brainfart_id: &Uuid, brain_id: &Uuid) -> bool {
+        self.store
+            .lock()
+            .unwrap()
+            .is_blocked_by_mastermind(brainfart_id, brain_id)
+    }
+
+    async fn is_mastermind(&self, brainfart_id: &Uuid, brain_id: &Uuid) -> bool {
+        self.store
+            .lock()
+            .unwrap()
+            .brainfarts
+            .iter()
+            .any(|brainfart| &brainfart.id == brainfart_id && &brainfart.mastermind == brain_id)
+    }
+
+    async fn add_brain_relation(
+        &self,
+        request: &BrainRelationRequest,
+        brain_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        let mut store = self.store.lock().unwrap();
+        let other_brain_id = store.find_brain_id(&request.brainname)?;
+        if !store.has_relation(brain_id, &other_brain_id, request.relation) {
+            store.relations.push(StoredRelation {
+                brain_id: *brain_id,
+                other_brain_id,
+                relation: request.relation,
+            });
+        }
+        Some(CreateResponse::empty())
+    }
+
+    async fn remove_brain_relation(&self, request: &BrainRelationRequest, brain_id: &Uuid) -> bool {
+        let mut store = self.store.lock().unwrap();
+        let other_brain_id = match store.find_brain_id(&request.brainname) {
+            Some(other_brain_id) => other_brain_id,
+            None => return false,
+        };
+        let count = store.relations.len();
+        store.relations.retain(|stored| {
+            !(&stored.brain_id == brain_id
+                && stored.other_brain_id == other_brain_id
+                && stored.relation == request.relation)
+        });
+        store.relations.len() < count
+    }
+
+    async fn get_brain_relations(&self, brain_id: &Uuid) -> Option<Vec<BrainRelationInformation>> {
+        let store = self.store.lock().unwrap();
+        Some(
+            store
+                .relations
+                .iter()
+                .filter(|stored| &stored.brain_id == brain_id)
+                .filter_map(|stored| {
+                    Some(BrainRelationInformation {
+                        brainname: store
+                            .get_brain_information(&stored.other_brain_id)?
+                            .get_name()
+                            .to_string(),
+                        relation: stored.relation,
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    async fn add_bookmark(
+        &self,
+        request: &BookmarkRequest,
+        brain_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        let brainfart_id = Uuid::parse_str(&request.brainfart_id).ok()?;
+        let mut store = self.store.lock().unwrap();
+        if !store
+            .brainfarts
+            .iter()
+            .any(|fart| fart.id == brainfart_id && fart.is_visible())
+            || !store.brains.iter().any(|brain| &brain.id == brain_id)
+        {
+            return None;
+        }
+        if !store.has_bookmark(&brainfart_id, brain_id) {
+            store.bookmarks.push(StoredBookmark {
+                brainfart_id,
+                brain_id: *brain_id,
+            });
+        }
+        Some(CreateResponse::empty())
+    }
+
+    async fn remove_bookmark(&self, request: &BookmarkRequest, brain_id: &Uuid) -> bool {
+        let brainfart_id = match Uuid::parse_str(&request.brainfart_id) {
+            Ok(brainfart_id) => brainfart_id,
+            Err(_) => return false,
+        };
+        let mut store = self.store.lock().unwrap();
+        let count = store.bookmarks.len();
+        store.bookmarks.retain(|bookmark| {
+            !(bookmark.brainfart_id == brainfart_id && &bookmark.brain_id == brain_id)
+        });
+        store.bookmarks.len() < count
+    }
+
+    async fn add_attachment(
+        &self,
+        attachment: &NewAttachment,
+        brainfart_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        let mut store = self.store.lock().unwrap();
+        if !store.brainfarts.iter().any(|fart| &fart.id == brainfart_id) {
+            return None;
+        }
+        let response = CreateResponse {
+            uuid: Uuid::new_v4(),
+            birthdate: Local::now(),
+            extra_information: None,
+        };
+        store.attachments.push(StoredAttachment {
+            brainfart_id: *brainfart_id,
+            attachment: AttachmentInformation {
+                id: response.uuid.to_string(),
+                filename: attachment.filename.clone(),
+                content_type: attachment.content_type.clone(),
+                size: attachment.size,
+                has_thumbnail: attachment.has_thumbnail,
+            },
+        });
+        Some(response)
+    }
+
+    async fn get_attachment(&self, attachment_id: &Uuid) -> Option<AttachedFile> {
+        let store = self.store.lock().unwrap();
+        store
+            .attachments
+            .iter()
+            .find(|stored| stored.attachment.id == attachment_id.to_string())
+            .map(|stored| AttachedFile {
+                brainfart_id: stored.brainfart_id,
+                attachment: stored.attachment.clone(),
+            })
+    }
+
+    async fn get_attachments(&self, brainfart_id: &Uuid) -> Option<Vec<AttachmentInformation>> {
+        Some(self.store.lock().unwrap().get_attachments(brainfart_id))
+    }
+
+    async fn remove_attachment(&self, attachment_id: &Uuid) -> bool {
+        let mut store = self.store.lock().unwrap();
+        let count = store.attachments.len();
+        store
+            .attachments
+            .retain(|stored| stored.attachment.id != attachment_id.to_string());
+        store.attachments.len() < count
+    }
+
+    async fn add_poll_vote(
+        &self,
+        brainfart_id: &Uuid,
+        brain_id: &Uuid,
+        positions: &[usize],
+    ) -> Option<CreateResponse> {
+        let mut store = self.store.lock().unwrap();
+        if !store
+            .brainfarts
+            .iter()
+            .any(|fart| &fart.id == brainfart_id && fart.is_visible())
+        {
+            return None;
+        }
+        let poll = store
+            .polls
+            .iter()
+            .find(|poll| &poll.brainfart_id == brainfart_id)?;
+        if positions
+            .iter()
+            .any(|position| *position >= poll.options.len())
+            || store
+                .ballots
+                .iter()
+                .any(|ballot| &ballot.brainfart_id == brainfart_id && &ballot.brain_id == brain_id)
+        {
+            return None;
+        }
+        store.ballots.push(StoredBallot {
+            brainfart_id: *brainfart_id,
+            brain_id: *brain_id,
+            positions: positions.to_vec(),
+        });
+        Some(CreateResponse {
+            uuid: Uuid::new_v4(),
+            birthdate: Local::now(),
+            extra_information: None,
+        })
+    }
+
+    async fn has_link_preview(&self, url: &str) -> bool {
+        let store = self.store.lock().unwrap();
+        store
+            .link_previews
+            .iter()
+            .any(|(cached_url, _)| cached_url == url)
+    }
+
+    async fn save_link_preview(&self, url: &str, preview: Option<&LinkPreview>) -> bool {
+        let mut store = self.store.lock().unwrap();
+        store
+            .link_previews
+            .retain(|(cached_url, _)| cached_url != url);
+        store
+            .link_previews
+            .push((url.to_string(), preview.cloned()));
+        true
+    }
+
+    async fn save_draft(
+        &self,
+        request: &SaveDraftRequest,
+        brain_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        let mut store = self.store.lock().unwrap();
+        if !store.brains.iter().any(|brain| &brain.id == brain_id) {
+            return None;
+        }
+        let draft = BrainfartDraft {
+            title: request.title.clone(),
+            content: request.content.clone(),
+            publish_at: request.publish_at,
+            lastupdatedate: Local::now(),
+        };
+        let response = CreateResponse {
+            uuid: Uuid::new_v4(),
+            birthdate: draft.lastupdatedate,
+            extra_information: None,
+        };
+        store.drafts.retain(|stored| &stored.brain_id != brain_id);
+        store.drafts.push(StoredDraft {
+            brain_id: *brain_id,
+            draft,
+        });
+        Some(response)
+    }
+
+    async fn get_draft(&self, brain_id: &Uuid) -> Option<BrainfartDraft> {
+        let store = self.store.lock().unwrap();
+        store
+            .drafts
+            .iter()
+            .find(|stored| &stored.brain_id == brain_id)
+            .map(|stored| stored.draft.clone())
+    }
+
+    async fn delete_draft(&self, brain_id: &Uuid) -> bool {
+        let mut store = self.store.lock().unwrap();
+        let count = store.drafts.len();
+        store.drafts.retain(|stored| &stored.brain_id != brain_id);
+        store.drafts.len() < count
+    }
+
+    async fn publish_scheduled_brainfarts(&self, now: DateTime<Local>) -> Option<Vec<Uuid>> {
+        let mut store = self.store.lock().unwrap();
+        let mut published = vec![];
+        for brainfart in store.brainfarts.iter_mut() {
+            if let Some(publish_at) = brainfart.publish_at.filter(|publish_at| publish_at <= &now) {
+                brainfart.birthdate = publish_at;
+                brainfart.publish_at = None;
+                published.push(brainfart.id);
+            }
+        }
+        Some(published)
+    }
+
+    async fn set_brainfart_as_viewed(&self, brainfart_id: &Uuid, brain_id: &Uuid) -> bool {
+        let mut store = self.store.lock().unwrap();
+        if store
+            .views
+            .iter()
+            .any(|view| &view.brainfart_id == brainfart_id && &view.brain_id == brain_id)
+        {
+            return false;
+        }
+        store.views.push(StoredView {
+            brainfart_id: *brainfart_id,
+            brain_id: *brain_id,
+            viewdate: Local::now(),
+        });
+        true
+    }
+
+    async fn report_brainfart(
+        &self,
+        request: &ReportBrainfartRequest,
+        brain_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        let brainfart_id = Uuid::parse_str(&request.brainfart_id).ok()?;
+        let mut store = self.store.lock().unwrap();
+        if !store.brainfarts.iter().any(|fart| fart.id == brainfart_id)
+            || !store.brains.iter().any(|brain| &brain.id == brain_id)
+        {
+            return None;
+        }
+        let reportdate = Local::now();
+        if let Some(report) = store
+            .reports
+            .iter_mut()
+            .find(|report| report.brainfart_id == brainfart_id && &report.brain_id == brain_id)
+        {
+            report.reason = request.reason.clone();
+            report.reportdate = reportdate;
+            report.resolved = false;
+        } else {
+            store.reports.push(StoredReport {
+                brainfart_id,
+                brain_id: *brain_id,
+                reason: request.reason.clone(),
+                reportdate,
+                resolved: false,
+            });
+        }
+        Some(CreateResponse {
+            uuid: Uuid::new_v4(),
+            birthdate: reportdate,
+            extra_information: None,
+        })
+    }
+
+    async fn get_reported_brainfarts(&self) -> Option<Vec<ReportedBrainfart>> {
+        let store = self.store.lock().unwrap();
+        let mut brainfarts = store
+            .brainfarts
+            .iter()
+            .rev()
+            .filter(|brainfart| {
+                store
+                    .reports
+                    .iter()
+                    .any(|report| report.brainfart_id == brainfart.id && !report.resolved)
+            })
+            .collect::<Vec<_>>();
+        brainfarts.sort_by_key(|brainfart| std::cmp::Reverse(brainfart.birthdate));
+        Some(
+            brainfarts
+                .into_iter()
+                .filter_map(|brainfart| {
+                    let mut reports = store
+                        .reports
+                        .iter()
+                        .filter(|report| report.brainfart_id == brainfart.id && !report.resolved)
+                        .collect::<Vec<_>>();
+                    reports.sort_by_key(|report| report.reportdate);
+                    Some(ReportedBrainfart {
+                        brainfart: store.to_information(brainfart, &Uuid::nil())?,
+                        hidden: brainfart.hidden,
+                        reports: reports
+                            .into_iter()
+                            .map(|report| ReportInformation {
+                                reporter_name: store
+                                    .get_brain_information(&report.brain_id)
+                                    .map(|brain| brain.get_name().to_string())
+                                    .unwrap_or_default(),
+                                reason: report.reason.clone(),
+                                reportdate: report.reportdate,
+                            })
+                            .collect(),
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    async fn set_brainfart_hidden(&self, brainfart_id: &Uuid, hidden: bool) -> bool {
+        let mut store = self.store.lock().unwrap();
+        if let Some(brainfart) = store
+            .brainfarts
+            .iter_mut()
+            .find(|brainfart| &brainfart.id == brainfart_id)
+        {
+            brainfart.hidden = hidden;
+        } else {
+            return false;
+        }
+        for report in store
+            .reports
+            .iter_mut()
+            .filter(|report| &report.brainfart_id == brainfart_id)
+        {
+            report.resolved = true;
+        }
+        true
+    }
+
+    async fn is_ready(&self) -> bool {
+        true
+    }
+
+    fn get_pool_status(&self) -> Option<PoolStatus> {
+        None
+    }
+
+    async fn get_statistics(&self) -> Option<UsageStatistics> {
+        let store = self.store.lock().unwrap();
+        let count = |amount: usize| amount as i64;
+        Some(UsageStatistics {
+            brains: count(store.brains.len()),
+            disabled_brains: count(store.brains.iter().filter(|brain| brain.disabled).count()),
+            brainfarts: count(store.brainfarts.len()),
+            explosions: count(store.reactions.iter().filter(|r| r.explosion).count()),
+            implosions: count(store.reactions.iter().filter(|r| !r.explosion).count()),
+            views: count(store.views.len()),
+        })
+    }
+
+    async fn get_top_brainfarts(
+        &self,
+        since: Option<DateTime<Local>>,
+        limit: i64,
+    ) -> Option<Vec<LeaderboardBrainfart>> {
+        let store = self.store.lock().unwrap();
+        let mut brainfarts = store.explosions_since(since);
+        brainfarts.sort_by_key(|(brainfart, explosions)| {
+            (
+                std::cmp::Reverse(*explosions),
+                std::cmp::Reverse(brainfart.birthdate),
+            )
+        });
+        brainfarts.truncate(limit.max(0) as usize);
+        brainfarts
+            .into_iter()
+            .map(|(brainfart, explosions)| {
+                Some(LeaderboardBrainfart {
+                    brainfart_id: brainfart.id.to_string(),
+                    title: brainfart.title.clone(),
+                    mastermind_name: store
+                        .get_brain_information_or_removed(&brainfart.mastermind)
+                        .get_name()
+                        .to_string(),
+                    explosions,
+                })
+            })
+            .collect()
+    }
+
+    async fn get_top_masterminds(
+        &self,
+        since: Option<DateTime<Local>>,
+        limit: i64,
+    ) -> Option<Vec<LeaderboardMastermind>> {
+        let store = self.store.lock().unwrap();
+        let mut masterminds: Vec<LeaderboardMastermind> = vec![];
+        for (brainfart, explosions) in store.explosions_since(since) {
+            let Some(brain) = store.get_brain_information(&brainfart.mastermind) else {
+                continue;
+            };
+            let brainname = brain.get_name().to_string();
+            match masterminds
+                .iter_mut()
+                .find(|mastermind| mastermind.brainname == brainname)
+            {
+                Some(mastermind) => mastermind.explosions += explosions,
+                None => masterminds.push(LeaderboardMastermind {
+                    brainname,
+                    explosions,
+                }),
+            }
+        }
+        masterminds.sort_by(|a, b| {
+            b.explosions
+                .cmp(&a.explosions)
+                .then_with(|| a.brainname.cmp(&b.brainname))
+        });
+        masterminds.truncate(limit.max(0) as usize);
+        Some(masterminds)
+    }
+
+    async fn export_brain(&self, brain_id: &Uuid, now: DateTime<Local>) -> Option<BrainExport> {
+        let store = self.store.lock().unwrap();
+        let brain = store.brains.iter().find(|brain| &brain.id == brain_id)?;
+        let mut brainfarts = store
+            .brainfarts
+            .iter()
+            .filter(|brainfart| &brainfart.mastermind == brain_id)
+            .collect::<Vec<_>>();
+        brainfarts.sort_by_key(|brainfart| brainfart.birthdate);
+        let mut reactions = store
+            .reactions
+            .iter()
+            .filter(|reaction| &reaction.brain_id == brain_id)
+            .collect::<Vec<_>>();
+        reactions.sort_by_key(|reaction| reaction.reactiondate);
+        Some(BrainExport {
+            version: EXPORT_VERSION,
+            exported_at: now,
+            brain: ExportedBrain {
+                id: brain.id.to_string(),
+                brainname: brain.brainname.clone(),
+                role: brain.role,
+                birthdate: brain.birthdate,
+            },
+            brainfarts: brainfarts
+                .into_iter()
+                .map(|brainfart| ExportedBrainfart {
+                    id: brainfart.id.to_string(),
+                    title: brainfart.title.clone(),
+                    content: brainfart.content.clone(),
+                    birthdate: brainfart.birthdate,
+                    hidden: brainfart.hidden,
+                    publish_at: brainfart.publish_at,
+                    original_id: brainfart.original_id.map(|id| id.to_string()),
+                })
+                .collect(),
+            reactions: reactions
+                .into_iter()
+                .map(|reaction| ExportedReaction {
+                    brainfart_id: reaction.brainfart_id.to_string(),
+                    kind: if reaction.explosion {
+                        ReactionKind::Explosion
+                    } else {
+                        ReactionKind::Implosion
+                    },
+                    reactiondate: reaction.reactiondate,
+                })
+                .collect(),
+            views: store
+                .views
+                .iter()
+                .filter(|view| &view.brain_id == brain_id)
+                .map(|view| ExportedView {
+                    brainfart_id: view.brainfart_id.to_string(),
+                    viewdate: view.viewdate,
+                })
+                .collect(),
+        })
+    }
+
+    async fn import_brain(&self, archive: &BrainExport, password: &str) -> Option<ImportSummary> {
+        let password_hash = hash_password(password)?;
+        let brain_id = Uuid::parse_str(&archive.brain.id).ok()?;
+        let mut brainfarts = vec![];
+        for brainfart in archive.brainfarts.iter() {
+            let original_id = match &brainfart.original_id {
+                Some(original_id) => Some(Uuid::parse_str(original_id).ok()?),
+                None => None,
+            };
+            brainfarts.push((Uuid::parse_str(&brainfart.id).ok()?, original_id, brainfart));
+        }
+        // Oldest first, so rebrainfarts of the brain's own brainfarts find their originals.
+        brainfarts.sort_by_key(|(_, _, brainfart)| brainfart.birthdate);
+        let mut store = self.store.lock().unwrap();
+        if store
+            .brains
+            .iter()
+            .any(|brain| brain.id == brain_id || brain.brainname == archive.brain.brainname)
+            || brainfarts
+                .iter()
+                .any(|(id, _, _)| store.brainfarts.iter().any(|stored| &stored.id == id))
+        {
+            return None;
+        }
+        store.brains.push(StoredBrain {
+            id: brain_id,
+            brainname: archive.brain.brainname.clone(),
+            password: password_hash,
+            birthdate: archive.brain.birthdate,
+            disabled: false,
+            role: Role::Brain,
+            deletion: None,
+            feed_token_generation: 0,
+        });
+        let mut summary = ImportSummary::default();
+        for (id, original_id, brainfart) in brainfarts {
+            let original_id = original_id.filter(|original_id| {
+                store
+                    .brainfarts
+                    .iter()
+                    .any(|original| &original.id == original_id)
+            });
+            if brainfart.original_id.is_some()
+                && original_id.is_none()
+                && brainfart.content.is_empty()
+            {
+                continue;
+            }
+            store.brainfarts.push(StoredBrainfart {
+                id,
+                title: brainfart.title.clone(),
+                content: brainfart.content.clone(),
+                birthdate: brainfart.birthdate,
+                mastermind: brain_id,
+                hidden: brainfart.hidden,
+                publish_at: brainfart.publish_at,
+                original_id,
+            });
+            summary.brainfarts += 1;
+        }
+        let exists = |store: &Store, brainfart_id: &str| {
+            Uuid::parse_str(brainfart_id)
+                .ok()
+                .filter(|id| store.brainfarts.iter().any(|brainfart| &brainfart.id == id))
+        };
+        for reaction in archive.reactions.iter() {
+            if let Some(brainfart_id) = exists(&store, &reaction.brainfart_id) {
+                store.reactions.push(StoredReaction {
+                    brainfart_id,
+                    brain_id,
+                    explosion: reaction.kind == ReactionKind::Explosion,
+                    reactiondate: reaction.reactiondate,
+                });
+                summary.reactions += 1;
+            }
+        }
+        for view in archive.views.iter() {
+            if let Some(brainfart_id) = exists(&store, &view.brainfart_id) {
+                store.views.push(StoredView {
+                    brainfart_id,
+                    brain_id,
+                    viewdate: view.viewdate,
+                });
+                summary.views += 1;
+            }
+        }
+        Some(summary)
+    }
+
+    async fn create_webhook(
+        &self,
+        request: &CreateWebhookRequest,
+        owner_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        let mut store = self.store.lock().unwrap();
+        if !store.brains.iter().any(|brain| &brain.id == owner_id) {
+            return None;
+        }
+        let webhook = Webhook {
+            id: Uuid::new_v4(),
+            owner_id: *owner_id,
+            url: request.url.clone(),
+            events: request.events.clone(),
+            secret: request.secret.clone(),
+            global: request.global,
+            createdate: Local::now(),
+        };
+        let response = CreateResponse {
+            uuid: webhook.id,
+            birthdate: webhook.createdate,
+            extra_information: None,
+        };
+        store.webhooks.push(webhook);
+        Some(response)
+    }
+
+    async fn get_webhook(&self, webhook_id: &Uuid) -> Option<Webhook> {
+        let store = self.store.lock().unwrap();
+        store
+            .webhooks
+            .iter()
+            .find(|webhook| &webhook.id == webhook_id)
+            .cloned()
+    }
+
+    async fn get_webhooks(&self, owner_id: &Uuid) -> Option<Vec<Webhook>> {
+        let store = self.store.lock().unwrap();
+        Some(
+            store
+                .webhooks
+                .iter()
+                .filter(|webhook| &webhook.owner_id == owner_id)
+                .cloned()
+                .collect(),
+        )
+    }
+
+    async fn get_subscribed_webhooks(
+        &self,
+        event: WebhookEvent,
+        mastermind_name: &str,
+    ) -> Option<Vec<Webhook>> {
+        let store = self.store.lock().unwrap();
+        let mastermind_id = store
+            .brains
+            .iter()
+            .find(|brain| brain.brainname == mastermind_name)
+            .map(|brain| brain.id);
+        Some(
+            store
+                .webhooks
+                .iter()
+                .filter(|webhook| webhook.global || Some(webhook.owner_id) == mastermind_id)
+                .filter(|webhook| webhook.events.contains(&event))
+                .cloned()
+                .collect(),
+        )
+    }
+
+    async fn delete_webhook(&self, webhook_id: &Uuid) -> bool {
+        let mut store = self.store.lock().unwrap();
+        store
+            .deliveries
+            .retain(|stored| &stored.webhook_id != webhook_id);
+        let count = store.webhooks.len();
+        store.webhooks.retain(|webhook| &webhook.id != webhook_id);
+        store.webhooks.len() < count
+    }
+
+    async fn add_webhook_delivery(
+        &self,
+        webhook_id: &Uuid,
+        event: WebhookEvent,
+        payload: &str,
+    ) -> Option<CreateResponse> {
+        let mut store = self.store.lock().unwrap();
+        if !store
+            .webhooks
+            .iter()
+            .any(|webhook| &webhook.id == webhook_id)
+        {
+            return None;
+        }
+        let now = Local::now();
+        let id = Uuid::new_v4();
+        let delivery = WebhookDeliveryInformation {
+            id: id.to_string(),
+            event,
+            status: DeliveryStatus::Pending,
+            attempts: 0,
+            response_status: None,
+            error: None,
+            createdate: now,
+            last_attempt_at: None,
+            next_attempt_at: Some(now),
+        };
+        let response = CreateResponse {
+            uuid: id,
+            birthdate: now,
+            extra_information: None,
+        };
+        store.deliveries.push(StoredDelivery {
+            id,
+            webhook_id: *webhook_id,
+            payload: payload.to_string(),
+            delivery,
+        });
+        Some(response)
+    }
+
+    async fn get_due_webhook_deliveries(
+        &self,
+        now: DateTime<Local>,
+        limit: i64,
+    ) -> Option<Vec<DueDelivery>> {
+        let store = self.store.lock().unwrap();
+        store
+            .deliveries
+            .iter()
+            .filter(|stored| {
+                stored.delivery.status == DeliveryStatus::Pending
+                    && stored
+                        .delivery
+                        .next_attempt_at
+                        .is_some_and(|next_attempt_at| next_attempt_at <= now)
+            })
+            .take(limit.max(0) as usize)
+            .map(|stored| {
+                let webhook = store
+                    .webhooks
+                    .iter()
+                    .find(|webhook| webhook.id == stored.webhook_id)?;
+                Some(DueDelivery {
+                    id: stored.id,
+                    webhook_id: webhook.id,
+                    url: webhook.url.clone(),
+                    secret: webhook.secret.clone(),
+                    event: stored.delivery.event,
+                    payload: stored.payload.clone(),
+                    attempts: stored.delivery.attempts,
+                })
+            })
+            .collect()
+    }
+
+    async fn record_webhook_delivery_attempt(
+        &self,
+        delivery_id: &Uuid,
+        attempt: &DeliveryAttempt,
+    ) -> bool {
+        let mut store = self.store.lock().unwrap();
+        match store
+            .deliveries
+            .iter_mut()
+            .find(|stored| &stored.id == delivery_id)
+        {
+            Some(stored) => {
+                let delivery = &mut stored.delivery;
+                delivery.status = attempt.status;
+                delivery.attempts += 1;
+                delivery.response_status = attempt.response_status;
+                delivery.error = attempt.error.clone();
+                delivery.last_attempt_at = Some(attempt.attempted_at);
+                delivery.next_attempt_at = attempt.next_attempt;
+                true
+            }
+            None => false,
+        }
+    }
+
+    async fn get_webhook_deliveries(
+        &self,
+        webhook_id: &Uuid,
+        limit: i64,
+    ) -> Option<Vec<WebhookDeliveryInformation>> {
+        let store = self.store.lock().unwrap();
+        Some(
+            store
+                .deliveries
+                .iter()
+                .rev()
+                .filter(|stored| &stored.webhook_id == webhook_id)
+                .take(limit.max(0) as usize)
+                .map(|stored| stored.delivery.clone())
+                .collect(),
+        )
+    }
+}
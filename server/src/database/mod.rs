@@ -1,3 +1,61 @@
+use std::sync::Arc;
+
+use sqlx::{migrate::Migrator, postgres::PgPoolOptions};
+
+use self::{
+    postgres_repository::PostgresRepository, repository::SharedRepository,
+    sqlite_repository::SqliteRepository,
+};
+
 pub mod create_models;
+pub mod delete_models;
 pub mod get_models;
+#[cfg(test)]
+pub mod memory_repository;
 pub mod password_verification;
+pub mod postgres_repository;
+pub mod repository;
+pub mod sqlite_repository;
+pub mod update_models;
+
+/// Logs a failed query, with what it was for, before the repositories turn the failure into a
+/// `None` or a `false`. A missing row is an answer rather than a failure, so it is not logged.
+pub trait LogError {
+    fn log_error(self, context: &str) -> Self;
+}
+
+impl<T> LogError for Result<T, sqlx::Error> {
+    fn log_error(self, context: &str) -> Self {
+        if let Err(err) = &self {
+            if !matches!(err, sqlx::Error::RowNotFound) {
+                tracing::error!(query = context, error = %err, "A database query failed");
+            }
+        }
+        self
+    }
+}
+
+/// The changes to schema.sql since the first release, which bring an older database up to date.
+/// They change nothing in a database created from the current schema.sql.
+static POSTGRES_MIGRATOR: Migrator = sqlx::migrate!("../migrations/postgres");
+
+/// Picks the storage backend from the scheme of the database url: `sqlite:` urls are served
+/// by SQLite, everything else is handed to Postgres. Either way the database is migrated before it
+/// is used.
+pub async fn connect(
+    database_url: &str,
+    max_connections: u32,
+) -> Result<SharedRepository, sqlx::Error> {
+    if database_url.starts_with("sqlite:") {
+        let repository = SqliteRepository::connect(database_url, max_connections).await?;
+        Ok(Arc::new(repository))
+    } else {
+        let pool = PgPoolOptions::new()
+            .max_connections(max_connections)
+            .connect(database_url)
+            .await?;
+        POSTGRES_MIGRATOR.run(&pool).await?;
+        update_models::fill_in_brainfart_tags(&pool).await?;
+        Ok(Arc::new(PostgresRepository::create(pool)))
+    }
+}
@@ -1,28 +1,43 @@
 use argon2::{
-    password_hash::{PasswordHash, PasswordVerifier},
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
     Argon2,
 };
 use shared::ProveOwnsBrainRequest;
 use sqlx::{types::Uuid, Pool, Postgres};
 
+use super::LogError;
+
+pub fn hash_password(password: &str) -> Option<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    let argon2 = Argon2::default();
+    argon2
+        .hash_password(password.as_bytes(), &salt)
+        .ok()
+        .map(|password_hash| password_hash.to_string())
+}
+
+pub fn password_matches(password_hash: &str, password: &str) -> bool {
+    let argon2 = Argon2::default();
+    let parsed_hash = PasswordHash::new(password_hash).unwrap();
+    argon2
+        .verify_password(password.as_bytes(), &parsed_hash)
+        .is_ok()
+}
+
 pub async fn verify_password(
     pool: &Pool<Postgres>,
     förfrågon: &ProveOwnsBrainRequest,
 ) -> Option<Uuid> {
     let brain_query = sqlx::query!(
-        "select id, password from brains where brainname=$1 LIMIT 1",
+        "select id, password from brains where brainname=$1 and not disabled LIMIT 1",
         &förfrågon.get_name(),
     )
     .fetch_one(pool)
-    .await;
+    .await
+    .log_error("password_verification::verify_password");
     if let Ok(result) = brain_query {
-        let argon2 = Argon2::default();
-        let parsed_hash = PasswordHash::new(&result.password).unwrap();
         //tokio::time::sleep(tokio::time::Duration::from_millis(10000)).await;
-        if argon2
-            .verify_password(förfrågon.get_password().as_bytes(), &parsed_hash)
-            .is_ok()
-        {
+        if password_matches(&result.password, förfrågon.get_password()) {
             return Some(result.id);
         }
     }
@@ -0,0 +1,369 @@
+use axum::async_trait;
+use chrono::{DateTime, Local};
+use shared::{
+    AttachmentInformation, BookmarkRequest, BrainDeletion, BrainExport, BrainInformation,
+    BrainRelationInformation, BrainRelationRequest, BrainfartDraft, BrainfartFilter,
+    BrainfartInformation, CreateBrainfartRequest, CreateWebhookRequest, DeletionMode,
+    LeaderboardBrainfart, LeaderboardMastermind, LinkPreview, NotifyAboutMindExplosionRequest,
+    NotifyAboutMindImplosionRequest, ProveOwnsBrainRequest, RebrainfartRequest,
+    RegisterBrainRequest, ReportBrainfartRequest, ReportedBrainfart, Role, SaveDraftRequest,
+    WebhookDeliveryInformation, WebhookEvent,
+};
+use sqlx::{types::Uuid, Pool, Postgres};
+
+use super::{
+    create_models::{self, CreateFromRequest, CreateResponse},
+    delete_models, get_models, password_verification,
+    repository::{
+        AttachedFile, BrainAccount, DeliveryAttempt, DueDelivery, ImportSummary, NewAttachment,
+        PoolStatus, Repository, UsageStatistics, Webhook,
+    },
+    update_models,
+};
+
+pub struct PostgresRepository {
+    pool: Pool<Postgres>,
+}
+
+impl PostgresRepository {
+    pub fn create(pool: Pool<Postgres>) -> PostgresRepository {
+        PostgresRepository { pool }
+    }
+}
+
+#[async_trait]
+impl Repository for PostgresRepository {
+    async fn create_brain(&self, request: &RegisterBrainRequest) -> Option<CreateResponse> {
+        request.create(&self.pool, &Uuid::nil()).await
+    }
+
+    async fn verify_password(&self, request: &ProveOwnsBrainRequest) -> Option<Uuid> {
+        password_verification::verify_password(&self.pool, request).await
+    }
+
+    async fn get_brain_information(&self, brain_id: &Uuid) -> Option<BrainInformation> {
+        get_models::get_brain_information(&self.pool, brain_id).await
+    }
+
+    async fn list_brains(&self) -> Option<Vec<BrainAccount>> {
+        get_models::list_brains(&self.pool).await
+    }
+
+    async fn set_brain_disabled(&self, brainname: &str, disabled: bool) -> bool {
+        update_models::set_brain_disabled(&self.pool, brainname, disabled).await
+    }
+
+    async fn reset_password(&self, brainname: &str, password: &str) -> bool {
+        update_models::reset_password(&self.pool, brainname, password).await
+    }
+
+    async fn request_brain_deletion(&self, brain_id: &Uuid, deletion: &BrainDeletion) -> bool {
+        update_models::request_brain_deletion(&self.pool, brain_id, deletion).await
+    }
+
+    async fn get_brain_deletion(&self, brain_id: &Uuid) -> Option<BrainDeletion> {
+        get_models::get_brain_deletion(&self.pool, brain_id).await
+    }
+
+    async fn cancel_brain_deletion(&self, brain_id: &Uuid) -> bool {
+        update_models::cancel_brain_deletion(&self.pool, brain_id).await
+    }
+
+    async fn get_due_brain_deletions(&self, now: DateTime<Local>) -> Option<Vec<Uuid>> {
+        get_models::get_due_brain_deletions(&self.pool, now).await
+    }
+
+    async fn delete_brain(
+        &self,
+        brain_id: &Uuid,
+        mode: DeletionMode,
+    ) -> Option<Vec<AttachmentInformation>> {
+        delete_models::delete_brain(&self.pool, brain_id, mode).await
+    }
+
+    async fn get_brain_role(&self, brain_id: &Uuid) -> Option<Role> {
+        get_models::get_brain_role(&self.pool, brain_id).await
+    }
+
+    async fn set_brain_role(&self, brainname: &str, role: Role) -> bool {
+        update_models::set_brain_role(&self.pool, brainname, role).await
+    }
+
+    async fn get_feed_token_generation(&self, brain_id: &Uuid) -> Option<i64> {
+        get_models::get_feed_token_generation(&self.pool, brain_id).await
+    }
+
+    async fn revoke_feed_tokens(&self, brain_id: &Uuid) -> bool {
+        update_models::revoke_feed_tokens(&self.pool, brain_id).await
+    }
+
+    async fn create_brainfart(
+        &self,
+        request: &CreateBrainfartRequest,
+        mastermind_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        request.create(&self.pool, mastermind_id).await
+    }
+
+    async fn get_brainfart(
+        &self,
+        brainfart_id: &Uuid,
+        viewer_id: &Uuid,
+    ) -> Option<BrainfartInformation> {
+        get_models::get_brainfart(&self.pool, brainfart_id, viewer_id).await
+    }
+
+    async fn get_any_brainfart(&self, brainfart_id: &Uuid) -> Option<BrainfartInformation> {
+        get_models::get_any_brainfart(&self.pool, brainfart_id).await
+    }
+
+    async fn get_brainfarts_using_filter(
+        &self,
+        filter: BrainfartFilter,
+        viewer_id: &Uuid,
+    ) -> Option<Vec<BrainfartInformation>> {
+        get_models::get_brainfarts_using_filter(&self.pool, filter, viewer_id).await
+    }
+
+    async fn delete_brainfart(&self, brainfart_id: &Uuid) -> bool {
+        delete_models::delete_brainfart(&self.pool, brainfart_id).await
+    }
+
+    async fn register_mind_explosion(
+        &self,
+        request: &NotifyAboutMindExplosionRequest,
+        brain_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        request.create(&self.pool, brain_id).await
+    }
+
+    async fn register_mind_implosion(
+        &self,
+        request: &NotifyAboutMindImplosionRequest,
+        brain_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        request.create(&self.pool, brain_id).await
+    }
+
+    async fn is_blocked_by_mastermind(&self, brainfart_id: &Uuid, brain_id: &Uuid) -> bool {
+        get_models::is_blocked_by_mastermind(&self.pool, brainfart_id, brain_id).await
+    }
+
+    async fn is_mastermind(&self, brainfart_id: &Uuid, brain_id: &Uuid) -> bool {
+        get_models::is_mastermind(&self.pool, brainfart_id, brain_id).await
+    }
+
+    async fn add_brain_relation(
+        &self,
+        request: &BrainRelationRequest,
+        brain_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        request.create(&self.pool, brain_id).await
+    }
+
+    async fn remove_brain_relation(&self, request: &BrainRelationRequest, brain_id: &Uuid) -> bool {
+        delete_models::remove_brain_relation(&self.pool, request, brain_id).await
+    }
+
+    async fn get_brain_relations(&self, brain_id: &Uuid) -> Option<Vec<BrainRelationInformation>> {
+        get_models::get_brain_relations(&self.pool, brain_id).await
+    }
+
+    async fn add_bookmark(
+        &self,
+        request: &BookmarkRequest,
+        brain_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        request.create(&self.pool, brain_id).await
+    }
+
+    async fn remove_bookmark(&self, request: &BookmarkRequest, brain_id: &Uuid) -> bool {
+        delete_models::remove_bookmark(&self.pool, request, brain_id).await
+    }
+
+    async fn add_attachment(
+        &self,
+        attachment: &NewAttachment,
+        brainfart_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        attachment.create(&self.pool, brainfart_id).await
+    }
+
+    async fn get_attachment(&self, attachment_id: &Uuid) -> Option<AttachedFile> {
+        get_models::get_attachment(&self.pool, attachment_id).await
+    }
+
+    async fn get_attachments(&self, brainfart_id: &Uuid) -> Option<Vec<AttachmentInformation>> {
+        get_models::get_attachments(&self.pool, brainfart_id).await
+    }
+
+    async fn remove_attachment(&self, attachment_id: &Uuid) -> bool {
+        delete_models::remove_attachment(&self.pool, attachment_id).await
+    }
+
+    async fn create_rebrainfart(
+        &self,
+        request: &RebrainfartRequest,
+        original_id: &Uuid,
+        mastermind_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        create_models::create_rebrainfart(&self.pool, request, original_id, mastermind_id).await
+    }
+
+    async fn add_poll_vote(
+        &self,
+        brainfart_id: &Uuid,
+        brain_id: &Uuid,
+        positions: &[usize],
+    ) -> Option<CreateResponse> {
+        create_models::add_poll_vote(&self.pool, brainfart_id, brain_id, positions).await
+    }
+
+    async fn has_link_preview(&self, url: &str) -> bool {
+        get_models::has_link_preview(&self.pool, url).await
+    }
+
+    async fn save_link_preview(&self, url: &str, preview: Option<&LinkPreview>) -> bool {
+        update_models::save_link_preview(&self.pool, url, preview).await
+    }
+
+    async fn save_draft(
+        &self,
+        request: &SaveDraftRequest,
+        brain_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        request.create(&self.pool, brain_id).await
+    }
+
+    async fn get_draft(&self, brain_id: &Uuid) -> Option<BrainfartDraft> {
+        get_models::get_draft(&self.pool, brain_id).await
+    }
+
+    async fn delete_draft(&self, brain_id: &Uuid) -> bool {
+        delete_models::delete_draft(&self.pool, brain_id).await
+    }
+
+    async fn publish_scheduled_brainfarts(&self, now: DateTime<Local>) -> Option<Vec<Uuid>> {
+        update_models::publish_scheduled_brainfarts(&self.pool, now).await
+    }
+
+    async fn set_brainfart_as_viewed(&self, brainfart_id: &Uuid, brain_id: &Uuid) -> bool {
+        get_models::set_brainfart_as_viewed(&self.pool, brainfart_id, brain_id).await
+    }
+
+    async fn report_brainfart(
+        &self,
+        request: &ReportBrainfartRequest,
+        brain_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        request.create(&self.pool, brain_id).await
+    }
+
+    async fn get_reported_brainfarts(&self) -> Option<Vec<ReportedBrainfart>> {
+        get_models::get_reported_brainfarts(&self.pool).await
+    }
+
+    async fn set_brainfart_hidden(&self, brainfart_id: &Uuid, hidden: bool) -> bool {
+        update_models::set_brainfart_hidden(&self.pool, brainfart_id, hidden).await
+    }
+
+    async fn get_statistics(&self) -> Option<UsageStatistics> {
+        get_models::get_statistics(&self.pool).await
+    }
+
+    async fn is_ready(&self) -> bool {
+        get_models::is_ready(&self.pool).await
+    }
+
+    fn get_pool_status(&self) -> Option<PoolStatus> {
+        Some(PoolStatus {
+            connections: self.pool.size(),
+            idle_connections: self.pool.num_idle() as u32,
+        })
+    }
+
+    async fn get_top_brainfarts(
+        &self,
+        since: Option<DateTime<Local>>,
+        limit: i64,
+    ) -> Option<Vec<LeaderboardBrainfart>> {
+        get_models::get_top_brainfarts(&self.pool, since, limit).await
+    }
+
+    async fn get_top_masterminds(
+        &self,
+        since: Option<DateTime<Local>>,
+        limit: i64,
+    ) -> Option<Vec<LeaderboardMastermind>> {
+        get_models::get_top_masterminds(&self.pool, since, limit).await
+    }
+
+    async fn export_brain(&self, brain_id: &Uuid, now: DateTime<Local>) -> Option<BrainExport> {
+        get_models::export_brain(&self.pool, brain_id, now).await
+    }
+
+    async fn import_brain(&self, archive: &BrainExport, password: &str) -> Option<ImportSummary> {
+        create_models::import_brain(&self.pool, archive, password).await
+    }
+
+    async fn create_webhook(
+        &self,
+        request: &CreateWebhookRequest,
+        owner_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        request.create(&self.pool, owner_id).await
+    }
+
+    async fn get_webhook(&self, webhook_id: &Uuid) -> Option<Webhook> {
+        get_models::get_webhook(&self.pool, webhook_id).await
+    }
+
+    async fn get_webhooks(&self, owner_id: &Uuid) -> Option<Vec<Webhook>> {
+        get_models::get_webhooks(&self.pool, owner_id).await
+    }
+
+    async fn get_subscribed_webhooks(
+        &self,
+        event: WebhookEvent,
+        mastermind_name: &str,
+    ) -> Option<Vec<Webhook>> {
+        get_models::get_subscribed_webhooks(&self.pool, event, mastermind_name).await
+    }
+
+    async fn delete_webhook(&self, webhook_id: &Uuid) -> bool {
+        delete_models::delete_webhook(&self.pool, webhook_id).await
+    }
+
+    async fn add_webhook_delivery(
+        &self,
+        webhook_id: &Uuid,
+        event: WebhookEvent,
+        payload: &str,
+    ) -> Option<CreateResponse> {
+        create_models::add_webhook_delivery(&self.pool, webhook_id, event, payload).await
+    }
+
+    async fn get_due_webhook_deliveries(
+        &self,
+        now: DateTime<Local>,
+        limit: i64,
+    ) -> Option<Vec<DueDelivery>> {
+        get_models::get_due_webhook_deliveries(&self.pool, now, limit).await
+    }
+
+    async fn record_webhook_delivery_attempt(
+        &self,
+        delivery_id: &Uuid,
+        attempt: &DeliveryAttempt,
+    ) -> bool {
+        update_models::record_webhook_delivery_attempt(&self.pool, delivery_id, attempt).await
+    }
+
+    async fn get_webhook_deliveries(
+        &self,
+        webhook_id: &Uuid,
+        limit: i64,
+    ) -> Option<Vec<WebhookDeliveryInformation>> {
+        get_models::get_webhook_deliveries(&self.pool, webhook_id, limit).await
+    }
+}
@@ -0,0 +1,389 @@
+use std::sync::Arc;
+
+use axum::async_trait;
+use chrono::{DateTime, Local};
+use shared::{
+    find_tags, AttachmentInformation, BookmarkRequest, BrainDeletion, BrainExport,
+    BrainInformation, BrainRelationInformation, BrainRelationRequest, BrainfartDraft,
+    BrainfartFilter, BrainfartInformation, CreateBrainfartRequest, CreateWebhookRequest,
+    DeletionMode, DeliveryStatus, LeaderboardBrainfart, LeaderboardMastermind, LinkPreview,
+    NotifyAboutMindExplosionRequest, NotifyAboutMindImplosionRequest, ProveOwnsBrainRequest,
+    RebrainfartRequest, RegisterBrainRequest, ReportBrainfartRequest, ReportedBrainfart, Role,
+    SaveDraftRequest, WebhookDeliveryInformation, WebhookEvent, WebhookInformation,
+};
+use sqlx::types::Uuid;
+
+use super::create_models::CreateResponse;
+
+pub type SharedRepository = Arc<dyn Repository>;
+
+/// A brain as the administration commands see it.
+#[derive(Debug, Clone)]
+pub struct BrainAccount {
+    pub id: Uuid,
+    pub brainname: String,
+    pub birthdate: DateTime<Local>,
+    pub disabled: bool,
+    pub role: Role,
+}
+
+/// What is recorded about an uploaded file. The file itself is kept by the attachment storage.
+#[derive(Debug, Clone)]
+pub struct NewAttachment {
+    pub filename: String,
+    pub content_type: String,
+    pub size: i64,
+    pub has_thumbnail: bool,
+}
+
+/// An attachment with the brainfart it is attached to.
+#[derive(Debug, Clone)]
+pub struct AttachedFile {
+    pub brainfart_id: Uuid,
+    pub attachment: AttachmentInformation,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct UsageStatistics {
+    pub brains: i64,
+    pub disabled_brains: i64,
+    pub brainfarts: i64,
+    pub explosions: i64,
+    pub implosions: i64,
+    pub views: i64,
+}
+
+/// How the connections of the database pool are used, for the metrics.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolStatus {
+    /// Open connections, idle or in use.
+    pub connections: u32,
+    pub idle_connections: u32,
+}
+
+/// A webhook together with its secret, which the API never shows.
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    pub id: Uuid,
+    pub owner_id: Uuid,
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    pub secret: String,
+    pub global: bool,
+    pub createdate: DateTime<Local>,
+}
+
+impl Webhook {
+    pub fn information(&self) -> WebhookInformation {
+        WebhookInformation {
+            id: self.id.to_string(),
+            url: self.url.clone(),
+            events: self.events.clone(),
+            global: self.global,
+            createdate: self.createdate,
+        }
+    }
+}
+
+/// The events of a webhook as they are stored, separated by commas.
+pub fn events_to_text(events: &[WebhookEvent]) -> String {
+    events
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+pub fn events_from_text(text: &str) -> Vec<WebhookEvent> {
+    text.split(',')
+        .filter_map(|event| event.parse().ok())
+        .collect()
+}
+
+/// The tags of a brainfart as they are stored, separated by spaces.
+pub fn tags_to_text(title: &str, content: &str) -> String {
+    find_tags(&format!("{} {}", title, content)).join(" ")
+}
+
+/// A delivery that should be attempted, with what is needed to send it.
+#[derive(Debug, Clone)]
+pub struct DueDelivery {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub url: String,
+    pub secret: String,
+    pub event: WebhookEvent,
+    pub payload: String,
+    pub attempts: i32,
+}
+
+/// How an attempt to deliver went. `next_attempt` is only set while the delivery stays pending.
+#[derive(Debug, Clone)]
+pub struct DeliveryAttempt {
+    pub status: DeliveryStatus,
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
+    pub attempted_at: DateTime<Local>,
+    pub next_attempt: Option<DateTime<Local>>,
+}
+
+/// What an import brought in. Reactions and views of brainfarts this instance does not have are
+/// left out, and so are rebrainfarts of them.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ImportSummary {
+    pub brainfarts: usize,
+    pub reactions: usize,
+    pub views: usize,
+}
+
+/// Everything the handlers need from storage, so they can run against Postgres or against
+/// the in-memory backend used by the tests.
+#[async_trait]
+pub trait Repository: Send + Sync {
+    // Brains
+    async fn create_brain(&self, request: &RegisterBrainRequest) -> Option<CreateResponse>;
+    async fn verify_password(&self, request: &ProveOwnsBrainRequest) -> Option<Uuid>;
+    async fn get_brain_information(&self, brain_id: &Uuid) -> Option<BrainInformation>;
+    async fn list_brains(&self) -> Option<Vec<BrainAccount>>;
+    /// Disabled brains can no longer log in or use their tokens.
+    async fn set_brain_disabled(&self, brainname: &str, disabled: bool) -> bool;
+    async fn reset_password(&self, brainname: &str, password: &str) -> bool;
+    /// None when the brain is gone or disabled, as it may no longer do anything then.
+    async fn get_brain_role(&self, brain_id: &Uuid) -> Option<Role>;
+    async fn set_brain_role(&self, brainname: &str, role: Role) -> bool;
+    /// Only feed tokens of the brain's current generation are accepted.
+    async fn get_feed_token_generation(&self, brain_id: &Uuid) -> Option<i64>;
+    /// Moves the brain on to the next generation of feed tokens, revoking all it has.
+    async fn revoke_feed_tokens(&self, brain_id: &Uuid) -> bool;
+
+    // Deletion
+    /// Schedules the deletion of the brain, replacing a deletion it had already asked for.
+    async fn request_brain_deletion(&self, brain_id: &Uuid, deletion: &BrainDeletion) -> bool;
+    async fn get_brain_deletion(&self, brain_id: &Uuid) -> Option<BrainDeletion>;
+    /// False when no deletion was scheduled.
+    async fn cancel_brain_deletion(&self, brain_id: &Uuid) -> bool;
+    /// The brains whose deletion is due at `now`.
+    async fn get_due_brain_deletions(&self, now: DateTime<Local>) -> Option<Vec<Uuid>>;
+    /// Deletes the brain with its relations, bookmarks, drafts, views, ballots, reports and
+    /// webhooks. Its brainfarts and reactions are deleted or kept without it, by the mode, but
+    /// scheduled brainfarts always go. Returns the attachments of the deleted brainfarts, whose
+    /// files are still stored.
+    async fn delete_brain(
+        &self,
+        brain_id: &Uuid,
+        mode: DeletionMode,
+    ) -> Option<Vec<AttachmentInformation>>;
+
+    // Brainfarts
+    /// Creates the poll of the request too, if it has one.
+    async fn create_brainfart(
+        &self,
+        request: &CreateBrainfartRequest,
+        mastermind_id: &Uuid,
+    ) -> Option<CreateResponse>;
+    /// `bookmarked_by_me` is filled in for the viewer. Hidden and scheduled brainfarts are only
+    /// returned to their mastermind.
+    async fn get_brainfart(
+        &self,
+        brainfart_id: &Uuid,
+        viewer_id: &Uuid,
+    ) -> Option<BrainfartInformation>;
+    /// The brainfart as nobody in particular sees it, even when it is hidden or scheduled. For
+    /// moderation and webhooks, never for answering a brain about someone else's brainfart.
+    async fn get_any_brainfart(&self, brainfart_id: &Uuid) -> Option<BrainfartInformation>;
+    /// Whether the brain is the mastermind of the brainfart.
+    async fn is_mastermind(&self, brainfart_id: &Uuid, brain_id: &Uuid) -> bool;
+    /// Hidden and scheduled brainfarts, and those of brains the viewer has muted, are never part of
+    /// the result.
+    async fn get_brainfarts_using_filter(
+        &self,
+        filter: BrainfartFilter,
+        viewer_id: &Uuid,
+    ) -> Option<Vec<BrainfartInformation>>;
+    /// Deletes the brainfart together with its reactions, views, reports, bookmarks, poll,
+    /// attachment records and plain rebrainfarts. Quotes of it stay without their original.
+    /// Removing the attached files is up to the caller.
+    async fn delete_brainfart(&self, brainfart_id: &Uuid) -> bool;
+
+    // Reactions
+    /// None if the brainfart is hidden, scheduled or missing, or its mastermind has blocked the
+    /// brain.
+    async fn register_mind_explosion(
+        &self,
+        request: &NotifyAboutMindExplosionRequest,
+        brain_id: &Uuid,
+    ) -> Option<CreateResponse>;
+    /// None in the same cases as `register_mind_explosion`.
+    async fn register_mind_implosion(
+        &self,
+        request: &NotifyAboutMindImplosionRequest,
+        brain_id: &Uuid,
+    ) -> Option<CreateResponse>;
+
+    /// Whether the mastermind of the brainfart has blocked the brain. Blocked brains cannot react.
+    async fn is_blocked_by_mastermind(&self, brainfart_id: &Uuid, brain_id: &Uuid) -> bool;
+
+    // Relations
+    async fn add_brain_relation(
+        &self,
+        request: &BrainRelationRequest,
+        brain_id: &Uuid,
+    ) -> Option<CreateResponse>;
+    async fn remove_brain_relation(&self, request: &BrainRelationRequest, brain_id: &Uuid) -> bool;
+    async fn get_brain_relations(&self, brain_id: &Uuid) -> Option<Vec<BrainRelationInformation>>;
+
+    // Bookmarks
+    /// Bookmarking a brainfart twice keeps the first bookmark. None if there is no such brainfart
+    /// or it is hidden or scheduled.
+    async fn add_bookmark(
+        &self,
+        request: &BookmarkRequest,
+        brain_id: &Uuid,
+    ) -> Option<CreateResponse>;
+    async fn remove_bookmark(&self, request: &BookmarkRequest, brain_id: &Uuid) -> bool;
+
+    // Attachments
+    async fn add_attachment(
+        &self,
+        attachment: &NewAttachment,
+        brainfart_id: &Uuid,
+    ) -> Option<CreateResponse>;
+    async fn get_attachment(&self, attachment_id: &Uuid) -> Option<AttachedFile>;
+    /// Oldest first.
+    async fn get_attachments(&self, brainfart_id: &Uuid) -> Option<Vec<AttachmentInformation>>;
+    async fn remove_attachment(&self, attachment_id: &Uuid) -> bool;
+
+    // Rebrainfarts
+    /// Shares the original as the mastermind. None if the original is hidden, scheduled or
+    /// missing, or if the mastermind has already made a plain rebrainfart of it.
+    async fn create_rebrainfart(
+        &self,
+        request: &RebrainfartRequest,
+        original_id: &Uuid,
+        mastermind_id: &Uuid,
+    ) -> Option<CreateResponse>;
+
+    // Polls
+    /// Records the ballot of the brain, with a vote for the options at each of the positions.
+    /// None if the brain has voted in the poll before, there is no such poll or option, or the
+    /// brainfart is hidden or scheduled.
+    async fn add_poll_vote(
+        &self,
+        brainfart_id: &Uuid,
+        brain_id: &Uuid,
+        positions: &[usize],
+    ) -> Option<CreateResponse>;
+
+    // Link previews
+    /// Whether the url was fetched before, whether that gave a preview or not.
+    async fn has_link_preview(&self, url: &str) -> bool;
+    /// Replaces the cached preview of the url. `None` records that the url has no preview, so it
+    /// is not fetched again.
+    async fn save_link_preview(&self, url: &str, preview: Option<&LinkPreview>) -> bool;
+
+    // Drafts
+    /// Replaces the brain's draft, if it has one.
+    async fn save_draft(
+        &self,
+        request: &SaveDraftRequest,
+        brain_id: &Uuid,
+    ) -> Option<CreateResponse>;
+    async fn get_draft(&self, brain_id: &Uuid) -> Option<BrainfartDraft>;
+    async fn delete_draft(&self, brain_id: &Uuid) -> bool;
+
+    // Scheduling
+    /// Puts every brainfart scheduled for `now` or earlier in the feed, with its publish time as
+    /// birthdate. Returns the ids of those that were published.
+    async fn publish_scheduled_brainfarts(&self, now: DateTime<Local>) -> Option<Vec<Uuid>>;
+
+    // Views
+    /// A brain views a brainfart by opening it, and each brain counts once. Answers with whether
+    /// this was its first view.
+    async fn set_brainfart_as_viewed(&self, brainfart_id: &Uuid, brain_id: &Uuid) -> bool;
+
+    // Moderation
+    async fn report_brainfart(
+        &self,
+        request: &ReportBrainfartRequest,
+        brain_id: &Uuid,
+    ) -> Option<CreateResponse>;
+    /// Brainfarts with reports no admin has acted on yet, hidden or not.
+    async fn get_reported_brainfarts(&self) -> Option<Vec<ReportedBrainfart>>;
+    /// Hides or restores a brainfart. Either way its open reports count as resolved.
+    async fn set_brainfart_hidden(&self, brainfart_id: &Uuid, hidden: bool) -> bool;
+
+    // Statistics
+    async fn get_statistics(&self) -> Option<UsageStatistics>;
+
+    // Health
+    /// Whether the database answers a query.
+    async fn is_ready(&self) -> bool;
+    /// None for a backend without a connection pool.
+    fn get_pool_status(&self) -> Option<PoolStatus>;
+
+    // Leaderboards
+    /// The visible brainfarts with the most explosions since `since`, or ever if it is None. Ties
+    /// go to the newest brainfart.
+    async fn get_top_brainfarts(
+        &self,
+        since: Option<DateTime<Local>>,
+        limit: i64,
+    ) -> Option<Vec<LeaderboardBrainfart>>;
+    /// The brains whose visible brainfarts caused the most explosions since `since`, or ever if it
+    /// is None. Ties are ordered by brainname.
+    async fn get_top_masterminds(
+        &self,
+        since: Option<DateTime<Local>>,
+        limit: i64,
+    ) -> Option<Vec<LeaderboardMastermind>>;
+
+    // Export and import
+    /// Everything the brain has made, stamped with `now`.
+    async fn export_brain(&self, brain_id: &Uuid, now: DateTime<Local>) -> Option<BrainExport>;
+    /// Restores the archive as an ordinary brain with the password, keeping the ids and
+    /// birthdates. Nothing is imported when the brain or one of its brainfarts already exists.
+    async fn import_brain(&self, archive: &BrainExport, password: &str) -> Option<ImportSummary>;
+
+    // Webhooks
+    async fn create_webhook(
+        &self,
+        request: &CreateWebhookRequest,
+        owner_id: &Uuid,
+    ) -> Option<CreateResponse>;
+    async fn get_webhook(&self, webhook_id: &Uuid) -> Option<Webhook>;
+    /// Oldest first.
+    async fn get_webhooks(&self, owner_id: &Uuid) -> Option<Vec<Webhook>>;
+    /// The global webhooks and those of the mastermind that listen for the event.
+    async fn get_subscribed_webhooks(
+        &self,
+        event: WebhookEvent,
+        mastermind_name: &str,
+    ) -> Option<Vec<Webhook>>;
+    /// Deletes the webhook together with its deliveries.
+    async fn delete_webhook(&self, webhook_id: &Uuid) -> bool;
+    /// Queues the payload for the webhook, due right away.
+    async fn add_webhook_delivery(
+        &self,
+        webhook_id: &Uuid,
+        event: WebhookEvent,
+        payload: &str,
+    ) -> Option<CreateResponse>;
+    /// Pending deliveries whose next attempt is due at `now`, oldest first.
+    async fn get_due_webhook_deliveries(
+        &self,
+        now: DateTime<Local>,
+        limit: i64,
+    ) -> Option<Vec<DueDelivery>>;
+    /// Counts the attempt and records how it went.
+    async fn record_webhook_delivery_attempt(
+        &self,
+        delivery_id: &Uuid,
+        attempt: &DeliveryAttempt,
+    ) -> bool;
+    /// Newest first.
+    async fn get_webhook_deliveries(
+        &self,
+        webhook_id: &Uuid,
+        limit: i64,
+    ) -> Option<Vec<WebhookDeliveryInformation>>;
+}
@@ -0,0 +1,1839 @@
+use std::str::FromStr;
+
+use axum::async_trait;
+use chrono::{DateTime, Local, Utc};
+use shared::{
+    find_urls, AttachmentInformation, BookmarkRequest, BrainDeletion, BrainExport,
+    BrainInformation, BrainRelationInformation, BrainRelationRequest, BrainfartDraft,
+    BrainfartFilter, BrainfartFilterMode, BrainfartInformation, BrainfartOrder,
+    CreateBrainfartRequest, CreateWebhookRequest, DeletionMode, ExportedBrain, ExportedBrainfart,
+    ExportedReaction, ExportedView, LeaderboardBrainfart, LeaderboardMastermind, LinkPreview,
+    NotifyAboutMindExplosionRequest, NotifyAboutMindImplosionRequest, PollInformation,
+    PollOptionResult, ProveOwnsBrainRequest, ReactionKind, RebrainfartRequest,
+    RegisterBrainRequest, ReportBrainfartRequest, ReportInformation, ReportedBrainfart, Role,
+    SaveDraftRequest, WebhookDeliveryInformation, WebhookEvent, EXPORT_VERSION, REMOVED_BRAIN_NAME,
+};
+use sqlx::{
+    migrate::Migrator,
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions, SqliteRow},
+    types::Uuid,
+    Pool, Row, Sqlite,
+};
+
+use crate::ranking;
+
+use super::{
+    create_models::CreateResponse,
+    password_verification::{hash_password, password_matches},
+    repository::{
+        events_from_text, events_to_text, tags_to_text, AttachedFile, BrainAccount,
+        DeliveryAttempt, DueDelivery, ImportSummary, NewAttachment, PoolStatus, Repository,
+        UsageStatistics, Webhook,
+    },
+    LogError,
+};
+
+static MIGRATOR: Migrator = sqlx::migrate!("../migrations/sqlite");
+
+/// Stores everything in a single SQLite file, for small teams and demos where running
+/// Postgres is not worth it. The schema is created, or brought up to date, on startup.
+pub struct SqliteRepository {
+    pool: Pool<Sqlite>,
+}
+
+impl SqliteRepository {
+    pub async fn connect(
+        database_url: &str,
+        max_connections: u32,
+    ) -> Result<SqliteRepository, sqlx::Error> {
+        let options = SqliteConnectOptions::from_str(database_url)?.create_if_missing(true);
+        let mut pool_options = SqlitePoolOptions::new().max_connections(max_connections);
+        if database_url.contains(":memory:") || database_url.contains("mode=memory") {
+            // Every connection to an in-memory database gets its own database, so keep exactly one.
+            pool_options = pool_options
+                .max_connections(1)
+                .idle_timeout(None)
+                .max_lifetime(None);
+        }
+        let pool = pool_options.connect_with(options).await?;
+        // Changing a foreign key means rebuilding the table, which only works with them off.
+        let mut connection = pool.acquire().await?;
+        sqlx::query("PRAGMA foreign_keys = OFF")
+            .execute(&mut connection)
+            .await?;
+        MIGRATOR.run(&mut *connection).await?;
+        sqlx::query("PRAGMA foreign_keys = ON")
+            .execute(&mut connection)
+            .await?;
+        drop(connection);
+        let repository = SqliteRepository { pool };
+        repository.fill_in_brainfart_tags().await?;
+        Ok(repository)
+    }
+
+    /// Fills in the tags of the brainfarts from before they were stored.
+    async fn fill_in_brainfart_tags(&self) -> Result<(), sqlx::Error> {
+        let untagged = sqlx::query("select id, title, content from brainfarts where tags is null")
+            .fetch_all(&self.pool)
+            .await?;
+        for row in untagged.iter() {
+            let title: String = row.try_get("title")?;
+            let content: String = row.try_get("content")?;
+            sqlx::query("update brainfarts set tags=? where id=?")
+                .bind(tags_to_text(&title, &content))
+                .bind(row.try_get::<String, _>("id")?)
+                .execute(&self.pool)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn to_information(
+        &self,
+        row: &SqliteRow,
+        viewer_id: &Uuid,
+    ) -> Option<BrainfartInformation> {
+        self.to_information_with_original(row, viewer_id, true)
+            .await
+    }
+
+    async fn to_information_with_original(
+        &self,
+        row: &SqliteRow,
+        viewer_id: &Uuid,
+        with_original: bool,
+    ) -> Option<BrainfartInformation> {
+        let id: String = row.try_get("id").ok()?;
+        let birthdate: DateTime<Utc> = row.try_get("birthdate").ok()?;
+        let reactions = sqlx::query(
+            "select explosion, brainid from mindsblownbyfarts where brainfartid=? order by rowid",
+        )
+        .bind(&id)
+        .fetch_all(&self.pool)
+        .await
+        .log_error("sqlite_repository::to_information_with_original")
+        .ok()?;
+        let mut minds_blown = vec![];
+        let mut minds_imploded = vec![];
+        for reaction in reactions.iter() {
+            // The reactions of deleted brains are kept without the brain.
+            let brain_info = match reaction.try_get::<Option<String>, _>("brainid").ok()? {
+                Some(brain_id) => {
+                    self.get_brain_information(&Uuid::parse_str(&brain_id).ok()?)
+                        .await
+                }
+                None => Some(BrainInformation::removed()),
+            };
+            if let Some(brain_info) = brain_info {
+                if reaction.try_get::<bool, _>("explosion").ok()? {
+                    minds_blown.push(brain_info);
+                } else {
+                    minds_imploded.push(brain_info);
+                }
+            }
+        }
+        let attachments = self
+            .get_attachments(&Uuid::parse_str(&id).ok()?)
+            .await
+            .unwrap_or_default();
+        let content: String = row.try_get("content").ok()?;
+        let link_previews = self.get_link_previews(&find_urls(&content)).await;
+        let poll = self.get_poll(&id, viewer_id).await;
+        let shares = sqlx::query(
+            "select originalid,
+                (select count(*) from brainfarts as shares where shares.originalid=brainfarts.id
+                    and not shares.hidden and shares.publishdate is null) as rebrainfarts,
+                exists(select 1 from brainfarts as shares where shares.originalid=brainfarts.id
+                    and shares.mastermind=? and shares.content='') as rebrainfarted_by_me
+                from brainfarts where id=? LIMIT 1",
+        )
+        .bind(viewer_id.to_string())
+        .bind(&id)
+        .fetch_one(&self.pool)
+        .await
+        .log_error("sqlite_repository::to_information_with_original")
+        .ok()?;
+        let original = match shares.try_get::<Option<String>, _>("originalid").ok()? {
+            Some(original_id) if with_original => self.get_original(&original_id, viewer_id).await,
+            _ => None,
+        };
+        Some(BrainfartInformation {
+            id,
+            title: row.try_get("title").ok()?,
+            content,
+            birthdate: birthdate.into(),
+            mastermind_name: row
+                .try_get::<Option<String>, _>("mastermind_name")
+                .ok()?
+                .unwrap_or_else(|| REMOVED_BRAIN_NAME.to_string()),
+            blew_minds: minds_blown,
+            imploded_minds: minds_imploded,
+            bookmarked_by_me: row.try_get("bookmarked_by_me").ok()?,
+            attachments,
+            link_previews,
+            poll,
+            original,
+            rebrainfarts: shares.try_get("rebrainfarts").ok()?,
+            rebrainfarted_by_me: shares.try_get("rebrainfarted_by_me").ok()?,
+        })
+    }
+
+    /// The original of a rebrainfart while it is in the feed, without its own original.
+    async fn get_original(
+        &self,
+        original_id: &str,
+        viewer_id: &Uuid,
+    ) -> Option<Box<BrainfartInformation>> {
+        let row = sqlx::query(
+            "select id, title, content, birthdate, (select brainname from brains where id=mastermind LIMIT 1) as mastermind_name,
+                exists(select 1 from bookmarkedfarts where brainfartid=brainfarts.id and brainid=?) as bookmarked_by_me FROM
+                brainfarts where id=? and not hidden and publishdate is null LIMIT 1",
+        )
+        .bind(viewer_id.to_string())
+        .bind(original_id)
+        .fetch_optional(&self.pool)
+        .await
+        .log_error("sqlite_repository::get_original")
+        .ok()??;
+        Box::pin(self.to_information_with_original(&row, viewer_id, false))
+            .await
+            .map(Box::new)
+    }
+
+    async fn get_poll(&self, brainfart_id: &str, viewer_id: &Uuid) -> Option<PollInformation> {
+        let poll = sqlx::query(
+            "select closedate, multiplechoice,
+                (select count(*) from pollballots where brainfartid=polls.brainfartid) as voters
+                from polls where brainfartid=? LIMIT 1",
+        )
+        .bind(brainfart_id)
+        .fetch_optional(&self.pool)
+        .await
+        .log_error("sqlite_repository::get_poll")
+        .ok()??;
+        let options = sqlx::query(
+            "select text,
+                (select count(*) from pollvotes where brainfartid=?1 and position=polloptions.position) as votes,
+                exists(select 1 from pollvotes join pollballots on pollballots.id=pollvotes.ballotid
+                    where pollvotes.brainfartid=?1 and pollvotes.position=polloptions.position
+                    and pollballots.brainid=?2) as voted_by_me
+                from polloptions where brainfartid=?1 ORDER BY position",
+        )
+        .bind(brainfart_id)
+        .bind(viewer_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .log_error("sqlite_repository::get_poll")
+        .ok()?;
+        let mut results = vec![];
+        for option in options.iter() {
+            results.push(PollOptionResult {
+                text: option.try_get("text").ok()?,
+                votes: option.try_get("votes").ok()?,
+                voted_by_me: option.try_get("voted_by_me").ok()?,
+            });
+        }
+        let closedate: Option<DateTime<Utc>> = poll.try_get("closedate").ok()?;
+        Some(PollInformation {
+            options: results,
+            closes_at: closedate.map(Into::into),
+            multiple_choice: poll.try_get("multiplechoice").ok()?,
+            voters: poll.try_get("voters").ok()?,
+        })
+    }
+
+    /// The previews of those urls that have one, in the order of `urls`.
+    async fn get_link_previews(&self, urls: &[String]) -> Vec<LinkPreview> {
+        let mut previews = vec![];
+        for url in urls {
+            let row = sqlx::query(
+                "select url, title, description, imageurl, sitename from linkpreviews
+                    where url=? and title is not null LIMIT 1",
+            )
+            .bind(url)
+            .fetch_optional(&self.pool)
+            .await
+            .log_error("sqlite_repository::get_link_previews");
+            if let Ok(Some(row)) = row {
+                if let Some(preview) = to_link_preview(&row) {
+                    previews.push(preview);
+                }
+            }
+        }
+        previews
+    }
+
+    /// Whether the brainfart is neither hidden nor scheduled.
+    async fn is_in_feed(&self, brainfart_id: &Uuid) -> bool {
+        let query = sqlx::query(
+            "select 1 from brainfarts where id=? and not hidden and publishdate is null LIMIT 1",
+        )
+        .bind(brainfart_id.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        .log_error("sqlite_repository::is_in_feed");
+        matches!(query, Ok(Some(_)))
+    }
+
+    async fn react(&self, brainfart_id: &str, brain_id: &Uuid, explosion: bool) -> Option<()> {
+        let brainfart_uuid = Uuid::parse_str(brainfart_id).ok()?;
+        if !self.is_in_feed(&brainfart_uuid).await
+            || self
+                .is_blocked_by_mastermind(&brainfart_uuid, brain_id)
+                .await
+        {
+            return None;
+        }
+        let brainfart_id = brainfart_uuid.to_string();
+        let existing = sqlx::query(
+            "select id from mindsblownbyfarts where brainfartid=? and brainid=? LIMIT 1",
+        )
+        .bind(&brainfart_id)
+        .bind(brain_id.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        .log_error("sqlite_repository::react")
+        .ok()?;
+        let change_result = match existing {
+            Some(row) => sqlx::query(
+                "update mindsblownbyfarts set explosion=?,
+                            reactiondate=strftime('%Y-%m-%dT%H:%M:%fZ', 'now') where id=?",
+            )
+            .bind(explosion)
+            .bind(row.try_get::<String, _>("id").ok()?)
+            .execute(&self.pool)
+            .await
+            .log_error("sqlite_repository::react"),
+            None => sqlx::query(
+                "insert into mindsblownbyfarts(brainfartid, brainid, explosion) VALUES(?,?,?)",
+            )
+            .bind(&brainfart_id)
+            .bind(brain_id.to_string())
+            .bind(explosion)
+            .execute(&self.pool)
+            .await
+            .log_error("sqlite_repository::react"),
+        }
+        .ok()?;
+        if change_result.rows_affected() > 0 {
+            Some(())
+        } else {
+            None
+        }
+    }
+}
+
+/// Formats a moment the way the schema's defaults do, so timestamps compare correctly as text.
+fn to_timestamp(moment: DateTime<Local>) -> String {
+    moment
+        .with_timezone(&Utc)
+        .format("%Y-%m-%dT%H:%M:%S%.3fZ")
+        .to_string()
+}
+
+fn to_attachment_information(row: &SqliteRow) -> Option<AttachmentInformation> {
+    Some(AttachmentInformation {
+        id: row.try_get("id").ok()?,
+        filename: row.try_get("filename").ok()?,
+        content_type: row.try_get("contenttype").ok()?,
+        size: row.try_get("size").ok()?,
+        has_thumbnail: row.try_get("hasthumbnail").ok()?,
+    })
+}
+
+fn to_link_preview(row: &SqliteRow) -> Option<LinkPreview> {
+    Some(LinkPreview {
+        url: row.try_get("url").ok()?,
+        title: row.try_get("title").ok()?,
+        description: row.try_get("description").ok()?,
+        image_url: row.try_get("imageurl").ok()?,
+        site_name: row.try_get("sitename").ok()?,
+    })
+}
+
+fn to_webhook(row: &SqliteRow) -> Option<Webhook> {
+    let createdate: DateTime<Utc> = row.try_get("createdate").ok()?;
+    Some(Webhook {
+        id: Uuid::parse_str(&row.try_get::<String, _>("id").ok()?).ok()?,
+        owner_id: Uuid::parse_str(&row.try_get::<String, _>("ownerid").ok()?).ok()?,
+        url: row.try_get("url").ok()?,
+        events: events_from_text(&row.try_get::<String, _>("events").ok()?),
+        secret: row.try_get("secret").ok()?,
+        global: row.try_get("global").ok()?,
+        createdate: createdate.into(),
+    })
+}
+
+fn to_create_response(row: SqliteRow, extra_information: Option<String>) -> Option<CreateResponse> {
+    let birthdate: DateTime<Utc> = row.try_get("birthdate").ok()?;
+    Some(CreateResponse {
+        uuid: Uuid::parse_str(&row.try_get::<String, _>("id").ok()?).ok()?,
+        birthdate: birthdate.into(),
+        extra_information,
+    })
+}
+
+#[async_trait]
+impl Repository for SqliteRepository {
+    async fn create_brain(&self, request: &RegisterBrainRequest) -> Option<CreateResponse> {
+        let password_hash = hash_password(request.get_password())?;
+        let row = sqlx::query(
+            "INSERT INTO brains (brainname, password) VALUES(?, ?) RETURNING id, birthdate",
+        )
+        .bind(request.get_name())
+        .bind(&password_hash)
+        .fetch_one(&self.pool)
+        .await
+        .log_error("sqlite_repository::create_brain")
+        .ok()?;
+        to_create_response(row, Some(password_hash))
+    }
+
+    async fn verify_password(&self, request: &ProveOwnsBrainRequest) -> Option<Uuid> {
+        let row = sqlx::query(
+            "select id, password from brains where brainname=? and not disabled LIMIT 1",
+        )
+        .bind(request.get_name())
+        .fetch_one(&self.pool)
+        .await
+        .log_error("sqlite_repository::verify_password")
+        .ok()?;
+        let password: String = row.try_get("password").ok()?;
+        if password_matches(&password, request.get_password()) {
+            Uuid::parse_str(&row.try_get::<String, _>("id").ok()?).ok()
+        } else {
+            None
+        }
+    }
+
+    async fn get_brain_information(&self, brain_id: &Uuid) -> Option<BrainInformation> {
+        let row = sqlx::query("select brainname, birthdate from brains where id=? LIMIT 1")
+            .bind(brain_id.to_string())
+            .fetch_one(&self.pool)
+            .await
+            .log_error("sqlite_repository::get_brain_information")
+            .ok()?;
+        let birthdate: DateTime<Utc> = row.try_get("birthdate").ok()?;
+        Some(BrainInformation::create(
+            brain_id.to_string(),
+            row.try_get("brainname").ok()?,
+            birthdate.into(),
+        ))
+    }
+
+    async fn list_brains(&self) -> Option<Vec<BrainAccount>> {
+        let rows = sqlx::query(
+            "select id, brainname, birthdate, disabled, role from brains ORDER BY birthdate",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .log_error("sqlite_repository::list_brains")
+        .ok()?;
+        let mut brains = vec![];
+        for row in rows.iter() {
+            let birthdate: DateTime<Utc> = row.try_get("birthdate").ok()?;
+            brains.push(BrainAccount {
+                id: Uuid::parse_str(&row.try_get::<String, _>("id").ok()?).ok()?,
+                brainname: row.try_get("brainname").ok()?,
+                birthdate: birthdate.into(),
+                disabled: row.try_get("disabled").ok()?,
+                role: row.try_get::<String, _>("role").ok()?.parse().ok()?,
+            });
+        }
+        Some(brains)
+    }
+
+    async fn set_brain_disabled(&self, brainname: &str, disabled: bool) -> bool {
+        let query = sqlx::query(
+            "update brains set disabled=?, lastupdatedate=strftime('%Y-%m-%dT%H:%M:%fZ', 'now') where brainname=?",
+        )
+        .bind(disabled)
+        .bind(brainname)
+        .execute(&self.pool)
+        .await
+        .log_error("sqlite_repository::set_brain_disabled");
+        matches!(query, Ok(result) if result.rows_affected() > 0)
+    }
+
+    async fn reset_password(&self, brainname: &str, password: &str) -> bool {
+        let password_hash = match hash_password(password) {
+            Some(password_hash) => password_hash,
+            None => return false,
+        };
+        let query = sqlx::query(
+            "update brains set password=?, lastupdatedate=strftime('%Y-%m-%dT%H:%M:%fZ', 'now') where brainname=?",
+        )
+        .bind(password_hash)
+        .bind(brainname)
+        .execute(&self.pool)
+        .await
+        .log_error("sqlite_repository::reset_password");
+        matches!(query, Ok(result) if result.rows_affected() > 0)
+    }
+
+    async fn get_brain_role(&self, brain_id: &Uuid) -> Option<Role> {
+        let row = sqlx::query("select role from brains where id=? and not disabled LIMIT 1")
+            .bind(brain_id.to_string())
+            .fetch_optional(&self.pool)
+            .await
+            .log_error("sqlite_repository::get_brain_role")
+            .ok()??;
+        row.try_get::<String, _>("role").ok()?.parse().ok()
+    }
+
+    async fn set_brain_role(&self, brainname: &str, role: Role) -> bool {
+        let query = sqlx::query(
+            "update brains set role=?, lastupdatedate=strftime('%Y-%m-%dT%H:%M:%fZ', 'now') where brainname=?",
+        )
+        .bind(role.to_string())
+        .bind(brainname)
+        .execute(&self.pool)
+        .await
+        .log_error("sqlite_repository::set_brain_role");
+        matches!(query, Ok(result) if result.rows_affected() > 0)
+    }
+
+    async fn get_feed_token_generation(&self, brain_id: &Uuid) -> Option<i64> {
+        let row = sqlx::query("select feedtokengeneration from brains where id=? LIMIT 1")
+            .bind(brain_id.to_string())
+            .fetch_optional(&self.pool)
+            .await
+            .log_error("sqlite_repository::get_feed_token_generation")
+            .ok()??;
+        row.try_get("feedtokengeneration").ok()
+    }
+
+    async fn revoke_feed_tokens(&self, brain_id: &Uuid) -> bool {
+        let query = sqlx::query(
+            "update brains set feedtokengeneration=feedtokengeneration+1, lastupdatedate=strftime('%Y-%m-%dT%H:%M:%fZ', 'now') where id=?",
+        )
+        .bind(brain_id.to_string())
+        .execute(&self.pool)
+        .await
+        .log_error("sqlite_repository::revoke_feed_tokens");
+        matches!(query, Ok(result) if result.rows_affected() > 0)
+    }
+
+    async fn request_brain_deletion(&self, brain_id: &Uuid, deletion: &BrainDeletion) -> bool {
+        let query = sqlx::query(
+            "update brains set deletiondate=?, deletionmode=?, lastupdatedate=strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+                where id=?",
+        )
+        .bind(to_timestamp(deletion.delete_at))
+        .bind(deletion.mode.to_string())
+        .bind(brain_id.to_string())
+        .execute(&self.pool)
+        .await
+        .log_error("sqlite_repository::request_brain_deletion");
+        matches!(query, Ok(result) if result.rows_affected() > 0)
+    }
+
+    async fn get_brain_deletion(&self, brain_id: &Uuid) -> Option<BrainDeletion> {
+        let row = sqlx::query("select deletiondate, deletionmode from brains where id=? LIMIT 1")
+            .bind(brain_id.to_string())
+            .fetch_one(&self.pool)
+            .await
+            .log_error("sqlite_repository::get_brain_deletion")
+            .ok()?;
+        let delete_at: DateTime<Utc> = row.try_get::<Option<_>, _>("deletiondate").ok()??;
+        Some(BrainDeletion {
+            mode: row
+                .try_get::<Option<String>, _>("deletionmode")
+                .ok()??
+                .parse()
+                .ok()?,
+            delete_at: delete_at.into(),
+        })
+    }
+
+    async fn cancel_brain_deletion(&self, brain_id: &Uuid) -> bool {
+        let query = sqlx::query(
+            "update brains set deletiondate=NULL, deletionmode=NULL, lastupdatedate=strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+                where id=? and deletiondate is not null",
+        )
+        .bind(brain_id.to_string())
+        .execute(&self.pool)
+        .await
+        .log_error("sqlite_repository::cancel_brain_deletion");
+        matches!(query, Ok(result) if result.rows_affected() > 0)
+    }
+
+    async fn get_due_brain_deletions(&self, now: DateTime<Local>) -> Option<Vec<Uuid>> {
+        let rows =
+            sqlx::query("select id from brains where deletiondate <= ? ORDER BY deletiondate")
+                .bind(to_timestamp(now))
+                .fetch_all(&self.pool)
+                .await
+                .log_error("sqlite_repository::get_due_brain_deletions")
+                .ok()?;
+        rows.iter()
+            .map(|row| Uuid::parse_str(&row.try_get::<String, _>("id").ok()?).ok())
+            .collect()
+    }
+
+    async fn delete_brain(
+        &self,
+        brain_id: &Uuid,
+        mode: DeletionMode,
+    ) -> Option<Vec<AttachmentInformation>> {
+        let brain_id = brain_id.to_string();
+        let mut attachments = vec![];
+        // One at a time, since a rebrainfart among them may go with its original.
+        while let Some(row) = sqlx::query(
+            "select id from brainfarts where mastermind=? and (? or publishdate is not null) LIMIT 1",
+        )
+        .bind(&brain_id)
+        .bind(mode == DeletionMode::Delete)
+        .fetch_optional(&self.pool)
+        .await
+        .log_error("sqlite_repository::delete_brain")
+        .ok()?
+        {
+            let brainfart_id = Uuid::parse_str(&row.try_get::<String, _>("id").ok()?).ok()?;
+            attachments.extend(
+                self.get_attachments(&brainfart_id)
+                    .await
+                    .unwrap_or_default(),
+            );
+            if !self.delete_brainfart(&brainfart_id).await {
+                return None;
+            }
+        }
+        if mode == DeletionMode::Delete {
+            sqlx::query("delete from mindsblownbyfarts where brainid=?")
+                .bind(&brain_id)
+                .execute(&self.pool)
+                .await
+                .log_error("sqlite_repository::delete_brain")
+                .ok()?;
+        }
+        let query = sqlx::query("delete from brains where id=?")
+            .bind(&brain_id)
+            .execute(&self.pool)
+            .await
+            .log_error("sqlite_repository::delete_brain")
+            .ok()?;
+        (query.rows_affected() > 0).then_some(attachments)
+    }
+
+    async fn create_brainfart(
+        &self,
+        request: &CreateBrainfartRequest,
+        mastermind_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .log_error("sqlite_repository::create_brainfart")
+            .ok()?;
+        let row = sqlx::query(
+            "INSERT INTO brainfarts (title, content, mastermind, publishdate, tags) VALUES(?, ?, ?, ?, ?)
+                RETURNING id, birthdate",
+        )
+        .bind(request.get_title())
+        .bind(request.get_content())
+        .bind(mastermind_id.to_string())
+        .bind(request.get_publish_at().map(to_timestamp))
+        .bind(tags_to_text(request.get_title(), request.get_content()))
+        .fetch_one(&mut transaction)
+        .await
+        .log_error("sqlite_repository::create_brainfart")
+        .ok()?;
+        if let Some(poll) = request.get_poll() {
+            let brainfart_id: String = row.try_get("id").ok()?;
+            sqlx::query(
+                "INSERT INTO polls (brainfartid, closedate, multiplechoice) VALUES(?, ?, ?)",
+            )
+            .bind(&brainfart_id)
+            .bind(poll.closes_at.map(to_timestamp))
+            .bind(poll.multiple_choice)
+            .execute(&mut transaction)
+            .await
+            .log_error("sqlite_repository::create_brainfart")
+            .ok()?;
+            for (position, text) in poll.options.iter().enumerate() {
+                sqlx::query(
+                    "INSERT INTO polloptions (brainfartid, position, text) VALUES(?, ?, ?)",
+                )
+                .bind(&brainfart_id)
+                .bind(position as i64)
+                .bind(text.trim())
+                .execute(&mut transaction)
+                .await
+                .log_error("sqlite_repository::create_brainfart")
+                .ok()?;
+            }
+        }
+        transaction
+            .commit()
+            .await
+            .log_error("sqlite_repository::create_brainfart")
+            .ok()?;
+        to_create_response(row, None)
+    }
+
+    async fn get_brainfart(
+        &self,
+        brainfart_id: &Uuid,
+        viewer_id: &Uuid,
+    ) -> Option<BrainfartInformation> {
+        let row = sqlx::query(
+            "select id, title, content, birthdate, (select brainname from brains where id=mastermind LIMIT 1) as mastermind_name,
+                exists(select 1 from bookmarkedfarts where brainfartid=brainfarts.id and brainid=?1) as bookmarked_by_me FROM
+                brainfarts where id=?2 and ((not hidden and publishdate is null) or mastermind=?1) LIMIT 1",
+        )
+        .bind(viewer_id.to_string())
+        .bind(brainfart_id.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        .log_error("sqlite_repository::get_brainfart")
+        .ok()??;
+        self.to_information(&row, viewer_id).await
+    }
+
+    async fn get_any_brainfart(&self, brainfart_id: &Uuid) -> Option<BrainfartInformation> {
+        let row = sqlx::query(
+            "select id, title, content, birthdate, (select brainname from brains where id=mastermind LIMIT 1) as mastermind_name,
+                false as bookmarked_by_me FROM brainfarts where id=? LIMIT 1",
+        )
+        .bind(brainfart_id.to_string())
+        .fetch_one(&self.pool)
+        .await
+        .log_error("sqlite_repository::get_any_brainfart")
+        .ok()?;
+        self.to_information(&row, &Uuid::nil()).await
+    }
+
+    async fn get_brainfarts_using_filter(
+        &self,
+        filter: BrainfartFilter,
+        viewer_id: &Uuid,
+    ) -> Option<Vec<BrainfartInformation>> {
+        let rows = sqlx::query(
+            "select id, title, content, birthdate, (select brainname from brains where id=mastermind LIMIT 1) as mastermind_name,
+                exists(select 1 from bookmarkedfarts where brainfartid=brainfarts.id and brainid=?1) as bookmarked_by_me,
+                (select count(*) from mindsblownbyfarts where brainfartid=brainfarts.id and explosion) as explosions,
+                (select count(*) from mindsblownbyfarts where brainfartid=brainfarts.id and not explosion) as implosions,
+                (select count(*) from hallucinatedfarts where brainfartid=brainfarts.id) as views FROM
+                brainfarts where not hidden and not exists
+                    (select 1 from brainrelations where brainid=?1 and otherbrainid=mastermind and relation='mute')
+                and publishdate is null
+                and not (content='' and originalid is not null and not exists(select 1 from brainfarts as originals
+                    where originals.id=brainfarts.originalid and not originals.hidden and originals.publishdate is null))
+                and (not ?2 or bookmarked_by_me)
+                and (?3 is null or mastermind=(select id from brains where brainname=?3))
+                and (?4 is null or instr(' ' || coalesce(case when content='' and originalid is not null
+                    then (select originals.tags from brainfarts as originals where originals.id=brainfarts.originalid)
+                    else tags end, '') || ' ', ' ' || ?4 || ' ') > 0)
+                ORDER BY birthdate DESC, rowid DESC LIMIT ?5",
+        )
+        .bind(viewer_id.to_string())
+        .bind(filter.mode == BrainfartFilterMode::Bookmarks)
+        .bind(&filter.brainname)
+        .bind(&filter.tag)
+        // The hot order is only known below, so the limit waits for it. -1 is no limit at all.
+        .bind(match filter.limit {
+            Some(limit) if filter.order != BrainfartOrder::Hot => i64::from(limit),
+            _ => -1,
+        })
+        .fetch_all(&self.pool)
+        .await
+        .log_error("sqlite_repository::get_brainfarts_using_filter")
+        .ok()?;
+        let mut rows = rows.iter().collect::<Vec<_>>();
+        if filter.order == BrainfartOrder::Hot {
+            // SQLite is usually built without pow, so the score is computed here.
+            let now = Local::now();
+            ranking::sort_by_hotness(&mut rows, |row| {
+                let birthdate: DateTime<Local> = row
+                    .try_get::<DateTime<Utc>, _>("birthdate")
+                    .map(Into::into)
+                    .unwrap_or(now);
+                let count = |column| row.try_get::<i64, _>(column).unwrap_or_default();
+                let score = ranking::hot_score(
+                    count("explosions"),
+                    count("implosions"),
+                    count("views"),
+                    ranking::age_in_hours(birthdate, now),
+                );
+                (score, birthdate)
+            });
+        }
+        if let Some(limit) = filter.limit {
+            rows.truncate(limit as usize);
+        }
+        let mut final_result = vec![];
+        for row in rows {
+            if let Some(information) = self.to_information(row, viewer_id).await {
+                final_result.push(information);
+            }
+        }
+        Some(final_result)
+    }
+
+    async fn delete_brainfart(&self, brainfart_id: &Uuid) -> bool {
+        let mut transaction = match self
+            .pool
+            .begin()
+            .await
+            .log_error("sqlite_repository::delete_brainfart")
+        {
+            Ok(transaction) => transaction,
+            Err(_) => return false,
+        };
+        let brainfart_id = brainfart_id.to_string();
+        let mut ids =
+            match sqlx::query("select id from brainfarts where originalid=? and content=''")
+                .bind(&brainfart_id)
+                .fetch_all(&mut transaction)
+                .await
+                .log_error("sqlite_repository::delete_brainfart")
+            {
+                Ok(rows) => rows
+                    .iter()
+                    .filter_map(|row| row.try_get::<String, _>("id").ok())
+                    .collect::<Vec<_>>(),
+                Err(_) => return false,
+            };
+        ids.push(brainfart_id.clone());
+        for statement in [
+            "delete from mindsblownbyfarts where brainfartid=?",
+            "delete from hallucinatedfarts where brainfartid=?",
+            "delete from reportedfarts where brainfartid=?",
+            "delete from bookmarkedfarts where brainfartid=?",
+            "delete from brainfartattachments where brainfartid=?",
+            "delete from pollvotes where brainfartid=?",
+            "delete from pollballots where brainfartid=?",
+            "delete from polloptions where brainfartid=?",
+            "delete from polls where brainfartid=?",
+        ] {
+            for id in ids.iter() {
+                if sqlx::query(statement)
+                    .bind(id)
+                    .execute(&mut transaction)
+                    .await
+                    .log_error("sqlite_repository::delete_brainfart")
+                    .is_err()
+                {
+                    return false;
+                }
+            }
+        }
+        if sqlx::query("update brainfarts set originalid=NULL where originalid=?")
+            .bind(&brainfart_id)
+            .execute(&mut transaction)
+            .await
+            .log_error("sqlite_repository::delete_brainfart")
+            .is_err()
+        {
+            return false;
+        }
+        // The brainfart itself comes last, after its plain rebrainfarts.
+        let mut deleted = false;
+        for id in ids.iter() {
+            deleted = matches!(
+                sqlx::query("delete from brainfarts where id=?")
+                    .bind(id)
+                    .execute(&mut transaction)
+                    .await
+                    .log_error("sqlite_repository::delete_brainfart"),
+                Ok(result) if result.rows_affected() > 0
+            );
+        }
+        deleted
+            && transaction
+                .commit()
+                .await
+                .log_error("sqlite_repository::delete_brainfart")
+                .is_ok()
+    }
+
+    async fn register_mind_explosion(
+        &self,
+        request: &NotifyAboutMindExplosionRequest,
+        brain_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        self.react(&request.brainfart_id, brain_id, true)
+            .await
+            .map(|_| CreateResponse::empty())
+    }
+
+    async fn register_mind_implosion(
+        &self,
+        request: &NotifyAboutMindImplosionRequest,
+        brain_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        self.react(&request.brainfart_id, brain_id, false)
+            .await
+            .map(|_| CreateResponse::empty())
+    }
+
+    async fn is_blocked_by_mastermind(&self, brainfart_id: &Uuid, brain_id: &Uuid) -> bool {
+        let query = sqlx::query(
+            "select exists(select 1 from brainrelations join brainfarts on brainfarts.mastermind=brainrelations.brainid
+                where brainfarts.id=? and brainrelations.otherbrainid=? and brainrelations.relation='block') as blocked",
+        )
+        .bind(brainfart_id.to_string())
+        .bind(brain_id.to_string())
+        .fetch_one(&self.pool)
+        .await
+        .log_error("sqlite_repository::is_blocked_by_mastermind");
+        matches!(
+            query.and_then(|row| row.try_get::<bool, _>("blocked")),
+            Ok(true)
+        )
+    }
+
+    async fn is_mastermind(&self, brainfart_id: &Uuid, brain_id: &Uuid) -> bool {
+        let query = sqlx::query(
+            "select exists(select 1 from brainfarts where id=? and mastermind=?) as mastermind",
+        )
+        .bind(brainfart_id.to_string())
+        .bind(brain_id.to_string())
+        .fetch_one(&self.pool)
+        .await
+        .log_error("sqlite_repository::is_mastermind");
+        matches!(
+            query.and_then(|row| row.try_get::<bool, _>("mastermind")),
+            Ok(true)
+        )
+    }
+
+    async fn add_brain_relation(
+        &self,
+        request: &BrainRelationRequest,
+        brain_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        let row = sqlx::query(
+            "INSERT INTO brainrelations (brainid, otherbrainid, relation)
+                SELECT ?, id, ? FROM brains where brainname=?
+                ON CONFLICT (brainid, otherbrainid, relation) DO UPDATE SET relation=excluded.relation
+                RETURNING id, createdate as birthdate",
+        )
+        .bind(brain_id.to_string())
+        .bind(request.relation.to_string())
+        .bind(&request.brainname)
+        .fetch_one(&self.pool)
+        .await
+        .log_error("sqlite_repository::add_brain_relation")
+        .ok()?;
+        to_create_response(row, None)
+    }
+
+    async fn remove_brain_relation(&self, request: &BrainRelationRequest, brain_id: &Uuid) -> bool {
+        let query = sqlx::query(
+            "delete from brainrelations where brainid=? and relation=?
+                and otherbrainid=(select id from brains where brainname=?)",
+        )
+        .bind(brain_id.to_string())
+        .bind(request.relation.to_string())
+        .bind(&request.brainname)
+        .execute(&self.pool)
+        .await
+        .log_error("sqlite_repository::remove_brain_relation");
+        matches!(query, Ok(result) if result.rows_affected() > 0)
+    }
+
+    async fn get_brain_relations(&self, brain_id: &Uuid) -> Option<Vec<BrainRelationInformation>> {
+        let rows = sqlx::query(
+            "select relation, (select brainname from brains where id=otherbrainid LIMIT 1) as brainname FROM
+                brainrelations where brainid=? ORDER BY createdate, rowid",
+        )
+        .bind(brain_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .log_error("sqlite_repository::get_brain_relations")
+        .ok()?;
+        let mut relations = vec![];
+        for row in rows.iter() {
+            relations.push(BrainRelationInformation {
+                brainname: row.try_get("brainname").ok()?,
+                relation: row.try_get::<String, _>("relation").ok()?.parse().ok()?,
+            });
+        }
+        Some(relations)
+    }
+
+    async fn add_bookmark(
+        &self,
+        request: &BookmarkRequest,
+        brain_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        let brainfart_id = Uuid::parse_str(&request.brainfart_id).ok()?;
+        let row = sqlx::query(
+            "INSERT INTO bookmarkedfarts (brainfartid, brainid)
+                SELECT id, ? FROM brainfarts where id=? and not hidden and publishdate is null
+                ON CONFLICT (brainfartid, brainid) DO UPDATE SET brainid=excluded.brainid
+                RETURNING id, bookmarkdate as birthdate",
+        )
+        .bind(brain_id.to_string())
+        .bind(brainfart_id.to_string())
+        .fetch_one(&self.pool)
+        .await
+        .log_error("sqlite_repository::add_bookmark")
+        .ok()?;
+        to_create_response(row, None)
+    }
+
+    async fn remove_bookmark(&self, request: &BookmarkRequest, brain_id: &Uuid) -> bool {
+        let brainfart_id = match Uuid::parse_str(&request.brainfart_id) {
+            Ok(brainfart_id) => brainfart_id,
+            Err(_) => return false,
+        };
+        let query = sqlx::query("delete from bookmarkedfarts where brainfartid=? and brainid=?")
+            .bind(brainfart_id.to_string())
+            .bind(brain_id.to_string())
+            .execute(&self.pool)
+            .await
+            .log_error("sqlite_repository::remove_bookmark");
+        matches!(query, Ok(result) if result.rows_affected() > 0)
+    }
+
+    async fn add_attachment(
+        &self,
+        attachment: &NewAttachment,
+        brainfart_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        let row = sqlx::query(
+            "INSERT INTO brainfartattachments (brainfartid, filename, contenttype, size, hasthumbnail)
+                SELECT id, ?, ?, ?, ? FROM brainfarts where id=?
+                RETURNING id, uploaddate as birthdate",
+        )
+        .bind(&attachment.filename)
+        .bind(&attachment.content_type)
+        .bind(attachment.size)
+        .bind(attachment.has_thumbnail)
+        .bind(brainfart_id.to_string())
+        .fetch_one(&self.pool)
+        .await
+        .log_error("sqlite_repository::add_attachment")
+        .ok()?;
+        to_create_response(row, None)
+    }
+
+    async fn get_attachment(&self, attachment_id: &Uuid) -> Option<AttachedFile> {
+        let row = sqlx::query(
+            "select id, brainfartid, filename, contenttype, size, hasthumbnail from brainfartattachments where id=? LIMIT 1",
+        )
+        .bind(attachment_id.to_string())
+        .fetch_one(&self.pool)
+        .await
+        .log_error("sqlite_repository::get_attachment")
+        .ok()?;
+        Some(AttachedFile {
+            brainfart_id: Uuid::parse_str(&row.try_get::<String, _>("brainfartid").ok()?).ok()?,
+            attachment: to_attachment_information(&row)?,
+        })
+    }
+
+    async fn get_attachments(&self, brainfart_id: &Uuid) -> Option<Vec<AttachmentInformation>> {
+        let rows = sqlx::query(
+            "select id, filename, contenttype, size, hasthumbnail from brainfartattachments
+                where brainfartid=? ORDER BY uploaddate, rowid",
+        )
+        .bind(brainfart_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .log_error("sqlite_repository::get_attachments")
+        .ok()?;
+        rows.iter().map(to_attachment_information).collect()
+    }
+
+    async fn remove_attachment(&self, attachment_id: &Uuid) -> bool {
+        let query = sqlx::query("delete from brainfartattachments where id=?")
+            .bind(attachment_id.to_string())
+            .execute(&self.pool)
+            .await
+            .log_error("sqlite_repository::remove_attachment");
+        matches!(query, Ok(result) if result.rows_affected() > 0)
+    }
+
+    async fn create_rebrainfart(
+        &self,
+        request: &RebrainfartRequest,
+        original_id: &Uuid,
+        mastermind_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        let row = sqlx::query(
+            "INSERT INTO brainfarts (title, content, mastermind, tags, originalid)
+                SELECT '', ?, ?, ?, id FROM brainfarts where id=? and not hidden and publishdate is null
+                RETURNING id, birthdate",
+        )
+        .bind(request.commentary.trim())
+        .bind(mastermind_id.to_string())
+        .bind(tags_to_text("", &request.commentary))
+        .bind(original_id.to_string())
+        .fetch_one(&self.pool)
+        .await
+        .log_error("sqlite_repository::create_rebrainfart")
+        .ok()?;
+        to_create_response(row, None)
+    }
+
+    async fn add_poll_vote(
+        &self,
+        brainfart_id: &Uuid,
+        brain_id: &Uuid,
+        positions: &[usize],
+    ) -> Option<CreateResponse> {
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .log_error("sqlite_repository::add_poll_vote")
+            .ok()?;
+        let row = sqlx::query(
+            "INSERT INTO pollballots (brainfartid, brainid) SELECT brainfartid, ? FROM polls where brainfartid=?
+                and brainfartid in (select id from brainfarts where not hidden and publishdate is null)
+                RETURNING id, votedate as birthdate",
+        )
+        .bind(brain_id.to_string())
+        .bind(brainfart_id.to_string())
+        .fetch_one(&mut transaction)
+        .await
+        .log_error("sqlite_repository::add_poll_vote")
+        .ok()?;
+        let ballot_id: String = row.try_get("id").ok()?;
+        for position in positions {
+            let inserted = sqlx::query(
+                "INSERT INTO pollvotes (ballotid, brainfartid, position)
+                    SELECT ?, brainfartid, position FROM polloptions where brainfartid=? and position=?",
+            )
+            .bind(&ballot_id)
+            .bind(brainfart_id.to_string())
+            .bind(*position as i64)
+            .execute(&mut transaction)
+            .await
+            .log_error("sqlite_repository::add_poll_vote")
+            .ok()?;
+            if inserted.rows_affected() == 0 {
+                return None;
+            }
+        }
+        transaction
+            .commit()
+            .await
+            .log_error("sqlite_repository::add_poll_vote")
+            .ok()?;
+        to_create_response(row, None)
+    }
+
+    async fn has_link_preview(&self, url: &str) -> bool {
+        let query = sqlx::query("select url from linkpreviews where url=? LIMIT 1")
+            .bind(url)
+            .fetch_optional(&self.pool)
+            .await
+            .log_error("sqlite_repository::has_link_preview");
+        matches!(query, Ok(Some(_)))
+    }
+
+    async fn save_link_preview(&self, url: &str, preview: Option<&LinkPreview>) -> bool {
+        let query = sqlx::query(
+            "INSERT INTO linkpreviews (url, title, description, imageurl, sitename) VALUES(?, ?, ?, ?, ?)
+                ON CONFLICT (url)
+                DO UPDATE SET title=excluded.title, description=excluded.description,
+                    imageurl=excluded.imageurl, sitename=excluded.sitename,
+                    fetchdate=strftime('%Y-%m-%dT%H:%M:%fZ', 'now')",
+        )
+        .bind(url)
+        .bind(preview.map(|preview| &preview.title))
+        .bind(preview.and_then(|preview| preview.description.as_ref()))
+        .bind(preview.and_then(|preview| preview.image_url.as_ref()))
+        .bind(preview.and_then(|preview| preview.site_name.as_ref()))
+        .execute(&self.pool)
+        .await
+        .log_error("sqlite_repository::save_link_preview");
+        query.is_ok()
+    }
+
+    async fn save_draft(
+        &self,
+        request: &SaveDraftRequest,
+        brain_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        let row = sqlx::query(
+            "INSERT INTO brainfartdrafts (brainid, title, content, publishdate) VALUES(?, ?, ?, ?)
+                ON CONFLICT (brainid)
+                DO UPDATE SET title=excluded.title, content=excluded.content, publishdate=excluded.publishdate,
+                    lastupdatedate=strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+                RETURNING id, lastupdatedate as birthdate",
+        )
+        .bind(brain_id.to_string())
+        .bind(&request.title)
+        .bind(&request.content)
+        .bind(request.publish_at.map(to_timestamp))
+        .fetch_one(&self.pool)
+        .await
+        .log_error("sqlite_repository::save_draft")
+        .ok()?;
+        to_create_response(row, None)
+    }
+
+    async fn get_draft(&self, brain_id: &Uuid) -> Option<BrainfartDraft> {
+        let row = sqlx::query(
+            "select title, content, publishdate, lastupdatedate from brainfartdrafts where brainid=? LIMIT 1",
+        )
+        .bind(brain_id.to_string())
+        .fetch_one(&self.pool)
+        .await
+        .log_error("sqlite_repository::get_draft")
+        .ok()?;
+        let publish_at: Option<DateTime<Utc>> = row.try_get("publishdate").ok()?;
+        let lastupdatedate: DateTime<Utc> = row.try_get("lastupdatedate").ok()?;
+        Some(BrainfartDraft {
+            title: row.try_get("title").ok()?,
+            content: row.try_get("content").ok()?,
+            publish_at: publish_at.map(Into::into),
+            lastupdatedate: lastupdatedate.into(),
+        })
+    }
+
+    async fn delete_draft(&self, brain_id: &Uuid) -> bool {
+        let query = sqlx::query("delete from brainfartdrafts where brainid=?")
+            .bind(brain_id.to_string())
+            .execute(&self.pool)
+            .await
+            .log_error("sqlite_repository::delete_draft");
+        matches!(query, Ok(result) if result.rows_affected() > 0)
+    }
+
+    async fn publish_scheduled_brainfarts(&self, now: DateTime<Local>) -> Option<Vec<Uuid>> {
+        let rows = sqlx::query(
+            "update brainfarts set birthdate=publishdate, publishdate=NULL where publishdate <= ?
+                RETURNING id",
+        )
+        .bind(to_timestamp(now))
+        .fetch_all(&self.pool)
+        .await
+        .log_error("sqlite_repository::publish_scheduled_brainfarts")
+        .ok()?;
+        rows.iter()
+            .map(|row| Uuid::parse_str(&row.try_get::<String, _>("id").ok()?).ok())
+            .collect()
+    }
+
+    async fn set_brainfart_as_viewed(&self, brainfart_id: &Uuid, brain_id: &Uuid) -> bool {
+        let existing = sqlx::query(
+            "select id from hallucinatedfarts where brainid=? and brainfartid=? LIMIT 1",
+        )
+        .bind(brain_id.to_string())
+        .bind(brainfart_id.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        .log_error("sqlite_repository::set_brainfart_as_viewed");
+        if let Ok(None) = existing {
+            sqlx::query("insert into hallucinatedfarts(brainid,brainfartid) VALUES(?,?)")
+                .bind(brain_id.to_string())
+                .bind(brainfart_id.to_string())
+                .execute(&self.pool)
+                .await
+                .log_error("sqlite_repository::set_brainfart_as_viewed")
+                .is_ok()
+        } else {
+            false
+        }
+    }
+
+    async fn report_brainfart(
+        &self,
+        request: &ReportBrainfartRequest,
+        brain_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        let brainfart_id = Uuid::parse_str(&request.brainfart_id).ok()?;
+        let row = sqlx::query(
+            "INSERT INTO reportedfarts (brainfartid, brainid, reason) VALUES(?, ?, ?)
+                ON CONFLICT (brainfartid, brainid)
+                DO UPDATE SET reason=excluded.reason, reportdate=strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), resolved=FALSE
+                RETURNING id, reportdate as birthdate",
+        )
+        .bind(brainfart_id.to_string())
+        .bind(brain_id.to_string())
+        .bind(&request.reason)
+        .fetch_one(&self.pool)
+        .await
+        .log_error("sqlite_repository::report_brainfart")
+        .ok()?;
+        to_create_response(row, None)
+    }
+
+    async fn get_reported_brainfarts(&self) -> Option<Vec<ReportedBrainfart>> {
+        let rows = sqlx::query(
+            "select id, title, content, birthdate, hidden, (select brainname from brains where id=mastermind LIMIT 1) as mastermind_name,
+                FALSE as bookmarked_by_me FROM
+                brainfarts where id in (select brainfartid from reportedfarts where not resolved)
+                ORDER BY birthdate DESC, rowid DESC",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .log_error("sqlite_repository::get_reported_brainfarts")
+        .ok()?;
+        let mut final_result = vec![];
+        for row in rows.iter() {
+            let reports = sqlx::query(
+                "select reason, reportdate, (select brainname from brains where id=brainid LIMIT 1) as reporter_name FROM
+                    reportedfarts where brainfartid=? and not resolved ORDER BY reportdate, rowid",
+            )
+            .bind(row.try_get::<String, _>("id").ok()?)
+            .fetch_all(&self.pool)
+            .await
+            .log_error("sqlite_repository::get_reported_brainfarts")
+            .ok()?;
+            let mut report_information = vec![];
+            for report in reports.iter() {
+                let reportdate: DateTime<Utc> = report.try_get("reportdate").ok()?;
+                report_information.push(ReportInformation {
+                    reporter_name: report
+                        .try_get::<Option<String>, _>("reporter_name")
+                        .ok()?
+                        .unwrap_or_default(),
+                    reason: report.try_get("reason").ok()?,
+                    reportdate: reportdate.into(),
+                });
+            }
+            if let Some(brainfart) = self.to_information(row, &Uuid::nil()).await {
+                final_result.push(ReportedBrainfart {
+                    brainfart,
+                    hidden: row.try_get("hidden").ok()?,
+                    reports: report_information,
+                });
+            }
+        }
+        Some(final_result)
+    }
+
+    async fn set_brainfart_hidden(&self, brainfart_id: &Uuid, hidden: bool) -> bool {
+        let mut transaction = match self
+            .pool
+            .begin()
+            .await
+            .log_error("sqlite_repository::set_brainfart_hidden")
+        {
+            Ok(transaction) => transaction,
+            Err(_) => return false,
+        };
+        let brainfart_id = brainfart_id.to_string();
+        let updated = matches!(
+            sqlx::query("update brainfarts set hidden=? where id=?")
+                .bind(hidden)
+                .bind(&brainfart_id)
+                .execute(&mut transaction)
+                .await
+                .log_error("sqlite_repository::set_brainfart_hidden"),
+            Ok(result) if result.rows_affected() > 0
+        );
+        let resolved = sqlx::query("update reportedfarts set resolved=TRUE where brainfartid=?")
+            .bind(&brainfart_id)
+            .execute(&mut transaction)
+            .await
+            .log_error("sqlite_repository::set_brainfart_hidden")
+            .is_ok();
+        updated
+            && resolved
+            && transaction
+                .commit()
+                .await
+                .log_error("sqlite_repository::set_brainfart_hidden")
+                .is_ok()
+    }
+
+    async fn is_ready(&self) -> bool {
+        sqlx::query("select 1")
+            .execute(&self.pool)
+            .await
+            .log_error("sqlite_repository::is_ready")
+            .is_ok()
+    }
+
+    fn get_pool_status(&self) -> Option<PoolStatus> {
+        Some(PoolStatus {
+            connections: self.pool.size(),
+            idle_connections: self.pool.num_idle() as u32,
+        })
+    }
+
+    async fn get_statistics(&self) -> Option<UsageStatistics> {
+        let row = sqlx::query(
+            "select
+                (select count(*) from brains) as brains,
+                (select count(*) from brains where disabled) as disabled_brains,
+                (select count(*) from brainfarts) as brainfarts,
+                (select count(*) from mindsblownbyfarts where explosion) as explosions,
+                (select count(*) from mindsblownbyfarts where not explosion) as implosions,
+                (select count(*) from hallucinatedfarts) as views",
+        )
+        .fetch_one(&self.pool)
+        .await
+        .log_error("sqlite_repository::get_statistics")
+        .ok()?;
+        Some(UsageStatistics {
+            brains: row.try_get("brains").ok()?,
+            disabled_brains: row.try_get("disabled_brains").ok()?,
+            brainfarts: row.try_get("brainfarts").ok()?,
+            explosions: row.try_get("explosions").ok()?,
+            implosions: row.try_get("implosions").ok()?,
+            views: row.try_get("views").ok()?,
+        })
+    }
+
+    async fn get_top_brainfarts(
+        &self,
+        since: Option<DateTime<Local>>,
+        limit: i64,
+    ) -> Option<Vec<LeaderboardBrainfart>> {
+        let rows = sqlx::query(
+            "select brainfarts.id, brainfarts.title, brains.brainname, count(*) as explosions
+                from mindsblownbyfarts
+                join brainfarts on brainfarts.id=mindsblownbyfarts.brainfartid
+                left join brains on brains.id=brainfarts.mastermind
+                where mindsblownbyfarts.explosion and not brainfarts.hidden and brainfarts.publishdate is null
+                and (? is null or mindsblownbyfarts.reactiondate >= ?)
+                group by brainfarts.id
+                ORDER BY explosions DESC, brainfarts.birthdate DESC, brainfarts.rowid DESC LIMIT ?",
+        )
+        .bind(since.map(to_timestamp))
+        .bind(since.map(to_timestamp))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .log_error("sqlite_repository::get_top_brainfarts")
+        .ok()?;
+        rows.iter()
+            .map(|row| {
+                Some(LeaderboardBrainfart {
+                    brainfart_id: row.try_get("id").ok()?,
+                    title: row.try_get("title").ok()?,
+                    mastermind_name: row
+                        .try_get::<Option<String>, _>("brainname")
+                        .ok()?
+                        .unwrap_or_else(|| REMOVED_BRAIN_NAME.to_string()),
+                    explosions: row.try_get("explosions").ok()?,
+                })
+            })
+            .collect()
+    }
+
+    async fn get_top_masterminds(
+        &self,
+        since: Option<DateTime<Local>>,
+        limit: i64,
+    ) -> Option<Vec<LeaderboardMastermind>> {
+        let rows = sqlx::query(
+            "select brains.brainname, count(*) as explosions
+                from mindsblownbyfarts
+                join brainfarts on brainfarts.id=mindsblownbyfarts.brainfartid
+                join brains on brains.id=brainfarts.mastermind
+                where mindsblownbyfarts.explosion and not brainfarts.hidden and brainfarts.publishdate is null
+                and (? is null or mindsblownbyfarts.reactiondate >= ?)
+                group by brains.id
+                ORDER BY explosions DESC, brains.brainname LIMIT ?",
+        )
+        .bind(since.map(to_timestamp))
+        .bind(since.map(to_timestamp))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .log_error("sqlite_repository::get_top_masterminds")
+        .ok()?;
+        rows.iter()
+            .map(|row| {
+                Some(LeaderboardMastermind {
+                    brainname: row.try_get("brainname").ok()?,
+                    explosions: row.try_get("explosions").ok()?,
+                })
+            })
+            .collect()
+    }
+
+    async fn export_brain(&self, brain_id: &Uuid, now: DateTime<Local>) -> Option<BrainExport> {
+        let brain_id = brain_id.to_string();
+        let brain =
+            sqlx::query("select id, brainname, role, birthdate from brains where id=? LIMIT 1")
+                .bind(&brain_id)
+                .fetch_one(&self.pool)
+                .await
+                .log_error("sqlite_repository::export_brain")
+                .ok()?;
+        let brainfarts = sqlx::query(
+            "select id, title, content, birthdate, hidden, publishdate, originalid from brainfarts
+                where mastermind=? ORDER BY birthdate",
+        )
+        .bind(&brain_id)
+        .fetch_all(&self.pool)
+        .await
+        .log_error("sqlite_repository::export_brain")
+        .ok()?;
+        let reactions = sqlx::query(
+            "select brainfartid, explosion, reactiondate from mindsblownbyfarts
+                where brainid=? ORDER BY reactiondate",
+        )
+        .bind(&brain_id)
+        .fetch_all(&self.pool)
+        .await
+        .log_error("sqlite_repository::export_brain")
+        .ok()?;
+        let views = sqlx::query(
+            "select brainfartid, hallucinationdate from hallucinatedfarts
+                where brainid=? ORDER BY hallucinationdate",
+        )
+        .bind(&brain_id)
+        .fetch_all(&self.pool)
+        .await
+        .log_error("sqlite_repository::export_brain")
+        .ok()?;
+        let date = |row: &SqliteRow, column: &str| -> Option<DateTime<Local>> {
+            row.try_get::<DateTime<Utc>, _>(column).ok().map(Into::into)
+        };
+        let mut export = BrainExport {
+            version: EXPORT_VERSION,
+            exported_at: now,
+            brain: ExportedBrain {
+                id: brain.try_get("id").ok()?,
+                brainname: brain.try_get("brainname").ok()?,
+                role: brain.try_get::<String, _>("role").ok()?.parse().ok()?,
+                birthdate: date(&brain, "birthdate")?,
+            },
+            brainfarts: vec![],
+            reactions: vec![],
+            views: vec![],
+        };
+        for row in brainfarts.iter() {
+            export.brainfarts.push(ExportedBrainfart {
+                id: row.try_get("id").ok()?,
+                title: row.try_get("title").ok()?,
+                content: row.try_get("content").ok()?,
+                birthdate: date(row, "birthdate")?,
+                hidden: row.try_get("hidden").ok()?,
+                publish_at: date(row, "publishdate"),
+                original_id: row.try_get("originalid").ok()?,
+            });
+        }
+        for row in reactions.iter() {
+            export.reactions.push(ExportedReaction {
+                brainfart_id: row.try_get("brainfartid").ok()?,
+                kind: if row.try_get("explosion").ok()? {
+                    ReactionKind::Explosion
+                } else {
+                    ReactionKind::Implosion
+                },
+                reactiondate: date(row, "reactiondate")?,
+            });
+        }
+        for row in views.iter() {
+            export.views.push(ExportedView {
+                brainfart_id: row.try_get("brainfartid").ok()?,
+                viewdate: date(row, "hallucinationdate")?,
+            });
+        }
+        Some(export)
+    }
+
+    async fn import_brain(&self, archive: &BrainExport, password: &str) -> Option<ImportSummary> {
+        let password_hash = hash_password(password)?;
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .log_error("sqlite_repository::import_brain")
+            .ok()?;
+        let brain_id = Uuid::parse_str(&archive.brain.id).ok()?.to_string();
+        sqlx::query("INSERT INTO brains (id, brainname, password, birthdate) VALUES(?, ?, ?, ?)")
+            .bind(&brain_id)
+            .bind(&archive.brain.brainname)
+            .bind(&password_hash)
+            .bind(to_timestamp(archive.brain.birthdate))
+            .execute(&mut transaction)
+            .await
+            .log_error("sqlite_repository::import_brain")
+            .ok()?;
+        let mut summary = ImportSummary::default();
+        // Oldest first, so rebrainfarts of the brain's own brainfarts find their originals.
+        let mut brainfarts = archive.brainfarts.iter().collect::<Vec<_>>();
+        brainfarts.sort_by_key(|brainfart| brainfart.birthdate);
+        for brainfart in brainfarts {
+            let original_id = match &brainfart.original_id {
+                Some(original_id) => sqlx::query("select id from brainfarts where id=?")
+                    .bind(Uuid::parse_str(original_id).ok()?.to_string())
+                    .fetch_optional(&mut transaction)
+                    .await
+                    .log_error("sqlite_repository::import_brain")
+                    .ok()?
+                    .and_then(|row| row.try_get::<String, _>("id").ok()),
+                None => None,
+            };
+            if brainfart.original_id.is_some()
+                && original_id.is_none()
+                && brainfart.content.is_empty()
+            {
+                continue;
+            }
+            sqlx::query(
+                "INSERT INTO brainfarts (id, title, content, birthdate, mastermind, hidden, publishdate, originalid, tags)
+                    VALUES(?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(Uuid::parse_str(&brainfart.id).ok()?.to_string())
+            .bind(&brainfart.title)
+            .bind(&brainfart.content)
+            .bind(to_timestamp(brainfart.birthdate))
+            .bind(&brain_id)
+            .bind(brainfart.hidden)
+            .bind(brainfart.publish_at.map(to_timestamp))
+            .bind(original_id)
+            .bind(tags_to_text(&brainfart.title, &brainfart.content))
+            .execute(&mut transaction)
+            .await
+            .log_error("sqlite_repository::import_brain")
+            .ok()?;
+            summary.brainfarts += 1;
+        }
+        for reaction in archive.reactions.iter() {
+            let inserted = sqlx::query(
+                "INSERT INTO mindsblownbyfarts (brainfartid, brainid, explosion, reactiondate)
+                    select id, ?, ?, ? from brainfarts where id=?",
+            )
+            .bind(&brain_id)
+            .bind(reaction.kind == ReactionKind::Explosion)
+            .bind(to_timestamp(reaction.reactiondate))
+            .bind(Uuid::parse_str(&reaction.brainfart_id).ok()?.to_string())
+            .execute(&mut transaction)
+            .await
+            .log_error("sqlite_repository::import_brain")
+            .ok()?;
+            summary.reactions += inserted.rows_affected() as usize;
+        }
+        for view in archive.views.iter() {
+            let inserted = sqlx::query(
+                "INSERT INTO hallucinatedfarts (brainfartid, brainid, hallucinationdate)
+                    select id, ?, ? from brainfarts where id=?",
+            )
+            .bind(&brain_id)
+            .bind(to_timestamp(view.viewdate))
+            .bind(Uuid::parse_str(&view.brainfart_id).ok()?.to_string())
+            .execute(&mut transaction)
+            .await
+            .log_error("sqlite_repository::import_brain")
+            .ok()?;
+            summary.views += inserted.rows_affected() as usize;
+        }
+        transaction
+            .commit()
+            .await
+            .log_error("sqlite_repository::import_brain")
+            .ok()?;
+        Some(summary)
+    }
+
+    async fn create_webhook(
+        &self,
+        request: &CreateWebhookRequest,
+        owner_id: &Uuid,
+    ) -> Option<CreateResponse> {
+        let row = sqlx::query(
+            "INSERT INTO webhooks (ownerid, url, events, secret, global) VALUES(?, ?, ?, ?, ?)
+                RETURNING id, createdate as birthdate",
+        )
+        .bind(owner_id.to_string())
+        .bind(&request.url)
+        .bind(events_to_text(&request.events))
+        .bind(&request.secret)
+        .bind(request.global)
+        .fetch_one(&self.pool)
+        .await
+        .log_error("sqlite_repository::create_webhook")
+        .ok()?;
+        to_create_response(row, None)
+    }
+
+    async fn get_webhook(&self, webhook_id: &Uuid) -> Option<Webhook> {
+        let row = sqlx::query(
+            "select id, ownerid, url, events, secret, global, createdate from webhooks where id=? LIMIT 1",
+        )
+        .bind(webhook_id.to_string())
+        .fetch_one(&self.pool)
+        .await
+        .log_error("sqlite_repository::get_webhook")
+        .ok()?;
+        to_webhook(&row)
+    }
+
+    async fn get_webhooks(&self, owner_id: &Uuid) -> Option<Vec<Webhook>> {
+        let rows = sqlx::query(
+            "select id, ownerid, url, events, secret, global, createdate from webhooks
+                where ownerid=? ORDER BY createdate, rowid",
+        )
+        .bind(owner_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .log_error("sqlite_repository::get_webhooks")
+        .ok()?;
+        rows.iter().map(to_webhook).collect()
+    }
+
+    async fn get_subscribed_webhooks(
+        &self,
+        event: WebhookEvent,
+        mastermind_name: &str,
+    ) -> Option<Vec<Webhook>> {
+        let rows = sqlx::query(
+            "select id, ownerid, url, events, secret, global, createdate from webhooks
+                where global or ownerid=(select id from brains where brainname=?)
+                ORDER BY createdate, rowid",
+        )
+        .bind(mastermind_name)
+        .fetch_all(&self.pool)
+        .await
+        .log_error("sqlite_repository::get_subscribed_webhooks")
+        .ok()?;
+        let webhooks: Vec<Webhook> = rows.iter().map(to_webhook).collect::<Option<_>>()?;
+        Some(
+            webhooks
+                .into_iter()
+                .filter(|webhook| webhook.events.contains(&event))
+                .collect(),
+        )
+    }
+
+    async fn delete_webhook(&self, webhook_id: &Uuid) -> bool {
+        let mut transaction = match self
+            .pool
+            .begin()
+            .await
+            .log_error("sqlite_repository::delete_webhook")
+        {
+            Ok(transaction) => transaction,
+            Err(_) => return false,
+        };
+        let deliveries_deleted = sqlx::query("delete from webhookdeliveries where webhookid=?")
+            .bind(webhook_id.to_string())
+            .execute(&mut transaction)
+            .await
+            .log_error("sqlite_repository::delete_webhook")
+            .is_ok();
+        let deleted = matches!(
+            sqlx::query("delete from webhooks where id=?")
+                .bind(webhook_id.to_string())
+                .execute(&mut transaction)
+                .await
+                .log_error("sqlite_repository::delete_webhook"),
+            Ok(result) if result.rows_affected() > 0
+        );
+        deliveries_deleted
+            && deleted
+            && transaction
+                .commit()
+                .await
+                .log_error("sqlite_repository::delete_webhook")
+                .is_ok()
+    }
+
+    async fn add_webhook_delivery(
+        &self,
+        webhook_id: &Uuid,
+        event: WebhookEvent,
+        payload: &str,
+    ) -> Option<CreateResponse> {
+        let row = sqlx::query(
+            "INSERT INTO webhookdeliveries (webhookid, event, payload, nextattemptdate)
+                VALUES(?, ?, ?, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+                RETURNING id, createdate as birthdate",
+        )
+        .bind(webhook_id.to_string())
+        .bind(event.to_string())
+        .bind(payload)
+        .fetch_one(&self.pool)
+        .await
+        .log_error("sqlite_repository::add_webhook_delivery")
+        .ok()?;
+        to_create_response(row, None)
+    }
+
+    async fn get_due_webhook_deliveries(
+        &self,
+        now: DateTime<Local>,
+        limit: i64,
+    ) -> Option<Vec<DueDelivery>> {
+        let rows = sqlx::query(
+            "select webhookdeliveries.id, webhookid, url, secret, event, payload, attempts
+                from webhookdeliveries join webhooks on webhooks.id=webhookdeliveries.webhookid
+                where status='pending' and nextattemptdate <= ?
+                ORDER BY webhookdeliveries.createdate, webhookdeliveries.rowid LIMIT ?",
+        )
+        .bind(to_timestamp(now))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .log_error("sqlite_repository::get_due_webhook_deliveries")
+        .ok()?;
+        rows.iter()
+            .map(|row| {
+                Some(DueDelivery {
+                    id: Uuid::parse_str(&row.try_get::<String, _>("id").ok()?).ok()?,
+                    webhook_id: Uuid::parse_str(&row.try_get::<String, _>("webhookid").ok()?)
+                        .ok()?,
+                    url: row.try_get("url").ok()?,
+                    secret: row.try_get("secret").ok()?,
+                    event: row.try_get::<String, _>("event").ok()?.parse().ok()?,
+                    payload: row.try_get("payload").ok()?,
+                    attempts: row.try_get("attempts").ok()?,
+                })
+            })
+            .collect()
+    }
+
+    async fn record_webhook_delivery_attempt(
+        &self,
+        delivery_id: &Uuid,
+        attempt: &DeliveryAttempt,
+    ) -> bool {
+        let query = sqlx::query(
+            "update webhookdeliveries set status=?, attempts=attempts+1, responsestatus=?,
+                lasterror=?, lastattemptdate=?, nextattemptdate=? where id=?",
+        )
+        .bind(attempt.status.to_string())
+        .bind(attempt.response_status)
+        .bind(attempt.error.as_ref())
+        .bind(to_timestamp(attempt.attempted_at))
+        .bind(attempt.next_attempt.map(to_timestamp))
+        .bind(delivery_id.to_string())
+        .execute(&self.pool)
+        .await
+        .log_error("sqlite_repository::record_webhook_delivery_attempt");
+        matches!(query, Ok(result) if result.rows_affected() > 0)
+    }
+
+    async fn get_webhook_deliveries(
+        &self,
+        webhook_id: &Uuid,
+        limit: i64,
+    ) -> Option<Vec<WebhookDeliveryInformation>> {
+        let rows = sqlx::query(
+            "select id, event, status, attempts, responsestatus, lasterror, createdate,
+                lastattemptdate, nextattemptdate
+                from webhookdeliveries where webhookid=? ORDER BY createdate DESC, rowid DESC LIMIT ?",
+        )
+        .bind(webhook_id.to_string())
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .log_error("sqlite_repository::get_webhook_deliveries")
+        .ok()?;
+        rows.iter()
+            .map(|row| {
+                let createdate: DateTime<Utc> = row.try_get("createdate").ok()?;
+                let last_attempt_at: Option<DateTime<Utc>> = row.try_get("lastattemptdate").ok()?;
+                let next_attempt_at: Option<DateTime<Utc>> = row.try_get("nextattemptdate").ok()?;
+                Some(WebhookDeliveryInformation {
+                    id: row.try_get("id").ok()?,
+                    event: row.try_get::<String, _>("event").ok()?.parse().ok()?,
+                    status: row.try_get::<String, _>("status").ok()?.parse().ok()?,
+                    attempts: row.try_get("attempts").ok()?,
+                    response_status: row.try_get("responsestatus").ok()?,
+                    error: row.try_get("lasterror").ok()?,
+                    createdate: createdate.into(),
+                    last_attempt_at: last_attempt_at.map(Into::into),
+                    next_attempt_at: next_attempt_at.map(Into::into),
+                })
+            })
+            .collect()
+    }
+}
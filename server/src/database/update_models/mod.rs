@@ -0,0 +1,208 @@
+use chrono::{DateTime, Local, Utc};
+use shared::{BrainDeletion, LinkPreview, Role};
+use sqlx::{types::Uuid, Pool, Postgres};
+
+use super::{
+    password_verification::hash_password,
+    repository::{tags_to_text, DeliveryAttempt},
+    LogError,
+};
+
+pub async fn set_brain_disabled(pool: &Pool<Postgres>, brainname: &str, disabled: bool) -> bool {
+    let query = sqlx::query!(
+        "update brains set disabled=$1, lastupdatedate=NOW() where brainname=$2",
+        disabled,
+        brainname
+    )
+    .execute(pool)
+    .await
+    .log_error("update_models::set_brain_disabled");
+    matches!(query, Ok(result) if result.rows_affected() > 0)
+}
+
+pub async fn revoke_feed_tokens(pool: &Pool<Postgres>, brain_id: &Uuid) -> bool {
+    let query = sqlx::query!(
+        "update brains set feedtokengeneration=feedtokengeneration+1, lastupdatedate=NOW() where id=$1",
+        brain_id
+    )
+    .execute(pool)
+    .await
+    .log_error("update_models::revoke_feed_tokens");
+    matches!(query, Ok(result) if result.rows_affected() > 0)
+}
+
+pub async fn request_brain_deletion(
+    pool: &Pool<Postgres>,
+    brain_id: &Uuid,
+    deletion: &BrainDeletion,
+) -> bool {
+    let query = sqlx::query!(
+        "update brains set deletiondate=$1, deletionmode=$2, lastupdatedate=NOW() where id=$3",
+        deletion.delete_at.with_timezone(&Utc),
+        deletion.mode.to_string(),
+        brain_id
+    )
+    .execute(pool)
+    .await
+    .log_error("update_models::request_brain_deletion");
+    matches!(query, Ok(result) if result.rows_affected() > 0)
+}
+
+pub async fn cancel_brain_deletion(pool: &Pool<Postgres>, brain_id: &Uuid) -> bool {
+    let query = sqlx::query!(
+        "update brains set deletiondate=NULL, deletionmode=NULL, lastupdatedate=NOW()
+            where id=$1 and deletiondate is not null",
+        brain_id
+    )
+    .execute(pool)
+    .await
+    .log_error("update_models::cancel_brain_deletion");
+    matches!(query, Ok(result) if result.rows_affected() > 0)
+}
+
+pub async fn reset_password(pool: &Pool<Postgres>, brainname: &str, password: &str) -> bool {
+    if let Some(password_hash) = hash_password(password) {
+        let query = sqlx::query!(
+            "update brains set password=$1, lastupdatedate=NOW() where brainname=$2",
+            password_hash,
+            brainname
+        )
+        .execute(pool)
+        .await
+        .log_error("update_models::reset_password");
+        matches!(query, Ok(result) if result.rows_affected() > 0)
+    } else {
+        false
+    }
+}
+
+pub async fn set_brain_role(pool: &Pool<Postgres>, brainname: &str, role: Role) -> bool {
+    let query = sqlx::query!(
+        "update brains set role=$1, lastupdatedate=NOW() where brainname=$2",
+        role.to_string(),
+        brainname
+    )
+    .execute(pool)
+    .await
+    .log_error("update_models::set_brain_role");
+    matches!(query, Ok(result) if result.rows_affected() > 0)
+}
+
+pub async fn set_brainfart_hidden(
+    pool: &Pool<Postgres>,
+    brainfart_id: &Uuid,
+    hidden: bool,
+) -> bool {
+    let mut transaction = match pool
+        .begin()
+        .await
+        .log_error("update_models::set_brainfart_hidden")
+    {
+        Ok(transaction) => transaction,
+        Err(_) => return false,
+    };
+    let updated = matches!(
+        sqlx::query!(
+            "update brainfarts set hidden=$1 where id=$2",
+            hidden,
+            brainfart_id
+        )
+        .execute(&mut transaction)
+        .await
+        .log_error("update_models::set_brainfart_hidden"),
+        Ok(result) if result.rows_affected() > 0
+    );
+    let resolved = sqlx::query!(
+        "update reportedfarts set resolved=TRUE where brainfartid=$1",
+        brainfart_id
+    )
+    .execute(&mut transaction)
+    .await
+    .log_error("update_models::set_brainfart_hidden")
+    .is_ok();
+    updated
+        && resolved
+        && transaction
+            .commit()
+            .await
+            .log_error("update_models::set_brainfart_hidden")
+            .is_ok()
+}
+
+pub async fn publish_scheduled_brainfarts(
+    pool: &Pool<Postgres>,
+    now: DateTime<Local>,
+) -> Option<Vec<Uuid>> {
+    let query = sqlx::query!(
+        "update brainfarts set birthdate=publishdate, publishdate=NULL where publishdate <= $1 RETURNING id",
+        now.with_timezone(&Utc)
+    )
+    .fetch_all(pool)
+    .await
+    .log_error("update_models::publish_scheduled_brainfarts");
+    query
+        .ok()
+        .map(|rows| rows.into_iter().map(|row| row.id).collect())
+}
+
+pub async fn save_link_preview(
+    pool: &Pool<Postgres>,
+    url: &str,
+    preview: Option<&LinkPreview>,
+) -> bool {
+    let query = sqlx::query!(
+        "INSERT INTO linkpreviews (url, title, description, imageurl, sitename) VALUES($1, $2, $3, $4, $5)
+            ON CONFLICT (url)
+            DO UPDATE SET title=EXCLUDED.title, description=EXCLUDED.description,
+                imageurl=EXCLUDED.imageurl, sitename=EXCLUDED.sitename, fetchdate=NOW()",
+        url,
+        preview.map(|preview| &preview.title),
+        preview.and_then(|preview| preview.description.as_ref()),
+        preview.and_then(|preview| preview.image_url.as_ref()),
+        preview.and_then(|preview| preview.site_name.as_ref())
+    )
+    .execute(pool)
+    .await
+    .log_error("update_models::save_link_preview");
+    query.is_ok()
+}
+
+pub async fn record_webhook_delivery_attempt(
+    pool: &Pool<Postgres>,
+    delivery_id: &Uuid,
+    attempt: &DeliveryAttempt,
+) -> bool {
+    let query = sqlx::query!(
+        "update webhookdeliveries set status=$1, attempts=attempts+1, responsestatus=$2,
+            lasterror=$3, lastattemptdate=$4, nextattemptdate=$5 where id=$6",
+        attempt.status.to_string(),
+        attempt.response_status,
+        attempt.error.as_ref(),
+        attempt.attempted_at.with_timezone(&Utc),
+        attempt
+            .next_attempt
+            .map(|next_attempt| next_attempt.with_timezone(&Utc)),
+        delivery_id
+    )
+    .execute(pool)
+    .await
+    .log_error("update_models::record_webhook_delivery_attempt");
+    matches!(query, Ok(result) if result.rows_affected() > 0)
+}
+
+/// Fills in the tags of the brainfarts from before they were stored.
+pub async fn fill_in_brainfart_tags(pool: &Pool<Postgres>) -> Result<(), sqlx::Error> {
+    let untagged = sqlx::query!("select id, title, content from brainfarts where tags is null")
+        .fetch_all(pool)
+        .await?;
+    for brainfart in untagged.iter() {
+        sqlx::query!(
+            "update brainfarts set tags=$1 where id=$2",
+            tags_to_text(&brainfart.title, &brainfart.content),
+            brainfart.id
+        )
+        .execute(pool)
+        .await?;
+    }
+    Ok(())
+}
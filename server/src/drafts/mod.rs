@@ -0,0 +1,84 @@
+use axum::{
+    extract::{rejection::JsonRejection, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use shared::SaveDraftRequest;
+
+use crate::{
+    authentication::JwtDataHolder, database::repository::SharedRepository, error_responders,
+};
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/draft",
+    tag = "drafts",
+    responses(
+        (status = 200, description = "Your draft", body = BrainfartDraft),
+        (status = 404, description = "You have no draft", body = String)
+    ),
+    security(("bearer" = []))
+)]
+pub async fn get_draft(
+    State(repository): State<SharedRepository>,
+    claims: JwtDataHolder,
+) -> impl IntoResponse {
+    match repository.get_draft(&claims.get_id()).await {
+        Some(draft) => Ok((StatusCode::OK, Json(draft))),
+        None => Err((StatusCode::NOT_FOUND, "You have no draft".to_string())),
+    }
+}
+
+/// Called by the editor while the brain types, so a draft does not have to be complete.
+#[utoipa::path(
+    put,
+    path = "/api/v1/draft",
+    tag = "drafts",
+    request_body = SaveDraftRequest,
+    responses(
+        (status = 200, description = "The saved draft", body = BrainfartDraft),
+        (status = 400, description = "Invalid data", body = String)
+    ),
+    security(("bearer" = []))
+)]
+pub async fn save_draft(
+    State(repository): State<SharedRepository>,
+    claims: JwtDataHolder,
+    result: Result<Json<SaveDraftRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    let payload = match result {
+        Ok(Json(payload)) => payload,
+        Err(err) => return Err(error_responders::post_error_responder(err)),
+    };
+    let brain_id = claims.get_id();
+    let saved = repository.save_draft(&payload, &brain_id).await.is_some();
+    match repository.get_draft(&brain_id).await {
+        Some(draft) if saved => Ok((StatusCode::OK, Json(draft))),
+        _ => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Something went wrong saving the draft".to_string(),
+        )),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/draft",
+    tag = "drafts",
+    responses(
+        (status = 200, description = "The draft was deleted"),
+        (status = 404, description = "You have no draft", body = String)
+    ),
+    security(("bearer" = []))
+)]
+pub async fn delete_draft(
+    State(repository): State<SharedRepository>,
+    claims: JwtDataHolder,
+) -> impl IntoResponse {
+    if repository.delete_draft(&claims.get_id()).await {
+        Ok((StatusCode::OK, Json(())))
+    } else {
+        Err((StatusCode::NOT_FOUND, "You have no draft".to_string()))
+    }
+}
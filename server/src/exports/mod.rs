@@ -0,0 +1,41 @@
+use axum::{
+    extract::State,
+    http::{header::CONTENT_DISPOSITION, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::Local;
+
+use crate::{authentication::JwtDataHolder, database::repository::SharedRepository};
+
+/// Served as a file to download. `brains import` restores it on another instance.
+#[utoipa::path(
+    get,
+    path = "/api/v1/export",
+    tag = "brains",
+    responses(
+        (status = 200, description = "Everything you have made", body = BrainExport)
+    ),
+    security(("bearer" = []))
+)]
+pub async fn get_export(
+    State(repository): State<SharedRepository>,
+    claims: JwtDataHolder,
+) -> impl IntoResponse {
+    match repository
+        .export_brain(&claims.get_id(), Local::now())
+        .await
+    {
+        Some(export) => Ok((
+            [(
+                CONTENT_DISPOSITION,
+                format!("attachment; filename=\"brains-{}.json\"", export.brain.id),
+            )],
+            Json(export),
+        )),
+        None => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Something went wrong exporting your brain".to_string(),
+        )),
+    }
+}
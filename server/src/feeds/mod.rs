@@ -0,0 +1,403 @@
+//! Atom and RSS feeds of the brainfarts, for reading Brains in a feed reader.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{rejection::QueryRejection, Path, Query, State},
+    http::{
+        header::{CONTENT_TYPE, HOST},
+        HeaderMap, StatusCode, Uri,
+    },
+    response::IntoResponse,
+    Json,
+};
+use chrono::Local;
+use shared::{
+    find_tags, BrainfartFilter, BrainfartInformation, FeedFormat, FeedQuery, FeedToken, FEED_LENGTH,
+};
+
+use crate::{
+    authentication::{FeedReader, JwtDataHolder, JwtKeys},
+    database::repository::SharedRepository,
+};
+
+#[derive(Clone, Default)]
+pub struct FeedSettings {
+    /// Where the site is reached, without a trailing `/`.
+    pub public_url: Option<String>,
+}
+
+/// Which of the brainfarts in the feed a feed holds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FeedSelection {
+    Everything,
+    /// The brainfarts of the brain with the name.
+    Brain(String),
+    /// The brainfarts with the tag, as `find_tags` gives it.
+    Tag(String),
+}
+
+impl FeedSelection {
+    fn title(&self) -> String {
+        match self {
+            FeedSelection::Everything => "Brains".to_string(),
+            FeedSelection::Brain(brainname) => format!("Brainfarts by {}", brainname),
+            FeedSelection::Tag(tag) => format!("Brainfarts tagged #{}", tag),
+        }
+    }
+
+    /// The newest brainfarts of the selection, as many as a feed holds.
+    fn to_filter(&self) -> BrainfartFilter {
+        let (brainname, tag) = match self {
+            FeedSelection::Everything => (None, None),
+            FeedSelection::Brain(brainname) => (Some(brainname.clone()), None),
+            FeedSelection::Tag(tag) => (None, Some(tag.clone())),
+        };
+        BrainfartFilter {
+            brainname,
+            tag,
+            limit: Some(FEED_LENGTH as u32),
+            ..Default::default()
+        }
+    }
+}
+
+/// A brainfart as it is shown in a feed. A rebrainfart shows the brainfart it shares.
+struct FeedEntry {
+    brainfart: BrainfartInformation,
+}
+
+impl FeedEntry {
+    fn shared(&self) -> Option<&BrainfartInformation> {
+        match &self.brainfart.original {
+            Some(original) if self.brainfart.content.is_empty() => Some(original),
+            _ => None,
+        }
+    }
+
+    fn title(&self) -> String {
+        match self.shared() {
+            Some(original) => format!(
+                "{} rebrainfarted {}",
+                self.brainfart.mastermind_name, original.title
+            ),
+            None => self.brainfart.title.clone(),
+        }
+    }
+
+    fn content(&self) -> &str {
+        match self.shared() {
+            Some(original) => &original.content,
+            None => &self.brainfart.content,
+        }
+    }
+}
+
+/// The site the feed links to. Unless it is configured, it is taken from the request, trusting
+/// the proxy in front to pass on the host and to tell whether it was reached over https.
+fn site_url(settings: &FeedSettings, headers: &HeaderMap) -> String {
+    if let Some(public_url) = &settings.public_url {
+        return public_url.clone();
+    }
+    let host = headers
+        .get(HOST)
+        .and_then(|host| host.to_str().ok())
+        .unwrap_or("localhost");
+    let scheme = headers
+        .get("x-forwarded-proto")
+        .and_then(|scheme| scheme.to_str().ok())
+        .filter(|scheme| *scheme == "https")
+        .unwrap_or("http");
+    format!("{}://{}", scheme, host)
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than whitespace are not allowed in XML at all.
+            c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// The id is the url without the query, so it stays the same when the token changes. The self
+/// link keeps only the format, as the token is the reader's secret.
+fn to_atom(title: &str, site: &str, uri: &Uri, entries: &[FeedEntry]) -> String {
+    let updated = entries
+        .iter()
+        .map(|entry| entry.brainfart.birthdate)
+        .max()
+        .unwrap_or_else(Local::now);
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+        <feed xmlns=\"http://www.w3.org/2005/Atom\">\n\
+        <title>{}</title>\n<id>{}</id>\n<updated>{}</updated>\n\
+        <link rel=\"alternate\" href=\"{}/\"/>\n<link rel=\"self\" href=\"{}\"/>\n",
+        escape(title),
+        escape(&format!("{}{}", site, uri.path())),
+        updated.to_rfc3339(),
+        escape(site),
+        escape(&format!("{}{}?format=atom", site, uri.path())),
+    );
+    for entry in entries {
+        xml.push_str(&format!(
+            "<entry>\n<title>{}</title>\n<id>urn:uuid:{}</id>\n<published>{}</published>\n\
+            <updated>{}</updated>\n<author><name>{}</name></author>\n\
+            <content type=\"text\">{}</content>\n</entry>\n",
+            escape(&entry.title()),
+            escape(&entry.brainfart.id),
+            entry.brainfart.birthdate.to_rfc3339(),
+            entry.brainfart.birthdate.to_rfc3339(),
+            escape(&entry.brainfart.mastermind_name),
+            escape(entry.content()),
+        ));
+    }
+    xml.push_str("</feed>\n");
+    xml
+}
+
+fn to_rss(title: &str, site: &str, entries: &[FeedEntry]) -> String {
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<rss version=\"2.0\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n<channel>\n\
+        <title>{}</title>\n<link>{}/</link>\n<description>{}</description>\n",
+        escape(title),
+        escape(site),
+        escape(title),
+    );
+    for entry in entries {
+        xml.push_str(&format!(
+            "<item>\n<title>{}</title>\n<guid isPermaLink=\"false\">{}</guid>\n\
+            <pubDate>{}</pubDate>\n<dc:creator>{}</dc:creator>\n<description>{}</description>\n</item>\n",
+            escape(&entry.title()),
+            escape(&entry.brainfart.id),
+            entry.brainfart.birthdate.to_rfc2822(),
+            escape(&entry.brainfart.mastermind_name),
+            escape(entry.content()),
+        ));
+    }
+    xml.push_str("</channel>\n</rss>\n");
+    xml
+}
+
+/// The newest brainfarts the reader gets to see, like in the feed. Reading a feed does not count
+/// as viewing the brainfarts, since feed readers fetch it whether anyone looks or not.
+async fn feed(
+    repository: &SharedRepository,
+    settings: &FeedSettings,
+    reader: &JwtDataHolder,
+    selection: FeedSelection,
+    query: Result<Query<FeedQuery>, QueryRejection>,
+    headers: &HeaderMap,
+    uri: &Uri,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let Ok(Query(query)) = query else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "The format has to be atom or rss".to_string(),
+        ));
+    };
+    let brainfarts = match &selection {
+        // Nothing is tagged with what `find_tags` would not take for a tag.
+        FeedSelection::Tag(tag) if find_tags(&format!("#{}", tag)) != [tag.as_str()] => {
+            Some(vec![])
+        }
+        _ => {
+            repository
+                .get_brainfarts_using_filter(selection.to_filter(), &reader.get_id())
+                .await
+        }
+    };
+    let Some(brainfarts) = brainfarts else {
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Something went wrong reading the brainfarts".to_string(),
+        ));
+    };
+    let entries = brainfarts
+        .into_iter()
+        .map(|brainfart| FeedEntry { brainfart })
+        .collect::<Vec<_>>();
+    let site = site_url(settings, headers);
+    let title = selection.title();
+    Ok(match query.format {
+        FeedFormat::Atom => (
+            [(CONTENT_TYPE, "application/atom+xml; charset=utf-8")],
+            to_atom(&title, &site, uri, &entries),
+        ),
+        FeedFormat::Rss => (
+            [(CONTENT_TYPE, "application/rss+xml; charset=utf-8")],
+            to_rss(&title, &site, &entries),
+        ),
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/feeds/brainfarts",
+    tag = "feeds",
+    params(FeedQuery),
+    responses(
+        (status = 200, description = "The newest brainfarts as Atom or RSS", body = String,
+            content_type = "application/atom+xml"),
+        (status = 400, description = "Unknown format", body = String)
+    ),
+    security(("bearer" = []), ("feed_token" = []))
+)]
+pub async fn get_feed(
+    State(repository): State<SharedRepository>,
+    State(settings): State<FeedSettings>,
+    FeedReader(reader): FeedReader,
+    headers: HeaderMap,
+    uri: Uri,
+    query: Result<Query<FeedQuery>, QueryRejection>,
+) -> impl IntoResponse {
+    feed(
+        &repository,
+        &settings,
+        &reader,
+        FeedSelection::Everything,
+        query,
+        &headers,
+        &uri,
+    )
+    .await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/feeds/brains/{brainname}",
+    tag = "feeds",
+    params(
+        ("brainname" = String, Path, description = "The mastermind of the brainfarts"),
+        FeedQuery
+    ),
+    responses(
+        (status = 200, description = "The brain's newest brainfarts as Atom or RSS", body = String,
+            content_type = "application/atom+xml"),
+        (status = 400, description = "Unknown format", body = String)
+    ),
+    security(("bearer" = []), ("feed_token" = []))
+)]
+pub async fn get_brain_feed(
+    State(repository): State<SharedRepository>,
+    State(settings): State<FeedSettings>,
+    FeedReader(reader): FeedReader,
+    Path(brainname): Path<String>,
+    headers: HeaderMap,
+    uri: Uri,
+    query: Result<Query<FeedQuery>, QueryRejection>,
+) -> impl IntoResponse {
+    feed(
+        &repository,
+        &settings,
+        &reader,
+        FeedSelection::Brain(brainname),
+        query,
+        &headers,
+        &uri,
+    )
+    .await
+}
+
+/// The tag is matched without regard to case, with or without its `#`.
+#[utoipa::path(
+    get,
+    path = "/api/v1/feeds/tags/{tag}",
+    tag = "feeds",
+    params(
+        ("tag" = String, Path, description = "The tag, e.g. `rust`"),
+        FeedQuery
+    ),
+    responses(
+        (status = 200, description = "The newest brainfarts with the tag as Atom or RSS",
+            body = String, content_type = "application/atom+xml"),
+        (status = 400, description = "Unknown format", body = String)
+    ),
+    security(("bearer" = []), ("feed_token" = []))
+)]
+pub async fn get_tag_feed(
+    State(repository): State<SharedRepository>,
+    State(settings): State<FeedSettings>,
+    FeedReader(reader): FeedReader,
+    Path(tag): Path<String>,
+    headers: HeaderMap,
+    uri: Uri,
+    query: Result<Query<FeedQuery>, QueryRejection>,
+) -> impl IntoResponse {
+    let tag = tag.trim_start_matches('#').to_lowercase();
+    feed(
+        &repository,
+        &settings,
+        &reader,
+        FeedSelection::Tag(tag),
+        query,
+        &headers,
+        &uri,
+    )
+    .await
+}
+
+/// A new token for `?token=`. It reads the feeds as you, with your mutes, for a year or until you
+/// revoke your feed tokens.
+#[utoipa::path(
+    post,
+    path = "/api/v1/feeds/tokens",
+    tag = "feeds",
+    responses(
+        (status = 201, description = "The feed token", body = FeedToken)
+    ),
+    security(("bearer" = []))
+)]
+pub async fn create_feed_token(
+    State(keys): State<Arc<JwtKeys>>,
+    State(repository): State<SharedRepository>,
+    claims: JwtDataHolder,
+) -> impl IntoResponse {
+    match repository.get_feed_token_generation(&claims.get_id()).await {
+        Some(generation) => {
+            let token = keys.create_feed_token(
+                claims.get_id(),
+                claims.information.brainname.clone(),
+                claims.information.role,
+                generation,
+            );
+            Ok((StatusCode::CREATED, Json(FeedToken { token })))
+        }
+        None => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Something went wrong creating the feed token".to_string(),
+        )),
+    }
+}
+
+/// Revokes every feed token you have handed out, e.g. when one has leaked.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/feeds/tokens",
+    tag = "feeds",
+    responses(
+        (status = 200, description = "Your feed tokens were revoked")
+    ),
+    security(("bearer" = []))
+)]
+pub async fn revoke_feed_tokens(
+    State(repository): State<SharedRepository>,
+    claims: JwtDataHolder,
+) -> impl IntoResponse {
+    if repository.revoke_feed_tokens(&claims.get_id()).await {
+        Ok((StatusCode::OK, Json(())))
+    } else {
+        Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Something went wrong revoking the feed tokens".to_string(),
+        ))
+    }
+}
@@ -0,0 +1,54 @@
+use axum::{
+    extract::{rejection::QueryRejection, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::Local;
+use shared::{Leaderboard, LeaderboardQuery, LEADERBOARD_SIZE};
+
+use crate::{authentication::JwtDataHolder, database::repository::SharedRepository};
+
+/// The window is given in the query string, e.g. `?window=month`, and is a week when left out.
+#[utoipa::path(
+    get,
+    path = "/api/v1/leaderboard",
+    tag = "leaderboards",
+    params(LeaderboardQuery),
+    responses(
+        (status = 200, description = "The leaderboard of the window", body = Leaderboard),
+        (status = 400, description = "Unknown window", body = String)
+    ),
+    security(("bearer" = []))
+)]
+pub async fn get_leaderboard(
+    State(repository): State<SharedRepository>,
+    _claims: JwtDataHolder,
+    query: Result<Query<LeaderboardQuery>, QueryRejection>,
+) -> impl IntoResponse {
+    let query = match query {
+        Ok(Query(query)) => query,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "The window has to be week, month or all".to_string(),
+            ))
+        }
+    };
+    let since = query.window.start(Local::now());
+    let brainfarts = repository.get_top_brainfarts(since, LEADERBOARD_SIZE).await;
+    let masterminds = repository
+        .get_top_masterminds(since, LEADERBOARD_SIZE)
+        .await;
+    match (brainfarts, masterminds) {
+        (Some(brainfarts), Some(masterminds)) => Ok(Json(Leaderboard {
+            window: query.window,
+            brainfarts,
+            masterminds,
+        })),
+        _ => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Something went wrong reading the leaderboard".to_string(),
+        )),
+    }
+}
@@ -0,0 +1,234 @@
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+
+use axum::async_trait;
+use hyper::{
+    body::HttpBody,
+    header::{ACCEPT, CONTENT_TYPE, HOST, LOCATION, USER_AGENT},
+    Body, Request, Response,
+};
+use shared::LinkPreview;
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::TcpStream,
+};
+use tokio_rustls::{
+    rustls::{ClientConfig, OwnedTrustAnchor, RootCertStore, ServerName},
+    TlsConnector,
+};
+use url::{Host, Position, Url};
+
+use super::{opengraph::parse_opengraph, PreviewFetcher};
+
+const MAX_REDIRECTS: usize = 3;
+/// The head of a page, where the OpenGraph tags are, is well within this.
+const MAX_PAGE_BYTES: usize = 512 * 1024;
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Whether the address belongs on the public internet. Anything else could let a brainfart make
+/// the server talk to itself or to the network it runs in.
+pub fn is_public_address(address: IpAddr) -> bool {
+    match address {
+        IpAddr::V4(address) => is_public_ipv4(address),
+        IpAddr::V6(address) => match embedded_ipv4(address) {
+            Some(embedded) => is_public_ipv4(embedded),
+            None => is_public_ipv6(address),
+        },
+    }
+}
+
+/// The IPv4 address that traffic to the IPv6 address ends up at: IPv4-mapped, `::ffff:0:0/96`,
+/// NAT64, `64:ff9b::/96`, and 6to4, `2002::/16`.
+fn embedded_ipv4(address: Ipv6Addr) -> Option<Ipv4Addr> {
+    let segments = address.segments();
+    let octets = address.octets();
+    if let Some(mapped) = address.to_ipv4_mapped() {
+        Some(mapped)
+    } else if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+        Some(Ipv4Addr::new(
+            octets[12], octets[13], octets[14], octets[15],
+        ))
+    } else if segments[0] == 0x2002 {
+        Some(Ipv4Addr::new(octets[2], octets[3], octets[4], octets[5]))
+    } else {
+        None
+    }
+}
+
+fn is_public_ipv4(address: Ipv4Addr) -> bool {
+    let [first, second, third, _] = address.octets();
+    !(address.is_unspecified()
+        || address.is_loopback()
+        || address.is_private()
+        || address.is_link_local()
+        || address.is_broadcast()
+        || address.is_multicast()
+        || address.is_documentation()
+        || first == 0
+        // Shared address space for carrier-grade NAT, 100.64.0.0/10
+        || (first == 100 && (64..128).contains(&second))
+        // IETF protocol assignments, 192.0.0.0/24
+        || (first == 192 && second == 0 && third == 0)
+        // Benchmarking, 198.18.0.0/15
+        || (first == 198 && (second == 18 || second == 19))
+        // Reserved, 240.0.0.0/4
+        || first >= 240)
+}
+
+fn is_public_ipv6(address: Ipv6Addr) -> bool {
+    let first_segment = address.segments()[0];
+    !(address.is_unspecified()
+        || address.is_loopback()
+        || address.is_multicast()
+        // Unique local, fc00::/7
+        || (first_segment & 0xfe00) == 0xfc00
+        // Link-local, fe80::/10
+        || (first_segment & 0xffc0) == 0xfe80
+        // Documentation, 2001:db8::/32
+        || (first_segment == 0x2001 && address.segments()[1] == 0x0db8))
+}
+
+/// Trusts the web's usual certificate authorities, not those of the system.
+pub fn tls_connector() -> TlsConnector {
+    let mut roots = RootCertStore::empty();
+    roots.add_server_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.0.iter().map(|anchor| {
+        OwnedTrustAnchor::from_subject_spki_name_constraints(
+            anchor.subject,
+            anchor.spki,
+            anchor.name_constraints,
+        )
+    }));
+    let configuration = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    TlsConnector::from(Arc::new(configuration))
+}
+
+/// Fetches pages over HTTP and HTTPS and reads their OpenGraph tags. Only public addresses are
+/// contacted, checked again after every redirect, and the connection goes to the address that
+/// was checked so the name cannot resolve to something else in between.
+pub struct HttpPreviewFetcher {
+    allow_private_addresses: bool,
+    tls: TlsConnector,
+}
+
+impl HttpPreviewFetcher {
+    /// `allow_private_addresses` is for tests against a local server and must not be used in
+    /// production.
+    pub fn create(allow_private_addresses: bool) -> HttpPreviewFetcher {
+        HttpPreviewFetcher {
+            allow_private_addresses,
+            tls: tls_connector(),
+        }
+    }
+
+    async fn resolve(&self, url: &Url) -> Option<SocketAddr> {
+        let port = url.port_or_known_default()?;
+        let addresses: Vec<SocketAddr> = match url.host()? {
+            Host::Ipv4(address) => vec![SocketAddr::new(address.into(), port)],
+            Host::Ipv6(address) => vec![SocketAddr::new(address.into(), port)],
+            Host::Domain(domain) => tokio::net::lookup_host((domain, port))
+                .await
+                .ok()?
+                .collect(),
+        };
+        if !self.allow_private_addresses
+            && addresses
+                .iter()
+                .any(|address| !is_public_address(address.ip()))
+        {
+            log::info!(
+                "Not fetching a link preview from {}: not a public address",
+                url
+            );
+            return None;
+        }
+        addresses.into_iter().next()
+    }
+
+    async fn send(&self, url: &Url) -> Option<Response<Body>> {
+        let address = self.resolve(url).await?;
+        let request = Request::get(&url[Position::BeforePath..Position::AfterQuery])
+            .header(HOST, &url[Position::BeforeHost..Position::AfterPort])
+            .header(USER_AGENT, "Brains link previews")
+            .header(ACCEPT, "text/html")
+            .body(Body::empty())
+            .ok()?;
+        let stream = TcpStream::connect(address).await.ok()?;
+        match url.scheme() {
+            "http" => send_request(stream, request).await,
+            "https" => {
+                let host = url
+                    .host_str()?
+                    .trim_start_matches('[')
+                    .trim_end_matches(']');
+                let server_name = ServerName::try_from(host).ok()?;
+                let stream = self.tls.connect(server_name, stream).await.ok()?;
+                send_request(stream, request).await
+            }
+            _ => None,
+        }
+    }
+
+    /// Follows redirects and answers with the final url and the start of the page.
+    async fn get_page(&self, url: &Url) -> Option<(Url, String)> {
+        let mut url = url.clone();
+        for _ in 0..=MAX_REDIRECTS {
+            let response = self.send(&url).await?;
+            if response.status().is_redirection() {
+                let location = response.headers().get(LOCATION)?.to_str().ok()?;
+                url = url.join(location).ok()?;
+                continue;
+            }
+            let content_type = response.headers().get(CONTENT_TYPE)?.to_str().ok()?;
+            if !response.status().is_success()
+                || !(content_type.starts_with("text/html")
+                    || content_type.starts_with("application/xhtml+xml"))
+            {
+                return None;
+            }
+            let mut body = response.into_body();
+            let mut page = vec![];
+            while let Some(chunk) = body.data().await {
+                page.extend_from_slice(&chunk.ok()?);
+                if page.len() >= MAX_PAGE_BYTES {
+                    page.truncate(MAX_PAGE_BYTES);
+                    break;
+                }
+            }
+            return Some((url, String::from_utf8_lossy(&page).into_owned()));
+        }
+        None
+    }
+}
+
+async fn send_request<T>(stream: T, request: Request<Body>) -> Option<Response<Body>>
+where
+    T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let (mut sender, connection) = hyper::client::conn::handshake(stream).await.ok()?;
+    tokio::spawn(async move {
+        if let Err(err) = connection.await {
+            log::debug!("A link preview connection failed: {}", err);
+        }
+    });
+    sender.send_request(request).await.ok()
+}
+
+#[async_trait]
+impl PreviewFetcher for HttpPreviewFetcher {
+    async fn fetch(&self, url: &str) -> Option<LinkPreview> {
+        let parsed = Url::parse(url).ok()?;
+        if parsed.scheme() != "http" && parsed.scheme() != "https" {
+            return None;
+        }
+        let (page_url, page) = tokio::time::timeout(TIMEOUT, self.get_page(&parsed))
+            .await
+            .ok()??;
+        parse_opengraph(url, &page_url, &page)
+    }
+}
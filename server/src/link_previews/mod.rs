@@ -0,0 +1,54 @@
+use std::sync::Arc;
+
+use axum::async_trait;
+use shared::{find_urls, LinkPreview};
+use tokio::task::JoinHandle;
+
+use crate::database::repository::SharedRepository;
+
+pub mod http_fetcher;
+pub mod opengraph;
+
+pub type SharedPreviewFetcher = Arc<dyn PreviewFetcher>;
+
+/// Finds out what a linked page is about. The server fetches pages over HTTP; other fetchers
+/// could ask a preview service instead.
+#[async_trait]
+pub trait PreviewFetcher: Send + Sync {
+    /// None if the page could not be fetched or says too little about itself.
+    async fn fetch(&self, url: &str) -> Option<LinkPreview>;
+}
+
+/// Fetches the previews of the urls that are not cached yet. Failures are cached too, so a
+/// broken link is only tried once.
+pub async fn fetch_missing_previews(
+    repository: &SharedRepository,
+    fetcher: &dyn PreviewFetcher,
+    urls: &[String],
+) {
+    for url in urls {
+        if repository.has_link_preview(url).await {
+            continue;
+        }
+        let preview = fetcher.fetch(url).await;
+        if !repository.save_link_preview(url, preview.as_ref()).await {
+            log::warn!("Saving the link preview of {} failed", url);
+        }
+    }
+}
+
+/// Fetches the previews of the links in the content in the background, so creating a brainfart
+/// does not wait for other servers.
+pub fn spawn_fetch(
+    repository: SharedRepository,
+    fetcher: SharedPreviewFetcher,
+    content: &str,
+) -> Option<JoinHandle<()>> {
+    let urls = find_urls(content);
+    if urls.is_empty() {
+        return None;
+    }
+    Some(tokio::spawn(async move {
+        fetch_missing_previews(&repository, fetcher.as_ref(), &urls).await;
+    }))
+}
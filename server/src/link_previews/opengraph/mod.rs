@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use shared::LinkPreview;
+use url::Url;
+
+const MAX_TITLE_CHARACTERS: usize = 200;
+const MAX_DESCRIPTION_CHARACTERS: usize = 500;
+
+/// The `property` or `name` of every `<meta>` tag, lowercased, with its `content`. The first tag
+/// with a name wins.
+fn meta_tags(html: &str) -> HashMap<String, String> {
+    let lowercase = html.to_ascii_lowercase();
+    let mut tags = HashMap::new();
+    let mut position = 0;
+    while let Some(start) = lowercase[position..].find("<meta") {
+        let start = position + start + "<meta".len();
+        let end = match html[start..].find('>') {
+            Some(end) => start + end,
+            None => break,
+        };
+        let attributes = attributes(&html[start..end]);
+        let name = attributes
+            .get("property")
+            .or_else(|| attributes.get("name"));
+        if let (Some(name), Some(content)) = (name, attributes.get("content")) {
+            tags.entry(name.to_ascii_lowercase())
+                .or_insert_with(|| content.clone());
+        }
+        position = end;
+    }
+    tags
+}
+
+/// Reads `name="value"`, `name='value'` and `name=value` pairs, with lowercased names.
+fn attributes(tag: &str) -> HashMap<String, String> {
+    let mut attributes = HashMap::new();
+    let mut rest = tag.trim_start_matches('/');
+    while let Some(equals) = rest.find('=') {
+        let name = rest[..equals]
+            .split_whitespace()
+            .last()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let value_part = rest[equals + 1..].trim_start();
+        let (value, after) = match value_part.chars().next() {
+            Some(quote) if quote == '"' || quote == '\'' => match value_part[1..].find(quote) {
+                Some(end) => (&value_part[1..end + 1], &value_part[end + 2..]),
+                None => (&value_part[1..], ""),
+            },
+            _ => {
+                let end = value_part
+                    .find(char::is_whitespace)
+                    .unwrap_or(value_part.len());
+                (value_part[..end].trim_end_matches('/'), &value_part[end..])
+            }
+        };
+        attributes.insert(name, value.to_string());
+        rest = after;
+    }
+    attributes
+}
+
+fn title_tag(html: &str) -> Option<String> {
+    let lowercase = html.to_ascii_lowercase();
+    let start = lowercase.find("<title")?;
+    let start = start + lowercase[start..].find('>')? + 1;
+    let end = start + lowercase[start..].find("</title")?;
+    Some(html[start..end].to_string())
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(ampersand) = rest.find('&') {
+        decoded.push_str(&rest[..ampersand]);
+        rest = &rest[ampersand..];
+        let entity = rest
+            .find(';')
+            .filter(|end| *end <= 10)
+            .map(|end| &rest[1..end]);
+        let character = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => match entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+            {
+                Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                None => entity
+                    .strip_prefix('#')
+                    .and_then(|decimal| decimal.parse().ok())
+                    .and_then(char::from_u32),
+            },
+        });
+        match (character, entity) {
+            (Some(character), Some(entity)) => {
+                decoded.push(character);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Decodes the text, collapses its whitespace and cuts it to `max_characters`. None if nothing
+/// is left.
+fn clean_text(text: &str, max_characters: usize) -> Option<String> {
+    let text = decode_entities(text)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if text.is_empty() {
+        None
+    } else if text.chars().count() > max_characters {
+        let cut: String = text.chars().take(max_characters - 1).collect();
+        Some(format!("{}…", cut.trim_end()))
+    } else {
+        Some(text)
+    }
+}
+
+/// Reads the OpenGraph tags of the page at `page_url`, falling back to the Twitter card tags and
+/// the `<title>`. Images are made absolute; only http and https images are kept. None if the
+/// page has no title.
+pub fn parse_opengraph(url: &str, page_url: &Url, html: &str) -> Option<LinkPreview> {
+    let tags = meta_tags(html);
+    let first = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|name| tags.get(*name).map(String::as_str))
+    };
+    let title = first(&["og:title", "twitter:title"])
+        .map(str::to_string)
+        .or_else(|| title_tag(html))
+        .and_then(|title| clean_text(&title, MAX_TITLE_CHARACTERS))?;
+    let description = first(&["og:description", "twitter:description", "description"])
+        .and_then(|description| clean_text(description, MAX_DESCRIPTION_CHARACTERS));
+    let image_url = first(&["og:image", "og:image:url", "twitter:image"])
+        .and_then(|image| page_url.join(decode_entities(image).trim()).ok())
+        .filter(|image| image.scheme() == "http" || image.scheme() == "https")
+        .map(String::from);
+    let site_name =
+        first(&["og:site_name"]).and_then(|site_name| clean_text(site_name, MAX_TITLE_CHARACTERS));
+    Some(LinkPreview {
+        url: url.to_string(),
+        title,
+        description,
+        image_url,
+        site_name,
+    })
+}
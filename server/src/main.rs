@@ -1,53 +1,176 @@
-use authentication::{create_jwt, JwtDataHolder};
+use accounts::AccountSettings;
+use admin::{AdminCommand, BrainfartsCommand, BrainsCommand};
+use attachments::AttachmentSettings;
+use authentication::{JwtDataHolder, JwtKeys};
 use axum::{
-    extract::{rejection::JsonRejection, State},
+    extract::{rejection::JsonRejection, FromRef, Query, State},
     http::StatusCode,
+    middleware,
     response::IntoResponse,
-    routing::{get, post},
+    routing::get,
     Json, Router,
 };
-use clap::Parser;
-use database::{
-    create_models::CreateFromRequest, get_models, password_verification::verify_password,
-};
-use jwt_simple::prelude::ES384KeyPair;
+use chrono::Local;
+use clap::{Parser, Subcommand};
+use configuration::{Configuration, ConfigurationLayer};
+use database::repository::SharedRepository;
+use feeds::FeedSettings;
+use link_previews::{http_fetcher::HttpPreviewFetcher, SharedPreviewFetcher};
+use monitoring::{MonitoringSettings, SharedMetrics};
 use shared::{
-    Brain, Brainfart, BrainfartFilter, CreateBrainfartRequest, NotifyAboutMindExplosionRequest,
-    NotifyAboutMindImplosionRequest, ProveOwnsBrainRequest, RegisterBrainRequest,
+    Brain, Brainfart, BrainfartFilter, BrainfartInformation, CreateBrainfartRequest,
+    NotifyAboutMindExplosionRequest, NotifyAboutMindImplosionRequest, ProveOwnsBrainRequest,
+    ReactionKind, RegisterBrainRequest, Role, WebhookEvent, WebhookReaction, MAX_POLL_OPTIONS,
+    MAX_POLL_OPTION_CHARACTERS, REMOVED_BRAIN_NAME,
 };
-use std::net::{IpAddr, Ipv6Addr, SocketAddr};
-use std::str::FromStr;
+use std::fmt::Display;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 
 use tower::ServiceBuilder;
-use tower_http::trace::TraceLayer;
+use tower_http::{
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    trace::TraceLayer,
+};
 
-use sqlx::{postgres::PgPoolOptions, types::Uuid, Pool, Postgres};
+use sqlx::types::Uuid;
+use storage::{local_disk_storage::LocalDiskStorage, SharedStorage};
+use webhooks::http_sender::HttpWebhookSender;
 
 use dotenv::dotenv;
 
+mod accounts;
+mod admin;
+mod attachments;
 mod authentication;
+mod bookmarks;
+mod configuration;
 mod database;
+mod drafts;
 mod error_responders;
+mod exports;
+mod feeds;
+mod leaderboards;
+mod link_previews;
+mod moderation;
+mod monitoring;
+mod openapi;
+mod polls;
+mod ranking;
+mod rebrainfarts;
+mod relations;
+mod routes;
+mod scheduling;
+mod storage;
+mod telemetry;
+#[cfg(test)]
+mod tests;
+mod webhooks;
+
+#[derive(Clone)]
+pub struct AppState {
+    pub repository: SharedRepository,
+    pub jwt_keys: Arc<JwtKeys>,
+    pub attachments: AttachmentSettings,
+    pub link_previews: SharedPreviewFetcher,
+    pub accounts: AccountSettings,
+    pub metrics: SharedMetrics,
+    pub feeds: FeedSettings,
+    pub monitoring: MonitoringSettings,
+}
+
+impl FromRef<AppState> for SharedRepository {
+    fn from_ref(state: &AppState) -> SharedRepository {
+        state.repository.clone()
+    }
+}
+
+impl FromRef<AppState> for AttachmentSettings {
+    fn from_ref(state: &AppState) -> AttachmentSettings {
+        state.attachments.clone()
+    }
+}
+
+impl FromRef<AppState> for AccountSettings {
+    fn from_ref(state: &AppState) -> AccountSettings {
+        state.accounts.clone()
+    }
+}
+
+impl FromRef<AppState> for FeedSettings {
+    fn from_ref(state: &AppState) -> FeedSettings {
+        state.feeds.clone()
+    }
+}
+
+impl FromRef<AppState> for MonitoringSettings {
+    fn from_ref(state: &AppState) -> MonitoringSettings {
+        state.monitoring.clone()
+    }
+}
+
+impl FromRef<AppState> for SharedMetrics {
+    fn from_ref(state: &AppState) -> SharedMetrics {
+        state.metrics.clone()
+    }
+}
+
+impl FromRef<AppState> for SharedPreviewFetcher {
+    fn from_ref(state: &AppState) -> SharedPreviewFetcher {
+        state.link_previews.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<JwtKeys> {
+    fn from_ref(state: &AppState) -> Arc<JwtKeys> {
+        state.jwt_keys.clone()
+    }
+}
 
-type ConnectionPool = Pool<Postgres>;
 // Setup the command line interface with clap.
 #[derive(Parser, Debug)]
 #[clap(name = "server", about = "A server for our wasm project!")]
 struct Opt {
-    ///set the log level
-    #[clap(short = 'l', long = "log", default_value = "debug")]
-    log_level: String,
-
-    /// set the listen addr
-    #[clap(short = 'a', long = "addr", default_value = "::1")]
-    addr: String,
-
-    /// set the listen port
-    #[clap(short = 'p', long = "port", default_value = "8080")]
-    port: u16,
-    /// set the directory where static files are to be found
-    #[clap(long = "static-dir", default_value = "./dist")]
-    static_dir: String,
+    /// read settings from this TOML file [default: brains.toml, if it exists]
+    #[clap(short = 'c', long = "config", env = "BRAINS_CONFIG", global = true)]
+    config: Option<PathBuf>,
+
+    #[clap(flatten)]
+    settings: ConfigurationLayer,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run the server (the default)
+    Serve,
+    /// Inspect the configuration
+    Config {
+        #[clap(subcommand)]
+        command: ConfigCommand,
+    },
+    /// Manage brains
+    Brains {
+        #[clap(subcommand)]
+        command: BrainsCommand,
+    },
+    /// Manage brainfarts
+    Brainfarts {
+        #[clap(subcommand)]
+        command: BrainfartsCommand,
+    },
+    /// Print usage statistics
+    Stats,
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigCommand {
+    /// Validate the configuration and print the effective settings
+    Check,
 }
 
 #[tokio::main]
@@ -55,68 +178,194 @@ async fn main() {
     dotenv().ok();
 
     let opt = Opt::parse();
+    let configuration = match Configuration::load(opt.config.as_deref(), opt.settings) {
+        Ok(configuration) => configuration,
+        Err(err) => exit_with_error(err),
+    };
 
-    //Setup logging & RUST_LOG from args
-    if std::env::var("RUST_LOG").is_err() {
-        std::env::set_var("RUST_LOG", format!("{},hyper=info,mio=info", opt.log_level));
+    match opt.command.unwrap_or(Command::Serve) {
+        Command::Serve => serve(configuration).await,
+        Command::Config {
+            command: ConfigCommand::Check,
+        } => check_configuration(configuration),
+        Command::Brains { command } => {
+            run_admin_command(configuration, AdminCommand::Brains(command)).await
+        }
+        Command::Brainfarts { command } => {
+            run_admin_command(configuration, AdminCommand::Brainfarts(command)).await
+        }
+        Command::Stats => run_admin_command(configuration, AdminCommand::Stats).await,
     }
-    //enable console logging
-    tracing_subscriber::fmt::init();
+}
 
-    let pool = PgPoolOptions::new()
-        .max_connections(5)
-        .connect(&(std::env::var("DATABASE_URL").expect("Environmental variable not set")))
+fn exit_with_error(err: impl Display) -> ! {
+    eprintln!("error: {}", err);
+    std::process::exit(1)
+}
+
+fn check_configuration(configuration: Configuration) {
+    print!("{}", configuration.to_printable());
+    if let Err(err) = JwtKeys::load(&configuration) {
+        exit_with_error(err);
+    }
+    println!("\nThe configuration is valid.");
+}
+
+async fn run_admin_command(configuration: Configuration, command: AdminCommand) {
+    let repository = database::connect(&configuration.database_url, 1)
         .await
-        .expect("Setting up database pool failed");
-    // Make a simple query to return the given parameter (use a question mark `?` instead of `$1` for MySQL)
-
-    let app = Router::new()
-        .route("/api/hello", get(hello))
-        .route("/api/createbrainfart", post(create_brainfarts))
-        .route("/api/registerbrain", post(register_brain))
-        .route("/api/getbrainfarts", get(get_some_brainfarts))
-        .route("/api/loginasbrain", post(show_i_own_brain))
-        .route("/api/registermindexplosion", post(register_mind_explosion))
-        .route("/api/registermindimplosion", post(register_mind_implosion))
-        .merge(axum_extra::routing::SpaRouter::new(
-            "/assets",
-            opt.static_dir,
-        ))
-        .with_state(pool)
-        .layer(ServiceBuilder::new().layer(TraceLayer::new_for_http()));
-
-    let sock_addr = SocketAddr::from((
-        IpAddr::from_str(opt.addr.as_str()).unwrap_or(IpAddr::V6(Ipv6Addr::LOCALHOST)),
-        opt.port,
-    ));
+        .unwrap_or_else(|err| {
+            exit_with_error(format!("Connecting to the database failed: {}", err))
+        });
+    let storage = LocalDiskStorage::create(&configuration.attachments_dir);
+    match admin::run(command, repository.as_ref(), &storage).await {
+        Ok(output) => print!("{}", output),
+        Err(err) => exit_with_error(err),
+    }
+}
+
+async fn serve(configuration: Configuration) {
+    telemetry::init(&configuration).unwrap_or_else(|err| exit_with_error(err));
+
+    let jwt_keys = JwtKeys::load(&configuration).unwrap_or_else(|err| exit_with_error(err));
+    let repository = database::connect(&configuration.database_url, configuration.max_connections)
+        .await
+        .unwrap_or_else(|err| {
+            exit_with_error(format!("Setting up the database pool failed: {}", err))
+        });
+    scheduling::spawn_publisher(
+        repository.clone(),
+        Duration::from_secs(configuration.publish_interval_seconds),
+    );
+    webhooks::spawn_deliverer(
+        repository.clone(),
+        Arc::new(HttpWebhookSender::create(
+            configuration.webhooks_allow_private_addresses,
+        )),
+        Duration::from_secs(configuration.webhook_interval_seconds),
+    );
+    let storage: SharedStorage = Arc::new(LocalDiskStorage::create(&configuration.attachments_dir));
+    accounts::spawn_deleter(
+        repository.clone(),
+        storage.clone(),
+        accounts::DELETION_INTERVAL,
+    );
+    let state = AppState {
+        repository,
+        jwt_keys: Arc::new(jwt_keys),
+        attachments: AttachmentSettings {
+            storage,
+            max_bytes: configuration.max_attachment_bytes,
+        },
+        link_previews: Arc::new(HttpPreviewFetcher::create(false)),
+        accounts: AccountSettings {
+            deletion_grace: chrono::Duration::days(configuration.deletion_grace_days as i64),
+        },
+        metrics: Arc::default(),
+        feeds: FeedSettings {
+            public_url: configuration.public_url.clone(),
+        },
+        monitoring: MonitoringSettings {
+            metrics_token: configuration.metrics_token.clone(),
+        },
+    };
+
+    let app = create_router(state, &configuration.static_dir);
+
+    let sock_addr = SocketAddr::from((configuration.addr, configuration.port));
 
     log::info!("listening on http://{}", sock_addr);
 
-    axum::Server::bind(&sock_addr)
+    axum::Server::try_bind(&sock_addr)
+        .unwrap_or_else(|err| exit_with_error(format!("Unable to start server: {}", err)))
         .serve(app.into_make_service())
         .await
-        .expect("Unable to start server");
+        .unwrap_or_else(|err| exit_with_error(format!("The server stopped: {}", err)));
 }
 
-async fn hello() -> impl IntoResponse {
-    let key_pair = ES384KeyPair::generate();
-    format!(
-        "{:?}, {:?}",
-        key_pair.public_key().to_pem(),
-        key_pair.to_pem()
-    )
+pub fn create_router(state: AppState, static_dir: &str) -> Router {
+    let attachment_body_limit = state.attachments.body_limit();
+    let metrics = state.metrics.clone();
+    Router::new()
+        .route("/health", get(monitoring::health))
+        .route("/ready", get(monitoring::ready))
+        .route("/metrics", get(monitoring::metrics))
+        .merge(routes::v1(attachment_body_limit))
+        .merge(routes::legacy(attachment_body_limit))
+        .merge(axum_extra::routing::SpaRouter::new("/assets", static_dir))
+        .with_state(state)
+        .layer(
+            ServiceBuilder::new()
+                .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+                .layer(TraceLayer::new_for_http().make_span_with(telemetry::make_request_span))
+                .layer(PropagateRequestIdLayer::x_request_id())
+                .layer(middleware::from_fn_with_state(
+                    metrics,
+                    monitoring::track_requests,
+                )),
+        )
 }
 
+/// Previews of the links in the content are fetched once the brainfart is created. The webhooks
+/// hear about it then too, or once it is published if it is scheduled.
+#[utoipa::path(
+    post,
+    path = "/api/v1/brainfarts",
+    tag = "brainfarts",
+    request_body = CreateBrainfartRequest,
+    responses(
+        (status = 201, description = "The brainfart was created", body = Brainfart),
+        (status = 400, description = "Invalid data, schedule or poll", body = String)
+    ),
+    security(("bearer" = []))
+)]
 async fn create_brainfarts(
-    State(pool): State<ConnectionPool>,
+    State(repository): State<SharedRepository>,
+    State(fetcher): State<SharedPreviewFetcher>,
+    State(metrics): State<SharedMetrics>,
     claims: JwtDataHolder,
     result: Result<Json<CreateBrainfartRequest>, JsonRejection>,
 ) -> impl IntoResponse {
     let jwt_information = claims.information;
     let mastermind_id = Uuid::parse_str(&jwt_information.id).unwrap();
     match result {
-        Ok(Json(payload)) => match payload.create(&pool, &mastermind_id).await {
+        Ok(Json(payload))
+            if !CreateBrainfartRequest::validate_publish_at(
+                payload.get_publish_at(),
+                Local::now(),
+            ) =>
+        {
+            Err((
+                StatusCode::BAD_REQUEST,
+                "A brainfart can only be scheduled for the future".to_string(),
+            ))
+        }
+        Ok(Json(payload))
+            if !payload.get_poll().is_none_or(|poll| {
+                poll.validate(payload.get_publish_at().unwrap_or_else(Local::now))
+            }) =>
+        {
+            Err((
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "A poll needs 2 to {MAX_POLL_OPTIONS} different options of at most \
+                    {MAX_POLL_OPTION_CHARACTERS} characters, and has to close after it opens"
+                ),
+            ))
+        }
+        Ok(Json(payload)) => match repository.create_brainfart(&payload, &mastermind_id).await {
             Some(response) => {
+                metrics.count_brainfart_created();
+                link_previews::spawn_fetch(repository.clone(), fetcher, payload.get_content());
+                if payload.get_publish_at().is_none() {
+                    webhooks::enqueue(
+                        &repository,
+                        WebhookEvent::BrainfartCreated,
+                        &response.uuid,
+                        None,
+                    )
+                    .await;
+                }
                 let brainfart = Brainfart::create(
                     response.uuid.to_string(),
                     payload,
@@ -131,35 +380,106 @@ async fn create_brainfarts(
     }
 }
 
+/// The filter can be sent as a JSON body or, since browsers cannot send a body with a GET, in
+/// the query string, e.g. `?mode=bookmarks`.
 async fn get_some_brainfarts(
-    State(pool): State<ConnectionPool>,
-    _claims: JwtDataHolder,
+    State(repository): State<SharedRepository>,
+    claims: JwtDataHolder,
+    query: Option<Query<BrainfartFilter>>,
     result: Result<Json<BrainfartFilter>, JsonRejection>,
 ) -> impl IntoResponse {
     let filter = if let Ok(Json(payload)) = result {
         payload
+    } else if let Some(Query(payload)) = query {
+        payload
     } else {
         BrainfartFilter::default()
     };
-    if let Some(brainfarts) = get_models::get_brainfarts_using_filter(&pool, filter).await {
+    list_brainfarts(&repository, &claims.get_id(), filter).await
+}
+
+async fn list_brainfarts(
+    repository: &SharedRepository,
+    brain_id: &Uuid,
+    filter: BrainfartFilter,
+) -> Result<(StatusCode, Json<Vec<BrainfartInformation>>), (StatusCode, String)> {
+    if let Some(brainfarts) = repository
+        .get_brainfarts_using_filter(filter, brain_id)
+        .await
+    {
         Ok((StatusCode::OK, Json(brainfarts)))
     } else {
         Err((StatusCode::NOT_FOUND, "Error".to_string()))
     }
 }
 
+/// A failed reaction is forbidden when the mastermind has blocked the brain and not found when the
+/// brain cannot see the brainfart, otherwise it is reported as a server error like before.
+async fn reaction_error_responder(
+    repository: &SharedRepository,
+    brainfart_id: &Uuid,
+    brain_id: &Uuid,
+    message: &str,
+) -> (StatusCode, String) {
+    if repository
+        .is_blocked_by_mastermind(brainfart_id, brain_id)
+        .await
+    {
+        (
+            StatusCode::FORBIDDEN,
+            "The mastermind of this brainfart has blocked you".to_string(),
+        )
+    } else if repository
+        .get_brainfart(brainfart_id, brain_id)
+        .await
+        .is_none()
+    {
+        (
+            StatusCode::NOT_FOUND,
+            "There is no such brainfart".to_string(),
+        )
+    } else {
+        (StatusCode::INTERNAL_SERVER_ERROR, message.to_string())
+    }
+}
+
+fn parse_brainfart_id(brainfart_id: &str) -> Result<Uuid, (StatusCode, String)> {
+    Uuid::parse_str(brainfart_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid brainfart id".to_string()))
+}
+
 async fn register_mind_explosion(
-    State(pool): State<ConnectionPool>,
+    State(repository): State<SharedRepository>,
+    State(metrics): State<SharedMetrics>,
     claims: JwtDataHolder,
     result: Result<Json<NotifyAboutMindExplosionRequest>, JsonRejection>,
 ) -> impl IntoResponse {
     match result {
         Ok(Json(payload)) => {
-            let brainfart_id = Uuid::parse_str(&payload.brainfart_id).unwrap();
-            match payload.create(&pool, &claims.get_id()).await {
+            let brainfart_id = match parse_brainfart_id(&payload.brainfart_id) {
+                Ok(brainfart_id) => brainfart_id,
+                Err(err) => return Err(err),
+            };
+            match repository
+                .register_mind_explosion(&payload, &claims.get_id())
+                .await
+            {
                 Some(_) => {
-                    if let Some(updated_brainfart) =
-                        get_models::get_brainfart(&pool, &brainfart_id).await
+                    metrics.count_reaction(ReactionKind::Explosion);
+                    let reaction = WebhookReaction {
+                        kind: ReactionKind::Explosion,
+                        brainname: claims.information.brainname.clone(),
+                    };
+                    webhooks::enqueue(
+                        &repository,
+                        WebhookEvent::BrainfartReacted,
+                        &brainfart_id,
+                        Some(reaction),
+                    )
+                    .await;
+                    if let Some(updated_brainfart) = repository
+                        .get_brainfart(&brainfart_id, &claims.get_id())
+                        .await
                     {
                         Ok((StatusCode::CREATED, Json(updated_brainfart)))
                     } else {
@@ -169,10 +489,13 @@ async fn register_mind_explosion(
                         ))
                     }
                 }
-                None => Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "Something went wrong registering the mindexplosion".to_string(),
-                )),
+                None => Err(reaction_error_responder(
+                    &repository,
+                    &brainfart_id,
+                    &claims.get_id(),
+                    "Something went wrong registering the mindexplosion",
+                )
+                .await),
             }
         }
         Err(err) => Err(error_responders::post_error_responder(err)),
@@ -180,17 +503,37 @@ async fn register_mind_explosion(
 }
 
 async fn register_mind_implosion(
-    State(pool): State<ConnectionPool>,
+    State(repository): State<SharedRepository>,
+    State(metrics): State<SharedMetrics>,
     claims: JwtDataHolder,
     result: Result<Json<NotifyAboutMindImplosionRequest>, JsonRejection>,
 ) -> impl IntoResponse {
     match result {
         Ok(Json(payload)) => {
-            let brainfart_id = Uuid::parse_str(&payload.brainfart_id).unwrap();
-            match payload.create(&pool, &claims.get_id()).await {
+            let brainfart_id = match parse_brainfart_id(&payload.brainfart_id) {
+                Ok(brainfart_id) => brainfart_id,
+                Err(err) => return Err(err),
+            };
+            match repository
+                .register_mind_implosion(&payload, &claims.get_id())
+                .await
+            {
                 Some(_) => {
-                    if let Some(updated_brainfart) =
-                        get_models::get_brainfart(&pool, &brainfart_id).await
+                    metrics.count_reaction(ReactionKind::Implosion);
+                    let reaction = WebhookReaction {
+                        kind: ReactionKind::Implosion,
+                        brainname: claims.information.brainname.clone(),
+                    };
+                    webhooks::enqueue(
+                        &repository,
+                        WebhookEvent::BrainfartReacted,
+                        &brainfart_id,
+                        Some(reaction),
+                    )
+                    .await;
+                    if let Some(updated_brainfart) = repository
+                        .get_brainfart(&brainfart_id, &claims.get_id())
+                        .await
                     {
                         Ok((StatusCode::CREATED, Json(updated_brainfart)))
                     } else {
@@ -200,22 +543,41 @@ async fn register_mind_implosion(
                         ))
                     }
                 }
-                None => Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "Something went wrong registering the mindimplosion".to_string(),
-                )),
+                None => Err(reaction_error_responder(
+                    &repository,
+                    &brainfart_id,
+                    &claims.get_id(),
+                    "Something went wrong registering the mindimplosion",
+                )
+                .await),
             }
         }
         Err(err) => Err(error_responders::post_error_responder(err)),
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/brains",
+    tag = "brains",
+    request_body = RegisterBrainRequest,
+    responses(
+        (status = 201, description = "The token of the new brain", body = String),
+        (status = 400, description = "Invalid data", body = String),
+        (status = 500, description = "The name is taken", body = String)
+    )
+)]
 async fn register_brain(
-    State(pool): State<ConnectionPool>,
+    State(repository): State<SharedRepository>,
+    State(jwt_keys): State<Arc<JwtKeys>>,
     result: Result<Json<RegisterBrainRequest>, JsonRejection>,
 ) -> impl IntoResponse {
     match result {
-        Ok(Json(payload)) => match payload.create(&pool, &Uuid::nil()).await {
+        Ok(Json(payload)) if payload.get_name() == REMOVED_BRAIN_NAME => Err((
+            StatusCode::BAD_REQUEST,
+            "That brainname is reserved".to_string(),
+        )),
+        Ok(Json(payload)) => match repository.create_brain(&payload).await {
             Some(response) => {
                 let brain = Brain::register(
                     response.uuid.to_string(),
@@ -225,9 +587,10 @@ async fn register_brain(
                 );
                 Ok((
                     StatusCode::CREATED,
-                    Json(create_jwt(
+                    Json(jwt_keys.create_jwt(
                         Uuid::parse_str(brain.get_id()).unwrap(),
                         brain.get_name().to_string(),
+                        Role::Brain,
                     )),
                 ))
             }
@@ -240,14 +603,26 @@ async fn register_brain(
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/sessions",
+    tag = "brains",
+    request_body = ProveOwnsBrainRequest,
+    responses(
+        (status = 202, description = "A token for the brain", body = String),
+        (status = 401, description = "Unknown brain or wrong password", body = String)
+    )
+)]
 async fn show_i_own_brain(
-    State(pool): State<ConnectionPool>,
+    State(repository): State<SharedRepository>,
+    State(jwt_keys): State<Arc<JwtKeys>>,
     result: Result<Json<ProveOwnsBrainRequest>, JsonRejection>,
 ) -> impl IntoResponse {
     match result {
         Ok(Json(result)) => {
-            if let Some(id) = verify_password(&pool, &result).await {
-                let token = create_jwt(id, (&result.get_name()).to_string());
+            if let Some(id) = repository.verify_password(&result).await {
+                let role = repository.get_brain_role(&id).await.unwrap_or_default();
+                let token = jwt_keys.create_jwt(id, result.get_name().to_string(), role);
                 Ok((StatusCode::ACCEPTED, Json(token)))
             } else {
                 Ok((StatusCode::UNAUTHORIZED, Json("Unknown brain!".to_string())))
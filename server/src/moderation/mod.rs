@@ -0,0 +1,204 @@
+use axum::{
+    extract::{rejection::JsonRejection, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use shared::{
+    ModerateBrainRequest, ModerateBrainfartRequest, ReportBrainfartRequest, ReportInformation,
+    Role, SetRoleRequest,
+};
+use sqlx::types::Uuid;
+
+use crate::{
+    authentication::{
+        CanModerateBrainfarts, CanReviewReports, CanSuspendBrains, JwtDataHolder, RequiresAdmin,
+        RequiresPermission,
+    },
+    database::repository::SharedRepository,
+    error_responders,
+};
+
+fn parse_brainfart_id(brainfart_id: &str) -> Result<Uuid, (StatusCode, String)> {
+    Uuid::parse_str(brainfart_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid brainfart id".to_string()))
+}
+
+pub async fn report_brainfart(
+    State(repository): State<SharedRepository>,
+    claims: JwtDataHolder,
+    result: Result<Json<ReportBrainfartRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    let payload = match result {
+        Ok(Json(payload)) => payload,
+        Err(err) => return Err(error_responders::post_error_responder(err)),
+    };
+    parse_brainfart_id(&payload.brainfart_id)?;
+    if !ReportBrainfartRequest::validate(&payload.reason) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "A report needs a reason".to_string(),
+        ));
+    }
+    match repository
+        .report_brainfart(&payload, &claims.get_id())
+        .await
+    {
+        Some(response) => Ok((
+            StatusCode::CREATED,
+            Json(ReportInformation {
+                reporter_name: claims.information.brainname,
+                reason: payload.reason,
+                reportdate: response.birthdate,
+            }),
+        )),
+        None => Err((
+            StatusCode::NOT_FOUND,
+            "There is no such brainfart to report".to_string(),
+        )),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/reports",
+    tag = "moderation",
+    responses(
+        (status = 200, description = "Brainfarts with open reports", body = [ReportedBrainfart]),
+        (status = 403, description = "You may not review reports", body = String)
+    ),
+    security(("bearer" = []))
+)]
+pub async fn get_reports(
+    State(repository): State<SharedRepository>,
+    _: RequiresPermission<CanReviewReports>,
+) -> impl IntoResponse {
+    if let Some(reported_brainfarts) = repository.get_reported_brainfarts().await {
+        Ok((StatusCode::OK, Json(reported_brainfarts)))
+    } else {
+        Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Something went wrong reading the reports".to_string(),
+        ))
+    }
+}
+
+async fn set_brainfart_hidden(
+    repository: SharedRepository,
+    result: Result<Json<ModerateBrainfartRequest>, JsonRejection>,
+    hidden: bool,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let payload = match result {
+        Ok(Json(payload)) => payload,
+        Err(err) => return Err(error_responders::post_error_responder(err)),
+    };
+    let brainfart_id = parse_brainfart_id(&payload.brainfart_id)?;
+    if !repository.set_brainfart_hidden(&brainfart_id, hidden).await {
+        return Err((
+            StatusCode::NOT_FOUND,
+            "There is no such brainfart".to_string(),
+        ));
+    }
+    if let Some(brainfart) = repository.get_any_brainfart(&brainfart_id).await {
+        Ok((StatusCode::OK, Json(brainfart)))
+    } else {
+        Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Something went wrong moderating the brainfart".to_string(),
+        ))
+    }
+}
+
+pub async fn hide_brainfart(
+    State(repository): State<SharedRepository>,
+    _: RequiresPermission<CanModerateBrainfarts>,
+    result: Result<Json<ModerateBrainfartRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    set_brainfart_hidden(repository, result, true).await
+}
+
+/// Puts a hidden brainfart back in the feed. Restoring a brainfart that was never hidden
+/// dismisses the reports against it.
+pub async fn restore_brainfart(
+    State(repository): State<SharedRepository>,
+    _: RequiresPermission<CanModerateBrainfarts>,
+    result: Result<Json<ModerateBrainfartRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    set_brainfart_hidden(repository, result, false).await
+}
+
+async fn set_brain_suspended(
+    repository: SharedRepository,
+    claims: JwtDataHolder,
+    result: Result<Json<ModerateBrainRequest>, JsonRejection>,
+    suspended: bool,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let payload = match result {
+        Ok(Json(payload)) => payload,
+        Err(err) => return Err(error_responders::post_error_responder(err)),
+    };
+    if suspended && payload.brainname == claims.information.brainname {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Admins cannot suspend themselves".to_string(),
+        ));
+    }
+    if repository
+        .set_brain_disabled(&payload.brainname, suspended)
+        .await
+    {
+        Ok((StatusCode::OK, Json(payload.brainname)))
+    } else {
+        Err((
+            StatusCode::NOT_FOUND,
+            format!("There is no brain called {}", payload.brainname),
+        ))
+    }
+}
+
+/// Suspended brains can no longer log in or use the tokens they already have, the same as brains
+/// disabled from the command line.
+pub async fn suspend_brain(
+    State(repository): State<SharedRepository>,
+    RequiresPermission { claims, .. }: RequiresPermission<CanSuspendBrains>,
+    result: Result<Json<ModerateBrainRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    set_brain_suspended(repository, claims, result, true).await
+}
+
+pub async fn unsuspend_brain(
+    State(repository): State<SharedRepository>,
+    RequiresPermission { claims, .. }: RequiresPermission<CanSuspendBrains>,
+    result: Result<Json<ModerateBrainRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    set_brain_suspended(repository, claims, result, false).await
+}
+
+/// Roles decide the permissions, so only admins may hand them out.
+pub async fn set_role(
+    State(repository): State<SharedRepository>,
+    RequiresAdmin(claims): RequiresAdmin,
+    result: Result<Json<SetRoleRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    let payload = match result {
+        Ok(Json(payload)) => payload,
+        Err(err) => return Err(error_responders::post_error_responder(err)),
+    };
+    if payload.role != Role::Admin && payload.brainname == claims.information.brainname {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Admins cannot take away their own role".to_string(),
+        ));
+    }
+    if repository
+        .set_brain_role(&payload.brainname, payload.role)
+        .await
+    {
+        Ok((StatusCode::OK, Json(payload.brainname)))
+    } else {
+        Err((
+            StatusCode::NOT_FOUND,
+            format!("There is no brain called {}", payload.brainname),
+        ))
+    }
+}
@@ -0,0 +1,255 @@
+//! Health checks and Prometheus metrics, for whatever keeps an eye on the server.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{MatchedPath, State},
+    headers::{authorization::Bearer, Authorization},
+    http::{header::CONTENT_TYPE, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    TypedHeader,
+};
+use shared::ReactionKind;
+
+use crate::database::repository::SharedRepository;
+
+/// The upper bounds of the request duration histogram, in seconds.
+const DURATION_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+pub type SharedMetrics = Arc<Metrics>;
+
+#[derive(Clone, Default)]
+pub struct MonitoringSettings {
+    /// The bearer token `/metrics` asks for. Without one the metrics are not served at all.
+    pub metrics_token: Option<String>,
+}
+
+#[derive(Default)]
+struct DurationHistogram {
+    /// How many requests took at most the bound of the bucket with the same index.
+    buckets: [u64; DURATION_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl DurationHistogram {
+    fn observe(&mut self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        for (bucket, bound) in self.buckets.iter_mut().zip(DURATION_BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+        self.sum += seconds;
+        self.count += 1;
+    }
+}
+
+/// What the server has done since it started. Requests are told apart by the route they matched
+/// rather than by their path, so there is one series per route and not one per brainfart.
+#[derive(Default)]
+pub struct Metrics {
+    /// By method, route and status.
+    requests: Mutex<BTreeMap<(String, String, u16), u64>>,
+    /// By method and route.
+    durations: Mutex<BTreeMap<(String, String), DurationHistogram>>,
+    brainfarts_created: AtomicU64,
+    explosions: AtomicU64,
+    implosions: AtomicU64,
+}
+
+impl Metrics {
+    pub fn record_request(&self, method: &str, route: &str, status: u16, duration: Duration) {
+        *self
+            .requests
+            .lock()
+            .unwrap()
+            .entry((method.to_string(), route.to_string(), status))
+            .or_default() += 1;
+        self.durations
+            .lock()
+            .unwrap()
+            .entry((method.to_string(), route.to_string()))
+            .or_default()
+            .observe(duration);
+    }
+
+    pub fn count_brainfart_created(&self) {
+        self.brainfarts_created.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn count_reaction(&self, kind: ReactionKind) {
+        match kind {
+            ReactionKind::Explosion => &self.explosions,
+            ReactionKind::Implosion => &self.implosions,
+        }
+        .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The metrics in the Prometheus text format, with the pool of the repository if it has one.
+    pub fn to_prometheus(&self, repository: &SharedRepository) -> String {
+        let mut text = String::new();
+        text.push_str(
+            "# HELP brains_http_requests_total Requests answered, by method, route and status.\n\
+            # TYPE brains_http_requests_total counter\n",
+        );
+        for ((method, route, status), count) in self.requests.lock().unwrap().iter() {
+            writeln!(
+                text,
+                "brains_http_requests_total{{method=\"{}\",route=\"{}\",status=\"{}\"}} {}",
+                method,
+                escape(route),
+                status,
+                count
+            )
+            .unwrap();
+        }
+        text.push_str(
+            "# HELP brains_http_request_duration_seconds How long requests took to answer, by method and route.\n\
+            # TYPE brains_http_request_duration_seconds histogram\n",
+        );
+        for ((method, route), histogram) in self.durations.lock().unwrap().iter() {
+            let labels = format!("method=\"{}\",route=\"{}\"", method, escape(route));
+            for (bound, count) in DURATION_BUCKETS.iter().zip(histogram.buckets) {
+                writeln!(
+                    text,
+                    "brains_http_request_duration_seconds_bucket{{{},le=\"{}\"}} {}",
+                    labels, bound, count
+                )
+                .unwrap();
+            }
+            writeln!(
+                text,
+                "brains_http_request_duration_seconds_bucket{{{},le=\"+Inf\"}} {}\n\
+                brains_http_request_duration_seconds_sum{{{}}} {}\n\
+                brains_http_request_duration_seconds_count{{{}}} {}",
+                labels, histogram.count, labels, histogram.sum, labels, histogram.count
+            )
+            .unwrap();
+        }
+        if let Some(pool) = repository.get_pool_status() {
+            writeln!(
+                text,
+                "# HELP brains_db_connections Open database connections, by whether they are in use.\n\
+                # TYPE brains_db_connections gauge\n\
+                brains_db_connections{{state=\"active\"}} {}\n\
+                brains_db_connections{{state=\"idle\"}} {}",
+                pool.connections.saturating_sub(pool.idle_connections),
+                pool.idle_connections
+            )
+            .unwrap();
+        }
+        writeln!(
+            text,
+            "# HELP brains_brainfarts_created_total Brainfarts created, scheduled ones included.\n\
+            # TYPE brains_brainfarts_created_total counter\n\
+            brains_brainfarts_created_total {}\n\
+            # HELP brains_reactions_total Reactions to brainfarts, by kind.\n\
+            # TYPE brains_reactions_total counter\n\
+            brains_reactions_total{{kind=\"explosion\"}} {}\n\
+            brains_reactions_total{{kind=\"implosion\"}} {}",
+            self.brainfarts_created.load(Ordering::Relaxed),
+            self.explosions.load(Ordering::Relaxed),
+            self.implosions.load(Ordering::Relaxed)
+        )
+        .unwrap();
+        text
+    }
+}
+
+fn escape(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Counts and times every request. Requests that matched no route are put together as
+/// `unmatched`.
+pub async fn track_requests<B>(
+    State(metrics): State<SharedMetrics>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+    let started = Instant::now();
+    let response = next.run(request).await;
+    metrics.record_request(
+        &method,
+        &route,
+        response.status().as_u16(),
+        started.elapsed(),
+    );
+    response
+}
+
+/// Answers as long as the server runs, whether the database does or not.
+pub async fn health() -> impl IntoResponse {
+    "ok"
+}
+
+/// Ready to take requests once the database answers.
+pub async fn ready(State(repository): State<SharedRepository>) -> impl IntoResponse {
+    if repository.is_ready().await {
+        (StatusCode::OK, "ready".to_string())
+    } else {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "The database does not answer".to_string(),
+        )
+    }
+}
+
+/// Compares every byte, so that how long it takes tells nothing about the token.
+fn same_token(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+/// The metrics tell what every route is used for, so they take the configured metrics token
+/// rather than being open to anyone who reaches the server.
+pub async fn metrics(
+    State(repository): State<SharedRepository>,
+    State(metrics): State<SharedMetrics>,
+    State(settings): State<MonitoringSettings>,
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let Some(metrics_token) = &settings.metrics_token else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            "There is no metrics_token, so the metrics are not served".to_string(),
+        ));
+    };
+    match authorization {
+        Some(TypedHeader(Authorization(bearer))) if same_token(bearer.token(), metrics_token) => {
+            Ok((
+                [(CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
+                metrics.to_prometheus(&repository),
+            ))
+        }
+        _ => Err((
+            StatusCode::UNAUTHORIZED,
+            "The metrics need the metrics token".to_string(),
+        )),
+    }
+}
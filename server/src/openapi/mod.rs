@@ -0,0 +1,191 @@
+//! The OpenAPI document of the versioned API, generated from the handlers and the `shared` types.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::Path,
+    http::{header::CONTENT_TYPE, StatusCode},
+    response::{Html, IntoResponse},
+    Json,
+};
+use shared::{
+    routes, AttachmentInformation, BrainDeletion, BrainExport, BrainInformation, BrainRelation,
+    BrainRelationInformation, BrainRelationRequest, Brainfart, BrainfartDraft, BrainfartFilterMode,
+    BrainfartInformation, BrainfartOrder, CreateBrainfartRequest, CreateWebhookRequest,
+    DeleteBrainRequest, DeletionMode, DeliveryStatus, ExportedBrain, ExportedBrainfart,
+    ExportedReaction, ExportedView, FeedFormat, FeedToken, Leaderboard, LeaderboardBrainfart,
+    LeaderboardMastermind, LeaderboardWindow, LinkPreview, PollInformation, PollOptionResult,
+    PollRequest, ProveOwnsBrainRequest, ReactionKind, ReactionRequest, RebrainfartRequest,
+    RegisterBrainRequest, ReportBrainfartRequest, ReportInformation, ReportedBrainfart, Role,
+    SaveDraftRequest, SetRoleRequest, VoteRequest, WebhookBrainfart, WebhookDeliveryInformation,
+    WebhookEvent, WebhookInformation, WebhookPayload, WebhookReaction,
+};
+use utoipa::{
+    openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Brains",
+        description = "Brainfarts, the brains that fart them and the minds they blow."
+    ),
+    paths(
+        crate::register_brain,
+        crate::show_i_own_brain,
+        crate::create_brainfarts,
+        crate::routes::get_brainfarts,
+        crate::routes::get_brainfart,
+        crate::routes::react,
+        crate::routes::add_bookmark,
+        crate::routes::remove_bookmark,
+        crate::routes::report_brainfart,
+        crate::routes::vote,
+        crate::routes::rebrainfart,
+        crate::relations::get_relations,
+        crate::relations::add_relation,
+        crate::routes::remove_relation,
+        crate::attachments::add_attachments,
+        crate::attachments::get_attachment,
+        crate::attachments::get_thumbnail,
+        crate::leaderboards::get_leaderboard,
+        crate::drafts::get_draft,
+        crate::drafts::save_draft,
+        crate::drafts::delete_draft,
+        crate::exports::get_export,
+        crate::accounts::get_deletion,
+        crate::accounts::request_deletion,
+        crate::accounts::cancel_deletion,
+        crate::webhooks::get_webhooks,
+        crate::webhooks::create_webhook,
+        crate::webhooks::delete_webhook,
+        crate::webhooks::get_webhook_deliveries,
+        crate::feeds::get_feed,
+        crate::feeds::get_brain_feed,
+        crate::feeds::get_tag_feed,
+        crate::feeds::create_feed_token,
+        crate::feeds::revoke_feed_tokens,
+        crate::moderation::get_reports,
+        crate::routes::hide_brainfart,
+        crate::routes::restore_brainfart,
+        crate::routes::suspend_brain,
+        crate::routes::unsuspend_brain,
+        crate::routes::set_role,
+    ),
+    components(schemas(
+        AttachmentInformation,
+        BrainDeletion,
+        BrainExport,
+        BrainInformation,
+        BrainRelation,
+        BrainRelationInformation,
+        BrainRelationRequest,
+        Brainfart,
+        BrainfartDraft,
+        BrainfartFilterMode,
+        BrainfartInformation,
+        BrainfartOrder,
+        CreateBrainfartRequest,
+        CreateWebhookRequest,
+        DeleteBrainRequest,
+        DeletionMode,
+        DeliveryStatus,
+        ExportedBrain,
+        ExportedBrainfart,
+        ExportedReaction,
+        ExportedView,
+        FeedFormat,
+        FeedToken,
+        Leaderboard,
+        LeaderboardBrainfart,
+        LeaderboardMastermind,
+        LeaderboardWindow,
+        LinkPreview,
+        PollInformation,
+        PollOptionResult,
+        PollRequest,
+        ProveOwnsBrainRequest,
+        ReactionKind,
+        ReactionRequest,
+        RebrainfartRequest,
+        RegisterBrainRequest,
+        ReportBrainfartRequest,
+        ReportInformation,
+        ReportedBrainfart,
+        Role,
+        SaveDraftRequest,
+        SetRoleRequest,
+        VoteRequest,
+        WebhookBrainfart,
+        WebhookDeliveryInformation,
+        WebhookEvent,
+        WebhookInformation,
+        WebhookPayload,
+        WebhookReaction,
+    )),
+    modifiers(&BearerToken)
+)]
+pub struct ApiDoc;
+
+/// The tokens handed out by `/api/v1/brains` and `/api/v1/sessions`, and the feed tokens handed
+/// out by `/api/v1/feeds/tokens`.
+struct BearerToken;
+
+impl Modify for BearerToken {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+        components.add_security_scheme(
+            "feed_token",
+            SecurityScheme::ApiKey(ApiKey::Query(ApiKeyValue::new("token"))),
+        );
+    }
+}
+
+pub async fn get_openapi() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+/// Swagger UI is embedded in the server and served from next to the page, so the docs work
+/// without reaching out to anyone else.
+pub async fn get_docs() -> Html<String> {
+    Html(format!(
+        r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <title>Brains API</title>
+  <link rel="stylesheet" href="{docs}/swagger-ui.css">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="{docs}/swagger-ui-bundle.js"></script>
+  <script>
+    window.onload = () => {{
+      window.ui = SwaggerUIBundle({{ url: "{openapi}", dom_id: "#swagger-ui" }});
+    }};
+  </script>
+</body>
+</html>"##,
+        docs = routes::DOCS,
+        openapi = routes::OPENAPI
+    ))
+}
+
+pub async fn get_docs_file(Path(file): Path<String>) -> impl IntoResponse {
+    let config = Arc::new(utoipa_swagger_ui::Config::from(routes::OPENAPI));
+    match utoipa_swagger_ui::serve(&file, config) {
+        Ok(Some(file)) => Ok(([(CONTENT_TYPE, file.content_type)], file.bytes.into_owned())),
+        Ok(None) => Err((StatusCode::NOT_FOUND, "There is no such file".to_string())),
+        Err(err) => Err((StatusCode::INTERNAL_SERVER_ERROR, err.to_string())),
+    }
+}
@@ -0,0 +1,111 @@
+use axum::{
+    extract::{rejection::JsonRejection, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::Local;
+use shared::{PollInformation, VoteRequest};
+use sqlx::types::Uuid;
+
+use crate::{
+    authentication::JwtDataHolder, database::repository::SharedRepository, error_responders,
+};
+
+fn already_voted() -> (StatusCode, String) {
+    (
+        StatusCode::CONFLICT,
+        "You have already voted in this poll".to_string(),
+    )
+}
+
+/// The options have to exist and be different, and a single choice poll takes exactly one.
+fn validate_options(poll: &PollInformation, options: &[usize]) -> Result<(), (StatusCode, String)> {
+    if options.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Choose at least one option".to_string(),
+        ));
+    }
+    if !poll.multiple_choice && options.len() > 1 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Only one option can be chosen in this poll".to_string(),
+        ));
+    }
+    let unknown = options.iter().any(|option| *option >= poll.options.len());
+    let repeated = options
+        .iter()
+        .enumerate()
+        .any(|(index, option)| options[..index].contains(option));
+    if unknown || repeated {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "The poll has no such options".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// A brain votes once in each poll. Answers with the brainfart including the results, which
+/// the frontend shows from then on.
+pub async fn vote(
+    State(repository): State<SharedRepository>,
+    claims: JwtDataHolder,
+    result: Result<Json<VoteRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    let payload = match result {
+        Ok(Json(payload)) => payload,
+        Err(err) => return Err(error_responders::post_error_responder(err)),
+    };
+    let brainfart_id = Uuid::parse_str(&payload.brainfart_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid brainfart id".to_string()))?;
+    let brain_id = claims.get_id();
+    let poll = repository
+        .get_brainfart(&brainfart_id, &brain_id)
+        .await
+        .and_then(|brainfart| brainfart.poll)
+        .ok_or((StatusCode::NOT_FOUND, "There is no such poll".to_string()))?;
+    if repository
+        .is_blocked_by_mastermind(&brainfart_id, &brain_id)
+        .await
+    {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "The mastermind of this brainfart has blocked you".to_string(),
+        ));
+    }
+    if poll.is_closed(Local::now()) {
+        return Err((StatusCode::BAD_REQUEST, "This poll is closed".to_string()));
+    }
+    if poll.has_voted() {
+        return Err(already_voted());
+    }
+    validate_options(&poll, &payload.options)?;
+    if repository
+        .add_poll_vote(&brainfart_id, &brain_id, &payload.options)
+        .await
+        .is_none()
+    {
+        // Another request from the same brain may have voted in between.
+        return Err(
+            match repository.get_brainfart(&brainfart_id, &brain_id).await {
+                Some(brainfart) if brainfart.poll.as_ref().is_some_and(|poll| poll.has_voted()) => {
+                    already_voted()
+                }
+                _ => (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Something went wrong saving the vote".to_string(),
+                ),
+            },
+        );
+    }
+    repository
+        .get_brainfart(&brainfart_id, &brain_id)
+        .await
+        .map(|brainfart| (StatusCode::CREATED, Json(brainfart)))
+        .ok_or((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Something went wrong reading the brainfart".to_string(),
+        ))
+}
@@ -0,0 +1,34 @@
+use chrono::{DateTime, Local};
+
+/// What a view is worth compared to an explosion.
+pub const VIEW_WEIGHT: f64 = 0.1;
+/// Added to the age, so that brand new brainfarts are not divided by almost nothing.
+pub const AGE_OFFSET_HOURS: f64 = 2.0;
+/// How quickly brainfarts cool down with age.
+pub const GRAVITY: f64 = 1.5;
+
+/// The score of the hot ordering. Postgres computes the same score in SQL from the constants
+/// above, the other backends use this function.
+pub fn hot_score(explosions: i64, implosions: i64, views: i64, age_hours: f64) -> f64 {
+    ((explosions - implosions) as f64 + VIEW_WEIGHT * views as f64)
+        / (age_hours.max(0.0) + AGE_OFFSET_HOURS).powf(GRAVITY)
+}
+
+pub fn age_in_hours(birthdate: DateTime<Local>, now: DateTime<Local>) -> f64 {
+    (now - birthdate).num_milliseconds() as f64 / 3_600_000.0
+}
+
+/// Hottest first. Equal scores keep the newest first, so the order does not depend on how
+/// the items came in.
+pub fn sort_by_hotness<T>(
+    items: &mut [T],
+    score_and_birthdate: impl Fn(&T) -> (f64, DateTime<Local>),
+) {
+    items.sort_by(|first, second| {
+        let (first_score, first_birthdate) = score_and_birthdate(first);
+        let (second_score, second_birthdate) = score_and_birthdate(second);
+        second_score
+            .total_cmp(&first_score)
+            .then(second_birthdate.cmp(&first_birthdate))
+    });
+}
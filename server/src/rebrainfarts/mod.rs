@@ -0,0 +1,79 @@
+use axum::{
+    extract::{rejection::JsonRejection, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use shared::{RebrainfartRequest, WebhookEvent};
+use sqlx::types::Uuid;
+
+use crate::{
+    authentication::JwtDataHolder, database::repository::SharedRepository, error_responders,
+    webhooks,
+};
+
+fn no_such_brainfart() -> (StatusCode, String) {
+    (
+        StatusCode::NOT_FOUND,
+        "There is no such brainfart".to_string(),
+    )
+}
+
+/// Rebrainfarting a plain rebrainfart shares its original instead, so rebrainfarts never
+/// stack. Answers with the new brainfart, including the original it embeds.
+pub async fn rebrainfart(
+    State(repository): State<SharedRepository>,
+    claims: JwtDataHolder,
+    result: Result<Json<RebrainfartRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    let payload = match result {
+        Ok(Json(payload)) => payload,
+        Err(err) => return Err(error_responders::post_error_responder(err)),
+    };
+    let brainfart_id = Uuid::parse_str(&payload.brainfart_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid brainfart id".to_string()))?;
+    let brain_id = claims.get_id();
+    let shared = repository
+        .get_brainfart(&brainfart_id, &brain_id)
+        .await
+        .ok_or_else(no_such_brainfart)?;
+    let original = match shared.original {
+        Some(original) if shared.is_plain_rebrainfart() => *original,
+        _ => shared,
+    };
+    let original_id = Uuid::parse_str(&original.id).map_err(|_| no_such_brainfart())?;
+    if repository
+        .is_blocked_by_mastermind(&original_id, &brain_id)
+        .await
+    {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "The mastermind of this brainfart has blocked you".to_string(),
+        ));
+    }
+    if payload.commentary.trim().is_empty() && original.rebrainfarted_by_me {
+        return Err((
+            StatusCode::CONFLICT,
+            "You have already rebrainfarted this brainfart".to_string(),
+        ));
+    }
+    let response = repository
+        .create_rebrainfart(&payload, &original_id, &brain_id)
+        .await
+        .ok_or_else(no_such_brainfart)?;
+    webhooks::enqueue(
+        &repository,
+        WebhookEvent::BrainfartCreated,
+        &response.uuid,
+        None,
+    )
+    .await;
+    repository
+        .get_brainfart(&response.uuid, &brain_id)
+        .await
+        .map(|brainfart| (StatusCode::CREATED, Json(brainfart)))
+        .ok_or((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Something went wrong reading the brainfart".to_string(),
+        ))
+}
@@ -0,0 +1,109 @@
+use axum::{
+    extract::{rejection::JsonRejection, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use shared::{BrainRelationInformation, BrainRelationRequest};
+use sqlx::types::Uuid;
+
+use crate::{
+    authentication::JwtDataHolder, database::repository::SharedRepository, error_responders,
+};
+
+async fn relations_of(
+    repository: &SharedRepository,
+    brain_id: &Uuid,
+) -> Result<Json<Vec<BrainRelationInformation>>, (StatusCode, String)> {
+    repository
+        .get_brain_relations(brain_id)
+        .await
+        .map(Json)
+        .ok_or((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Something went wrong reading the muted and blocked brains".to_string(),
+        ))
+}
+
+/// Every brain the requester has muted or blocked.
+#[utoipa::path(
+    get,
+    path = "/api/v1/relations",
+    tag = "relations",
+    responses(
+        (status = 200, description = "The brains you have muted or blocked", body = [BrainRelationInformation])
+    ),
+    security(("bearer" = []))
+)]
+pub async fn get_relations(
+    State(repository): State<SharedRepository>,
+    claims: JwtDataHolder,
+) -> impl IntoResponse {
+    relations_of(&repository, &claims.get_id()).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/relations",
+    tag = "relations",
+    request_body = BrainRelationRequest,
+    responses(
+        (status = 201, description = "All your relations", body = [BrainRelationInformation]),
+        (status = 400, description = "Invalid brain name, or it is yourself", body = String),
+        (status = 404, description = "There is no such brain", body = String)
+    ),
+    security(("bearer" = []))
+)]
+pub async fn add_relation(
+    State(repository): State<SharedRepository>,
+    claims: JwtDataHolder,
+    result: Result<Json<BrainRelationRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    let payload = match result {
+        Ok(Json(payload)) => payload,
+        Err(err) => return Err(error_responders::post_error_responder(err)),
+    };
+    if !BrainRelationRequest::validate(&payload.brainname) {
+        return Err((StatusCode::BAD_REQUEST, "Invalid data".to_string()));
+    }
+    if payload.brainname == claims.information.brainname {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("You cannot {} yourself", payload.relation),
+        ));
+    }
+    let brain_id = claims.get_id();
+    if repository
+        .add_brain_relation(&payload, &brain_id)
+        .await
+        .is_none()
+    {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("There is no brain called {}", payload.brainname),
+        ));
+    }
+    Ok((
+        StatusCode::CREATED,
+        relations_of(&repository, &brain_id).await?,
+    ))
+}
+
+pub async fn remove_relation(
+    State(repository): State<SharedRepository>,
+    claims: JwtDataHolder,
+    result: Result<Json<BrainRelationRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    let payload = match result {
+        Ok(Json(payload)) => payload,
+        Err(err) => return Err(error_responders::post_error_responder(err)),
+    };
+    let brain_id = claims.get_id();
+    if !repository.remove_brain_relation(&payload, &brain_id).await {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("You did not {} {}", payload.relation, payload.brainname),
+        ));
+    }
+    Ok((StatusCode::OK, relations_of(&repository, &brain_id).await?))
+}
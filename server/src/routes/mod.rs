@@ -0,0 +1,581 @@
+//! The versioned, resource oriented API, and the verb named paths it replaces.
+
+use axum::{
+    extract::{
+        rejection::{JsonRejection, QueryRejection},
+        DefaultBodyLimit, Path, Query, State,
+    },
+    http::{HeaderValue, StatusCode},
+    middleware,
+    response::{IntoResponse, Response},
+    routing::{delete, get, post, put, MethodRouter},
+    Json, Router,
+};
+use shared::{
+    routes, BookmarkRequest, BrainRelation, BrainRelationRequest, BrainfartFilter,
+    ModerateBrainRequest, ModerateBrainfartRequest, NotifyAboutMindExplosionRequest,
+    NotifyAboutMindImplosionRequest, ReactionKind, ReactionRequest, RebrainfartRequest,
+    ReportBrainfartRequest, SetRoleRequest, VoteRequest,
+};
+use sqlx::types::Uuid;
+
+use crate::{
+    accounts, attachments,
+    authentication::{
+        CanModerateBrainfarts, CanSuspendBrains, JwtDataHolder, RequiresAdmin, RequiresPermission,
+    },
+    bookmarks, create_brainfarts,
+    database::repository::SharedRepository,
+    drafts, error_responders, exports, feeds, get_some_brainfarts, leaderboards, list_brainfarts,
+    moderation,
+    monitoring::SharedMetrics,
+    openapi, polls, rebrainfarts, register_brain, register_mind_explosion, register_mind_implosion,
+    relations, show_i_own_brain, webhooks, AppState,
+};
+
+/// The API by path, with one entry for each of `routes::ALL`. The OpenAPI test goes over this
+/// list, so a route cannot be served without being documented.
+pub fn v1_routes(attachment_body_limit: usize) -> Vec<(&'static str, MethodRouter<AppState>)> {
+    vec![
+        (routes::BRAINS, post(register_brain)),
+        (routes::SESSIONS, post(show_i_own_brain)),
+        (
+            routes::BRAINFARTS,
+            get(get_brainfarts).post(create_brainfarts),
+        ),
+        (routes::BRAINFART, get(get_brainfart)),
+        (routes::BRAINFART_REACTIONS, post(react)),
+        (
+            routes::BRAINFART_BOOKMARK,
+            put(add_bookmark).delete(remove_bookmark),
+        ),
+        (routes::BRAINFART_REPORTS, post(report_brainfart)),
+        (routes::BRAINFART_VOTES, post(vote)),
+        (routes::BRAINFART_REBRAINFARTS, post(rebrainfart)),
+        (
+            routes::RELATIONS,
+            get(relations::get_relations).post(relations::add_relation),
+        ),
+        (routes::RELATION, delete(remove_relation)),
+        (
+            routes::ATTACHMENTS,
+            post(attachments::add_attachments).layer(DefaultBodyLimit::max(attachment_body_limit)),
+        ),
+        (routes::ATTACHMENT, get(attachments::get_attachment)),
+        (
+            routes::ATTACHMENT_THUMBNAIL,
+            get(attachments::get_thumbnail),
+        ),
+        (routes::LEADERBOARD, get(leaderboards::get_leaderboard)),
+        (
+            routes::DRAFT,
+            get(drafts::get_draft)
+                .put(drafts::save_draft)
+                .delete(drafts::delete_draft),
+        ),
+        (routes::EXPORT, get(exports::get_export)),
+        (
+            routes::ACCOUNT_DELETION,
+            get(accounts::get_deletion)
+                .put(accounts::request_deletion)
+                .delete(accounts::cancel_deletion),
+        ),
+        (
+            routes::WEBHOOKS,
+            get(webhooks::get_webhooks).post(webhooks::create_webhook),
+        ),
+        (routes::WEBHOOK, delete(webhooks::delete_webhook)),
+        (
+            routes::WEBHOOK_DELIVERIES,
+            get(webhooks::get_webhook_deliveries),
+        ),
+        (routes::FEED, get(feeds::get_feed)),
+        (routes::BRAIN_FEED, get(feeds::get_brain_feed)),
+        (routes::TAG_FEED, get(feeds::get_tag_feed)),
+        (
+            routes::FEED_TOKENS,
+            post(feeds::create_feed_token).delete(feeds::revoke_feed_tokens),
+        ),
+        (routes::ADMIN_REPORTS, get(moderation::get_reports)),
+        (
+            routes::ADMIN_HIDDEN_BRAINFART,
+            put(hide_brainfart).delete(restore_brainfart),
+        ),
+        (
+            routes::ADMIN_BRAIN_SUSPENSION,
+            put(suspend_brain).delete(unsuspend_brain),
+        ),
+        (routes::ADMIN_BRAIN_ROLE, put(set_role)),
+    ]
+}
+
+pub fn v1(attachment_body_limit: usize) -> Router<AppState> {
+    v1_routes(attachment_body_limit)
+        .into_iter()
+        .fold(Router::new(), |router, (path, method_router)| {
+            router.route(path, method_router)
+        })
+        .route(routes::OPENAPI, get(openapi::get_openapi))
+        .route(routes::DOCS, get(openapi::get_docs))
+        .route(routes::DOCS_FILE, get(openapi::get_docs_file))
+}
+
+/// The paths from before `/api/v1`. They answer like they always did, with a `Deprecation`
+/// header so clients can tell they should move on.
+pub fn legacy(attachment_body_limit: usize) -> Router<AppState> {
+    Router::new()
+        .route("/api/createbrainfart", post(create_brainfarts))
+        .route("/api/registerbrain", post(register_brain))
+        .route("/api/getbrainfarts", get(get_some_brainfarts))
+        .route("/api/loginasbrain", post(show_i_own_brain))
+        .route("/api/registermindexplosion", post(register_mind_explosion))
+        .route("/api/registermindimplosion", post(register_mind_implosion))
+        .route("/api/reportbrainfart", post(moderation::report_brainfart))
+        .route("/api/admin/reports", get(moderation::get_reports))
+        .route("/api/admin/hidebrainfart", post(moderation::hide_brainfart))
+        .route(
+            "/api/admin/restorebrainfart",
+            post(moderation::restore_brainfart),
+        )
+        .route("/api/admin/suspendbrain", post(moderation::suspend_brain))
+        .route(
+            "/api/admin/unsuspendbrain",
+            post(moderation::unsuspend_brain),
+        )
+        .route("/api/admin/setrole", post(moderation::set_role))
+        .route("/api/getrelations", get(relations::get_relations))
+        .route("/api/addrelation", post(relations::add_relation))
+        .route("/api/removerelation", post(relations::remove_relation))
+        .route("/api/addbookmark", post(bookmarks::add_bookmark))
+        .route("/api/removebookmark", post(bookmarks::remove_bookmark))
+        .route("/api/vote", post(polls::vote))
+        .route("/api/rebrainfart", post(rebrainfarts::rebrainfart))
+        .route(
+            "/api/addattachments",
+            post(attachments::add_attachments).layer(DefaultBodyLimit::max(attachment_body_limit)),
+        )
+        .route(
+            "/api/attachment/:attachment_id",
+            get(attachments::get_attachment),
+        )
+        .route(
+            "/api/attachment/:attachment_id/thumbnail",
+            get(attachments::get_thumbnail),
+        )
+        .route("/api/leaderboard", get(leaderboards::get_leaderboard))
+        .route("/api/getdraft", get(drafts::get_draft))
+        .route("/api/savedraft", post(drafts::save_draft))
+        .route("/api/deletedraft", post(drafts::delete_draft))
+        .layer(middleware::map_response(mark_as_deprecated))
+}
+
+async fn mark_as_deprecated(mut response: Response) -> Response {
+    response
+        .headers_mut()
+        .insert("deprecation", HeaderValue::from_static("true"));
+    response
+}
+
+/// The filter is only read from the query string, e.g. `?mode=bookmarks&order=hot`.
+#[utoipa::path(
+    get,
+    path = "/api/v1/brainfarts",
+    tag = "brainfarts",
+    params(BrainfartFilter),
+    responses(
+        (status = 200, description = "The feed", body = [BrainfartInformation]),
+        (status = 400, description = "Invalid filter", body = String)
+    ),
+    security(("bearer" = []))
+)]
+async fn get_brainfarts(
+    State(repository): State<SharedRepository>,
+    claims: JwtDataHolder,
+    query: Result<Query<BrainfartFilter>, QueryRejection>,
+) -> impl IntoResponse {
+    match query {
+        Ok(Query(filter)) => list_brainfarts(&repository, &claims.get_id(), filter)
+            .await
+            .into_response(),
+        Err(_) => (StatusCode::BAD_REQUEST, "Invalid filter".to_string()).into_response(),
+    }
+}
+
+/// Opening a brainfart is what counts as viewing it, passing it in the feed is not.
+#[utoipa::path(
+    get,
+    path = "/api/v1/brainfarts/{brainfart_id}",
+    tag = "brainfarts",
+    params(
+        ("brainfart_id" = String, Path, description = "The id of the brainfart")
+    ),
+    responses(
+        (status = 200, description = "The brainfart", body = BrainfartInformation),
+        (status = 404, description = "There is no such brainfart", body = String)
+    ),
+    security(("bearer" = []))
+)]
+async fn get_brainfart(
+    State(repository): State<SharedRepository>,
+    claims: JwtDataHolder,
+    Path(brainfart_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let brain_id = claims.get_id();
+    if repository
+        .get_brainfart(&brainfart_id, &brain_id)
+        .await
+        .is_none()
+    {
+        return Err((
+            StatusCode::NOT_FOUND,
+            "There is no such brainfart".to_string(),
+        ));
+    }
+    repository
+        .set_brainfart_as_viewed(&brainfart_id, &brain_id)
+        .await;
+    match repository.get_brainfart(&brainfart_id, &brain_id).await {
+        Some(brainfart) => Ok((StatusCode::OK, Json(brainfart))),
+        None => Err((
+            StatusCode::NOT_FOUND,
+            "There is no such brainfart".to_string(),
+        )),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/brainfarts/{brainfart_id}/reactions",
+    tag = "brainfarts",
+    params(
+        ("brainfart_id" = String, Path, description = "The id of the brainfart")
+    ),
+    request_body = ReactionRequest,
+    responses(
+        (status = 201, description = "The brainfart with the reaction", body = BrainfartInformation),
+        (status = 403, description = "The mastermind has blocked you", body = String),
+        (status = 404, description = "There is no such brainfart", body = String)
+    ),
+    security(("bearer" = []))
+)]
+async fn react(
+    State(repository): State<SharedRepository>,
+    State(metrics): State<SharedMetrics>,
+    claims: JwtDataHolder,
+    Path(brainfart_id): Path<Uuid>,
+    result: Result<Json<ReactionRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    let brainfart_id = brainfart_id.to_string();
+    match result {
+        Ok(Json(ReactionRequest {
+            kind: ReactionKind::Explosion,
+        })) => register_mind_explosion(
+            State(repository),
+            State(metrics),
+            claims,
+            Ok(Json(NotifyAboutMindExplosionRequest { brainfart_id })),
+        )
+        .await
+        .into_response(),
+        Ok(Json(ReactionRequest {
+            kind: ReactionKind::Implosion,
+        })) => register_mind_implosion(
+            State(repository),
+            State(metrics),
+            claims,
+            Ok(Json(NotifyAboutMindImplosionRequest { brainfart_id })),
+        )
+        .await
+        .into_response(),
+        Err(err) => error_responders::post_error_responder(err).into_response(),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/brainfarts/{brainfart_id}/bookmark",
+    tag = "bookmarks",
+    params(
+        ("brainfart_id" = String, Path, description = "The id of the brainfart")
+    ),
+    responses(
+        (status = 201, description = "The bookmarked brainfart", body = BrainfartInformation),
+        (status = 404, description = "There is no such brainfart", body = String)
+    ),
+    security(("bearer" = []))
+)]
+async fn add_bookmark(
+    state: State<SharedRepository>,
+    claims: JwtDataHolder,
+    Path(brainfart_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let brainfart_id = brainfart_id.to_string();
+    bookmarks::add_bookmark(state, claims, Ok(Json(BookmarkRequest { brainfart_id }))).await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/brainfarts/{brainfart_id}/bookmark",
+    tag = "bookmarks",
+    params(
+        ("brainfart_id" = String, Path, description = "The id of the brainfart")
+    ),
+    responses(
+        (status = 200, description = "The brainfart without the bookmark", body = BrainfartInformation),
+        (status = 404, description = "There is no such bookmark", body = String)
+    ),
+    security(("bearer" = []))
+)]
+async fn remove_bookmark(
+    state: State<SharedRepository>,
+    claims: JwtDataHolder,
+    Path(brainfart_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let brainfart_id = brainfart_id.to_string();
+    bookmarks::remove_bookmark(state, claims, Ok(Json(BookmarkRequest { brainfart_id }))).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/brainfarts/{brainfart_id}/reports",
+    tag = "moderation",
+    params(
+        ("brainfart_id" = String, Path, description = "The id of the brainfart")
+    ),
+    request_body = ReportBrainfartRequest,
+    responses(
+        (status = 201, description = "The report that was made", body = ReportInformation),
+        (status = 400, description = "Invalid reason", body = String),
+        (status = 404, description = "There is no such brainfart, or you reported it already", body = String)
+    ),
+    security(("bearer" = []))
+)]
+async fn report_brainfart(
+    state: State<SharedRepository>,
+    claims: JwtDataHolder,
+    Path(brainfart_id): Path<Uuid>,
+    result: Result<Json<ReportBrainfartRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    let result = result.map(|Json(payload)| {
+        Json(ReportBrainfartRequest {
+            brainfart_id: brainfart_id.to_string(),
+            ..payload
+        })
+    });
+    moderation::report_brainfart(state, claims, result).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/brainfarts/{brainfart_id}/votes",
+    tag = "polls",
+    params(
+        ("brainfart_id" = String, Path, description = "The id of the brainfart")
+    ),
+    request_body = VoteRequest,
+    responses(
+        (status = 201, description = "The brainfart with the new results", body = BrainfartInformation),
+        (status = 400, description = "The poll is closed or the options are wrong", body = String),
+        (status = 403, description = "The mastermind has blocked you", body = String),
+        (status = 404, description = "There is no such poll", body = String),
+        (status = 409, description = "You have already voted", body = String)
+    ),
+    security(("bearer" = []))
+)]
+async fn vote(
+    state: State<SharedRepository>,
+    claims: JwtDataHolder,
+    Path(brainfart_id): Path<Uuid>,
+    result: Result<Json<VoteRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    let result = result.map(|Json(payload)| {
+        Json(VoteRequest {
+            brainfart_id: brainfart_id.to_string(),
+            ..payload
+        })
+    });
+    polls::vote(state, claims, result).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/brainfarts/{brainfart_id}/rebrainfarts",
+    tag = "brainfarts",
+    params(
+        ("brainfart_id" = String, Path, description = "The id of the brainfart")
+    ),
+    request_body = RebrainfartRequest,
+    responses(
+        (status = 201, description = "The rebrainfart", body = BrainfartInformation),
+        (status = 403, description = "The mastermind has blocked you", body = String),
+        (status = 404, description = "There is no such brainfart", body = String),
+        (status = 409, description = "You have already rebrainfarted it", body = String)
+    ),
+    security(("bearer" = []))
+)]
+async fn rebrainfart(
+    state: State<SharedRepository>,
+    claims: JwtDataHolder,
+    Path(brainfart_id): Path<Uuid>,
+    result: Result<Json<RebrainfartRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    let result = result.map(|Json(payload)| {
+        Json(RebrainfartRequest {
+            brainfart_id: brainfart_id.to_string(),
+            ..payload
+        })
+    });
+    rebrainfarts::rebrainfart(state, claims, result).await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/relations/{relation}/{brainname}",
+    tag = "relations",
+    params(
+        ("relation" = BrainRelation, Path, description = "mute or block"),
+        ("brainname" = String, Path, description = "The name of the brain")
+    ),
+    responses(
+        (status = 200, description = "The relations that are left", body = [BrainRelationInformation]),
+        (status = 404, description = "There is no such relation", body = String)
+    ),
+    security(("bearer" = []))
+)]
+async fn remove_relation(
+    state: State<SharedRepository>,
+    claims: JwtDataHolder,
+    Path((relation, brainname)): Path<(BrainRelation, String)>,
+) -> impl IntoResponse {
+    let request = BrainRelationRequest {
+        brainname,
+        relation,
+    };
+    relations::remove_relation(state, claims, Ok(Json(request))).await
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/brainfarts/{brainfart_id}/hidden",
+    tag = "moderation",
+    params(
+        ("brainfart_id" = String, Path, description = "The id of the brainfart")
+    ),
+    responses(
+        (status = 200, description = "The hidden brainfart", body = BrainfartInformation),
+        (status = 403, description = "You may not moderate brainfarts", body = String),
+        (status = 404, description = "There is no such brainfart", body = String)
+    ),
+    security(("bearer" = []))
+)]
+async fn hide_brainfart(
+    state: State<SharedRepository>,
+    permission: RequiresPermission<CanModerateBrainfarts>,
+    Path(brainfart_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let brainfart_id = brainfart_id.to_string();
+    let request = ModerateBrainfartRequest { brainfart_id };
+    moderation::hide_brainfart(state, permission, Ok(Json(request))).await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/brainfarts/{brainfart_id}/hidden",
+    tag = "moderation",
+    params(
+        ("brainfart_id" = String, Path, description = "The id of the brainfart")
+    ),
+    responses(
+        (status = 200, description = "The restored brainfart", body = BrainfartInformation),
+        (status = 403, description = "You may not moderate brainfarts", body = String),
+        (status = 404, description = "There is no such brainfart", body = String)
+    ),
+    security(("bearer" = []))
+)]
+async fn restore_brainfart(
+    state: State<SharedRepository>,
+    permission: RequiresPermission<CanModerateBrainfarts>,
+    Path(brainfart_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let brainfart_id = brainfart_id.to_string();
+    let request = ModerateBrainfartRequest { brainfart_id };
+    moderation::restore_brainfart(state, permission, Ok(Json(request))).await
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/brains/{brainname}/suspension",
+    tag = "moderation",
+    params(
+        ("brainname" = String, Path, description = "The name of the brain")
+    ),
+    responses(
+        (status = 200, description = "The name of the suspended brain", body = String),
+        (status = 400, description = "You cannot suspend yourself", body = String),
+        (status = 403, description = "You may not suspend brains", body = String),
+        (status = 404, description = "There is no such brain", body = String)
+    ),
+    security(("bearer" = []))
+)]
+async fn suspend_brain(
+    state: State<SharedRepository>,
+    permission: RequiresPermission<CanSuspendBrains>,
+    Path(brainname): Path<String>,
+) -> impl IntoResponse {
+    let request = ModerateBrainRequest { brainname };
+    moderation::suspend_brain(state, permission, Ok(Json(request))).await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/brains/{brainname}/suspension",
+    tag = "moderation",
+    params(
+        ("brainname" = String, Path, description = "The name of the brain")
+    ),
+    responses(
+        (status = 200, description = "The name of the brain", body = String),
+        (status = 403, description = "You may not suspend brains", body = String),
+        (status = 404, description = "There is no such brain", body = String)
+    ),
+    security(("bearer" = []))
+)]
+async fn unsuspend_brain(
+    state: State<SharedRepository>,
+    permission: RequiresPermission<CanSuspendBrains>,
+    Path(brainname): Path<String>,
+) -> impl IntoResponse {
+    let request = ModerateBrainRequest { brainname };
+    moderation::unsuspend_brain(state, permission, Ok(Json(request))).await
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/brains/{brainname}/role",
+    tag = "moderation",
+    params(
+        ("brainname" = String, Path, description = "The name of the brain")
+    ),
+    request_body = SetRoleRequest,
+    responses(
+        (status = 200, description = "The name of the brain", body = String),
+        (status = 400, description = "You cannot change your own role", body = String),
+        (status = 403, description = "Only admins can change roles", body = String),
+        (status = 404, description = "There is no such brain", body = String)
+    ),
+    security(("bearer" = []))
+)]
+async fn set_role(
+    state: State<SharedRepository>,
+    admin: RequiresAdmin,
+    Path(brainname): Path<String>,
+    result: Result<Json<SetRoleRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    let result = result.map(|Json(payload)| {
+        Json(SetRoleRequest {
+            brainname,
+            ..payload
+        })
+    });
+    moderation::set_role(state, admin, result).await
+}
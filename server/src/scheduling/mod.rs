@@ -0,0 +1,34 @@
+use std::time::Duration;
+
+use chrono::Local;
+use shared::WebhookEvent;
+use tokio::task::JoinHandle;
+
+use crate::{database::repository::SharedRepository, webhooks};
+
+/// Puts scheduled brainfarts in the feed once they are due, checking every `interval`, and lets
+/// the webhooks know about them. Runs for as long as the server does.
+pub fn spawn_publisher(repository: SharedRepository, interval: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match repository.publish_scheduled_brainfarts(Local::now()).await {
+                Some(published) if published.is_empty() => {}
+                Some(published) => {
+                    log::info!("Published {} scheduled brainfarts", published.len());
+                    for brainfart_id in published.iter() {
+                        webhooks::enqueue(
+                            &repository,
+                            WebhookEvent::BrainfartCreated,
+                            brainfart_id,
+                            None,
+                        )
+                        .await;
+                    }
+                }
+                None => log::warn!("Publishing the scheduled brainfarts failed"),
+            }
+        }
+    })
+}
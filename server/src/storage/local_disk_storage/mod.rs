@@ -0,0 +1,52 @@
+use std::path::PathBuf;
+
+use axum::async_trait;
+
+use super::{is_valid_key, AttachmentStorage};
+
+/// Stores every file directly in one directory, which is created when the first file arrives.
+pub struct LocalDiskStorage {
+    root: PathBuf,
+}
+
+impl LocalDiskStorage {
+    pub fn create(root: impl Into<PathBuf>) -> LocalDiskStorage {
+        LocalDiskStorage { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> Option<PathBuf> {
+        is_valid_key(key).then(|| self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl AttachmentStorage for LocalDiskStorage {
+    async fn store(&self, key: &str, content: &[u8]) -> bool {
+        let path = match self.path(key) {
+            Some(path) => path,
+            None => return false,
+        };
+        if let Err(err) = tokio::fs::create_dir_all(&self.root).await {
+            log::warn!("Creating {} failed: {}", self.root.display(), err);
+            return false;
+        }
+        match tokio::fs::write(&path, content).await {
+            Ok(()) => true,
+            Err(err) => {
+                log::warn!("Writing {} failed: {}", path.display(), err);
+                false
+            }
+        }
+    }
+
+    async fn load(&self, key: &str) -> Option<Vec<u8>> {
+        tokio::fs::read(self.path(key)?).await.ok()
+    }
+
+    async fn delete(&self, key: &str) -> bool {
+        match self.path(key) {
+            Some(path) => tokio::fs::remove_file(path).await.is_ok(),
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use axum::async_trait;
+
+use super::{is_valid_key, AttachmentStorage};
+
+#[derive(Default)]
+pub struct MemoryStorage {
+    files: Mutex<HashMap<String, Vec<u8>>>,
+}
+
+impl MemoryStorage {
+    pub fn create() -> MemoryStorage {
+        MemoryStorage::default()
+    }
+}
+
+#[async_trait]
+impl AttachmentStorage for MemoryStorage {
+    async fn store(&self, key: &str, content: &[u8]) -> bool {
+        if !is_valid_key(key) {
+            return false;
+        }
+        self.files
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), content.to_vec());
+        true
+    }
+
+    async fn load(&self, key: &str) -> Option<Vec<u8>> {
+        self.files.lock().unwrap().get(key).cloned()
+    }
+
+    async fn delete(&self, key: &str) -> bool {
+        self.files.lock().unwrap().remove(key).is_some()
+    }
+}
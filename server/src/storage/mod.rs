@@ -0,0 +1,27 @@
+use std::sync::Arc;
+
+use axum::async_trait;
+
+pub mod local_disk_storage;
+#[cfg(test)]
+pub mod memory_storage;
+
+pub type SharedStorage = Arc<dyn AttachmentStorage>;
+
+/// Keeps the files attached to brainfarts. Keys are made up by the server from attachment ids,
+/// so a backend can use them as file or object names directly.
+#[async_trait]
+pub trait AttachmentStorage: Send + Sync {
+    /// Replaces whatever was stored under the key.
+    async fn store(&self, key: &str, content: &[u8]) -> bool;
+    async fn load(&self, key: &str) -> Option<Vec<u8>>;
+    async fn delete(&self, key: &str) -> bool;
+}
+
+/// Only ids and the suffixes the server adds to them are valid keys.
+pub fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|character| character.is_ascii_alphanumeric() || character == '-')
+}
@@ -0,0 +1,86 @@
+//! Logging and tracing. Every request gets a span carrying its request id, so the log lines it
+//! leads to can be told apart from those of the requests running alongside it.
+
+use axum::http::Request;
+use tracing::Span;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
+
+use crate::configuration::{Configuration, LogFormat};
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Sets up the logging. `RUST_LOG` wins over the configured log level when it is set.
+pub fn init(configuration: &Configuration) -> Result<(), String> {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| {
+        EnvFilter::new(format!("{},hyper=info,mio=info", configuration.log_level))
+    });
+    let output = match configuration.log_format {
+        LogFormat::Text => tracing_subscriber::fmt::layer().boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .with_current_span(true)
+            .with_span_list(false)
+            .boxed(),
+    };
+    let subscriber = tracing_subscriber::registry().with(filter).with(output);
+    #[cfg(feature = "otel")]
+    let subscriber = subscriber.with(otel::layer(configuration)?);
+    subscriber
+        .try_init()
+        .map_err(|err| format!("Setting up the logging failed: {}", err))
+}
+
+/// The span of a request. Only the path is kept from the uri, as the query may hold a feed token.
+pub fn make_request_span<B>(request: &Request<B>) -> Span {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    tracing::info_span!(
+        "request",
+        method = %request.method(),
+        path = request.uri().path(),
+        request_id,
+    )
+}
+
+#[cfg(feature = "otel")]
+mod otel {
+    use opentelemetry::{
+        sdk::{trace, Resource},
+        KeyValue,
+    };
+    use opentelemetry_otlp::WithExportConfig;
+    use tracing::Subscriber;
+    use tracing_opentelemetry::OpenTelemetryLayer;
+    use tracing_subscriber::registry::LookupSpan;
+
+    use crate::configuration::Configuration;
+
+    /// Sends the spans to the collector at `otel_endpoint`, if one is configured.
+    pub fn layer<S>(
+        configuration: &Configuration,
+    ) -> Result<Option<OpenTelemetryLayer<S, trace::Tracer>>, String>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>,
+    {
+        let Some(endpoint) = &configuration.otel_endpoint else {
+            return Ok(None);
+        };
+        let tracer = opentelemetry_otlp::new_pipeline()
+            .tracing()
+            .with_exporter(
+                opentelemetry_otlp::new_exporter()
+                    .tonic()
+                    .with_endpoint(endpoint),
+            )
+            .with_trace_config(
+                trace::config()
+                    .with_resource(Resource::new(vec![KeyValue::new("service.name", "brains")])),
+            )
+            .install_batch(opentelemetry::runtime::Tokio)
+            .map_err(|err| format!("Setting up the OpenTelemetry export failed: {}", err))?;
+        Ok(Some(tracing_opentelemetry::layer().with_tracer(tracer)))
+    }
+}
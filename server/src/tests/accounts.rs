@@ -0,0 +1,181 @@
+use std::sync::Arc;
+
+use axum::{
+    http::{Method, StatusCode},
+    Router,
+};
+use chrono::{Duration, Local};
+use serde_json::json;
+use shared::{BrainDeletion, DeletionMode, REMOVED_BRAIN_NAME};
+
+use super::{app_with, backends, create_brainfart, get_brainfarts, log_in, parse, register, send};
+use crate::{
+    accounts::delete_due_brains,
+    database::{memory_repository::MemoryRepository, repository::SharedRepository},
+    storage::memory_storage::MemoryStorage,
+};
+
+async fn request_deletion(app: &Router, token: &str, mode: &str) -> BrainDeletion {
+    let (status, body) = send(
+        app,
+        Method::PUT,
+        "/api/v1/account/deletion",
+        Some(token),
+        Some(json!({"password": "lösenord", "mode": mode})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    parse(&body)
+}
+
+async fn explode(app: &Router, token: &str, brainfart_id: &str) {
+    let (status, _) = send(
+        app,
+        Method::POST,
+        &format!("/api/v1/brainfarts/{}/reactions", brainfart_id),
+        Some(token),
+        Some(json!({"kind": "explosion"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+/// Runs the deleter as if the grace period had run out.
+async fn run_deleter(repository: &SharedRepository) -> usize {
+    delete_due_brains(
+        repository,
+        &MemoryStorage::create(),
+        Local::now() + Duration::days(15),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn deletions_need_the_password_and_can_be_called_off() {
+    for repository in backends().await {
+        let app = app_with(repository.clone());
+        let anna = register(&app, "anna", "lösenord").await;
+        let (status, _) = send(
+            &app,
+            Method::PUT,
+            "/api/v1/account/deletion",
+            Some(&anna),
+            Some(json!({"password": "fel"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, _) = send(
+            &app,
+            Method::GET,
+            "/api/v1/account/deletion",
+            Some(&anna),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let deletion = request_deletion(&app, &anna, "delete").await;
+        assert_eq!(deletion.mode, DeletionMode::Delete);
+        assert!(deletion.delete_at > Local::now() + Duration::days(13));
+        let (status, body) = send(
+            &app,
+            Method::GET,
+            "/api/v1/account/deletion",
+            Some(&anna),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let pending: BrainDeletion = parse(&body);
+        assert_eq!(pending.mode, DeletionMode::Delete);
+        // Nothing happens before the grace period is over.
+        assert_eq!(
+            delete_due_brains(&repository, &MemoryStorage::create(), Local::now()).await,
+            0
+        );
+
+        let (status, _) = send(
+            &app,
+            Method::DELETE,
+            "/api/v1/account/deletion",
+            Some(&anna),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(run_deleter(&repository).await, 0);
+        let (status, _) = log_in(&app, "anna", "lösenord").await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+    }
+}
+
+#[tokio::test]
+async fn deleted_brains_take_their_brainfarts_and_reactions_along() {
+    for repository in backends().await {
+        let app = app_with(repository.clone());
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+        create_brainfart(&app, &anna, "mine").await;
+        let his = create_brainfart(&app, &bertil, "his").await;
+        explode(&app, &anna, &his.id).await;
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            "/api/v1/relations",
+            Some(&anna),
+            Some(json!({"brainname": "bertil", "relation": "mute"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        request_deletion(&app, &anna, "delete").await;
+        assert_eq!(run_deleter(&repository).await, 1);
+
+        let brainfarts = get_brainfarts(&app, &bertil).await;
+        assert_eq!(brainfarts.len(), 1);
+        assert_eq!(brainfarts[0].title, "his");
+        assert!(brainfarts[0].blew_minds.is_empty());
+        let (status, _) = log_in(&app, "anna", "lösenord").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        // The name is free again.
+        register(&app, "anna", "nytt").await;
+    }
+}
+
+#[tokio::test]
+async fn anonymized_brains_leave_their_brainfarts_behind() {
+    for repository in backends().await {
+        let app = app_with(repository.clone());
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+        let mine = create_brainfart(&app, &anna, "mine").await;
+        let his = create_brainfart(&app, &bertil, "his").await;
+        explode(&app, &anna, &his.id).await;
+        explode(&app, &bertil, &mine.id).await;
+
+        request_deletion(&app, &anna, "anonymize").await;
+        assert_eq!(run_deleter(&repository).await, 1);
+
+        let brainfarts = get_brainfarts(&app, &bertil).await;
+        assert_eq!(brainfarts.len(), 2);
+        let mine = brainfarts.iter().find(|b| b.title == "mine").unwrap();
+        assert_eq!(mine.mastermind_name, REMOVED_BRAIN_NAME);
+        assert_eq!(mine.blew_minds[0].get_name(), "bertil");
+        let his = brainfarts.iter().find(|b| b.title == "his").unwrap();
+        assert_eq!(his.blew_minds[0].get_name(), REMOVED_BRAIN_NAME);
+    }
+}
+
+#[tokio::test]
+async fn the_removed_name_cannot_be_registered() {
+    let app = app_with(Arc::new(MemoryRepository::create()));
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/v1/brains",
+        None,
+        Some(json!({"brainname": REMOVED_BRAIN_NAME, "password": "a", "password_extra": "a"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
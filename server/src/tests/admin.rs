@@ -0,0 +1,166 @@
+use shared::{CreateBrainfartRequest, NotifyAboutMindExplosionRequest, ProveOwnsBrainRequest};
+
+use super::backends;
+use crate::{
+    admin::{self, AdminCommand, BrainfartsCommand, BrainsCommand},
+    database::repository::SharedRepository,
+    storage::memory_storage::MemoryStorage,
+};
+
+async fn create_brain(repository: &SharedRepository, brainname: &str) {
+    admin::run(
+        AdminCommand::Brains(BrainsCommand::Create {
+            brainname: brainname.to_string(),
+            password: "lösenord".to_string(),
+        }),
+        repository.as_ref(),
+        &MemoryStorage::create(),
+    )
+    .await
+    .unwrap();
+}
+
+async fn can_log_in(repository: &SharedRepository, brainname: &str, password: &str) -> bool {
+    repository
+        .verify_password(&ProveOwnsBrainRequest::create(
+            brainname.to_string(),
+            password.to_string(),
+        ))
+        .await
+        .is_some()
+}
+
+#[tokio::test]
+async fn created_brains_are_listed() {
+    for repository in backends().await {
+        create_brain(&repository, "hjärna").await;
+        let output = admin::run(
+            AdminCommand::Brains(BrainsCommand::List),
+            repository.as_ref(),
+            &MemoryStorage::create(),
+        )
+        .await
+        .unwrap();
+        assert!(output.contains("active"));
+        assert!(output.trim_end().ends_with("hjärna"));
+    }
+}
+
+#[tokio::test]
+async fn disabled_brains_cannot_log_in() {
+    for repository in backends().await {
+        create_brain(&repository, "hjärna").await;
+        admin::run(
+            AdminCommand::Brains(BrainsCommand::Disable {
+                brainname: "hjärna".to_string(),
+            }),
+            repository.as_ref(),
+            &MemoryStorage::create(),
+        )
+        .await
+        .unwrap();
+        assert!(!can_log_in(&repository, "hjärna", "lösenord").await);
+
+        admin::run(
+            AdminCommand::Brains(BrainsCommand::Enable {
+                brainname: "hjärna".to_string(),
+            }),
+            repository.as_ref(),
+            &MemoryStorage::create(),
+        )
+        .await
+        .unwrap();
+        assert!(can_log_in(&repository, "hjärna", "lösenord").await);
+
+        let err = admin::run(
+            AdminCommand::Brains(BrainsCommand::Disable {
+                brainname: "okänd".to_string(),
+            }),
+            repository.as_ref(),
+            &MemoryStorage::create(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err, "There is no brain called okänd");
+    }
+}
+
+#[tokio::test]
+async fn resetting_the_password_replaces_the_old_one() {
+    for repository in backends().await {
+        create_brain(&repository, "hjärna").await;
+        admin::run(
+            AdminCommand::Brains(BrainsCommand::ResetPassword {
+                brainname: "hjärna".to_string(),
+                password: "nytt".to_string(),
+            }),
+            repository.as_ref(),
+            &MemoryStorage::create(),
+        )
+        .await
+        .unwrap();
+        assert!(!can_log_in(&repository, "hjärna", "lösenord").await);
+        assert!(can_log_in(&repository, "hjärna", "nytt").await);
+    }
+}
+
+#[tokio::test]
+async fn deleting_a_brainfart_removes_its_reactions_and_views() {
+    for repository in backends().await {
+        create_brain(&repository, "hjärna").await;
+        let brain_id = repository.list_brains().await.unwrap()[0].id;
+        let brainfart = repository
+            .create_brainfart(
+                &CreateBrainfartRequest::create("title".to_string(), "content".to_string()),
+                &brain_id,
+            )
+            .await
+            .unwrap();
+        repository
+            .register_mind_explosion(
+                &NotifyAboutMindExplosionRequest {
+                    brainfart_id: brainfart.uuid.to_string(),
+                },
+                &brain_id,
+            )
+            .await
+            .unwrap();
+        repository
+            .set_brainfart_as_viewed(&brainfart.uuid, &brain_id)
+            .await;
+
+        let output = admin::run(
+            AdminCommand::Stats,
+            repository.as_ref(),
+            &MemoryStorage::create(),
+        )
+        .await
+        .unwrap();
+        assert!(output.contains("brainfarts:  1\nexplosions:  1\n"));
+
+        admin::run(
+            AdminCommand::Brainfarts(BrainfartsCommand::Delete {
+                brainfart_id: brainfart.uuid.to_string(),
+            }),
+            repository.as_ref(),
+            &MemoryStorage::create(),
+        )
+        .await
+        .unwrap();
+        let statistics = repository.get_statistics().await.unwrap();
+        assert_eq!(statistics.brains, 1);
+        assert_eq!(statistics.brainfarts, 0);
+        assert_eq!(statistics.explosions, 0);
+        assert_eq!(statistics.views, 0);
+
+        assert!(admin::run(
+            AdminCommand::Brainfarts(BrainfartsCommand::Delete {
+                brainfart_id: brainfart.uuid.to_string(),
+            }),
+            repository.as_ref(),
+            &MemoryStorage::create(),
+        )
+        .await
+        .is_err());
+    }
+}
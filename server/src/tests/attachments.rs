@@ -0,0 +1,327 @@
+use std::io::Cursor;
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use chrono::{Duration, Local};
+use image::{ImageOutputFormat, Rgb, RgbImage};
+use serde_json::json;
+use shared::{Brainfart, BrainfartInformation, CreateBrainfartRequest, RegisterBrainRequest, Role};
+use tower::ServiceExt;
+
+use super::{
+    app_with, backends, create_brainfart, get_brainfarts, jwt_keys, parse, register, send,
+};
+use crate::{
+    admin::{self, AdminCommand, BrainfartsCommand},
+    attachments::thumbnail_key,
+    database::repository::NewAttachment,
+    storage::{memory_storage::MemoryStorage, AttachmentStorage},
+};
+
+const BOUNDARY: &str = "hjarnstorm";
+
+fn png(width: u32, height: u32) -> Vec<u8> {
+    let image = RgbImage::from_pixel(width, height, Rgb([200, 30, 30]));
+    let mut content = Cursor::new(vec![]);
+    image
+        .write_to(&mut content, ImageOutputFormat::Png)
+        .unwrap();
+    content.into_inner()
+}
+
+/// Sends the files, given as (filename, content type, content), the way a browser form would.
+async fn upload(
+    app: &Router,
+    token: &str,
+    brainfart_id: &str,
+    files: &[(&str, &str, &[u8])],
+) -> (StatusCode, Vec<u8>) {
+    let mut body = format!(
+        "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"brainfart_id\"\r\n\r\n{brainfart_id}\r\n"
+    )
+    .into_bytes();
+    for (filename, content_type, content) in files {
+        body.extend_from_slice(
+            format!(
+                "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{filename}\"\r\nContent-Type: {content_type}\r\n\r\n"
+            )
+            .as_bytes(),
+        );
+        body.extend_from_slice(content);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{BOUNDARY}--\r\n").as_bytes());
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri("/api/addattachments")
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .header(
+            header::CONTENT_TYPE,
+            format!("multipart/form-data; boundary={}", BOUNDARY),
+        )
+        .body(Body::from(body))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    (status, bytes.to_vec())
+}
+
+/// Answers with the status, the content type and the content.
+async fn download(app: &Router, uri: &str) -> (StatusCode, String, Vec<u8>) {
+    let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .map(|value| value.to_str().unwrap().to_string())
+        .unwrap_or_default();
+    let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    (status, content_type, bytes.to_vec())
+}
+
+#[tokio::test]
+async fn attached_images_get_a_thumbnail() {
+    for repository in backends().await {
+        let app = app_with(repository);
+        let anna = register(&app, "anna", "lösenord").await;
+        let brainfart = create_brainfart(&app, &anna, "look at this").await;
+        let image = png(600, 300);
+
+        let (status, body) = upload(
+            &app,
+            &anna,
+            &brainfart.id,
+            &[
+                ("C:\\bilder\\röd.png", "image/png", &image),
+                ("notes.txt", "text/plain; charset=utf-8", b"hej"),
+            ],
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        let information: BrainfartInformation = parse(&body);
+        assert_eq!(information.attachments.len(), 2);
+        let picture = &information.attachments[0];
+        assert_eq!(picture.filename, "röd.png");
+        assert_eq!(picture.size, image.len() as i64);
+        assert!(picture.has_thumbnail);
+        let notes = &information.attachments[1];
+        assert_eq!(notes.content_type, "text/plain");
+        assert!(!notes.has_thumbnail);
+
+        let (status, content_type, content) = download(&app, &picture.url()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, "image/png");
+        assert_eq!(content, image);
+
+        let (status, _, content) = download(&app, &picture.thumbnail_url().unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        let thumbnail = image::load_from_memory(&content).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (256, 128));
+
+        let (status, _, _) =
+            download(&app, &format!("/api/attachment/{}/thumbnail", notes.id)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let feed = get_brainfarts(&app, &anna).await;
+        assert_eq!(feed[0].attachments, information.attachments);
+    }
+}
+
+#[tokio::test]
+async fn only_the_mastermind_can_attach_files() {
+    for repository in backends().await {
+        let app = app_with(repository);
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+        let brainfart = create_brainfart(&app, &anna, "mine").await;
+
+        let (status, _) = upload(
+            &app,
+            &bertil,
+            &brainfart.id,
+            &[("notes.txt", "text/plain", b"hej")],
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        // The mastermind is told by the id, whatever name the token carries.
+        let id = |token: &str| {
+            uuid::Uuid::parse_str(&jwt_keys().konvertera_jwt(token).unwrap().id).unwrap()
+        };
+        let bertil_as_anna = jwt_keys().create_jwt(id(&bertil), "anna".to_string(), Role::Brain);
+        let (status, _) = upload(
+            &app,
+            &bertil_as_anna,
+            &brainfart.id,
+            &[("notes.txt", "text/plain", b"hej")],
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let anna_renamed = jwt_keys().create_jwt(id(&anna), "annika".to_string(), Role::Brain);
+        let (status, _) = upload(
+            &app,
+            &anna_renamed,
+            &brainfart.id,
+            &[("notes.txt", "text/plain", b"hej")],
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let (status, _) = upload(
+            &app,
+            &anna,
+            &uuid::Uuid::new_v4().to_string(),
+            &[("notes.txt", "text/plain", b"hej")],
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, _) = send(&app, Method::POST, "/api/addattachments", Some(&anna), None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+async fn files_are_only_served_to_those_who_see_the_brainfart() {
+    for repository in backends().await {
+        let app = app_with(repository);
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+        let (status, body) = send(
+            &app,
+            Method::POST,
+            "/api/v1/brainfarts",
+            Some(&anna),
+            Some(json!({
+                "title": "soon",
+                "content": "content",
+                "publish_at": Local::now() + Duration::days(1),
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        let scheduled: Brainfart = parse(&body);
+        let (status, body) = upload(
+            &app,
+            &anna,
+            &scheduled.id,
+            &[("röd.png", "image/png", &png(300, 300))],
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        let picture = &parse::<BrainfartInformation>(&body).attachments[0];
+
+        for uri in [picture.url(), picture.thumbnail_url().unwrap()] {
+            let (status, _) = send(&app, Method::GET, &uri, Some(&anna), None).await;
+            assert_eq!(status, StatusCode::OK);
+            let (status, _) = send(&app, Method::GET, &uri, Some(&bertil), None).await;
+            assert_eq!(status, StatusCode::NOT_FOUND);
+            let (status, _, _) = download(&app, &uri).await;
+            assert_eq!(status, StatusCode::NOT_FOUND);
+        }
+    }
+}
+
+#[tokio::test]
+async fn attachments_are_limited_in_type_size_and_number() {
+    for repository in backends().await {
+        let app = app_with(repository);
+        let anna = register(&app, "anna", "lösenord").await;
+        let brainfart = create_brainfart(&app, &anna, "limits").await;
+
+        let (status, body) = upload(
+            &app,
+            &anna,
+            &brainfart.id,
+            &[("page.html", "text/html", b"<script></script>")],
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(body, b"text/html files cannot be attached");
+
+        let (status, body) = upload(
+            &app,
+            &anna,
+            &brainfart.id,
+            &[("fake.png", "image/png", b"not a picture")],
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body, b"fake.png is not a valid image");
+
+        let too_large = vec![b'a'; 64 * 1024 + 1];
+        let (status, _) = upload(
+            &app,
+            &anna,
+            &brainfart.id,
+            &[("large.txt", "text/plain", &too_large)],
+        )
+        .await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+
+        let file: (&str, &str, &[u8]) = ("notes.txt", "text/plain", b"hej");
+        let (status, _) = upload(&app, &anna, &brainfart.id, &[file; 4]).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let (status, _) = upload(&app, &anna, &brainfart.id, &[file]).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // Nothing from the rejected uploads was kept.
+        let feed = get_brainfarts(&app, &anna).await;
+        assert_eq!(feed[0].attachments.len(), 4);
+    }
+}
+
+#[tokio::test]
+async fn deleting_a_brainfart_deletes_its_files() {
+    for repository in backends().await {
+        let storage = MemoryStorage::create();
+        let brain = repository
+            .create_brain(&RegisterBrainRequest::create(
+                "anna".to_string(),
+                "lösenord".to_string(),
+                "lösenord".to_string(),
+            ))
+            .await
+            .unwrap();
+        let brainfart = repository
+            .create_brainfart(
+                &CreateBrainfartRequest::create("title".to_string(), "content".to_string()),
+                &brain.uuid,
+            )
+            .await
+            .unwrap();
+        let attachment = repository
+            .add_attachment(
+                &NewAttachment {
+                    filename: "röd.png".to_string(),
+                    content_type: "image/png".to_string(),
+                    size: 3,
+                    has_thumbnail: true,
+                },
+                &brainfart.uuid,
+            )
+            .await
+            .unwrap();
+        let key = attachment.uuid.to_string();
+        assert!(storage.store(&key, b"png").await);
+        assert!(storage.store(&thumbnail_key(&key), b"png").await);
+
+        admin::run(
+            AdminCommand::Brainfarts(BrainfartsCommand::Delete {
+                brainfart_id: brainfart.uuid.to_string(),
+            }),
+            repository.as_ref(),
+            &storage,
+        )
+        .await
+        .unwrap();
+        assert!(repository.get_attachment(&attachment.uuid).await.is_none());
+        assert!(storage.load(&key).await.is_none());
+        assert!(storage.load(&thumbnail_key(&key)).await.is_none());
+    }
+}
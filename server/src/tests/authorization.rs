@@ -0,0 +1,148 @@
+use std::sync::Arc;
+
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+use shared::{JwtInformation, Permission, Role};
+
+use super::{admin_token, app, app_with, register, send};
+use crate::database::{memory_repository::MemoryRepository, repository::SharedRepository};
+
+#[test]
+fn tokens_without_a_role_belong_to_ordinary_brains() {
+    let information: JwtInformation =
+        serde_json::from_value(json!({"brainname": "hjärna", "id": "id"})).unwrap();
+    assert_eq!(information.role, Role::Brain);
+    assert!(!information.is_admin());
+    assert!(!information.has_permission(Permission::ReviewReports));
+}
+
+#[test]
+fn admins_have_every_permission() {
+    for permission in [
+        Permission::ReviewReports,
+        Permission::ModerateBrainfarts,
+        Permission::SuspendBrains,
+    ] {
+        assert!(Role::Admin.permissions().contains(&permission));
+        assert!(!Role::Brain.permissions().contains(&permission));
+    }
+}
+
+#[tokio::test]
+async fn guards_reject_missing_and_invalid_tokens_like_jwt_data_holder() {
+    let app = app();
+    for token in [None, Some("not a token")] {
+        let (status, _) = send(&app, Method::GET, "/api/admin/reports", token, None).await;
+        let (expected, _) = send(&app, Method::GET, "/api/getbrainfarts", token, None).await;
+        assert_eq!(status, expected);
+    }
+}
+
+#[tokio::test]
+async fn forbidden_responses_name_what_is_missing() {
+    let app = app();
+    let token = register(&app, "hjärna", "lösenord").await;
+    let brainfart = json!({ "brainfart_id": uuid::Uuid::new_v4().to_string() });
+    let brain = json!({"brainname": "hjärna"});
+    for (method, uri, body, reason) in [
+        (
+            Method::GET,
+            "/api/admin/reports",
+            None,
+            "You are not allowed to review reports",
+        ),
+        (
+            Method::POST,
+            "/api/admin/hidebrainfart",
+            Some(brainfart.clone()),
+            "You are not allowed to moderate brainfarts",
+        ),
+        (
+            Method::POST,
+            "/api/admin/restorebrainfart",
+            Some(brainfart),
+            "You are not allowed to moderate brainfarts",
+        ),
+        (
+            Method::POST,
+            "/api/admin/suspendbrain",
+            Some(brain.clone()),
+            "You are not allowed to suspend brains",
+        ),
+        (
+            Method::POST,
+            "/api/admin/unsuspendbrain",
+            Some(brain),
+            "You are not allowed to suspend brains",
+        ),
+        (
+            Method::POST,
+            "/api/admin/setrole",
+            Some(json!({"brainname": "hjärna", "role": "admin"})),
+            "Only admins may do this",
+        ),
+    ] {
+        let (status, body) = send(&app, method, uri, Some(&token), body).await;
+        assert_eq!(status, StatusCode::FORBIDDEN, "{}", uri);
+        assert_eq!(body, reason.as_bytes(), "{}", uri);
+    }
+}
+
+#[tokio::test]
+async fn admins_hand_out_roles_that_apply_at_once() {
+    let repository: SharedRepository = Arc::new(MemoryRepository::create());
+    let app = app_with(repository.clone());
+    let admin = admin_token(&app, repository.as_ref()).await;
+    let token = register(&app, "hjärna", "lösenord").await;
+    let global_webhook = json!({
+        "url": "https://example.com/hooks",
+        "events": ["brainfart_created"],
+        "secret": "a secret of some length",
+        "global": true
+    });
+
+    for (role, expected, webhook) in [
+        ("admin", StatusCode::OK, StatusCode::CREATED),
+        ("brain", StatusCode::FORBIDDEN, StatusCode::FORBIDDEN),
+    ] {
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            "/api/admin/setrole",
+            Some(&admin),
+            Some(json!({"brainname": "hjärna", "role": role})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(&app, Method::GET, "/api/admin/reports", Some(&token), None).await;
+        assert_eq!(status, expected, "{}", role);
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            "/api/v1/webhooks",
+            Some(&token),
+            Some(global_webhook.clone()),
+        )
+        .await;
+        assert_eq!(status, webhook, "{}", role);
+    }
+
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/admin/setrole",
+        Some(&admin),
+        Some(json!({"brainname": "admin", "role": "brain"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/admin/setrole",
+        Some(&admin),
+        Some(json!({"brainname": "okänd", "role": "admin"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,108 @@
+use axum::{
+    http::{Method, StatusCode},
+    Router,
+};
+use serde_json::json;
+use shared::BrainfartInformation;
+
+use super::{app_with, backends, create_brainfart, get_brainfarts, parse, register, send};
+
+async fn bookmark(
+    app: &Router,
+    uri: &str,
+    token: &str,
+    brainfart_id: &str,
+) -> (StatusCode, Vec<u8>) {
+    send(
+        app,
+        Method::POST,
+        uri,
+        Some(token),
+        Some(json!({ "brainfart_id": brainfart_id })),
+    )
+    .await
+}
+
+async fn get_bookmarks(app: &Router, token: &str) -> Vec<BrainfartInformation> {
+    let (status, body) = send(
+        app,
+        Method::GET,
+        "/api/getbrainfarts?mode=bookmarks",
+        Some(token),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    parse(&body)
+}
+
+#[tokio::test]
+async fn bookmarks_are_private_to_the_bookmarker() {
+    for repository in backends().await {
+        let app = app_with(repository);
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+        let kept = create_brainfart(&app, &bertil, "worth keeping").await;
+        create_brainfart(&app, &bertil, "not so much").await;
+
+        let (status, body) = bookmark(&app, "/api/addbookmark", &anna, &kept.id).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let brainfart: BrainfartInformation = parse(&body);
+        assert!(brainfart.bookmarked_by_me);
+
+        let bookmarks = get_bookmarks(&app, &anna).await;
+        assert_eq!(bookmarks.len(), 1);
+        assert_eq!(bookmarks[0].id, kept.id);
+        assert!(bookmarks[0].bookmarked_by_me);
+        assert!(get_bookmarks(&app, &bertil).await.is_empty());
+
+        let feed = get_brainfarts(&app, &bertil).await;
+        assert_eq!(feed.len(), 2);
+        assert!(feed.iter().all(|fart| !fart.bookmarked_by_me));
+        let feed = get_brainfarts(&app, &anna).await;
+        assert_eq!(
+            feed.iter()
+                .filter(|fart| fart.bookmarked_by_me)
+                .map(|fart| fart.id.clone())
+                .collect::<Vec<_>>(),
+            vec![kept.id.clone()]
+        );
+    }
+}
+
+#[tokio::test]
+async fn bookmarking_twice_keeps_one_bookmark_and_removing_it_twice_fails() {
+    for repository in backends().await {
+        let app = app_with(repository);
+        let anna = register(&app, "anna", "lösenord").await;
+        let brainfart = create_brainfart(&app, &anna, "my own").await;
+
+        for _ in 0..2 {
+            let (status, _) = bookmark(&app, "/api/addbookmark", &anna, &brainfart.id).await;
+            assert_eq!(status, StatusCode::CREATED);
+        }
+        assert_eq!(get_bookmarks(&app, &anna).await.len(), 1);
+
+        let (status, body) = bookmark(&app, "/api/removebookmark", &anna, &brainfart.id).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(!parse::<BrainfartInformation>(&body).bookmarked_by_me);
+        assert!(get_bookmarks(&app, &anna).await.is_empty());
+
+        let (status, _) = bookmark(&app, "/api/removebookmark", &anna, &brainfart.id).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
+
+#[tokio::test]
+async fn bookmarking_an_unknown_brainfart_fails() {
+    for repository in backends().await {
+        let app = app_with(repository);
+        let anna = register(&app, "anna", "lösenord").await;
+
+        let unknown = sqlx::types::Uuid::new_v4().to_string();
+        let (status, _) = bookmark(&app, "/api/addbookmark", &anna, &unknown).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = bookmark(&app, "/api/addbookmark", &anna, "not an id").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,193 @@
+use std::{net::TcpListener, sync::Arc};
+
+use axum::{
+    body::Body,
+    http::{header, Request},
+    Router,
+};
+use client::{
+    reqwest_transport::ReqwestTransport, Client, ClientError, HttpRequest, HttpResponse,
+    RequestBody, Transport, Upload,
+};
+use shared::{
+    BrainfartFilter, BrainfartFilterMode, CreateBrainfartRequest, LeaderboardWindow,
+    ProveOwnsBrainRequest, ReactionKind, RegisterBrainRequest,
+};
+use tower::ServiceExt;
+
+use super::{app_with, backends};
+use crate::database::memory_repository::MemoryRepository;
+
+/// Hands the requests straight to the router, without a network in between.
+struct RouterTransport(Router);
+
+#[axum::async_trait(?Send)]
+impl Transport for RouterTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, ClientError> {
+        let mut builder = Request::builder()
+            .method(request.method.to_string().as_str())
+            .uri(&request.path);
+        if let Some(token) = &request.token {
+            builder = builder.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        let request = match request.body {
+            RequestBody::Empty => builder.body(Body::empty()),
+            RequestBody::Json(json) => builder
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(json)),
+            RequestBody::Multipart(_) => {
+                return Err(ClientError::Transport("Not supported here".to_string()))
+            }
+        }
+        .unwrap();
+        let response = self.0.clone().oneshot(request).await.unwrap();
+        let status = response.status().as_u16();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        Ok(HttpResponse {
+            status,
+            body: body.to_vec(),
+        })
+    }
+}
+
+fn register_request(brainname: &str) -> RegisterBrainRequest {
+    RegisterBrainRequest::create(
+        brainname.to_string(),
+        "lösenord".to_string(),
+        "lösenord".to_string(),
+    )
+}
+
+#[tokio::test]
+async fn the_client_speaks_the_api() {
+    for repository in backends().await {
+        let mut client = Client::new(RouterTransport(app_with(repository)));
+        let anna = client.register(&register_request("anna")).await.unwrap();
+        let bertil = client.register(&register_request("bertil")).await.unwrap();
+
+        client.set_token(Some(anna));
+        let brainfart = client
+            .create_brainfart(&CreateBrainfartRequest::create(
+                "idea".to_string(),
+                "content".to_string(),
+            ))
+            .await
+            .unwrap();
+
+        client.set_token(Some(bertil));
+        let reacted = client
+            .react(&brainfart.id, ReactionKind::Explosion)
+            .await
+            .unwrap();
+        assert_eq!(reacted.blew_minds.len(), 1);
+        assert!(
+            client
+                .add_bookmark(&brainfart.id)
+                .await
+                .unwrap()
+                .bookmarked_by_me
+        );
+
+        let bookmarks = client
+            .get_brainfarts(&BrainfartFilter {
+                mode: BrainfartFilterMode::Bookmarks,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(bookmarks.len(), 1);
+        assert_eq!(bookmarks[0].id, brainfart.id);
+
+        let leaderboard = client
+            .get_leaderboard(LeaderboardWindow::All)
+            .await
+            .unwrap();
+        assert_eq!(leaderboard.window, LeaderboardWindow::All);
+        assert_eq!(leaderboard.masterminds[0].brainname, "anna");
+
+        let report = client.report(&brainfart.id, "spam").await.unwrap();
+        assert_eq!(report.reporter_name, "bertil");
+    }
+}
+
+#[tokio::test]
+async fn refusals_come_back_as_api_errors() {
+    for repository in backends().await {
+        let mut client = Client::new(RouterTransport(app_with(repository)));
+
+        let err = client
+            .log_in(&ProveOwnsBrainRequest::create(
+                "ingen".to_string(),
+                "lösenord".to_string(),
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ClientError::Api {
+                status: 401,
+                message: "Unknown brain!".to_string()
+            }
+        );
+
+        let token = client.register(&register_request("anna")).await.unwrap();
+        client.set_token(Some(token));
+        let err = client.get_draft().await.unwrap_err();
+        assert_eq!(err.status(), Some(404));
+        let err = client.get_reports().await.unwrap_err();
+        assert_eq!(err.status(), Some(403));
+    }
+}
+
+#[tokio::test]
+async fn the_native_transport_reaches_a_running_server() {
+    // Served under a path, which the transport must keep.
+    let app = Router::new().nest("/brains", app_with(Arc::new(MemoryRepository::create())));
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app.into_make_service())
+            .await
+            .unwrap();
+    });
+    let transport = ReqwestTransport::create(&format!("http://{}/brains/", address)).unwrap();
+    let mut client = Client::new(transport);
+
+    let token = client.register(&register_request("anna")).await.unwrap();
+    client.set_token(Some(token));
+    let brainfart = client
+        .create_brainfart(&CreateBrainfartRequest::create(
+            "notes".to_string(),
+            "content".to_string(),
+        ))
+        .await
+        .unwrap();
+    let with_attachments = client
+        .add_attachments(
+            &brainfart.id,
+            vec![Upload {
+                filename: "anteckningar.txt".to_string(),
+                content_type: "text/plain".to_string(),
+                data: b"hej".to_vec(),
+            }],
+        )
+        .await
+        .unwrap();
+    assert_eq!(with_attachments.attachments.len(), 1);
+    assert_eq!(with_attachments.attachments[0].filename, "anteckningar.txt");
+
+    let err = client
+        .add_attachments(
+            &brainfart.id,
+            vec![Upload {
+                filename: "program.exe".to_string(),
+                content_type: "application/octet-stream".to_string(),
+                data: vec![0; 8],
+            }],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.status(), Some(415));
+}
@@ -0,0 +1,172 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    http::{Method, StatusCode},
+    Router,
+};
+use chrono::Local;
+use serde_json::json;
+use shared::{Brainfart, BrainfartDraft};
+
+use super::{app_with, assert_out_of_reach, backends, get_brainfarts, parse, register, send};
+use crate::{
+    database::{memory_repository::MemoryRepository, repository::SharedRepository},
+    scheduling,
+};
+
+async fn schedule_brainfart(
+    app: &Router,
+    token: &str,
+    title: &str,
+    publish_at: chrono::DateTime<Local>,
+) -> (StatusCode, Vec<u8>) {
+    send(
+        app,
+        Method::POST,
+        "/api/createbrainfart",
+        Some(token),
+        Some(json!({"title": title, "content": "content", "publish_at": publish_at})),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn a_saved_draft_replaces_the_previous_one_until_it_is_deleted() {
+    for repository in backends().await {
+        let app = app_with(repository);
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+
+        let (status, _) = send(&app, Method::GET, "/api/getdraft", Some(&anna), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        for title in ["half a thou", "half a thought"] {
+            let (status, body) = send(
+                &app,
+                Method::POST,
+                "/api/savedraft",
+                Some(&anna),
+                Some(json!({"title": title, "content": ""})),
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(parse::<BrainfartDraft>(&body).title, title);
+        }
+
+        let (status, body) = send(&app, Method::GET, "/api/getdraft", Some(&anna), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let draft: BrainfartDraft = parse(&body);
+        assert_eq!(draft.title, "half a thought");
+        assert_eq!(draft.content, "");
+        assert_eq!(draft.publish_at, None);
+        let (status, _) = send(&app, Method::GET, "/api/getdraft", Some(&bertil), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, _) = send(&app, Method::POST, "/api/deletedraft", Some(&anna), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(&app, Method::POST, "/api/deletedraft", Some(&anna), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
+
+#[tokio::test]
+async fn scheduled_brainfarts_join_the_feed_once_published() {
+    for repository in backends().await {
+        let app = app_with(repository.clone());
+        let anna = register(&app, "anna", "lösenord").await;
+        let publish_at = Local::now() + chrono::Duration::hours(1);
+
+        let (status, body) = schedule_brainfart(&app, &anna, "later", publish_at).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let scheduled: Brainfart = parse(&body);
+        assert!(get_brainfarts(&app, &anna).await.is_empty());
+
+        assert_eq!(
+            repository
+                .publish_scheduled_brainfarts(Local::now())
+                .await
+                .map(|published| published.len()),
+            Some(0)
+        );
+        assert_eq!(
+            repository
+                .publish_scheduled_brainfarts(publish_at + chrono::Duration::minutes(1))
+                .await
+                .map(|published| published.len()),
+            Some(1)
+        );
+        let feed = get_brainfarts(&app, &anna).await;
+        assert_eq!(feed.len(), 1);
+        assert_eq!(feed[0].id, scheduled.id);
+        assert_eq!(
+            feed[0].birthdate.timestamp_millis(),
+            publish_at.timestamp_millis()
+        );
+    }
+}
+
+#[tokio::test]
+async fn brainfarts_cannot_be_scheduled_for_the_past() {
+    let app = app_with(Arc::new(MemoryRepository::create()));
+    let anna = register(&app, "anna", "lösenord").await;
+    let publish_at = Local::now() - chrono::Duration::minutes(1);
+
+    let (status, _) = schedule_brainfart(&app, &anna, "too late", publish_at).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn the_publisher_publishes_due_brainfarts_in_the_background() {
+    let repository: SharedRepository = Arc::new(MemoryRepository::create());
+    let app = app_with(repository.clone());
+    let anna = register(&app, "anna", "lösenord").await;
+    let publish_at = Local::now() + chrono::Duration::milliseconds(200);
+    let (status, _) = schedule_brainfart(&app, &anna, "soon", publish_at).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let publisher = scheduling::spawn_publisher(repository, Duration::from_millis(20));
+    tokio::time::sleep(Duration::from_millis(600)).await;
+    publisher.abort();
+
+    assert_eq!(get_brainfarts(&app, &anna).await.len(), 1);
+}
+
+#[tokio::test]
+async fn scheduled_brainfarts_are_out_of_reach_of_other_brains_until_published() {
+    for repository in backends().await {
+        let app = app_with(repository.clone());
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+        let publish_at = Local::now() + chrono::Duration::hours(1);
+        let (status, body) = send(
+            &app,
+            Method::POST,
+            "/api/createbrainfart",
+            Some(&anna),
+            Some(json!({
+                "title": "later",
+                "content": "not yet",
+                "publish_at": publish_at,
+                "poll": {"options": ["ja", "nej"]}
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        let brainfart: Brainfart = parse(&body);
+        assert_out_of_reach(&app, &bertil, &brainfart).await;
+
+        repository
+            .publish_scheduled_brainfarts(publish_at + chrono::Duration::minutes(1))
+            .await
+            .unwrap();
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            "/api/addbookmark",
+            Some(&bertil),
+            Some(json!({ "brainfart_id": brainfart.id })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+}
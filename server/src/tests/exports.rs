@@ -0,0 +1,164 @@
+use std::sync::Arc;
+
+use axum::{
+    http::{Method, StatusCode},
+    Router,
+};
+use chrono::Local;
+use serde_json::json;
+use shared::{BrainExport, ProveOwnsBrainRequest, ReactionKind};
+use sqlx::types::Uuid;
+
+use super::{app_with, create_brainfart, parse, register, send};
+use crate::{
+    admin::{self, AdminCommand, BrainsCommand},
+    database::{self, memory_repository::MemoryRepository, repository::SharedRepository},
+    storage::memory_storage::MemoryStorage,
+};
+
+/// Pairs of empty repositories of the same backend, to export from one and import into the other.
+async fn backend_pairs() -> Vec<(SharedRepository, SharedRepository)> {
+    vec![
+        (
+            Arc::new(MemoryRepository::create()),
+            Arc::new(MemoryRepository::create()),
+        ),
+        (
+            database::connect("sqlite::memory:", 1).await.unwrap(),
+            database::connect("sqlite::memory:", 1).await.unwrap(),
+        ),
+    ]
+}
+
+async fn export(app: &Router, token: &str) -> BrainExport {
+    let (status, body) = send(app, Method::GET, "/api/v1/export", Some(token), None).await;
+    assert_eq!(status, StatusCode::OK);
+    parse(&body)
+}
+
+async fn import(repository: &SharedRepository, archive: &BrainExport) -> Result<String, String> {
+    let path = std::env::temp_dir().join(format!("brains-export-{}.json", Uuid::new_v4()));
+    std::fs::write(&path, serde_json::to_string(archive).unwrap()).unwrap();
+    let result = admin::run(
+        AdminCommand::Brains(BrainsCommand::Import {
+            path: path.clone(),
+            password: "nytt".to_string(),
+        }),
+        repository.as_ref(),
+        &MemoryStorage::create(),
+    )
+    .await;
+    std::fs::remove_file(path).unwrap();
+    result
+}
+
+#[tokio::test]
+async fn exports_hold_what_the_brain_made() {
+    for (repository, _) in backend_pairs().await {
+        let app = app_with(repository.clone());
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+        let own = create_brainfart(&app, &anna, "mine").await;
+        let other = create_brainfart(&app, &bertil, "his").await;
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            &format!("/api/v1/brainfarts/{}/reactions", other.id),
+            Some(&anna),
+            Some(json!({"kind": "implosion"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        for brainfart in [&own, &other] {
+            let uri = format!("/api/v1/brainfarts/{}", brainfart.id);
+            let (status, _) = send(&app, Method::GET, &uri, Some(&anna), None).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+
+        let archive = export(&app, &anna).await;
+        assert_eq!(archive.brain.brainname, "anna");
+        assert_eq!(archive.brainfarts.len(), 1);
+        assert_eq!(archive.brainfarts[0].id, own.id);
+        assert_eq!(archive.brainfarts[0].title, "mine");
+        assert_eq!(archive.reactions.len(), 1);
+        assert_eq!(archive.reactions[0].brainfart_id, other.id);
+        assert_eq!(archive.reactions[0].kind, ReactionKind::Implosion);
+        let mut viewed = archive
+            .views
+            .iter()
+            .map(|view| view.brainfart_id.clone())
+            .collect::<Vec<_>>();
+        viewed.sort();
+        let mut expected = vec![own.id.clone(), other.id.clone()];
+        expected.sort();
+        assert_eq!(viewed, expected);
+
+        let (status, _) = send(&app, Method::GET, "/api/v1/export", None, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+}
+
+#[tokio::test]
+async fn imports_keep_ids_and_birthdates() {
+    for (source, target) in backend_pairs().await {
+        let app = app_with(source);
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+        let own = create_brainfart(&app, &anna, "mine").await;
+        let other = create_brainfart(&app, &bertil, "his").await;
+        for brainfart_id in [&own.id, &other.id] {
+            let (status, _) = send(
+                &app,
+                Method::POST,
+                &format!("/api/v1/brainfarts/{}/reactions", brainfart_id),
+                Some(&anna),
+                Some(json!({"kind": "explosion"})),
+            )
+            .await;
+            assert_eq!(status, StatusCode::CREATED);
+        }
+        // A rebrainfart of bertil's has nothing to show where bertil's brainfart is missing.
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            &format!("/api/v1/brainfarts/{}/rebrainfarts", other.id),
+            Some(&anna),
+            Some(json!({})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        let archive = export(&app, &anna).await;
+        assert_eq!(archive.brainfarts.len(), 2);
+
+        let output = import(&target, &archive).await.unwrap();
+        assert!(output.contains("with 1 brainfarts, 1 reactions and 0 views"));
+        let brain_id = target
+            .verify_password(&ProveOwnsBrainRequest::create(
+                "anna".to_string(),
+                "nytt".to_string(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(brain_id.to_string(), archive.brain.id);
+        let imported = target.export_brain(&brain_id, Local::now()).await.unwrap();
+        assert_eq!(imported.brain.birthdate, archive.brain.birthdate);
+        assert_eq!(imported.brainfarts.len(), 1);
+        assert_eq!(imported.brainfarts[0], archive.brainfarts[0]);
+        assert_eq!(imported.reactions[0].brainfart_id, own.id);
+
+        // Importing twice would clash with the brain that is already there.
+        assert!(import(&target, &archive).await.is_err());
+    }
+}
+
+#[tokio::test]
+async fn imports_of_other_versions_are_refused() {
+    let repository: SharedRepository = Arc::new(MemoryRepository::create());
+    let app = app_with(repository.clone());
+    let anna = register(&app, "anna", "lösenord").await;
+    let mut archive = export(&app, &anna).await;
+    archive.version += 1;
+    let target: SharedRepository = Arc::new(MemoryRepository::create());
+    let err = import(&target, &archive).await.unwrap_err();
+    assert!(err.contains("only version 1"));
+}
@@ -0,0 +1,261 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use serde_json::json;
+use shared::{find_tags, Brainfart, FeedToken, FEED_LENGTH};
+use tower::ServiceExt;
+
+use super::{app_with, backends, create_brainfart, parse, register, send, state_with};
+use crate::{create_router, database::memory_repository::MemoryRepository, feeds::FeedSettings};
+
+async fn fart(app: &Router, token: &str, title: &str, content: &str) -> Brainfart {
+    let (status, body) = send(
+        app,
+        Method::POST,
+        "/api/v1/brainfarts",
+        Some(token),
+        Some(json!({"title": title, "content": content})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    parse(&body)
+}
+
+async fn get_feed(app: &Router, token: Option<&str>, uri: &str) -> String {
+    let (status, body) = send(app, Method::GET, uri, token, None).await;
+    assert_eq!(status, StatusCode::OK);
+    String::from_utf8(body).unwrap()
+}
+
+#[test]
+fn tags_are_found_in_the_content() {
+    assert_eq!(
+        find_tags("#Rust is fun, (#rust) #web-dev! #tea.time # ## #rust"),
+        vec!["rust", "web-dev", "tea"]
+    );
+    assert!(find_tags("no tags, only a#b and C#").is_empty());
+}
+
+#[tokio::test]
+async fn feeds_hold_the_newest_brainfarts_as_atom_or_rss() {
+    for repository in backends().await {
+        let app = app_with(repository);
+        let anna = register(&app, "anna", "lösenord").await;
+        fart(&app, &anna, "Fish & <chips>", "tasty").await;
+        create_brainfart(&app, &anna, "newer").await;
+
+        let atom = get_feed(&app, Some(&anna), "/api/v1/feeds/brainfarts").await;
+        assert!(atom.contains("<feed xmlns=\"http://www.w3.org/2005/Atom\">"));
+        assert!(atom.contains("<title>Fish &amp; &lt;chips&gt;</title>"));
+        assert!(atom.contains("<author><name>anna</name></author>"));
+        assert!(atom.find("newer").unwrap() < atom.find("Fish").unwrap());
+
+        let rss = get_feed(&app, Some(&anna), "/api/v1/feeds/brainfarts?format=rss").await;
+        assert!(rss.contains("<rss version=\"2.0\""));
+        assert!(rss.contains("<dc:creator>anna</dc:creator>"));
+        assert_eq!(rss.matches("<item>").count(), 2);
+
+        let (status, _) = send(
+            &app,
+            Method::GET,
+            "/api/v1/feeds/brainfarts?format=json",
+            Some(&anna),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+async fn brain_and_tag_feeds_only_hold_their_brainfarts() {
+    for repository in backends().await {
+        let app = app_with(repository);
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+        let crabs = fart(&app, &anna, "crabs", "I like #Rust").await;
+        fart(&app, &bertil, "snakes", "#python is fine").await;
+        fart(&app, &bertil, "#rust too", "me too").await;
+        fart(&app, &anna, "crustaceans", "#rustacean and #rust-lang").await;
+        // A plain rebrainfart has the tags of its original.
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            "/api/rebrainfart",
+            Some(&bertil),
+            Some(json!({"brainfart_id": crabs.id, "commentary": ""})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let bertils = get_feed(&app, Some(&anna), "/api/v1/feeds/brains/bertil").await;
+        assert!(bertils.contains("<title>Brainfarts by bertil</title>"));
+        assert_eq!(bertils.matches("<entry>").count(), 3);
+        assert!(!bertils.contains("<title>crabs</title>"));
+
+        let rust = get_feed(&app, Some(&anna), "/api/v1/feeds/tags/RUST").await;
+        assert!(rust.contains("<title>Brainfarts tagged #rust</title>"));
+        assert_eq!(rust.matches("<entry>").count(), 3);
+        assert!(rust.contains("bertil rebrainfarted crabs"));
+        assert!(!rust.contains("snakes"));
+        assert!(!rust.contains("crustaceans"));
+        let rust = get_feed(&app, Some(&anna), "/api/v1/feeds/tags/%23rust").await;
+        assert_eq!(rust.matches("<entry>").count(), 3);
+        let rustacean = get_feed(&app, Some(&anna), "/api/v1/feeds/tags/rustacean").await;
+        assert_eq!(rustacean.matches("<entry>").count(), 1);
+        let two_tags = get_feed(&app, Some(&anna), "/api/v1/feeds/tags/rustacean%20and").await;
+        assert_eq!(two_tags.matches("<entry>").count(), 0);
+    }
+}
+
+#[tokio::test]
+async fn feeds_pick_their_brainfarts_before_cutting_them_short() {
+    for repository in backends().await {
+        let app = app_with(repository);
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+        fart(&app, &anna, "rare", "#sällsynt").await;
+        for _ in 0..FEED_LENGTH {
+            create_brainfart(&app, &bertil, "common").await;
+        }
+
+        let everything = get_feed(&app, Some(&anna), "/api/v1/feeds/brainfarts").await;
+        assert_eq!(everything.matches("<entry>").count(), FEED_LENGTH);
+        assert!(!everything.contains("rare"));
+        let annas = get_feed(&app, Some(&anna), "/api/v1/feeds/brains/anna").await;
+        assert_eq!(annas.matches("<entry>").count(), 1);
+        let rare = get_feed(&app, Some(&anna), "/api/v1/feeds/tags/S%C3%84LLSYNT").await;
+        assert_eq!(rare.matches("<entry>").count(), 1);
+    }
+}
+
+#[tokio::test]
+async fn feed_tokens_only_open_the_feeds() {
+    let app = app_with(Arc::new(MemoryRepository::create()));
+    let anna = register(&app, "anna", "lösenord").await;
+    create_brainfart(&app, &anna, "hello").await;
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        "/api/v1/feeds/tokens",
+        Some(&anna),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let feed_token: FeedToken = parse(&body);
+
+    let uri = format!("/api/v1/feeds/brainfarts?token={}", feed_token.token);
+    let feed = get_feed(&app, None, &uri).await;
+    assert!(feed.contains("hello"));
+    // The atom id and self link leave the token out.
+    assert!(feed.contains("<id>http://localhost/api/v1/feeds/brainfarts</id>"));
+    assert!(feed.contains(
+        "<link rel=\"self\" href=\"http://localhost/api/v1/feeds/brainfarts?format=atom\"/>"
+    ));
+    assert!(!feed.contains(&feed_token.token));
+
+    let (status, _) = send(
+        &app,
+        Method::GET,
+        "/api/v1/brainfarts",
+        Some(&feed_token.token),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let uri = format!("/api/v1/feeds/brainfarts?token={}", anna);
+    let (status, _) = send(&app, Method::GET, &uri, None, None).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let (status, _) = send(&app, Method::GET, "/api/v1/feeds/brainfarts", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn revoking_feed_tokens_shuts_out_every_one_handed_out_before() {
+    for repository in backends().await {
+        let app = app_with(repository);
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+        let mut feed_tokens = Vec::new();
+        for brain in [&anna, &anna, &bertil] {
+            let (status, body) = send(
+                &app,
+                Method::POST,
+                "/api/v1/feeds/tokens",
+                Some(brain),
+                None,
+            )
+            .await;
+            assert_eq!(status, StatusCode::CREATED);
+            feed_tokens.push(parse::<FeedToken>(&body).token);
+        }
+
+        let (status, _) = send(
+            &app,
+            Method::DELETE,
+            "/api/v1/feeds/tokens",
+            Some(&anna),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        for feed_token in &feed_tokens[..2] {
+            let uri = format!("/api/v1/feeds/brainfarts?token={}", feed_token);
+            let (status, _) = send(&app, Method::GET, &uri, None, None).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+        }
+        // Other brains keep theirs, and new ones work.
+        let uri = format!("/api/v1/feeds/brainfarts?token={}", feed_tokens[2]);
+        get_feed(&app, None, &uri).await;
+        let (_, body) = send(
+            &app,
+            Method::POST,
+            "/api/v1/feeds/tokens",
+            Some(&anna),
+            None,
+        )
+        .await;
+        let uri = format!(
+            "/api/v1/feeds/brainfarts?token={}",
+            parse::<FeedToken>(&body).token
+        );
+        get_feed(&app, None, &uri).await;
+    }
+}
+
+async fn get_feed_from_host(app: &Router, token: &str, host: &str) -> String {
+    let request = Request::builder()
+        .uri("/api/v1/feeds/brainfarts")
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .header(header::HOST, host)
+        .header("x-forwarded-proto", "https")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn a_configured_public_url_wins_over_the_host_header() {
+    let app = app_with(Arc::new(MemoryRepository::create()));
+    let anna = register(&app, "anna", "lösenord").await;
+    let feed = get_feed_from_host(&app, &anna, "brains.example.com").await;
+    assert!(feed.contains("<id>https://brains.example.com/api/v1/feeds/brainfarts</id>"));
+
+    let mut state = state_with(Arc::new(MemoryRepository::create()));
+    state.feeds = FeedSettings {
+        public_url: Some("https://example.com/brains".to_string()),
+    };
+    let app = create_router(state, "./dist");
+    let anna = register(&app, "anna", "lösenord").await;
+    let feed = get_feed_from_host(&app, &anna, "evil.example").await;
+    assert!(feed.contains("<id>https://example.com/brains/api/v1/feeds/brainfarts</id>"));
+    assert!(!feed.contains("evil.example"));
+}
@@ -0,0 +1,128 @@
+use axum::{
+    http::{Method, StatusCode},
+    Router,
+};
+use chrono::{Duration, Local};
+use serde_json::json;
+use shared::{Leaderboard, LeaderboardWindow};
+
+use super::{app_with, backends, create_brainfart, parse, register, send};
+
+async fn react(app: &Router, token: &str, brainfart_id: &str, uri: &str) {
+    let (status, _) = send(
+        app,
+        Method::POST,
+        uri,
+        Some(token),
+        Some(json!({ "brainfart_id": brainfart_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+async fn get_leaderboard(app: &Router, token: &str, uri: &str) -> Leaderboard {
+    let (status, body) = send(app, Method::GET, uri, Some(token), None).await;
+    assert_eq!(status, StatusCode::OK);
+    parse(&body)
+}
+
+#[tokio::test]
+async fn leaderboards_count_explosions_only() {
+    for repository in backends().await {
+        let app = app_with(repository);
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+        let cecilia = register(&app, "cecilia", "lösenord").await;
+        let loved = create_brainfart(&app, &anna, "loved").await;
+        let liked = create_brainfart(&app, &bertil, "liked").await;
+        let disliked = create_brainfart(&app, &cecilia, "disliked").await;
+        let also_liked = create_brainfart(&app, &cecilia, "also liked").await;
+        react(&app, &bertil, &loved.id, "/api/registermindexplosion").await;
+        react(&app, &cecilia, &loved.id, "/api/registermindexplosion").await;
+        react(&app, &anna, &liked.id, "/api/registermindexplosion").await;
+        react(&app, &anna, &also_liked.id, "/api/registermindexplosion").await;
+        react(&app, &anna, &disliked.id, "/api/registermindimplosion").await;
+        react(&app, &bertil, &disliked.id, "/api/registermindimplosion").await;
+
+        let leaderboard = get_leaderboard(&app, &anna, "/api/leaderboard").await;
+        assert_eq!(leaderboard.window, LeaderboardWindow::Week);
+        // Equally loved brainfarts are newest first.
+        assert_eq!(
+            leaderboard
+                .brainfarts
+                .iter()
+                .map(|brainfart| (brainfart.title.as_str(), brainfart.explosions))
+                .collect::<Vec<_>>(),
+            vec![("loved", 2), ("also liked", 1), ("liked", 1)]
+        );
+        assert_eq!(leaderboard.brainfarts[0].mastermind_name, "anna");
+        // Equally mind blowing masterminds are in alphabetical order.
+        assert_eq!(
+            leaderboard
+                .masterminds
+                .iter()
+                .map(|mastermind| (mastermind.brainname.as_str(), mastermind.explosions))
+                .collect::<Vec<_>>(),
+            vec![("anna", 2), ("bertil", 1), ("cecilia", 1)]
+        );
+    }
+}
+
+#[tokio::test]
+async fn leaderboards_only_count_explosions_in_the_window() {
+    for repository in backends().await {
+        let app = app_with(repository.clone());
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+        let brainfart = create_brainfart(&app, &anna, "loved").await;
+        react(&app, &bertil, &brainfart.id, "/api/registermindexplosion").await;
+
+        let all = get_leaderboard(&app, &anna, "/api/leaderboard?window=all").await;
+        assert_eq!(all.window, LeaderboardWindow::All);
+        assert_eq!(all.brainfarts.len(), 1);
+        let month = get_leaderboard(&app, &anna, "/api/leaderboard?window=month").await;
+        assert_eq!(month.brainfarts, all.brainfarts);
+        assert_eq!(month.masterminds, all.masterminds);
+
+        let later = Some(Local::now() + Duration::hours(1));
+        assert_eq!(repository.get_top_brainfarts(later, 10).await, Some(vec![]));
+        assert_eq!(
+            repository.get_top_masterminds(later, 10).await,
+            Some(vec![])
+        );
+        assert_eq!(
+            repository
+                .get_top_brainfarts(None, 10)
+                .await
+                .map(|brainfarts| brainfarts.len()),
+            Some(1)
+        );
+    }
+}
+
+#[tokio::test]
+async fn leaderboards_leave_out_hidden_brainfarts_and_unknown_windows() {
+    for repository in backends().await {
+        let app = app_with(repository.clone());
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+        let brainfart = create_brainfart(&app, &anna, "offensive").await;
+        react(&app, &bertil, &brainfart.id, "/api/registermindexplosion").await;
+        let brainfart_id = sqlx::types::Uuid::parse_str(&brainfart.id).unwrap();
+        assert!(repository.set_brainfart_hidden(&brainfart_id, true).await);
+
+        let leaderboard = get_leaderboard(&app, &anna, "/api/leaderboard?window=all").await;
+        assert!(leaderboard.brainfarts.is_empty());
+        assert!(leaderboard.masterminds.is_empty());
+
+        let (status, _) = send(
+            &app,
+            Method::GET,
+            "/api/leaderboard?window=decade",
+            Some(&anna),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,209 @@
+use std::{
+    net::{SocketAddr, TcpListener},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Redirect},
+    routing::get,
+    Router,
+};
+use serde_json::json;
+use shared::{find_urls, BrainfartInformation, LinkPreview};
+
+use super::{backends, create_router, get_brainfarts, register, send, state_with};
+use crate::link_previews::{
+    http_fetcher::{is_public_address, HttpPreviewFetcher},
+    PreviewFetcher,
+};
+
+const PAGE: &str = r#"<!doctype html>
+<html><head>
+<title>Not this title</title>
+<meta property="og:title" content="Brains &amp; farts">
+<meta property='og:description' content="Everything   about
+    brains">
+<META NAME="twitter:title" CONTENT="Not this one either">
+<meta property="og:image" content="/images/brain.png" />
+<meta property="og:site_name" content="Hjärnor">
+</head><body></body></html>"#;
+
+/// Serves pages on a local port, counting the requests. Answers with its address.
+fn start_stub_server(requests: Arc<AtomicUsize>) -> SocketAddr {
+    async fn page(State(requests): State<Arc<AtomicUsize>>) -> impl IntoResponse {
+        requests.fetch_add(1, Ordering::SeqCst);
+        ([(header::CONTENT_TYPE, "text/html; charset=utf-8")], PAGE)
+    }
+    let stub = Router::new()
+        .route("/page", get(page))
+        .route("/redirect", get(|| async { Redirect::temporary("/page") }))
+        .route("/untitled", get(|| async { "<html></html>" }))
+        .route(
+            "/missing",
+            get(|| async { (StatusCode::NOT_FOUND, "There is nothing here") }),
+        )
+        .with_state(requests);
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(stub.into_make_service())
+            .await
+            .unwrap();
+    });
+    address
+}
+
+fn expected_preview(address: SocketAddr, path: &str) -> LinkPreview {
+    LinkPreview {
+        url: format!("http://{}{}", address, path),
+        title: "Brains & farts".to_string(),
+        description: Some("Everything about brains".to_string()),
+        image_url: Some(format!("http://{}/images/brain.png", address)),
+        site_name: Some("Hjärnor".to_string()),
+    }
+}
+
+/// Previews are fetched in the background, so wait for them to show up.
+async fn wait_for_previews(app: &Router, token: &str) -> Vec<BrainfartInformation> {
+    for _ in 0..250 {
+        let brainfarts = get_brainfarts(app, token).await;
+        if brainfarts
+            .iter()
+            .all(|brainfart| !brainfart.link_previews.is_empty())
+        {
+            return brainfarts;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("The link previews were never fetched");
+}
+
+#[test]
+fn urls_are_found_in_the_content() {
+    assert_eq!(
+        find_urls(
+            "See (https://example.com/a?b=c), http://example.org. and https://example.com/a?b=c!"
+        ),
+        vec!["https://example.com/a?b=c", "http://example.org"]
+    );
+    assert!(find_urls("no links, only https:// and ftp://example.com").is_empty());
+    assert_eq!(
+        find_urls("http://a.se http://b.se http://c.se http://d.se").len(),
+        3
+    );
+}
+
+#[test]
+fn only_public_addresses_are_fetched() {
+    for address in [
+        "127.0.0.1",
+        "10.1.2.3",
+        "172.16.0.1",
+        "192.168.1.1",
+        "169.254.169.254",
+        "100.64.0.1",
+        "0.0.0.0",
+        "::1",
+        "fd00::1",
+        "fe80::1",
+        "::ffff:127.0.0.1",
+        // NAT64 and 6to4 addresses lead to the IPv4 address inside them.
+        "64:ff9b::127.0.0.1",
+        "64:ff9b::a9fe:a9fe",
+        "2002:7f00:1::1",
+        "2002:c0a8:101::",
+    ] {
+        assert!(!is_public_address(address.parse().unwrap()), "{}", address);
+    }
+    for address in [
+        "93.184.216.34",
+        "2606:2800:220:1:248:1893:25c8:1946",
+        "64:ff9b::93.184.216.34",
+        "2002:5db8:d822::1",
+    ] {
+        assert!(is_public_address(address.parse().unwrap()), "{}", address);
+    }
+}
+
+#[tokio::test]
+async fn the_fetcher_reads_opengraph_tags() {
+    let address = start_stub_server(Arc::new(AtomicUsize::new(0)));
+    let fetcher = HttpPreviewFetcher::create(true);
+
+    let url = format!("http://{}/redirect", address);
+    assert_eq!(
+        fetcher.fetch(&url).await,
+        Some(expected_preview(address, "/redirect"))
+    );
+    for path in ["/untitled", "/missing"] {
+        assert_eq!(
+            fetcher.fetch(&format!("http://{}{}", address, path)).await,
+            None
+        );
+    }
+}
+
+#[tokio::test]
+async fn the_fetcher_refuses_private_addresses() {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let address = start_stub_server(requests.clone());
+    let fetcher = HttpPreviewFetcher::create(false);
+
+    for url in [
+        format!("http://{}/page", address),
+        format!("http://localhost:{}/page", address.port()),
+    ] {
+        assert_eq!(fetcher.fetch(&url).await, None);
+    }
+    assert_eq!(requests.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn brainfarts_show_cached_previews_of_their_links() {
+    for repository in backends().await {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let address = start_stub_server(requests.clone());
+        let mut state = state_with(repository.clone());
+        state.link_previews = Arc::new(HttpPreviewFetcher::create(true));
+        let app = create_router(state, "./dist");
+        let anna = register(&app, "anna", "lösenord").await;
+
+        for title in ["first", "second"] {
+            let content = format!("Read http://{0}/untitled and http://{0}/page.", address);
+            let (status, _) = send(
+                &app,
+                axum::http::Method::POST,
+                "/api/createbrainfart",
+                Some(&anna),
+                Some(json!({"title": title, "content": content})),
+            )
+            .await;
+            assert_eq!(status, StatusCode::CREATED);
+            wait_for_previews(&app, &anna).await;
+        }
+
+        let brainfarts = wait_for_previews(&app, &anna).await;
+        for brainfart in brainfarts.iter() {
+            assert_eq!(
+                brainfart.link_previews,
+                vec![expected_preview(address, "/page")]
+            );
+        }
+        // The page was fetched for the first brainfart only, and the page without a preview,
+        // which was fetched before it, is remembered as such.
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        assert!(
+            repository
+                .has_link_preview(&format!("http://{}/untitled", address))
+                .await
+        );
+    }
+}
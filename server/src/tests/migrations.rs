@@ -0,0 +1,86 @@
+use std::str::FromStr;
+
+use shared::{BrainfartFilter, DeletionMode, Role, REMOVED_BRAIN_NAME};
+use sqlx::{sqlite::SqliteConnectOptions, types::Uuid, ConnectOptions, Connection, Executor};
+
+use crate::database;
+
+const SQLITE_BASELINE: &str = include_str!("../../../migrations/sqlite/0001_baseline.sql");
+const ANNA: &str = "6f1c0b5e-3d2a-4c1b-9e8f-0a1b2c3d4e5f";
+const BERTIL: &str = "7a2d1c6f-4e3b-4d2c-8f9a-1b2c3d4e5f60";
+
+/// A database as the first release with SQLite support left it: anna's brainfart, exploded by
+/// bertil.
+async fn old_sqlite_database() -> (std::path::PathBuf, String) {
+    let path = std::env::temp_dir().join(format!("brains-{}.db", Uuid::new_v4()));
+    let url = format!("sqlite:{}", path.display());
+    let mut connection = SqliteConnectOptions::from_str(&url)
+        .unwrap()
+        .create_if_missing(true)
+        .connect()
+        .await
+        .unwrap();
+    connection.execute(SQLITE_BASELINE).await.unwrap();
+    connection
+        .execute(
+            format!(
+                "insert into brains(id, brainname, password) values('{ANNA}', 'anna', 'x'), ('{BERTIL}', 'bertil', 'x');
+                insert into brainfarts(title, content, mastermind) values('old', 'from #Förr', '{ANNA}');
+                insert into mindsblownbyfarts(brainfartid, brainid, explosion)
+                    select id, '{BERTIL}', true from brainfarts;"
+            )
+            .as_str(),
+        )
+        .await
+        .unwrap();
+    connection.close().await.unwrap();
+    (path, url)
+}
+
+#[tokio::test]
+async fn older_sqlite_databases_are_brought_up_to_date() {
+    let (path, url) = old_sqlite_database().await;
+    let repository = database::connect(&url, 1).await.unwrap();
+    let anna = Uuid::parse_str(ANNA).unwrap();
+    let bertil = Uuid::parse_str(BERTIL).unwrap();
+
+    let brains = repository.list_brains().await.unwrap();
+    assert_eq!(brains.len(), 2);
+    assert!(brains
+        .iter()
+        .all(|brain| brain.role == Role::Brain && !brain.disabled));
+    let brainfarts = repository
+        .get_brainfarts_using_filter(BrainfartFilter::default(), &anna)
+        .await
+        .unwrap();
+    assert_eq!(brainfarts.len(), 1);
+    assert_eq!(brainfarts[0].blew_minds[0].get_name(), "bertil");
+    // Their tags were filled in.
+    let tagged = repository
+        .get_brainfarts_using_filter(
+            BrainfartFilter {
+                tag: Some("förr".to_string()),
+                ..Default::default()
+            },
+            &anna,
+        )
+        .await
+        .unwrap();
+    assert_eq!(tagged.len(), 1);
+
+    // The foreign keys were rebuilt to let brains go without their reactions.
+    assert!(repository
+        .delete_brain(&bertil, DeletionMode::Anonymize)
+        .await
+        .is_some());
+    let brainfarts = repository
+        .get_brainfarts_using_filter(BrainfartFilter::default(), &anna)
+        .await
+        .unwrap();
+    assert_eq!(brainfarts[0].blew_minds[0].get_name(), REMOVED_BRAIN_NAME);
+
+    // A second start has nothing left to migrate.
+    drop(repository);
+    database::connect(&url, 1).await.unwrap();
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,570 @@
+use std::sync::{Arc, OnceLock};
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use jwt_simple::prelude::RS384KeyPair;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use shared::{Brainfart, BrainfartInformation, Role};
+use sqlx::{Connection, Executor, PgConnection};
+use tower::ServiceExt;
+
+use crate::{
+    accounts::AccountSettings,
+    attachments::AttachmentSettings,
+    authentication::JwtKeys,
+    create_router,
+    database::{
+        self,
+        memory_repository::MemoryRepository,
+        repository::{Repository, SharedRepository},
+    },
+    feeds::FeedSettings,
+    link_previews::http_fetcher::HttpPreviewFetcher,
+    monitoring::MonitoringSettings,
+    storage::memory_storage::MemoryStorage,
+    AppState,
+};
+
+mod accounts;
+mod admin;
+mod attachments;
+mod authorization;
+mod bookmarks;
+mod client;
+mod configuration;
+mod drafts;
+mod exports;
+mod feeds;
+mod leaderboards;
+mod link_previews;
+mod migrations;
+mod moderation;
+mod monitoring;
+mod openapi;
+mod polls;
+mod ranking;
+mod rebrainfarts;
+mod relations;
+mod routes;
+mod webhooks;
+
+fn jwt_keys() -> Arc<JwtKeys> {
+    static KEYS: OnceLock<Arc<JwtKeys>> = OnceLock::new();
+    KEYS.get_or_init(|| {
+        let key_pair = RS384KeyPair::generate(2048).unwrap();
+        let public_key = key_pair.public_key();
+        Arc::new(JwtKeys::create(key_pair, public_key, 1))
+    })
+    .clone()
+}
+
+fn state_with(repository: SharedRepository) -> AppState {
+    AppState {
+        repository,
+        jwt_keys: jwt_keys(),
+        attachments: AttachmentSettings {
+            storage: Arc::new(MemoryStorage::create()),
+            max_bytes: 64 * 1024,
+        },
+        link_previews: Arc::new(HttpPreviewFetcher::create(false)),
+        accounts: AccountSettings {
+            deletion_grace: chrono::Duration::days(14),
+        },
+        metrics: Arc::default(),
+        feeds: FeedSettings::default(),
+        monitoring: MonitoringSettings::default(),
+    }
+}
+
+/// One of each backend the tests run against, all of them empty. Postgres is left out when
+/// `DATABASE_URL` is not set.
+async fn backends() -> Vec<SharedRepository> {
+    let mut backends: Vec<SharedRepository> = vec![
+        Arc::new(MemoryRepository::create()),
+        database::connect("sqlite::memory:", 1).await.unwrap(),
+    ];
+    if let Ok(database_url) = std::env::var("DATABASE_URL") {
+        backends.push(postgres_backend(&database_url).await);
+    }
+    backends
+}
+
+/// A new database next to the one in `DATABASE_URL`, created from schema.sql, so each test has
+/// one of its own. The databases of the previous run are dropped by the first test to get here.
+async fn postgres_backend(database_url: &str) -> SharedRepository {
+    static STALE_DATABASES_DROPPED: tokio::sync::OnceCell<()> = tokio::sync::OnceCell::const_new();
+    const PREFIX: &str = "brains_test_";
+
+    let mut connection = PgConnection::connect(database_url).await.unwrap();
+    STALE_DATABASES_DROPPED
+        .get_or_init(|| async {
+            let stale: Vec<(String,)> =
+                sqlx::query_as("select datname from pg_database where starts_with(datname, $1)")
+                    .bind(PREFIX)
+                    .fetch_all(&mut PgConnection::connect(database_url).await.unwrap())
+                    .await
+                    .unwrap();
+            for (name,) in stale {
+                sqlx::query(&format!("drop database if exists {} with (force)", name))
+                    .execute(&mut PgConnection::connect(database_url).await.unwrap())
+                    .await
+                    .unwrap();
+            }
+        })
+        .await;
+    let name = format!("{}{}", PREFIX, uuid::Uuid::new_v4().simple());
+    connection
+        .execute(format!("create database {}", name).as_str())
+        .await
+        .unwrap();
+    connection.close().await.unwrap();
+
+    let mut url = url::Url::parse(database_url).unwrap();
+    url.set_path(&name);
+    let mut connection = PgConnection::connect(url.as_str()).await.unwrap();
+    connection
+        .execute(include_str!("../../../schema.sql"))
+        .await
+        .unwrap();
+    connection.close().await.unwrap();
+    database::connect(url.as_str(), 2).await.unwrap()
+}
+
+fn app_with(repository: SharedRepository) -> Router {
+    create_router(state_with(repository), "./dist")
+}
+
+fn app() -> Router {
+    app_with(Arc::new(MemoryRepository::create()))
+}
+
+async fn sqlite_app() -> Router {
+    app_with(database::connect("sqlite::memory:", 1).await.unwrap())
+}
+
+async fn send(
+    app: &Router,
+    method: Method,
+    uri: &str,
+    token: Option<&str>,
+    body: Option<Value>,
+) -> (StatusCode, Vec<u8>) {
+    let mut request = Request::builder().method(method).uri(uri);
+    if let Some(token) = token {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+    let request = match body {
+        Some(body) => request
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string())),
+        None => request.body(Body::empty()),
+    }
+    .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    (status, bytes.to_vec())
+}
+
+fn parse<T: DeserializeOwned>(bytes: &[u8]) -> T {
+    serde_json::from_slice(bytes).unwrap()
+}
+
+async fn register(app: &Router, brainname: &str, password: &str) -> String {
+    let (status, body) = send(
+        app,
+        Method::POST,
+        "/api/registerbrain",
+        None,
+        Some(json!({"brainname": brainname, "password": password, "password_extra": password})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    parse(&body)
+}
+
+async fn log_in(app: &Router, brainname: &str, password: &str) -> (StatusCode, String) {
+    let (status, body) = send(
+        app,
+        Method::POST,
+        "/api/loginasbrain",
+        None,
+        Some(json!({"brainname": brainname, "password": password})),
+    )
+    .await;
+    (status, parse(&body))
+}
+
+/// Registers an admin and returns its token, which has to come from a login so it carries the role.
+async fn admin_token(app: &Router, repository: &dyn Repository) -> String {
+    register(app, "admin", "lösenord").await;
+    assert!(repository.set_brain_role("admin", Role::Admin).await);
+    let (status, token) = log_in(app, "admin", "lösenord").await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    token
+}
+
+async fn create_brainfart(app: &Router, token: &str, title: &str) -> Brainfart {
+    let (status, body) = send(
+        app,
+        Method::POST,
+        "/api/createbrainfart",
+        Some(token),
+        Some(json!({"title": title, "content": "content"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    parse(&body)
+}
+
+async fn get_brainfarts(app: &Router, token: &str) -> Vec<BrainfartInformation> {
+    let (status, body) = send(app, Method::GET, "/api/getbrainfarts", Some(token), None).await;
+    assert_eq!(status, StatusCode::OK);
+    parse(&body)
+}
+
+/// Reacting to, bookmarking, voting in and rebrainfarting the brainfart all answer as if there
+/// were no such brainfart, without giving away its content.
+async fn assert_out_of_reach(app: &Router, token: &str, brainfart: &Brainfart) {
+    let id = &brainfart.id;
+    for (uri, body) in [
+        ("/api/registermindexplosion", json!({ "brainfart_id": id })),
+        ("/api/registermindimplosion", json!({ "brainfart_id": id })),
+        ("/api/addbookmark", json!({ "brainfart_id": id })),
+        ("/api/vote", json!({ "brainfart_id": id, "options": [0] })),
+        (
+            "/api/rebrainfart",
+            json!({ "brainfart_id": id, "commentary": "" }),
+        ),
+    ] {
+        let (status, body) = send(app, Method::POST, uri, Some(token), Some(body)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{}", uri);
+        assert!(
+            !String::from_utf8_lossy(&body).contains(&brainfart.content),
+            "{}",
+            uri
+        );
+    }
+    let uri = format!("/api/v1/brainfarts/{}", id);
+    let (status, body) = send(app, Method::GET, &uri, Some(token), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(!String::from_utf8_lossy(&body).contains(&brainfart.content));
+}
+
+#[tokio::test]
+async fn registering_returns_a_valid_token() {
+    let app = app();
+    let token = register(&app, "hjärna", "lösenord").await;
+    let information = jwt_keys().konvertera_jwt(&token).unwrap();
+    assert_eq!(information.brainname, "hjärna");
+}
+
+#[tokio::test]
+async fn registering_the_same_name_twice_fails() {
+    let app = app();
+    register(&app, "hjärna", "lösenord").await;
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/registerbrain",
+        None,
+        Some(json!({"brainname": "hjärna", "password": "a", "password_extra": "a"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[tokio::test]
+async fn registering_with_invalid_json_is_a_bad_request() {
+    let (status, body) = send(
+        &app(),
+        Method::POST,
+        "/api/registerbrain",
+        None,
+        Some(json!({"brainname": "hjärna"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body, b"Invalid data");
+}
+
+#[tokio::test]
+async fn logging_in_checks_the_password() {
+    let app = app();
+    register(&app, "hjärna", "lösenord").await;
+
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        "/api/loginasbrain",
+        None,
+        Some(json!({"brainname": "hjärna", "password": "lösenord"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    let token: String = parse(&body);
+    assert!(jwt_keys().konvertera_jwt(&token).is_some());
+
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/loginasbrain",
+        None,
+        Some(json!({"brainname": "hjärna", "password": "fel"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn creating_a_brainfart_requires_a_valid_token() {
+    let app = app();
+    let body = json!({"title": "title", "content": "content"});
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/createbrainfart",
+        None,
+        Some(body.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/createbrainfart",
+        Some("not a token"),
+        Some(body),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn brainfarts_are_listed_newest_first() {
+    let app = app();
+    let token = register(&app, "hjärna", "lösenord").await;
+    let first = create_brainfart(&app, &token, "first").await;
+    let second = create_brainfart(&app, &token, "second").await;
+    assert_eq!(first.title, "first");
+
+    let brainfarts = get_brainfarts(&app, &token).await;
+    let ids = brainfarts.iter().map(|fart| &fart.id).collect::<Vec<_>>();
+    assert_eq!(ids, vec![&second.id, &first.id]);
+    assert_eq!(brainfarts[0].mastermind_name, "hjärna");
+}
+
+#[tokio::test]
+async fn listing_brainfarts_does_not_count_as_viewing_them() {
+    let repository: SharedRepository = Arc::new(MemoryRepository::create());
+    let app = app_with(repository.clone());
+    let token = register(&app, "hjärna", "lösenord").await;
+    create_brainfart(&app, &token, "first").await;
+    get_brainfarts(&app, &token).await;
+    assert_eq!(repository.get_statistics().await.unwrap().views, 0);
+}
+
+#[tokio::test]
+async fn opening_a_brainfart_counts_as_viewing_it_once_per_brain() {
+    for repository in backends().await {
+        let app = app_with(repository.clone());
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+        let brainfart = create_brainfart(&app, &anna, "first").await;
+        let uri = format!("/api/v1/brainfarts/{}", brainfart.id);
+        for token in [&anna, &bertil, &bertil] {
+            let (status, body) = send(&app, Method::GET, &uri, Some(token), None).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(parse::<BrainfartInformation>(&body).title, "first");
+        }
+        assert_eq!(repository.get_statistics().await.unwrap().views, 2);
+
+        let uri = format!("/api/v1/brainfarts/{}", uuid::Uuid::new_v4());
+        let (status, _) = send(&app, Method::GET, &uri, Some(&anna), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(repository.get_statistics().await.unwrap().views, 2);
+    }
+}
+
+#[tokio::test]
+async fn brainfarts_can_be_filtered_by_brain_and_tag_and_limited() {
+    for repository in backends().await {
+        let app = app_with(repository);
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+        create_brainfart(&app, &anna, "gammal #kaffe").await;
+        create_brainfart(&app, &anna, "ny #kaffe").await;
+        create_brainfart(&app, &bertil, "#kaffe").await;
+        create_brainfart(&app, &anna, "#te").await;
+
+        let titles = |uri: &'static str| {
+            let app = app.clone();
+            let bertil = bertil.clone();
+            async move {
+                let (status, body) = send(&app, Method::GET, uri, Some(&bertil), None).await;
+                assert_eq!(status, StatusCode::OK);
+                parse::<Vec<BrainfartInformation>>(&body)
+                    .into_iter()
+                    .map(|brainfart| brainfart.title)
+                    .collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(
+            titles("/api/getbrainfarts?brainname=anna&tag=kaffe").await,
+            ["ny #kaffe", "gammal #kaffe"]
+        );
+        assert_eq!(
+            titles("/api/getbrainfarts?tag=kaffe&limit=2").await,
+            ["#kaffe", "ny #kaffe"]
+        );
+        assert!(titles("/api/getbrainfarts?brainname=cecilia")
+            .await
+            .is_empty());
+    }
+}
+
+#[tokio::test]
+async fn getting_brainfarts_requires_a_token() {
+    let (status, _) = send(&app(), Method::GET, "/api/getbrainfarts", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn explosions_and_implosions_replace_each_other() {
+    let app = app();
+    let token = register(&app, "hjärna", "lösenord").await;
+    let brainfart = create_brainfart(&app, &token, "title").await;
+
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        "/api/registermindexplosion",
+        Some(&token),
+        Some(json!({ "brainfart_id": brainfart.id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let updated: BrainfartInformation = parse(&body);
+    assert_eq!(updated.blew_minds.len(), 1);
+    assert_eq!(updated.imploded_minds.len(), 0);
+
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        "/api/registermindimplosion",
+        Some(&token),
+        Some(json!({ "brainfart_id": brainfart.id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let updated: BrainfartInformation = parse(&body);
+    assert_eq!(updated.blew_minds.len(), 0);
+    assert_eq!(updated.imploded_minds.len(), 1);
+}
+
+#[tokio::test]
+async fn reacting_to_an_unknown_brainfart_is_not_found() {
+    let app = app();
+    let token = register(&app, "hjärna", "lösenord").await;
+    for uri in ["/api/registermindexplosion", "/api/registermindimplosion"] {
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            uri,
+            Some(&token),
+            Some(json!({ "brainfart_id": uuid::Uuid::new_v4().to_string() })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
+
+#[tokio::test]
+async fn reacting_with_an_invalid_brainfart_id_is_a_bad_request() {
+    let app = app();
+    let token = register(&app, "hjärna", "lösenord").await;
+    for uri in ["/api/registermindexplosion", "/api/registermindimplosion"] {
+        let (status, body) = send(
+            &app,
+            Method::POST,
+            uri,
+            Some(&token),
+            Some(json!({ "brainfart_id": "not a brainfart" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body, b"Invalid brainfart id");
+    }
+}
+
+#[tokio::test]
+async fn sqlite_backend_serves_every_route() {
+    let app = sqlite_app().await;
+    let token = register(&app, "hjärna", "lösenord").await;
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/registerbrain",
+        None,
+        Some(json!({"brainname": "hjärna", "password": "a", "password_extra": "a"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/loginasbrain",
+        None,
+        Some(json!({"brainname": "hjärna", "password": "lösenord"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+
+    let first = create_brainfart(&app, &token, "first").await;
+    let second = create_brainfart(&app, &token, "second").await;
+    let brainfarts = get_brainfarts(&app, &token).await;
+    let ids = brainfarts.iter().map(|fart| &fart.id).collect::<Vec<_>>();
+    assert_eq!(ids, vec![&second.id, &first.id]);
+    assert_eq!(brainfarts[0].mastermind_name, "hjärna");
+
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        "/api/registermindexplosion",
+        Some(&token),
+        Some(json!({ "brainfart_id": first.id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let updated: BrainfartInformation = parse(&body);
+    assert_eq!(updated.blew_minds.len(), 1);
+
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        "/api/registermindimplosion",
+        Some(&token),
+        Some(json!({ "brainfart_id": first.id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let updated: BrainfartInformation = parse(&body);
+    assert_eq!(updated.blew_minds.len(), 0);
+    assert_eq!(updated.imploded_minds.len(), 1);
+
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/registermindexplosion",
+        Some(&token),
+        Some(json!({ "brainfart_id": uuid::Uuid::new_v4().to_string() })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,308 @@
+use axum::{
+    http::{Method, StatusCode},
+    Router,
+};
+use serde_json::json;
+use shared::{Brainfart, BrainfartInformation, FeedToken, ReportedBrainfart};
+
+use super::{
+    admin_token, app, app_with, assert_out_of_reach, backends, create_brainfart, get_brainfarts,
+    log_in, parse, register, send,
+};
+
+async fn get_reports(app: &Router, token: &str) -> Vec<ReportedBrainfart> {
+    let (status, body) = send(app, Method::GET, "/api/admin/reports", Some(token), None).await;
+    assert_eq!(status, StatusCode::OK);
+    parse(&body)
+}
+
+#[tokio::test]
+async fn reports_need_a_reason_and_an_existing_brainfart() {
+    let app = app();
+    let token = register(&app, "hjärna", "lösenord").await;
+    let brainfart = create_brainfart(&app, &token, "title").await;
+
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/reportbrainfart",
+        Some(&token),
+        Some(json!({"brainfart_id": brainfart.id, "reason": "  "})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/reportbrainfart",
+        Some(&token),
+        Some(json!({"brainfart_id": uuid::Uuid::new_v4().to_string(), "reason": "spam"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/reportbrainfart",
+        Some(&token),
+        Some(json!({"brainfart_id": brainfart.id, "reason": "spam"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn only_admins_may_moderate() {
+    let app = app();
+    let token = register(&app, "hjärna", "lösenord").await;
+    let brainfart = create_brainfart(&app, &token, "title").await;
+
+    let (status, _) = send(&app, Method::GET, "/api/admin/reports", Some(&token), None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/admin/hidebrainfart",
+        Some(&token),
+        Some(json!({ "brainfart_id": brainfart.id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/admin/suspendbrain",
+        Some(&token),
+        Some(json!({"brainname": "hjärna"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(get_brainfarts(&app, &token).await.len(), 1);
+}
+
+#[tokio::test]
+async fn hidden_brainfarts_leave_the_feed_until_restored() {
+    for repository in backends().await {
+        let app = app_with(repository.clone());
+        let admin = admin_token(&app, repository.as_ref()).await;
+        let token = register(&app, "hjärna", "lösenord").await;
+        let kept = create_brainfart(&app, &token, "kept").await;
+        let reported = create_brainfart(&app, &token, "reported").await;
+
+        for reason in ["spam", "still spam"] {
+            let (status, _) = send(
+                &app,
+                Method::POST,
+                "/api/reportbrainfart",
+                Some(&token),
+                Some(json!({"brainfart_id": reported.id, "reason": reason})),
+            )
+            .await;
+            assert_eq!(status, StatusCode::CREATED);
+        }
+        let reports = get_reports(&app, &admin).await;
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].brainfart.id, reported.id);
+        assert!(!reports[0].hidden);
+        assert_eq!(reports[0].reports.len(), 1);
+        assert_eq!(reports[0].reports[0].reporter_name, "hjärna");
+        assert_eq!(reports[0].reports[0].reason, "still spam");
+
+        let (status, body) = send(
+            &app,
+            Method::POST,
+            "/api/admin/hidebrainfart",
+            Some(&admin),
+            Some(json!({ "brainfart_id": reported.id })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(parse::<BrainfartInformation>(&body).id, reported.id);
+        let ids = get_brainfarts(&app, &token)
+            .await
+            .into_iter()
+            .map(|fart| fart.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![kept.id.clone()]);
+        assert!(get_reports(&app, &admin).await.is_empty());
+
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            "/api/admin/restorebrainfart",
+            Some(&admin),
+            Some(json!({ "brainfart_id": reported.id })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(get_brainfarts(&app, &token).await.len(), 2);
+
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            "/api/admin/hidebrainfart",
+            Some(&admin),
+            Some(json!({ "brainfart_id": uuid::Uuid::new_v4().to_string() })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
+
+#[tokio::test]
+async fn suspended_brains_cannot_log_in() {
+    for repository in backends().await {
+        let app = app_with(repository.clone());
+        let admin = admin_token(&app, repository.as_ref()).await;
+        register(&app, "hjärna", "lösenord").await;
+
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            "/api/admin/suspendbrain",
+            Some(&admin),
+            Some(json!({"brainname": "hjärna"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            log_in(&app, "hjärna", "lösenord").await.0,
+            StatusCode::UNAUTHORIZED
+        );
+
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            "/api/admin/unsuspendbrain",
+            Some(&admin),
+            Some(json!({"brainname": "hjärna"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            log_in(&app, "hjärna", "lösenord").await.0,
+            StatusCode::ACCEPTED
+        );
+
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            "/api/admin/suspendbrain",
+            Some(&admin),
+            Some(json!({"brainname": "admin"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+async fn suspension_ends_the_sessions_the_brain_already_has() {
+    for repository in backends().await {
+        let app = app_with(repository.clone());
+        let admin = admin_token(&app, repository.as_ref()).await;
+        let token = register(&app, "hjärna", "lösenord").await;
+        let (status, body) = send(
+            &app,
+            Method::POST,
+            "/api/v1/feeds/tokens",
+            Some(&token),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        let feed_uri = format!(
+            "/api/v1/feeds/brainfarts?token={}",
+            parse::<FeedToken>(&body).token
+        );
+
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            "/api/admin/suspendbrain",
+            Some(&admin),
+            Some(json!({"brainname": "hjärna"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            "/api/createbrainfart",
+            Some(&token),
+            Some(json!({"title": "still here", "content": "content"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = send(&app, Method::GET, "/api/getbrainfarts", Some(&token), None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = send(&app, Method::GET, &feed_uri, None, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            "/api/admin/unsuspendbrain",
+            Some(&admin),
+            Some(json!({"brainname": "hjärna"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(&app, Method::GET, "/api/getbrainfarts", Some(&token), None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn hidden_brainfarts_are_out_of_reach_of_other_brains() {
+    for repository in backends().await {
+        let app = app_with(repository.clone());
+        let admin = admin_token(&app, repository.as_ref()).await;
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+        let (status, body) = send(
+            &app,
+            Method::POST,
+            "/api/createbrainfart",
+            Some(&anna),
+            Some(
+                json!({"title": "spam", "content": "buy now", "poll": {"options": ["ja", "nej"]}}),
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        let brainfart: Brainfart = parse(&body);
+
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            "/api/admin/hidebrainfart",
+            Some(&admin),
+            Some(json!({ "brainfart_id": brainfart.id })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_out_of_reach(&app, &bertil, &brainfart).await;
+
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            "/api/admin/restorebrainfart",
+            Some(&admin),
+            Some(json!({ "brainfart_id": brainfart.id })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            "/api/registermindexplosion",
+            Some(&bertil),
+            Some(json!({ "brainfart_id": brainfart.id })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+}
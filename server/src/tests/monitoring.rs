@@ -0,0 +1,104 @@
+use axum::{
+    body::Body,
+    http::{Method, Request, StatusCode},
+};
+use serde_json::json;
+use tower::ServiceExt;
+
+use super::{app, app_with, backends, create_brainfart, register, send, state_with};
+use crate::{create_router, monitoring::MonitoringSettings};
+
+#[tokio::test]
+async fn health_and_readiness_answer() {
+    for repository in backends().await {
+        let app = app_with(repository);
+        let (status, body) = send(&app, Method::GET, "/health", None, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, b"ok");
+        let (status, body) = send(&app, Method::GET, "/ready", None, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, b"ready");
+    }
+}
+
+#[tokio::test]
+async fn responses_carry_the_request_id() {
+    let app = app();
+    let response = app
+        .clone()
+        .oneshot(Request::get("/health").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let generated = response.headers()["x-request-id"].to_str().unwrap();
+    assert!(uuid::Uuid::parse_str(generated).is_ok());
+
+    let response = app
+        .oneshot(
+            Request::get("/health")
+                .header("x-request-id", "from-the-proxy")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.headers()["x-request-id"], "from-the-proxy");
+}
+
+#[tokio::test]
+async fn the_key_dumping_hello_is_gone() {
+    let (status, _) = send(&app(), Method::GET, "/api/hello", None, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn metrics_count_requests_by_route_and_what_the_brains_do() {
+    for repository in backends().await {
+        let sqlite = repository.get_pool_status().is_some();
+        let mut state = state_with(repository);
+        state.monitoring = MonitoringSettings {
+            metrics_token: Some("hemlig".to_string()),
+        };
+        let app = create_router(state, "./dist");
+        let anna = register(&app, "anna", "lösenord").await;
+        let brainfart = create_brainfart(&app, &anna, "hello").await;
+        for _ in 0..2 {
+            let (status, _) = send(
+                &app,
+                Method::POST,
+                &format!("/api/v1/brainfarts/{}/reactions", brainfart.id),
+                Some(&anna),
+                Some(json!({"kind": "explosion"})),
+            )
+            .await;
+            assert_eq!(status, StatusCode::CREATED);
+        }
+
+        for token in [None, Some(anna.as_str()), Some("hemli")] {
+            let (status, _) = send(&app, Method::GET, "/metrics", token, None).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+        }
+        let (status, body) = send(&app, Method::GET, "/metrics", Some("hemlig"), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let metrics = String::from_utf8(body).unwrap();
+        assert!(metrics.contains(
+            "brains_http_requests_total{method=\"POST\",route=\"/api/v1/brainfarts/:brainfart_id/reactions\",status=\"201\"} 2"
+        ));
+        assert!(metrics.contains(
+            "brains_http_request_duration_seconds_count{method=\"POST\",route=\"/api/createbrainfart\"} 1"
+        ));
+        assert!(metrics.contains("brains_brainfarts_created_total 1\n"));
+        assert!(metrics.contains("brains_reactions_total{kind=\"explosion\"} 2\n"));
+        assert!(metrics.contains("brains_reactions_total{kind=\"implosion\"} 0\n"));
+        assert_eq!(
+            metrics.contains("brains_db_connections{state=\"idle\"}"),
+            sqlite
+        );
+        assert!(!metrics.contains(&brainfart.id));
+    }
+}
+
+#[tokio::test]
+async fn metrics_are_not_served_without_a_metrics_token() {
+    let (status, _) = send(&app(), Method::GET, "/metrics", None, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,147 @@
+use std::{collections::BTreeSet, sync::Arc};
+
+use axum::{
+    body::Body,
+    http::{Method, Request, StatusCode},
+    Router,
+};
+use serde_json::Value;
+use shared::routes;
+use tower::ServiceExt;
+use utoipa::OpenApi;
+
+use super::{app_with, send};
+use crate::{database::memory_repository::MemoryRepository, openapi::ApiDoc, routes::v1_routes};
+
+const METHODS: [Method; 4] = [Method::GET, Method::POST, Method::PUT, Method::DELETE];
+
+/// `/api/v1/relations/:relation/:brainname` is written `/api/v1/relations/{relation}/{brainname}`.
+fn openapi_path(route: &str) -> String {
+    route
+        .split('/')
+        .map(|segment| match segment.strip_prefix(':') {
+            Some(parameter) => format!("{{{}}}", parameter),
+            None => segment.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn documented_operations() -> BTreeSet<(String, String)> {
+    let document = serde_json::to_value(ApiDoc::openapi()).unwrap();
+    let mut operations = BTreeSet::new();
+    for (path, item) in document["paths"].as_object().unwrap() {
+        for method in item.as_object().unwrap().keys() {
+            operations.insert((path.clone(), method.to_uppercase()));
+        }
+    }
+    operations
+}
+
+/// Whether the router has a handler for the method, found by asking without a token. Handlers
+/// say why they turn a request down, only the router answers with an empty 404 or a 405.
+async fn is_routed(app: &Router, method: &Method, route: &str) -> bool {
+    let parameters = ["mute", "00000000-0000-0000-0000-000000000000"];
+    let values = route
+        .split('/')
+        .filter_map(|segment| segment.strip_prefix(':'))
+        .map(|parameter| match parameter {
+            "relation" => parameters[0],
+            _ => parameters[1],
+        })
+        .collect::<Vec<_>>();
+    let request = Request::builder()
+        .method(method)
+        .uri(routes::path(route, &values))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    status != StatusCode::METHOD_NOT_ALLOWED
+        && !(status == StatusCode::NOT_FOUND && body.is_empty())
+}
+
+#[tokio::test]
+async fn the_openapi_document_matches_the_routes() {
+    let app = app_with(Arc::new(MemoryRepository::create()));
+    let served = v1_routes(0)
+        .into_iter()
+        .map(|(route, _)| route)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        served.iter().collect::<BTreeSet<_>>(),
+        routes::ALL.iter().collect::<BTreeSet<_>>()
+    );
+
+    let documented = documented_operations();
+    let mut routed = BTreeSet::new();
+    for route in served {
+        for method in METHODS.iter() {
+            if is_routed(&app, method, route).await {
+                routed.insert((openapi_path(route), method.to_string()));
+            }
+        }
+    }
+    assert_eq!(documented, routed);
+}
+
+#[test]
+fn the_openapi_document_describes_every_type_it_mentions() {
+    fn collect_references(value: &Value, references: &mut BTreeSet<String>) {
+        match value {
+            Value::Object(object) => {
+                if let Some(Value::String(reference)) = object.get("$ref") {
+                    references.insert(reference.clone());
+                }
+                object
+                    .values()
+                    .for_each(|value| collect_references(value, references));
+            }
+            Value::Array(array) => array
+                .iter()
+                .for_each(|value| collect_references(value, references)),
+            _ => {}
+        }
+    }
+    let document = serde_json::to_value(ApiDoc::openapi()).unwrap();
+    let mut references = BTreeSet::new();
+    collect_references(&document, &mut references);
+    assert!(references.contains("#/components/schemas/BrainfartInformation"));
+    for reference in references {
+        let name = reference.trim_start_matches("#/components/schemas/");
+        assert!(
+            document["components"]["schemas"].get(name).is_some(),
+            "{} is not described",
+            reference
+        );
+    }
+}
+
+#[tokio::test]
+async fn the_openapi_document_and_its_docs_are_served() {
+    let app = app_with(Arc::new(MemoryRepository::create()));
+    let (status, body) = send(&app, Method::GET, routes::OPENAPI, None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let document: Value = serde_json::from_slice(&body).unwrap();
+    assert!(document["openapi"].as_str().unwrap().starts_with("3."));
+    assert!(document["paths"].get("/api/v1/brainfarts").is_some());
+
+    let (status, body) = send(&app, Method::GET, routes::DOCS, None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let page = String::from_utf8(body).unwrap();
+    assert!(page.contains("swagger-ui"));
+    assert!(page.contains(routes::OPENAPI));
+    // Swagger UI is served by the server itself.
+    assert!(!page.contains("https://"));
+    for file in ["swagger-ui.css", "swagger-ui-bundle.js"] {
+        let uri = routes::path(routes::DOCS_FILE, &[file]);
+        assert!(page.contains(&uri));
+        let (status, body) = send(&app, Method::GET, &uri, None, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(!body.is_empty());
+    }
+    let uri = routes::path(routes::DOCS_FILE, &["nothing.js"]);
+    let (status, _) = send(&app, Method::GET, &uri, None, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,180 @@
+use axum::{
+    http::{Method, StatusCode},
+    Router,
+};
+use chrono::{Duration, Local};
+use serde_json::{json, Value};
+use shared::{Brainfart, BrainfartInformation, CreateBrainfartRequest, PollRequest};
+use sqlx::types::Uuid;
+
+use super::{app_with, backends, get_brainfarts, parse, register, send};
+
+async fn create_poll(app: &Router, token: &str, poll: Value) -> (StatusCode, Vec<u8>) {
+    send(
+        app,
+        Method::POST,
+        "/api/createbrainfart",
+        Some(token),
+        Some(json!({"title": "Fråga", "content": "Which one?", "poll": poll})),
+    )
+    .await
+}
+
+async fn vote(
+    app: &Router,
+    token: &str,
+    brainfart_id: &str,
+    options: &[usize],
+) -> (StatusCode, Vec<u8>) {
+    send(
+        app,
+        Method::POST,
+        "/api/vote",
+        Some(token),
+        Some(json!({ "brainfart_id": brainfart_id, "options": options })),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn votes_are_counted_once_per_brain() {
+    for repository in backends().await {
+        let app = app_with(repository);
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+        let (status, body) = create_poll(&app, &anna, json!({"options": ["kaffe", "te"]})).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let brainfart: Brainfart = parse(&body);
+
+        let (status, body) = vote(&app, &bertil, &brainfart.id, &[1]).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let voted: BrainfartInformation = parse(&body);
+        let poll = voted.poll.unwrap();
+        assert_eq!(poll.voters, 1);
+        assert_eq!(
+            poll.options
+                .iter()
+                .map(|option| (option.text.as_str(), option.votes, option.voted_by_me))
+                .collect::<Vec<_>>(),
+            vec![("kaffe", 0, false), ("te", 1, true)]
+        );
+
+        let (status, body) = vote(&app, &bertil, &brainfart.id, &[0]).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body, b"You have already voted in this poll");
+
+        assert_eq!(
+            vote(&app, &anna, &brainfart.id, &[0]).await.0,
+            StatusCode::CREATED
+        );
+        let feed = get_brainfarts(&app, &anna).await;
+        let poll = feed[0].poll.as_ref().unwrap();
+        assert_eq!(poll.voters, 2);
+        assert_eq!(
+            poll.options
+                .iter()
+                .map(|option| (option.votes, option.voted_by_me))
+                .collect::<Vec<_>>(),
+            vec![(1, true), (1, false)]
+        );
+    }
+}
+
+#[tokio::test]
+async fn multiple_choice_polls_take_several_options() {
+    for repository in backends().await {
+        let app = app_with(repository);
+        let anna = register(&app, "anna", "lösenord").await;
+        let (_, body) = create_poll(
+            &app,
+            &anna,
+            json!({"options": ["ett", "två", "tre"], "multiple_choice": true}),
+        )
+        .await;
+        let multiple: Brainfart = parse(&body);
+        let (_, body) = create_poll(&app, &anna, json!({"options": ["ja", "nej"]})).await;
+        let single: Brainfart = parse(&body);
+
+        let (status, body) = vote(&app, &anna, &single.id, &[0, 1]).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body, b"Only one option can be chosen in this poll");
+        for options in [vec![], vec![0, 0], vec![3]] {
+            assert_eq!(
+                vote(&app, &anna, &multiple.id, &options).await.0,
+                StatusCode::BAD_REQUEST
+            );
+        }
+
+        let (status, body) = vote(&app, &anna, &multiple.id, &[0, 2]).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let poll = parse::<BrainfartInformation>(&body).poll.unwrap();
+        assert_eq!(poll.voters, 1);
+        assert_eq!(
+            poll.options
+                .iter()
+                .map(|option| option.votes)
+                .collect::<Vec<_>>(),
+            vec![1, 0, 1]
+        );
+    }
+}
+
+#[tokio::test]
+async fn invalid_polls_are_rejected() {
+    for repository in backends().await {
+        let app = app_with(repository);
+        let anna = register(&app, "anna", "lösenord").await;
+        let too_many = (0..11).map(|n| n.to_string()).collect::<Vec<_>>();
+        for poll in [
+            json!({"options": ["bara en"]}),
+            json!({"options": too_many}),
+            json!({"options": ["samma", " samma "]}),
+            json!({"options": ["något", ""]}),
+            json!({"options": ["ja", "x".repeat(101)]}),
+            json!({"options": ["ja", "nej"], "closes_at": Local::now() - Duration::hours(1)}),
+        ] {
+            assert_eq!(
+                create_poll(&app, &anna, poll).await.0,
+                StatusCode::BAD_REQUEST
+            );
+        }
+        assert!(get_brainfarts(&app, &anna).await.is_empty());
+    }
+}
+
+#[tokio::test]
+async fn closed_polls_take_no_votes() {
+    for repository in backends().await {
+        let app = app_with(repository.clone());
+        let anna = register(&app, "anna", "lösenord").await;
+        let (_, body) = create_poll(&app, &anna, json!({"options": ["ja", "nej"]})).await;
+        let open: Brainfart = parse(&body);
+        // The handler refuses polls that are already closed, so this one goes in directly.
+        let closed = repository
+            .create_brainfart(
+                &CreateBrainfartRequest::create("Stängd".to_string(), "För sent".to_string())
+                    .with_poll(PollRequest {
+                        options: vec!["ja".to_string(), "nej".to_string()],
+                        closes_at: Some(Local::now() - Duration::minutes(1)),
+                        multiple_choice: false,
+                    }),
+                &Uuid::parse_str(&open.mastermind_id).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let (status, body) = vote(&app, &anna, &closed.uuid.to_string(), &[0]).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body, b"This poll is closed");
+        let (status, _) = vote(&app, &anna, &Uuid::new_v4().to_string(), &[0]).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let feed = get_brainfarts(&app, &anna).await;
+        let closed_poll = feed
+            .iter()
+            .find(|fart| fart.id == closed.uuid.to_string())
+            .and_then(|fart| fart.poll.as_ref())
+            .unwrap();
+        assert!(closed_poll.is_closed(Local::now()));
+        assert_eq!(closed_poll.voters, 0);
+    }
+}
@@ -0,0 +1,99 @@
+use axum::{
+    http::{Method, StatusCode},
+    Router,
+};
+use chrono::{Duration, Local, TimeZone};
+use serde_json::json;
+use shared::BrainfartInformation;
+
+use super::{app_with, backends, create_brainfart, get_brainfarts, parse, register, send};
+use crate::ranking::{age_in_hours, hot_score, sort_by_hotness};
+
+async fn react(app: &Router, token: &str, brainfart_id: &str, uri: &str) {
+    let (status, _) = send(
+        app,
+        Method::POST,
+        uri,
+        Some(token),
+        Some(json!({ "brainfart_id": brainfart_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+async fn get_hot_brainfarts(app: &Router, token: &str) -> Vec<String> {
+    let (status, body) = send(
+        app,
+        Method::GET,
+        "/api/getbrainfarts?order=hot",
+        Some(token),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    parse::<Vec<BrainfartInformation>>(&body)
+        .into_iter()
+        .map(|brainfart| brainfart.title)
+        .collect()
+}
+
+#[test]
+fn hot_scores_cool_down_with_age() {
+    assert!(hot_score(5, 0, 0, 0.0) > hot_score(5, 0, 0, 10.0));
+    assert!(hot_score(5, 0, 0, 10.0) > hot_score(5, 0, 0, 100.0));
+    assert!(hot_score(5, 0, 0, 100.0) > 0.0);
+    // A day-old hit loses to a fresh brainfart that is doing well.
+    assert!(hot_score(10, 0, 0, 48.0) < hot_score(2, 0, 0, 0.0));
+    // Clocks that disagree slightly do not give brainfarts from the future a boost.
+    assert_eq!(hot_score(1, 0, 0, -1.0), hot_score(1, 0, 0, 0.0));
+}
+
+#[test]
+fn hot_scores_weigh_implosions_and_views() {
+    assert_eq!(hot_score(3, 3, 0, 1.0), 0.0);
+    assert!(hot_score(1, 3, 0, 1.0) < 0.0);
+    assert!(hot_score(0, 0, 5, 1.0) < hot_score(1, 0, 0, 1.0));
+    assert_eq!(hot_score(0, 0, 10, 1.0), hot_score(1, 0, 0, 1.0));
+}
+
+#[test]
+fn equally_hot_brainfarts_are_newest_first() {
+    let now = Local.with_ymd_and_hms(2023, 1, 20, 12, 0, 0).unwrap();
+    let older = now - Duration::hours(3);
+    assert_eq!(age_in_hours(older, now), 3.0);
+    let mut items = vec![("old", 0.0, older), ("new", 0.0, now), ("hot", 0.5, older)];
+    sort_by_hotness(&mut items, |(_, score, birthdate)| (*score, *birthdate));
+    assert_eq!(
+        items.iter().map(|(name, _, _)| *name).collect::<Vec<_>>(),
+        vec!["hot", "new", "old"]
+    );
+}
+
+#[tokio::test]
+async fn hot_feed_puts_mind_blowing_brainfarts_first() {
+    for repository in backends().await {
+        let app = app_with(repository);
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+        let cecilia = register(&app, "cecilia", "lösenord").await;
+        create_brainfart(&app, &anna, "ignored").await;
+        let loved = create_brainfart(&app, &anna, "loved").await;
+        let disliked = create_brainfart(&app, &anna, "disliked").await;
+        react(&app, &bertil, &loved.id, "/api/registermindexplosion").await;
+        react(&app, &cecilia, &loved.id, "/api/registermindexplosion").await;
+        react(&app, &cecilia, &disliked.id, "/api/registermindimplosion").await;
+
+        assert_eq!(
+            get_brainfarts(&app, &anna)
+                .await
+                .into_iter()
+                .map(|brainfart| brainfart.title)
+                .collect::<Vec<_>>(),
+            vec!["disliked", "loved", "ignored"]
+        );
+        assert_eq!(
+            get_hot_brainfarts(&app, &anna).await,
+            vec!["loved", "ignored", "disliked"]
+        );
+    }
+}
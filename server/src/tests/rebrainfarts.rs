@@ -0,0 +1,167 @@
+use axum::{
+    http::{Method, StatusCode},
+    Router,
+};
+use serde_json::json;
+use shared::BrainfartInformation;
+use sqlx::types::Uuid;
+
+use super::{app_with, backends, create_brainfart, get_brainfarts, parse, register, send};
+
+async fn rebrainfart(
+    app: &Router,
+    token: &str,
+    brainfart_id: &str,
+    commentary: &str,
+) -> (StatusCode, Vec<u8>) {
+    send(
+        app,
+        Method::POST,
+        "/api/rebrainfart",
+        Some(token),
+        Some(json!({ "brainfart_id": brainfart_id, "commentary": commentary })),
+    )
+    .await
+}
+
+fn find<'a>(feed: &'a [BrainfartInformation], id: &str) -> &'a BrainfartInformation {
+    feed.iter().find(|fart| fart.id == id).unwrap()
+}
+
+#[tokio::test]
+async fn rebrainfarts_embed_their_original() {
+    for repository in backends().await {
+        let app = app_with(repository);
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+        let cecilia = register(&app, "cecilia", "lösenord").await;
+        let original = create_brainfart(&app, &anna, "worth sharing").await;
+
+        let (status, body) = rebrainfart(&app, &bertil, &original.id, "").await;
+        assert_eq!(status, StatusCode::CREATED);
+        let shared: BrainfartInformation = parse(&body);
+        assert!(shared.is_plain_rebrainfart());
+        assert_eq!(shared.mastermind_name, "bertil");
+        assert_eq!(shared.original.as_ref().unwrap().id, original.id);
+        assert!(shared.original.as_ref().unwrap().rebrainfarted_by_me);
+
+        let (status, body) = rebrainfart(&app, &bertil, &original.id, "  ").await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body, b"You have already rebrainfarted this brainfart");
+        // Sharing the rebrainfart shares the original.
+        assert_eq!(
+            rebrainfart(&app, &bertil, &shared.id, "").await.0,
+            StatusCode::CONFLICT
+        );
+        let (status, body) = rebrainfart(&app, &cecilia, &shared.id, "").await;
+        assert_eq!(status, StatusCode::CREATED);
+        let second: BrainfartInformation = parse(&body);
+        assert_eq!(second.original.unwrap().id, original.id);
+
+        let feed = get_brainfarts(&app, &bertil).await;
+        assert_eq!(feed.len(), 3);
+        let original_information = find(&feed, &original.id);
+        assert_eq!(original_information.rebrainfarts, 2);
+        assert!(original_information.rebrainfarted_by_me);
+        assert!(original_information.original.is_none());
+        assert_eq!(find(&feed, &shared.id).rebrainfarts, 0);
+        assert!(!find(&get_brainfarts(&app, &anna).await, &original.id).rebrainfarted_by_me);
+
+        let (status, _) = rebrainfart(&app, &anna, &Uuid::new_v4().to_string(), "").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
+
+#[tokio::test]
+async fn quotes_keep_their_commentary() {
+    for repository in backends().await {
+        let app = app_with(repository);
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+        let original = create_brainfart(&app, &anna, "a bold claim").await;
+
+        let (status, body) = rebrainfart(&app, &bertil, &original.id, "Really?").await;
+        assert_eq!(status, StatusCode::CREATED);
+        let quote: BrainfartInformation = parse(&body);
+        assert!(!quote.is_plain_rebrainfart());
+        assert_eq!(quote.content, "Really?");
+        assert_eq!(quote.original.as_ref().unwrap().id, original.id);
+        assert_eq!(
+            rebrainfart(&app, &bertil, &original.id, "Still not sure")
+                .await
+                .0,
+            StatusCode::CREATED
+        );
+        // A quote of a quote embeds the quote, but not what it quotes.
+        let (_, body) = rebrainfart(&app, &anna, &quote.id, "Yes!").await;
+        let answer: BrainfartInformation = parse(&body);
+        let embedded = answer.original.unwrap();
+        assert_eq!(embedded.id, quote.id);
+        assert!(embedded.original.is_none());
+
+        let feed = get_brainfarts(&app, &bertil).await;
+        let original_information = find(&feed, &original.id);
+        assert_eq!(original_information.rebrainfarts, 2);
+        assert!(!original_information.rebrainfarted_by_me);
+        assert_eq!(find(&feed, &quote.id).rebrainfarts, 1);
+    }
+}
+
+#[tokio::test]
+async fn rebrainfarts_follow_their_original_out_of_the_feed() {
+    for repository in backends().await {
+        let app = app_with(repository.clone());
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+        let hidden = create_brainfart(&app, &anna, "soon hidden").await;
+        let deleted = create_brainfart(&app, &anna, "soon deleted").await;
+        rebrainfart(&app, &bertil, &hidden.id, "").await;
+        rebrainfart(&app, &bertil, &deleted.id, "").await;
+        let (_, body) = rebrainfart(&app, &bertil, &deleted.id, "Remember this").await;
+        let quote: BrainfartInformation = parse(&body);
+        assert_eq!(get_brainfarts(&app, &bertil).await.len(), 5);
+
+        let hidden_id = Uuid::parse_str(&hidden.id).unwrap();
+        assert!(repository.set_brainfart_hidden(&hidden_id, true).await);
+        assert!(
+            repository
+                .delete_brainfart(&Uuid::parse_str(&deleted.id).unwrap())
+                .await
+        );
+
+        let feed = get_brainfarts(&app, &bertil).await;
+        assert_eq!(feed.len(), 1);
+        assert_eq!(feed[0].id, quote.id);
+        assert_eq!(feed[0].content, "Remember this");
+        assert!(feed[0].original.is_none());
+        assert_eq!(
+            rebrainfart(&app, &bertil, &hidden.id, "Where did it go?")
+                .await
+                .0,
+            StatusCode::NOT_FOUND
+        );
+    }
+}
+
+#[tokio::test]
+async fn blocked_brains_cannot_rebrainfart() {
+    for repository in backends().await {
+        let app = app_with(repository);
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+        let original = create_brainfart(&app, &anna, "not for bertil").await;
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            "/api/addrelation",
+            Some(&anna),
+            Some(json!({"brainname": "bertil", "relation": "block"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let (status, body) = rebrainfart(&app, &bertil, &original.id, "").await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body, b"The mastermind of this brainfart has blocked you");
+    }
+}
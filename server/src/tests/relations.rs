@@ -0,0 +1,150 @@
+use axum::{
+    http::{Method, StatusCode},
+    Router,
+};
+use serde_json::{json, Value};
+use shared::{BrainRelation, BrainRelationInformation};
+
+use super::{app_with, backends, create_brainfart, get_brainfarts, parse, register, send};
+
+async fn relate(
+    app: &Router,
+    uri: &str,
+    token: &str,
+    brainname: &str,
+    relation: &str,
+) -> (StatusCode, Vec<u8>) {
+    send(
+        app,
+        Method::POST,
+        uri,
+        Some(token),
+        Some(json!({"brainname": brainname, "relation": relation})),
+    )
+    .await
+}
+
+async fn explode(app: &Router, token: &str, brainfart_id: &str) -> StatusCode {
+    send(
+        app,
+        Method::POST,
+        "/api/registermindexplosion",
+        Some(token),
+        Some(json!({ "brainfart_id": brainfart_id })),
+    )
+    .await
+    .0
+}
+
+#[tokio::test]
+async fn muted_brains_leave_only_the_muters_feed() {
+    for repository in backends().await {
+        let app = app_with(repository);
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+        let cecilia = register(&app, "cecilia", "lösenord").await;
+        create_brainfart(&app, &bertil, "from bertil").await;
+        let kept = create_brainfart(&app, &cecilia, "from cecilia").await;
+
+        let (status, body) = relate(&app, "/api/addrelation", &anna, "bertil", "mute").await;
+        assert_eq!(status, StatusCode::CREATED);
+        let relations: Vec<BrainRelationInformation> = parse(&body);
+        assert_eq!(
+            relations,
+            vec![BrainRelationInformation {
+                brainname: "bertil".to_string(),
+                relation: BrainRelation::Mute,
+            }]
+        );
+
+        let ids = get_brainfarts(&app, &anna)
+            .await
+            .into_iter()
+            .map(|fart| fart.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![kept.id.clone()]);
+        assert_eq!(get_brainfarts(&app, &cecilia).await.len(), 2);
+
+        let (status, _) = relate(&app, "/api/removerelation", &anna, "bertil", "mute").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(get_brainfarts(&app, &anna).await.len(), 2);
+    }
+}
+
+#[tokio::test]
+async fn blocked_brains_cannot_react_to_the_blockers_brainfarts() {
+    for repository in backends().await {
+        let app = app_with(repository);
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+        let cecilia = register(&app, "cecilia", "lösenord").await;
+        let annas = create_brainfart(&app, &anna, "from anna").await;
+        let cecilias = create_brainfart(&app, &cecilia, "from cecilia").await;
+
+        let (status, _) = relate(&app, "/api/addrelation", &anna, "bertil", "block").await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        assert_eq!(
+            explode(&app, &bertil, &annas.id).await,
+            StatusCode::FORBIDDEN
+        );
+        let (status, body) = send(
+            &app,
+            Method::POST,
+            "/api/registermindimplosion",
+            Some(&bertil),
+            Some(json!({ "brainfart_id": annas.id })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body, b"The mastermind of this brainfart has blocked you");
+        assert_eq!(
+            explode(&app, &bertil, &cecilias.id).await,
+            StatusCode::CREATED
+        );
+        assert_eq!(
+            explode(&app, &cecilia, &annas.id).await,
+            StatusCode::CREATED
+        );
+        // Blocking does not mute, so anna's brainfarts still show up for bertil.
+        assert_eq!(get_brainfarts(&app, &bertil).await.len(), 2);
+
+        let (status, _) = relate(&app, "/api/removerelation", &anna, "bertil", "block").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(explode(&app, &bertil, &annas.id).await, StatusCode::CREATED);
+    }
+}
+
+#[tokio::test]
+async fn relations_need_another_existing_brain() {
+    for repository in backends().await {
+        let app = app_with(repository);
+        let anna = register(&app, "anna", "lösenord").await;
+        register(&app, "bertil", "lösenord").await;
+
+        let (status, body) = relate(&app, "/api/addrelation", &anna, "anna", "block").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body, b"You cannot block yourself");
+        let (status, _) = relate(&app, "/api/addrelation", &anna, "okänd", "mute").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = relate(&app, "/api/addrelation", &anna, "bertil", "ignore").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = relate(&app, "/api/removerelation", &anna, "bertil", "mute").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        for relation in ["mute", "block", "mute"] {
+            let (status, _) = relate(&app, "/api/addrelation", &anna, "bertil", relation).await;
+            assert_eq!(status, StatusCode::CREATED);
+        }
+        let (status, body) = send(&app, Method::GET, "/api/getrelations", Some(&anna), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let relations: Value = parse(&body);
+        assert_eq!(
+            relations,
+            json!([
+                {"brainname": "bertil", "relation": "mute"},
+                {"brainname": "bertil", "relation": "block"},
+            ])
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    http::{Method, Request, StatusCode},
+    Router,
+};
+use serde_json::json;
+use shared::{routes, BrainRelationInformation, Brainfart, BrainfartInformation};
+use tower::ServiceExt;
+
+use super::{
+    admin_token, app_with, backends, create_brainfart, get_brainfarts, parse, register, send,
+};
+use crate::database::memory_repository::MemoryRepository;
+
+async fn get_feed(app: &Router, token: &str, query: &str) -> Vec<BrainfartInformation> {
+    let uri = format!("{}{}", routes::BRAINFARTS, query);
+    let (status, body) = send(app, Method::GET, &uri, Some(token), None).await;
+    assert_eq!(status, StatusCode::OK);
+    parse(&body)
+}
+
+#[test]
+fn route_parameters_are_filled_in_and_encoded() {
+    assert_eq!(
+        routes::path(routes::BRAINFART_VOTES, &["42"]),
+        "/api/v1/brainfarts/42/votes"
+    );
+    assert_eq!(
+        routes::path(routes::RELATION, &["mute", "glad hjärna/2"]),
+        "/api/v1/relations/mute/glad%20hj%C3%A4rna%2F2"
+    );
+}
+
+#[tokio::test]
+async fn brainfarts_can_be_handled_through_the_versioned_api() {
+    for repository in backends().await {
+        let app = app_with(repository);
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            routes::SESSIONS,
+            None,
+            Some(json!({"brainname": "anna", "password": "lösenord"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+
+        let (status, body) = send(
+            &app,
+            Method::POST,
+            routes::BRAINFARTS,
+            Some(&anna),
+            Some(json!({"title": "idé", "content": "content"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        let brainfart_id = parse::<Brainfart>(&body).id;
+
+        let reactions = routes::path(routes::BRAINFART_REACTIONS, &[&brainfart_id]);
+        let (status, body) = send(
+            &app,
+            Method::POST,
+            &reactions,
+            Some(&bertil),
+            Some(json!({"kind": "explosion"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(parse::<BrainfartInformation>(&body).blew_minds.len(), 1);
+        let (status, body) = send(
+            &app,
+            Method::POST,
+            &reactions,
+            Some(&bertil),
+            Some(json!({"kind": "implosion"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        let brainfart = parse::<BrainfartInformation>(&body);
+        assert!(brainfart.blew_minds.is_empty());
+        assert_eq!(brainfart.imploded_minds.len(), 1);
+
+        let bookmark = routes::path(routes::BRAINFART_BOOKMARK, &[&brainfart_id]);
+        let (status, _) = send(&app, Method::PUT, &bookmark, Some(&bertil), None).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(get_feed(&app, &bertil, "?mode=bookmarks").await.len(), 1);
+        let (status, _) = send(&app, Method::DELETE, &bookmark, Some(&bertil), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(get_feed(&app, &bertil, "?mode=bookmarks").await.is_empty());
+
+        let rebrainfarts = routes::path(routes::BRAINFART_REBRAINFARTS, &[&brainfart_id]);
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            &rebrainfarts,
+            Some(&bertil),
+            Some(json!({})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(get_feed(&app, &anna, "?order=hot").await.len(), 2);
+
+        let reports = routes::path(routes::BRAINFART_REPORTS, &[&brainfart_id]);
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            &reports,
+            Some(&bertil),
+            Some(json!({"reason": "spam"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+}
+
+#[tokio::test]
+async fn relations_and_moderation_are_addressed_by_path() {
+    for repository in backends().await {
+        let app = app_with(repository.clone());
+        let admin = admin_token(&app, repository.as_ref()).await;
+        let anna = register(&app, "anna", "lösenord").await;
+        register(&app, "glad hjärna", "lösenord").await;
+        let brainfart = create_brainfart(&app, &anna, "offensive").await;
+
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            routes::RELATIONS,
+            Some(&anna),
+            Some(json!({"brainname": "glad hjärna", "relation": "mute"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        let relation = routes::path(routes::RELATION, &["mute", "glad hjärna"]);
+        let (status, body) = send(&app, Method::DELETE, &relation, Some(&anna), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(parse::<Vec<BrainRelationInformation>>(&body).is_empty());
+
+        let hidden = routes::path(routes::ADMIN_HIDDEN_BRAINFART, &[&brainfart.id]);
+        let (status, _) = send(&app, Method::PUT, &hidden, Some(&anna), None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, _) = send(&app, Method::PUT, &hidden, Some(&admin), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(get_brainfarts(&app, &anna).await.is_empty());
+        let (status, _) = send(&app, Method::DELETE, &hidden, Some(&admin), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(get_brainfarts(&app, &anna).await.len(), 1);
+
+        let suspension = routes::path(routes::ADMIN_BRAIN_SUSPENSION, &["glad hjärna"]);
+        let (status, _) = send(&app, Method::PUT, &suspension, Some(&admin), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            routes::SESSIONS,
+            None,
+            Some(json!({"brainname": "glad hjärna", "password": "lösenord"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let role = routes::path(routes::ADMIN_BRAIN_ROLE, &["anna"]);
+        let (status, _) = send(
+            &app,
+            Method::PUT,
+            &role,
+            Some(&admin),
+            Some(json!({"role": "admin"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn the_versioned_api_rejects_bad_ids_and_filters() {
+    for repository in backends().await {
+        let app = app_with(repository);
+        let anna = register(&app, "anna", "lösenord").await;
+        let brainfart = create_brainfart(&app, &anna, "idé").await;
+
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            &routes::path(routes::BRAINFART_REACTIONS, &["not-an-id"]),
+            Some(&anna),
+            Some(json!({"kind": "explosion"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            &routes::path(routes::BRAINFART_REACTIONS, &[&brainfart.id]),
+            Some(&anna),
+            Some(json!({"kind": "indifference"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let uri = format!("{}?order=sideways", routes::BRAINFARTS);
+        let (status, _) = send(&app, Method::GET, &uri, Some(&anna), None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+async fn old_paths_still_answer_but_are_marked_as_deprecated() {
+    let app = app_with(Arc::new(MemoryRepository::create()));
+    let anna = register(&app, "anna", "lösenord").await;
+    let deprecation = |uri: &str| {
+        let request = Request::builder()
+            .uri(uri)
+            .header("authorization", format!("Bearer {}", anna))
+            .body(Body::empty())
+            .unwrap();
+        let app = app.clone();
+        async move {
+            let response = app.oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            response
+                .headers()
+                .get("deprecation")
+                .map(|value| value.to_str().unwrap().to_string())
+        }
+    };
+    assert_eq!(
+        deprecation("/api/getbrainfarts").await.as_deref(),
+        Some("true")
+    );
+    assert_eq!(deprecation(routes::BRAINFARTS).await, None);
+}
@@ -0,0 +1,380 @@
+use std::{
+    collections::VecDeque,
+    net::{SocketAddr, TcpListener},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, Method, StatusCode},
+    routing::post,
+    Router,
+};
+use chrono::Local;
+use serde_json::{json, Value};
+use shared::{
+    DeliveryStatus, ReactionKind, WebhookDeliveryInformation, WebhookEvent, WebhookInformation,
+    WebhookPayload,
+};
+
+use super::{admin_token, app_with, backends, create_brainfart, parse, register, send};
+use crate::{
+    database::{memory_repository::MemoryRepository, repository::SharedRepository},
+    scheduling,
+    webhooks::{
+        self, http_sender::HttpWebhookSender, WebhookSender, DELIVERY_HEADER, EVENT_HEADER,
+        MAX_ATTEMPTS, SIGNATURE_HEADER,
+    },
+};
+
+const SECRET: &str = "a secret of some length";
+
+struct Received {
+    hook: String,
+    event: String,
+    delivery: String,
+    signature: String,
+    body: String,
+}
+
+/// What the stub receiver was sent, and the statuses it answers with next. Once those run out it
+/// answers 200.
+#[derive(Default)]
+struct Receiver {
+    received: Vec<Received>,
+    statuses: VecDeque<u16>,
+}
+
+type SharedReceiver = Arc<Mutex<Receiver>>;
+
+/// Takes deliveries at `/hooks/<name>` on a local port. Answers with its address.
+fn start_receiver(receiver: SharedReceiver) -> SocketAddr {
+    async fn receive(
+        State(receiver): State<SharedReceiver>,
+        Path(hook): Path<String>,
+        headers: HeaderMap,
+        body: String,
+    ) -> StatusCode {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+                .to_string()
+        };
+        let mut receiver = receiver.lock().unwrap();
+        receiver.received.push(Received {
+            hook,
+            event: header(EVENT_HEADER),
+            delivery: header(DELIVERY_HEADER),
+            signature: header(SIGNATURE_HEADER),
+            body,
+        });
+        let status = receiver.statuses.pop_front().unwrap_or(200);
+        StatusCode::from_u16(status).unwrap()
+    }
+    let stub = Router::new()
+        .route("/hooks/:hook", post(receive))
+        .with_state(receiver);
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(stub.into_make_service())
+            .await
+            .unwrap();
+    });
+    address
+}
+
+async fn add_webhook(app: &Router, token: &str, body: Value) -> (StatusCode, Vec<u8>) {
+    send(
+        app,
+        Method::POST,
+        "/api/v1/webhooks",
+        Some(token),
+        Some(body),
+    )
+    .await
+}
+
+async fn create_webhook(
+    app: &Router,
+    token: &str,
+    url: String,
+    events: Value,
+) -> WebhookInformation {
+    let (status, body) = add_webhook(
+        app,
+        token,
+        json!({"url": url, "events": events, "secret": SECRET}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    parse(&body)
+}
+
+async fn get_deliveries(
+    app: &Router,
+    token: &str,
+    webhook_id: &str,
+) -> Vec<WebhookDeliveryInformation> {
+    let uri = format!("/api/v1/webhooks/{}/deliveries", webhook_id);
+    let (status, body) = send(app, Method::GET, &uri, Some(token), None).await;
+    assert_eq!(status, StatusCode::OK);
+    parse(&body)
+}
+
+#[tokio::test]
+async fn signed_events_reach_the_webhooks_that_listen_for_them() {
+    for repository in backends().await {
+        let receiver = SharedReceiver::default();
+        let address = start_receiver(receiver.clone());
+        let sender = HttpWebhookSender::create(true);
+        let app = app_with(repository.clone());
+        let admin = admin_token(&app, repository.as_ref()).await;
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+
+        let annas = create_webhook(
+            &app,
+            &anna,
+            format!("http://{}/hooks/anna", address),
+            json!(["brainfart_created", "brainfart_reacted"]),
+        )
+        .await;
+        create_webhook(
+            &app,
+            &bertil,
+            format!("http://{}/hooks/bertil", address),
+            json!(["brainfart_created"]),
+        )
+        .await;
+        let (status, body) = add_webhook(
+            &app,
+            &admin,
+            json!({
+                "url": format!("http://{}/hooks/global", address),
+                "events": ["brainfart_reacted"],
+                "secret": SECRET,
+                "global": true
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert!(parse::<WebhookInformation>(&body).global);
+
+        let brainfart = create_brainfart(&app, &anna, "idea").await;
+        let uri = format!("/api/v1/brainfarts/{}/reactions", brainfart.id);
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            &uri,
+            Some(&bertil),
+            Some(json!({"kind": "explosion"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        assert_eq!(
+            webhooks::deliver_due(&repository, &sender, Local::now()).await,
+            3
+        );
+        let reacted_delivery = {
+            let receiver = receiver.lock().unwrap();
+            let received: Vec<(&str, &str)> = receiver
+                .received
+                .iter()
+                .map(|received| (received.hook.as_str(), received.event.as_str()))
+                .collect();
+            assert_eq!(
+                received,
+                vec![
+                    ("anna", "brainfart_created"),
+                    ("anna", "brainfart_reacted"),
+                    ("global", "brainfart_reacted"),
+                ]
+            );
+            for received in receiver.received.iter() {
+                assert_eq!(received.signature, webhooks::sign(SECRET, &received.body));
+            }
+            let reacted: WebhookPayload = serde_json::from_str(&receiver.received[1].body).unwrap();
+            assert_eq!(reacted.event, WebhookEvent::BrainfartReacted);
+            assert_eq!(reacted.brainfart.id, brainfart.id);
+            assert_eq!(reacted.brainfart.mastermind_name, "anna");
+            let reaction = reacted.reaction.unwrap();
+            assert_eq!(reaction.kind, ReactionKind::Explosion);
+            assert_eq!(reaction.brainname, "bertil");
+            receiver.received[1].delivery.clone()
+        };
+
+        let deliveries = get_deliveries(&app, &anna, &annas.id).await;
+        assert_eq!(deliveries.len(), 2);
+        assert_eq!(deliveries[0].event, WebhookEvent::BrainfartReacted);
+        assert_eq!(deliveries[0].id, reacted_delivery);
+        for delivery in deliveries.iter() {
+            assert_eq!(delivery.status, DeliveryStatus::Delivered);
+            assert_eq!(delivery.attempts, 1);
+            assert_eq!(delivery.response_status, Some(200));
+        }
+    }
+}
+
+#[tokio::test]
+async fn failed_deliveries_are_retried_until_they_are_given_up() {
+    for repository in backends().await {
+        let receiver = SharedReceiver::default();
+        let address = start_receiver(receiver.clone());
+        let sender = HttpWebhookSender::create(true);
+        let app = app_with(repository.clone());
+        let anna = register(&app, "anna", "lösenord").await;
+        let webhook = create_webhook(
+            &app,
+            &anna,
+            format!("http://{}/hooks/anna", address),
+            json!(["brainfart_created"]),
+        )
+        .await;
+
+        receiver.lock().unwrap().statuses = VecDeque::from([500]);
+        create_brainfart(&app, &anna, "first").await;
+        let now = Local::now();
+        assert_eq!(webhooks::deliver_due(&repository, &sender, now).await, 0);
+        let deliveries = get_deliveries(&app, &anna, &webhook.id).await;
+        assert_eq!(deliveries[0].status, DeliveryStatus::Pending);
+        assert_eq!(deliveries[0].response_status, Some(500));
+        assert!(deliveries[0].error.is_some());
+        assert!(deliveries[0].next_attempt_at.unwrap() > now);
+
+        // Not due again right away.
+        assert_eq!(webhooks::deliver_due(&repository, &sender, now).await, 0);
+        assert_eq!(receiver.lock().unwrap().received.len(), 1);
+        let later = now + chrono::Duration::minutes(2);
+        assert_eq!(webhooks::deliver_due(&repository, &sender, later).await, 1);
+        let deliveries = get_deliveries(&app, &anna, &webhook.id).await;
+        assert_eq!(deliveries[0].status, DeliveryStatus::Delivered);
+        assert_eq!(deliveries[0].attempts, 2);
+        assert_eq!(deliveries[0].error, None);
+
+        receiver.lock().unwrap().statuses = VecDeque::from([503; MAX_ATTEMPTS]);
+        create_brainfart(&app, &anna, "second").await;
+        let mut now = Local::now();
+        for _ in 0..MAX_ATTEMPTS {
+            webhooks::deliver_due(&repository, &sender, now).await;
+            now += chrono::Duration::hours(3);
+        }
+        let deliveries = get_deliveries(&app, &anna, &webhook.id).await;
+        assert_eq!(deliveries[0].status, DeliveryStatus::Failed);
+        assert_eq!(deliveries[0].attempts, MAX_ATTEMPTS as i32);
+        assert_eq!(deliveries[0].next_attempt_at, None);
+        assert_eq!(webhooks::deliver_due(&repository, &sender, now).await, 0);
+        assert_eq!(receiver.lock().unwrap().received.len(), 2 + MAX_ATTEMPTS);
+    }
+}
+
+#[tokio::test]
+async fn webhooks_are_only_managed_by_their_owners() {
+    for repository in backends().await {
+        let app = app_with(repository.clone());
+        let anna = register(&app, "anna", "lösenord").await;
+        let bertil = register(&app, "bertil", "lösenord").await;
+        let url = "https://example.com/hooks";
+
+        for invalid in [
+            json!({"url": url, "events": ["brainfart_created"], "secret": "short"}),
+            json!({"url": "ftp://example.com", "events": ["brainfart_created"], "secret": SECRET}),
+            json!({"url": url, "events": [], "secret": SECRET}),
+        ] {
+            let (status, _) = add_webhook(&app, &anna, invalid).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+        }
+        let (status, _) = add_webhook(
+            &app,
+            &anna,
+            json!({"url": url, "events": ["brainfart_created"], "secret": SECRET, "global": true}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let webhook =
+            create_webhook(&app, &anna, url.to_string(), json!(["brainfart_created"])).await;
+        let (status, body) = send(&app, Method::GET, "/api/v1/webhooks", Some(&bertil), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(parse::<Vec<WebhookInformation>>(&body).is_empty());
+
+        let uri = format!("/api/v1/webhooks/{}", webhook.id);
+        let deliveries_uri = format!("{}/deliveries", uri);
+        let (status, _) = send(&app, Method::DELETE, &uri, Some(&bertil), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = send(&app, Method::GET, &deliveries_uri, Some(&bertil), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        create_brainfart(&app, &anna, "idea").await;
+        let (status, _) = send(&app, Method::DELETE, &uri, Some(&anna), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, body) = send(&app, Method::GET, "/api/v1/webhooks", Some(&anna), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(parse::<Vec<WebhookInformation>>(&body).is_empty());
+        assert_eq!(
+            repository
+                .get_due_webhook_deliveries(Local::now(), 10)
+                .await
+                .map(|deliveries| deliveries.len()),
+            Some(0)
+        );
+    }
+}
+
+#[tokio::test]
+async fn scheduled_brainfarts_are_sent_once_published() {
+    let repository: SharedRepository = Arc::new(MemoryRepository::create());
+    let app = app_with(repository.clone());
+    let anna = register(&app, "anna", "lösenord").await;
+    create_webhook(
+        &app,
+        &anna,
+        "https://example.com/hooks".to_string(),
+        json!(["brainfart_created"]),
+    )
+    .await;
+    let publish_at = Local::now() + chrono::Duration::milliseconds(200);
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/v1/brainfarts",
+        Some(&anna),
+        Some(json!({"title": "soon", "content": "content", "publish_at": publish_at})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let due = repository
+        .get_due_webhook_deliveries(Local::now(), 10)
+        .await;
+    assert_eq!(due.map(|deliveries| deliveries.len()), Some(0));
+
+    let publisher = scheduling::spawn_publisher(repository.clone(), Duration::from_millis(20));
+    tokio::time::sleep(Duration::from_millis(600)).await;
+    publisher.abort();
+
+    let due = repository
+        .get_due_webhook_deliveries(Local::now(), 10)
+        .await
+        .unwrap();
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].event, WebhookEvent::BrainfartCreated);
+}
+
+#[tokio::test]
+async fn only_public_addresses_get_deliveries_by_default() {
+    let receiver = SharedReceiver::default();
+    let address = start_receiver(receiver.clone());
+    let sender = HttpWebhookSender::create(false);
+
+    let result = sender
+        .send(&format!("http://{}/hooks/anna", address), &[], "{}")
+        .await;
+    assert!(result.is_err());
+    assert!(receiver.lock().unwrap().received.is_empty());
+}
@@ -0,0 +1,127 @@
+use std::{net::SocketAddr, time::Duration};
+
+use axum::async_trait;
+use hyper::{
+    header::{CONTENT_TYPE, HOST, USER_AGENT},
+    Body, Request,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::TcpStream,
+};
+use tokio_rustls::{rustls::ServerName, TlsConnector};
+use url::{Host, Position, Url};
+
+use super::WebhookSender;
+use crate::link_previews::http_fetcher::{is_public_address, tls_connector};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Posts deliveries over HTTP and HTTPS. Like the link previews, only public addresses are
+/// contacted, and redirects are not followed, since they could lead anywhere.
+pub struct HttpWebhookSender {
+    allow_private_addresses: bool,
+    tls: TlsConnector,
+}
+
+impl HttpWebhookSender {
+    /// `allow_private_addresses` lets webhooks reach the network the server runs in, e.g. a chat
+    /// server next to it. Anyone who can create a webhook can then make the server post there.
+    pub fn create(allow_private_addresses: bool) -> HttpWebhookSender {
+        HttpWebhookSender {
+            allow_private_addresses,
+            tls: tls_connector(),
+        }
+    }
+
+    async fn resolve(&self, url: &Url) -> Result<SocketAddr, String> {
+        let port = url
+            .port_or_known_default()
+            .ok_or_else(|| "The url has no port".to_string())?;
+        let addresses: Vec<SocketAddr> = match url.host() {
+            Some(Host::Ipv4(address)) => vec![SocketAddr::new(address.into(), port)],
+            Some(Host::Ipv6(address)) => vec![SocketAddr::new(address.into(), port)],
+            Some(Host::Domain(domain)) => tokio::net::lookup_host((domain, port))
+                .await
+                .map_err(|err| format!("Looking up {} failed: {}", domain, err))?
+                .collect(),
+            None => return Err("The url has no host".to_string()),
+        };
+        if !self.allow_private_addresses
+            && addresses
+                .iter()
+                .any(|address| !is_public_address(address.ip()))
+        {
+            return Err("The url does not lead to a public address".to_string());
+        }
+        addresses
+            .into_iter()
+            .next()
+            .ok_or_else(|| "The host has no address".to_string())
+    }
+
+    async fn post(&self, url: &Url, headers: &[(&str, String)], body: &str) -> Result<u16, String> {
+        let address = self.resolve(url).await?;
+        let mut builder = Request::post(&url[Position::BeforePath..Position::AfterQuery])
+            .header(HOST, &url[Position::BeforeHost..Position::AfterPort])
+            .header(USER_AGENT, "Brains webhooks")
+            .header(CONTENT_TYPE, "application/json");
+        for (name, value) in headers {
+            builder = builder.header(*name, value);
+        }
+        let request = builder
+            .body(Body::from(body.to_string()))
+            .map_err(|err| err.to_string())?;
+        let stream = TcpStream::connect(address)
+            .await
+            .map_err(|err| format!("Connecting failed: {}", err))?;
+        match url.scheme() {
+            "http" => send_request(stream, request).await,
+            "https" => {
+                let host = url
+                    .host_str()
+                    .unwrap_or_default()
+                    .trim_start_matches('[')
+                    .trim_end_matches(']');
+                let server_name =
+                    ServerName::try_from(host).map_err(|_| format!("{} is not a host", host))?;
+                let stream = self
+                    .tls
+                    .connect(server_name, stream)
+                    .await
+                    .map_err(|err| format!("TLS failed: {}", err))?;
+                send_request(stream, request).await
+            }
+            scheme => Err(format!("{} is not http or https", scheme)),
+        }
+    }
+}
+
+async fn send_request<T>(stream: T, request: Request<Body>) -> Result<u16, String>
+where
+    T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let (mut sender, connection) = hyper::client::conn::handshake(stream)
+        .await
+        .map_err(|err| err.to_string())?;
+    tokio::spawn(async move {
+        if let Err(err) = connection.await {
+            log::debug!("A webhook connection failed: {}", err);
+        }
+    });
+    let response = sender
+        .send_request(request)
+        .await
+        .map_err(|err| format!("Sending failed: {}", err))?;
+    Ok(response.status().as_u16())
+}
+
+#[async_trait]
+impl WebhookSender for HttpWebhookSender {
+    async fn send(&self, url: &str, headers: &[(&str, String)], body: &str) -> Result<u16, String> {
+        let parsed = Url::parse(url).map_err(|err| format!("Invalid url: {}", err))?;
+        tokio::time::timeout(TIMEOUT, self.post(&parsed, headers, body))
+            .await
+            .map_err(|_| format!("No answer within {} seconds", TIMEOUT.as_secs()))?
+    }
+}
@@ -0,0 +1,388 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    async_trait,
+    extract::{rejection::JsonRejection, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Local};
+use shared::{
+    CreateWebhookRequest, DeliveryStatus, WebhookBrainfart, WebhookEvent, WebhookPayload,
+    WebhookReaction, MAX_WEBHOOKS_PER_BRAIN, MAX_WEBHOOK_URL_CHARACTERS,
+    MIN_WEBHOOK_SECRET_CHARACTERS,
+};
+use sqlx::types::Uuid;
+use tokio::task::JoinHandle;
+
+use crate::{
+    authentication::JwtDataHolder,
+    database::repository::{DeliveryAttempt, SharedRepository, Webhook},
+    error_responders,
+};
+
+pub mod http_sender;
+
+pub type SharedWebhookSender = Arc<dyn WebhookSender>;
+
+pub const EVENT_HEADER: &str = "x-brains-event";
+pub const DELIVERY_HEADER: &str = "x-brains-delivery";
+/// `sha256=` followed by the hex encoded HMAC-SHA256 of the body, keyed with the secret.
+pub const SIGNATURE_HEADER: &str = "x-brains-signature";
+
+/// How long to wait after each failed attempt before trying again.
+const RETRY_DELAYS_SECONDS: [i64; 4] = [60, 5 * 60, 30 * 60, 2 * 60 * 60];
+/// A delivery is given up on after this many attempts.
+pub const MAX_ATTEMPTS: usize = RETRY_DELAYS_SECONDS.len() + 1;
+const DELIVERIES_PER_ROUND: i64 = 50;
+const DELIVERIES_SHOWN: i64 = 50;
+
+/// Posts deliveries to the receivers. The server sends them over HTTP; the tests record them.
+#[async_trait]
+pub trait WebhookSender: Send + Sync {
+    /// Answers with the status of the response, or why there was none.
+    async fn send(&self, url: &str, headers: &[(&str, String)], body: &str) -> Result<u16, String>;
+}
+
+/// What goes in the signature header for the body.
+pub fn sign(secret: &str, body: &str) -> String {
+    let mac = hmac_sha256::HMAC::mac(body.as_bytes(), secret.as_bytes());
+    let hex: String = mac.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("sha256={}", hex)
+}
+
+/// Queues a delivery of the event for every webhook that listens for it. The deliverer sends
+/// them, so nobody waits for the receivers.
+pub async fn enqueue(
+    repository: &SharedRepository,
+    event: WebhookEvent,
+    brainfart_id: &Uuid,
+    reaction: Option<WebhookReaction>,
+) {
+    let brainfart = match repository.get_any_brainfart(brainfart_id).await {
+        Some(brainfart) => brainfart,
+        None => {
+            log::warn!(
+                "Not sending {} for {}: no such brainfart",
+                event,
+                brainfart_id
+            );
+            return;
+        }
+    };
+    let webhooks = match repository
+        .get_subscribed_webhooks(event, &brainfart.mastermind_name)
+        .await
+    {
+        Some(webhooks) if !webhooks.is_empty() => webhooks,
+        Some(_) => return,
+        None => {
+            log::warn!("Looking up the webhooks for {} failed", event);
+            return;
+        }
+    };
+    let payload = WebhookPayload {
+        event,
+        occurred_at: Local::now(),
+        brainfart: WebhookBrainfart {
+            original_id: brainfart.original.map(|original| original.id),
+            id: brainfart.id,
+            title: brainfart.title,
+            content: brainfart.content,
+            birthdate: brainfart.birthdate,
+            mastermind_name: brainfart.mastermind_name,
+        },
+        reaction,
+    };
+    let body = match serde_json::to_string(&payload) {
+        Ok(body) => body,
+        Err(err) => {
+            log::warn!("Serializing the {} payload failed: {}", event, err);
+            return;
+        }
+    };
+    for webhook in webhooks {
+        if repository
+            .add_webhook_delivery(&webhook.id, event, &body)
+            .await
+            .is_none()
+        {
+            log::warn!("Queueing {} for webhook {} failed", event, webhook.id);
+        }
+    }
+}
+
+/// A 2xx answer delivers; anything else is tried again later, until there have been
+/// `MAX_ATTEMPTS`.
+fn to_attempt(
+    attempts: usize,
+    result: Result<u16, String>,
+    now: DateTime<Local>,
+) -> DeliveryAttempt {
+    let (response_status, error) = match result {
+        Ok(status) if (200..300).contains(&status) => {
+            return DeliveryAttempt {
+                status: DeliveryStatus::Delivered,
+                response_status: Some(status.into()),
+                error: None,
+                attempted_at: now,
+                next_attempt: None,
+            }
+        }
+        Ok(status) => (
+            Some(status.into()),
+            Some(format!("The receiver answered with {}", status)),
+        ),
+        Err(err) => (None, Some(err)),
+    };
+    let next_attempt = (attempts < MAX_ATTEMPTS)
+        .then(|| now + chrono::Duration::seconds(RETRY_DELAYS_SECONDS[attempts.max(1) - 1]));
+    DeliveryAttempt {
+        status: if next_attempt.is_some() {
+            DeliveryStatus::Pending
+        } else {
+            DeliveryStatus::Failed
+        },
+        response_status,
+        error,
+        attempted_at: now,
+        next_attempt,
+    }
+}
+
+/// Attempts the deliveries that are due at `now`. Returns how many of them were delivered.
+pub async fn deliver_due(
+    repository: &SharedRepository,
+    sender: &dyn WebhookSender,
+    now: DateTime<Local>,
+) -> usize {
+    let deliveries = match repository
+        .get_due_webhook_deliveries(now, DELIVERIES_PER_ROUND)
+        .await
+    {
+        Some(deliveries) => deliveries,
+        None => {
+            log::warn!("Looking up the due webhook deliveries failed");
+            return 0;
+        }
+    };
+    let mut delivered = 0;
+    for delivery in deliveries {
+        let headers = [
+            (EVENT_HEADER, delivery.event.to_string()),
+            (DELIVERY_HEADER, delivery.id.to_string()),
+            (SIGNATURE_HEADER, sign(&delivery.secret, &delivery.payload)),
+        ];
+        let result = sender
+            .send(&delivery.url, &headers, &delivery.payload)
+            .await;
+        let attempt = to_attempt(delivery.attempts as usize + 1, result, now);
+        if attempt.status == DeliveryStatus::Delivered {
+            delivered += 1;
+        } else {
+            log::info!(
+                "Delivering to webhook {} failed: {}",
+                delivery.webhook_id,
+                attempt.error.as_deref().unwrap_or_default()
+            );
+        }
+        if !repository
+            .record_webhook_delivery_attempt(&delivery.id, &attempt)
+            .await
+        {
+            log::warn!("Recording the delivery {} failed", delivery.id);
+        }
+    }
+    delivered
+}
+
+/// Sends the queued deliveries, looking for due ones every `interval`. Runs for as long as the
+/// server does.
+pub fn spawn_deliverer(
+    repository: SharedRepository,
+    sender: SharedWebhookSender,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            deliver_due(&repository, sender.as_ref(), Local::now()).await;
+        }
+    })
+}
+
+fn no_such_webhook() -> (StatusCode, String) {
+    (
+        StatusCode::NOT_FOUND,
+        "There is no such webhook".to_string(),
+    )
+}
+
+/// Brains manage their own webhooks, admins the global ones too.
+async fn find_webhook(
+    repository: &SharedRepository,
+    claims: &JwtDataHolder,
+    webhook_id: &Uuid,
+) -> Result<Webhook, (StatusCode, String)> {
+    match repository.get_webhook(webhook_id).await {
+        Some(webhook)
+            if webhook.owner_id == claims.get_id()
+                || (webhook.global && claims.information.is_admin()) =>
+        {
+            Ok(webhook)
+        }
+        _ => Err(no_such_webhook()),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/webhooks",
+    tag = "webhooks",
+    responses(
+        (status = 200, description = "Your webhooks, oldest first", body = [WebhookInformation])
+    ),
+    security(("bearer" = []))
+)]
+pub async fn get_webhooks(
+    State(repository): State<SharedRepository>,
+    claims: JwtDataHolder,
+) -> impl IntoResponse {
+    match repository.get_webhooks(&claims.get_id()).await {
+        Some(webhooks) => Ok((
+            StatusCode::OK,
+            Json(
+                webhooks
+                    .iter()
+                    .map(Webhook::information)
+                    .collect::<Vec<_>>(),
+            ),
+        )),
+        None => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Something went wrong reading the webhooks".to_string(),
+        )),
+    }
+}
+
+/// The receiver gets a signed `WebhookPayload` for each of the events.
+#[utoipa::path(
+    post,
+    path = "/api/v1/webhooks",
+    tag = "webhooks",
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 201, description = "The new webhook", body = WebhookInformation),
+        (status = 400, description = "Invalid data, or you have too many webhooks", body = String),
+        (status = 403, description = "Only admins can create global webhooks", body = String)
+    ),
+    security(("bearer" = []))
+)]
+pub async fn create_webhook(
+    State(repository): State<SharedRepository>,
+    claims: JwtDataHolder,
+    result: Result<Json<CreateWebhookRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    let payload = match result {
+        Ok(Json(payload)) => payload,
+        Err(err) => return Err(error_responders::post_error_responder(err)),
+    };
+    if !payload.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "A webhook needs an http or https url of at most {MAX_WEBHOOK_URL_CHARACTERS} \
+                characters, at least one event and a secret of at least \
+                {MIN_WEBHOOK_SECRET_CHARACTERS} characters"
+            ),
+        ));
+    }
+    if payload.global && !claims.information.is_admin() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only admins can create global webhooks".to_string(),
+        ));
+    }
+    let owner_id = claims.get_id();
+    let existing = repository
+        .get_webhooks(&owner_id)
+        .await
+        .map_or(0, |webhooks| webhooks.len());
+    if existing >= MAX_WEBHOOKS_PER_BRAIN {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("You can have at most {MAX_WEBHOOKS_PER_BRAIN} webhooks"),
+        ));
+    }
+    let created = match repository.create_webhook(&payload, &owner_id).await {
+        Some(response) => repository.get_webhook(&response.uuid).await,
+        None => None,
+    };
+    match created {
+        Some(webhook) => Ok((StatusCode::CREATED, Json(webhook.information()))),
+        None => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Something went wrong creating the webhook".to_string(),
+        )),
+    }
+}
+
+/// Deliveries that are still pending are dropped with the webhook.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/webhooks/{webhook_id}",
+    tag = "webhooks",
+    params(
+        ("webhook_id" = String, Path, description = "The id of the webhook")
+    ),
+    responses(
+        (status = 200, description = "The webhook was deleted"),
+        (status = 404, description = "There is no such webhook", body = String)
+    ),
+    security(("bearer" = []))
+)]
+pub async fn delete_webhook(
+    State(repository): State<SharedRepository>,
+    claims: JwtDataHolder,
+    Path(webhook_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let webhook = find_webhook(&repository, &claims, &webhook_id).await?;
+    if repository.delete_webhook(&webhook.id).await {
+        Ok((StatusCode::OK, Json(())))
+    } else {
+        Err(no_such_webhook())
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/webhooks/{webhook_id}/deliveries",
+    tag = "webhooks",
+    params(
+        ("webhook_id" = String, Path, description = "The id of the webhook")
+    ),
+    responses(
+        (status = 200, description = "The latest deliveries, newest first", body = [WebhookDeliveryInformation]),
+        (status = 404, description = "There is no such webhook", body = String)
+    ),
+    security(("bearer" = []))
+)]
+pub async fn get_webhook_deliveries(
+    State(repository): State<SharedRepository>,
+    claims: JwtDataHolder,
+    Path(webhook_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let webhook = find_webhook(&repository, &claims, &webhook_id).await?;
+    match repository
+        .get_webhook_deliveries(&webhook.id, DELIVERIES_SHOWN)
+        .await
+    {
+        Some(deliveries) => Ok((StatusCode::OK, Json(deliveries))),
+        None => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Something went wrong reading the deliveries".to_string(),
+        )),
+    }
+}
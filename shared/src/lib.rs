@@ -1,10 +1,16 @@
 use chrono::DateTime;
+use chrono::Duration;
 use chrono::Local;
 use serde::Deserialize;
 use serde::Serialize;
+use std::fmt::Display;
+use std::str::FromStr;
+pub mod routes;
+
 pub type Uuid = String;
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct BrainInformation {
     id: Uuid,
     name: String,
@@ -15,6 +21,9 @@ impl BrainInformation {
     pub fn get_id(&self) -> &Uuid {
         &self.id
     }
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
     pub fn create_from_brain(brain: Brain) -> BrainInformation {
         BrainInformation {
             id: brain.id,
@@ -23,6 +32,15 @@ impl BrainInformation {
         }
     }
 
+    /// Stands in for a brain that has been deleted but whose reactions were kept.
+    pub fn removed() -> BrainInformation {
+        BrainInformation {
+            id: Uuid::new(),
+            name: REMOVED_BRAIN_NAME.to_string(),
+            birthdate: DateTime::default(),
+        }
+    }
+
     pub fn create(id: Uuid, name: String, birthdate: DateTime<Local>) -> BrainInformation {
         BrainInformation {
             id,
@@ -32,6 +50,7 @@ impl BrainInformation {
     }
 }
 #[derive(Deserialize, Serialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct BrainfartInformation {
     pub id: Uuid,
     pub title: String,
@@ -40,6 +59,26 @@ pub struct BrainfartInformation {
     pub mastermind_name: String,
     pub blew_minds: Vec<BrainInformation>,
     pub imploded_minds: Vec<BrainInformation>,
+    /// Whether the brain asking for the brainfart has bookmarked it.
+    #[serde(default)]
+    pub bookmarked_by_me: bool,
+    #[serde(default)]
+    pub attachments: Vec<AttachmentInformation>,
+    /// Cards for the links in the content, for the links that have a preview.
+    #[serde(default)]
+    pub link_previews: Vec<LinkPreview>,
+    #[serde(default)]
+    pub poll: Option<PollInformation>,
+    /// The brainfart this one rebrainfarts or quotes, one level deep. Missing once the original
+    /// is deleted or hidden.
+    #[serde(default)]
+    pub original: Option<Box<BrainfartInformation>>,
+    /// How many times the brainfart has been rebrainfarted or quoted.
+    #[serde(default)]
+    pub rebrainfarts: i64,
+    /// Whether the brain asking for the brainfart has rebrainfarted it, not counting quotes.
+    #[serde(default)]
+    pub rebrainfarted_by_me: bool,
 }
 
 impl PartialEq for BrainfartInformation {
@@ -63,9 +102,21 @@ impl BrainfartInformation {
             mastermind_name,
             blew_minds,
             imploded_minds,
+            bookmarked_by_me: false,
+            attachments: vec![],
+            link_previews: vec![],
+            poll: None,
+            original: None,
+            rebrainfarts: 0,
+            rebrainfarted_by_me: false,
         }
     }
 
+    /// A rebrainfart without commentary only shows its original.
+    pub fn is_plain_rebrainfart(&self) -> bool {
+        self.original.is_some() && self.content.is_empty()
+    }
+
     pub fn empty() -> BrainfartInformation {
         BrainfartInformation {
             id: Uuid::default(),
@@ -75,8 +126,111 @@ impl BrainfartInformation {
             mastermind_name: String::default(),
             blew_minds: vec![],
             imploded_minds: vec![],
+            bookmarked_by_me: false,
+            attachments: vec![],
+            link_previews: vec![],
+            poll: None,
+            original: None,
+            rebrainfarts: 0,
+            rebrainfarted_by_me: false,
+        }
+    }
+}
+
+/// The kinds of files that can be attached to a brainfart.
+pub const ALLOWED_ATTACHMENT_TYPES: [&str; 5] = [
+    "image/png",
+    "image/jpeg",
+    "image/gif",
+    "application/pdf",
+    "text/plain",
+];
+pub const MAX_ATTACHMENTS_PER_BRAINFART: usize = 4;
+
+/// A file attached to a brainfart. Anyone who knows the id can download the file, so that
+/// browsers can show images without sending a token.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AttachmentInformation {
+    pub id: Uuid,
+    pub filename: String,
+    pub content_type: String,
+    pub size: i64,
+    pub has_thumbnail: bool,
+}
+
+impl AttachmentInformation {
+    pub fn url(&self) -> String {
+        routes::path(routes::ATTACHMENT, &[&self.id])
+    }
+
+    pub fn thumbnail_url(&self) -> Option<String> {
+        self.has_thumbnail
+            .then(|| routes::path(routes::ATTACHMENT_THUMBNAIL, &[&self.id]))
+    }
+
+    pub fn is_image(&self) -> bool {
+        self.content_type.starts_with("image/")
+    }
+
+    pub fn is_allowed_type(content_type: &str) -> bool {
+        ALLOWED_ATTACHMENT_TYPES.contains(&content_type)
+    }
+}
+
+/// What the linked page says about itself in its OpenGraph tags.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LinkPreview {
+    pub url: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub site_name: Option<String>,
+}
+
+pub const MAX_LINK_PREVIEWS_PER_BRAINFART: usize = 3;
+
+/// The http and https links in the content, in order and without duplicates. Punctuation right
+/// after a link is taken to belong to the sentence.
+pub fn find_urls(content: &str) -> Vec<String> {
+    let mut urls: Vec<String> = vec![];
+    for word in content.split_whitespace() {
+        let word = word.trim_start_matches(['(', '<', '[', '"', '\'']);
+        if !word.starts_with("http://") && !word.starts_with("https://") {
+            continue;
+        }
+        let url = word.trim_end_matches(['.', ',', ';', ':', '!', '?', ')', '>', ']', '"', '\'']);
+        if url.len() > "https://".len() && url.len() <= 2048 && !urls.iter().any(|seen| seen == url)
+        {
+            urls.push(url.to_string());
+        }
+        if urls.len() == MAX_LINK_PREVIEWS_PER_BRAINFART {
+            break;
+        }
+    }
+    urls
+}
+
+/// The `#tags` in the content, lowercased, in order and without duplicates. A tag is made of
+/// letters, digits, `_` and `-`, so punctuation right after it is left out.
+pub fn find_tags(content: &str) -> Vec<String> {
+    let mut tags: Vec<String> = vec![];
+    for word in content.split_whitespace() {
+        let Some(word) = word.trim_start_matches(['(', '"', '\'']).strip_prefix('#') else {
+            continue;
+        };
+        let tag = word
+            .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+            .next()
+            .unwrap_or_default()
+            .trim_end_matches('-')
+            .to_lowercase();
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
         }
     }
+    tags
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -84,12 +238,28 @@ pub struct NotifyAboutMindExplosionRequest {
     pub brainfart_id: Uuid,
 }
 
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum ReactionKind {
+    Explosion,
+    Implosion,
+}
+
+/// The body of a reaction to the brainfart in the path.
+#[derive(Deserialize, Serialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ReactionRequest {
+    pub kind: ReactionKind,
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct NotifyAboutMindImplosionRequest {
     pub brainfart_id: Uuid,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct Brainfart {
     pub id: Uuid,
     pub title: String,
@@ -115,18 +285,255 @@ impl Brainfart {
     }
 }
 
-#[derive(Deserialize, Serialize, Debug)]
-pub struct BrainfartFilter {}
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum BrainfartFilterMode {
+    #[default]
+    All,
+    /// Only the brainfarts the requesting brain has bookmarked.
+    Bookmarks,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum BrainfartOrder {
+    #[default]
+    Newest,
+    /// Brainfarts blowing minds right now first: net explosions and views, weighed down by age.
+    Hot,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct BrainfartFilter {
+    #[serde(default)]
+    pub mode: BrainfartFilterMode,
+    #[serde(default)]
+    pub order: BrainfartOrder,
+    /// Only the brainfarts of this brain.
+    pub brainname: Option<String>,
+    /// Only the brainfarts with this tag, written like `find_tags` gives it: `rust` for `#Rust`.
+    /// A plain rebrainfart has the tags of its original.
+    pub tag: Option<String>,
+    /// At most this many brainfarts.
+    pub limit: Option<u32>,
+}
+
+/// How far back a leaderboard counts explosions.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum LeaderboardWindow {
+    #[default]
+    Week,
+    Month,
+    All,
+}
+
+impl LeaderboardWindow {
+    /// The first moment that counts, or None for all time.
+    pub fn start(&self, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        match self {
+            LeaderboardWindow::Week => Some(now - Duration::days(7)),
+            LeaderboardWindow::Month => Some(now - Duration::days(30)),
+            LeaderboardWindow::All => None,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct LeaderboardQuery {
+    #[serde(default)]
+    pub window: LeaderboardWindow,
+}
+
+pub const LEADERBOARD_SIZE: i64 = 10;
+
+/// The syndication format of a feed.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum FeedFormat {
+    #[default]
+    Atom,
+    Rss,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct FeedQuery {
+    #[serde(default)]
+    pub format: FeedFormat,
+    /// A feed token, for feed readers that cannot send an `Authorization` header.
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+/// A token that only gives access to the feeds, so it can be put in a feed reader's url.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FeedToken {
+    pub token: String,
+}
+
+/// How many of the newest brainfarts a feed holds.
+pub const FEED_LENGTH: usize = 50;
+
+/// Shown as the mastermind of brainfarts whose brain has been deleted, and as the brain of its
+/// reactions. No brain can be registered with this name.
+pub const REMOVED_BRAIN_NAME: &str = "[removed]";
+
+/// What happens to a brain's brainfarts and reactions when it is deleted.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum DeletionMode {
+    /// They are deleted along with the brain.
+    #[default]
+    Delete,
+    /// They stay, with the brain shown as removed.
+    Anonymize,
+}
+
+impl Display for DeletionMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeletionMode::Delete => write!(f, "delete"),
+            DeletionMode::Anonymize => write!(f, "anonymize"),
+        }
+    }
+}
+
+impl FromStr for DeletionMode {
+    type Err = String;
 
-impl BrainfartFilter {
-    pub fn default() -> BrainfartFilter {
-        BrainfartFilter {}
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode {
+            "delete" => Ok(DeletionMode::Delete),
+            "anonymize" => Ok(DeletionMode::Anonymize),
+            _ => Err(format!("{} is not delete or anonymize", mode)),
+        }
     }
 }
+
+/// The password has to be given again, so a forgotten open session cannot delete the brain.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DeleteBrainRequest {
+    pub password: String,
+    #[serde(default)]
+    pub mode: DeletionMode,
+}
+
+/// A deletion waiting for its grace period to run out. Until then it can be called off.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct BrainDeletion {
+    pub mode: DeletionMode,
+    pub delete_at: DateTime<Local>,
+}
+
+/// The version of the export format, raised when an archive could no longer be read as before.
+pub const EXPORT_VERSION: u32 = 1;
+
+/// Everything a brain takes with it: its profile, its brainfarts and the reactions and views it
+/// has given. Attachments, polls and drafts are left out.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct BrainExport {
+    pub version: u32,
+    pub exported_at: DateTime<Local>,
+    pub brain: ExportedBrain,
+    pub brainfarts: Vec<ExportedBrainfart>,
+    pub reactions: Vec<ExportedReaction>,
+    pub views: Vec<ExportedView>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ExportedBrain {
+    pub id: Uuid,
+    pub brainname: String,
+    pub role: Role,
+    pub birthdate: DateTime<Local>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ExportedBrainfart {
+    pub id: Uuid,
+    pub title: String,
+    pub content: String,
+    pub birthdate: DateTime<Local>,
+    pub hidden: bool,
+    /// Set while the brainfart is scheduled.
+    pub publish_at: Option<DateTime<Local>>,
+    /// The brainfart this one rebrainfarts or quotes.
+    pub original_id: Option<Uuid>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ExportedReaction {
+    pub brainfart_id: Uuid,
+    pub kind: ReactionKind,
+    pub reactiondate: DateTime<Local>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ExportedView {
+    pub brainfart_id: Uuid,
+    pub viewdate: DateTime<Local>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LeaderboardBrainfart {
+    pub brainfart_id: Uuid,
+    pub title: String,
+    pub mastermind_name: String,
+    pub explosions: i64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LeaderboardMastermind {
+    pub brainname: String,
+    /// Explosions caused by all of the mastermind's brainfarts together.
+    pub explosions: i64,
+}
+
+/// The brainfarts and masterminds with the most explosions in the window, most first.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Leaderboard {
+    pub window: LeaderboardWindow,
+    pub brainfarts: Vec<LeaderboardBrainfart>,
+    pub masterminds: Vec<LeaderboardMastermind>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct BookmarkRequest {
+    pub brainfart_id: Uuid,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct CreateBrainfartRequest {
     title: String,
     content: String,
+    /// When set the brainfart stays out of the feed until this moment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    publish_at: Option<DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    poll: Option<PollRequest>,
 }
 
 impl CreateBrainfartRequest {
@@ -136,14 +543,151 @@ impl CreateBrainfartRequest {
     pub fn get_content(&self) -> &str {
         &self.content
     }
+    pub fn get_publish_at(&self) -> Option<DateTime<Local>> {
+        self.publish_at
+    }
+    pub fn get_poll(&self) -> Option<&PollRequest> {
+        self.poll.as_ref()
+    }
 
     pub fn validate(title: &str, innehall: &str) -> bool {
         !title.is_empty() && !innehall.is_empty()
     }
 
+    /// A brainfart can only be scheduled for later, never for the past.
+    pub fn validate_publish_at(publish_at: Option<DateTime<Local>>, now: DateTime<Local>) -> bool {
+        !matches!(publish_at, Some(publish_at) if publish_at <= now)
+    }
+
     pub fn create(title: String, content: String) -> CreateBrainfartRequest {
-        CreateBrainfartRequest { title, content }
+        CreateBrainfartRequest {
+            title,
+            content,
+            publish_at: None,
+            poll: None,
+        }
+    }
+
+    pub fn create_scheduled(
+        title: String,
+        content: String,
+        publish_at: Option<DateTime<Local>>,
+    ) -> CreateBrainfartRequest {
+        CreateBrainfartRequest {
+            title,
+            content,
+            publish_at,
+            poll: None,
+        }
+    }
+
+    pub fn with_poll(self, poll: PollRequest) -> CreateBrainfartRequest {
+        CreateBrainfartRequest {
+            poll: Some(poll),
+            ..self
+        }
+    }
+}
+
+pub const MAX_POLL_OPTIONS: usize = 10;
+pub const MAX_POLL_OPTION_CHARACTERS: usize = 100;
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PollRequest {
+    pub options: Vec<String>,
+    /// Votes are taken until this moment. Without it the poll stays open.
+    #[serde(default)]
+    pub closes_at: Option<DateTime<Local>>,
+    /// Whether a brain can vote for more than one option.
+    #[serde(default)]
+    pub multiple_choice: bool,
+}
+
+impl PollRequest {
+    /// Between two and `MAX_POLL_OPTIONS` different, non-empty options, and a closing time after
+    /// `opens_at`, which is when the brainfart is published.
+    pub fn validate(&self, opens_at: DateTime<Local>) -> bool {
+        (2..=MAX_POLL_OPTIONS).contains(&self.options.len())
+            && self.options.iter().enumerate().all(|(index, option)| {
+                !option.trim().is_empty()
+                    && option.chars().count() <= MAX_POLL_OPTION_CHARACTERS
+                    && !self.options[..index]
+                        .iter()
+                        .any(|other| other.trim() == option.trim())
+            })
+            && !matches!(self.closes_at, Some(closes_at) if closes_at <= opens_at)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PollOptionResult {
+    pub text: String,
+    pub votes: i64,
+    pub voted_by_me: bool,
+}
+
+/// A poll with the votes so far. The options are in the order they were given.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PollInformation {
+    pub options: Vec<PollOptionResult>,
+    pub closes_at: Option<DateTime<Local>>,
+    pub multiple_choice: bool,
+    /// How many brains have voted.
+    pub voters: i64,
+}
+
+impl PollInformation {
+    pub fn is_closed(&self, now: DateTime<Local>) -> bool {
+        matches!(self.closes_at, Some(closes_at) if closes_at <= now)
     }
+
+    pub fn has_voted(&self) -> bool {
+        self.options.iter().any(|option| option.voted_by_me)
+    }
+}
+
+/// Shares another brainfart. With commentary it becomes a quote, otherwise a plain
+/// rebrainfart, which a brain can only make once for each brainfart.
+#[derive(Deserialize, Serialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RebrainfartRequest {
+    /// Left out when the path of the request names it.
+    #[serde(default)]
+    pub brainfart_id: Uuid,
+    #[serde(default)]
+    pub commentary: String,
+}
+
+/// Votes for the options at the given positions. A single choice poll takes exactly one.
+#[derive(Deserialize, Serialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct VoteRequest {
+    /// Left out when the path of the request names it.
+    #[serde(default)]
+    pub brainfart_id: Uuid,
+    pub options: Vec<usize>,
+}
+
+/// What a brain was writing when it left the editor. Every brain has at most one draft.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct BrainfartDraft {
+    pub title: String,
+    pub content: String,
+    pub publish_at: Option<DateTime<Local>>,
+    pub lastupdatedate: DateTime<Local>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SaveDraftRequest {
+    pub title: String,
+    pub content: String,
+    #[serde(default)]
+    pub publish_at: Option<DateTime<Local>>,
 }
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Brain {
@@ -182,6 +726,7 @@ impl Brain {
 }
 
 #[derive(Deserialize, Serialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct RegisterBrainRequest {
     brainname: String,
     password: String,
@@ -213,11 +758,15 @@ impl RegisterBrainRequest {
     }
 
     pub fn validate(brainname: &str, password: &str, password_extra: &str) -> bool {
-        !brainname.is_empty() && !password.is_empty() && password == password_extra
+        !brainname.is_empty()
+            && brainname != REMOVED_BRAIN_NAME
+            && !password.is_empty()
+            && password == password_extra
     }
 }
 
 #[derive(Deserialize, Serialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct ProveOwnsBrainRequest {
     brainname: String,
     password: String,
@@ -242,8 +791,342 @@ impl ProveOwnsBrainRequest {
     }
 }
 
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    #[default]
+    Brain,
+    Admin,
+}
+
+impl Role {
+    pub fn permissions(&self) -> &'static [Permission] {
+        match self {
+            Role::Brain => &[],
+            Role::Admin => &[
+                Permission::ReviewReports,
+                Permission::ModerateBrainfarts,
+                Permission::SuspendBrains,
+            ],
+        }
+    }
+}
+
+impl Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Role::Brain => write!(f, "brain"),
+            Role::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "brain" => Ok(Role::Brain),
+            "admin" => Ok(Role::Admin),
+            _ => Err(format!("{} is not a role, expected brain or admin", s)),
+        }
+    }
+}
+
+/// Something only some roles may do. Which roles may do what is decided by `Role::permissions`.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    ReviewReports,
+    ModerateBrainfarts,
+    SuspendBrains,
+}
+
+impl Display for Permission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Permission::ReviewReports => write!(f, "review reports"),
+            Permission::ModerateBrainfarts => write!(f, "moderate brainfarts"),
+            Permission::SuspendBrains => write!(f, "suspend brains"),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct JwtInformation {
     pub brainname: String,
     pub id: Uuid,
+    /// Tokens issued before roles existed carry no role and are treated as ordinary brains.
+    #[serde(default)]
+    pub role: Role,
+}
+
+impl JwtInformation {
+    pub fn is_admin(&self) -> bool {
+        self.role == Role::Admin
+    }
+
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        self.role.permissions().contains(&permission)
+    }
+}
+
+/// How a brain keeps another brain at a distance. Muted brains' brainfarts are left out of the
+/// feed, blocked brains may not react to your brainfarts.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum BrainRelation {
+    Mute,
+    Block,
+}
+
+impl Display for BrainRelation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BrainRelation::Mute => write!(f, "mute"),
+            BrainRelation::Block => write!(f, "block"),
+        }
+    }
+}
+
+impl FromStr for BrainRelation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mute" => Ok(BrainRelation::Mute),
+            "block" => Ok(BrainRelation::Block),
+            _ => Err(format!("{} is not a relation, expected mute or block", s)),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct BrainRelationRequest {
+    pub brainname: String,
+    pub relation: BrainRelation,
+}
+
+impl BrainRelationRequest {
+    pub fn validate(brainname: &str) -> bool {
+        !brainname.is_empty()
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct BrainRelationInformation {
+    pub brainname: String,
+    pub relation: BrainRelation,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ReportBrainfartRequest {
+    /// Left out when the path of the request names it.
+    #[serde(default)]
+    pub brainfart_id: Uuid,
+    pub reason: String,
+}
+
+impl ReportBrainfartRequest {
+    pub fn validate(reason: &str) -> bool {
+        !reason.trim().is_empty()
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ModerateBrainfartRequest {
+    pub brainfart_id: Uuid,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ModerateBrainRequest {
+    pub brainname: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SetRoleRequest {
+    /// Left out when the path of the request names it.
+    #[serde(default)]
+    pub brainname: String,
+    pub role: Role,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ReportInformation {
+    pub reporter_name: String,
+    pub reason: String,
+    pub reportdate: DateTime<Local>,
+}
+
+/// A brainfart waiting for an admin to look at it, together with the reports against it.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ReportedBrainfart {
+    pub brainfart: BrainfartInformation,
+    pub hidden: bool,
+    pub reports: Vec<ReportInformation>,
+}
+
+/// What a webhook can be told about.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    /// A brainfart joined the feed, right away or once its scheduled time came.
+    BrainfartCreated,
+    /// A brain blew its mind over a brainfart, or imploded.
+    BrainfartReacted,
+}
+
+impl Display for WebhookEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WebhookEvent::BrainfartCreated => write!(f, "brainfart_created"),
+            WebhookEvent::BrainfartReacted => write!(f, "brainfart_reacted"),
+        }
+    }
+}
+
+impl FromStr for WebhookEvent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "brainfart_created" => Ok(WebhookEvent::BrainfartCreated),
+            "brainfart_reacted" => Ok(WebhookEvent::BrainfartReacted),
+            _ => Err(format!(
+                "{} is not an event, expected brainfart_created or brainfart_reacted",
+                s
+            )),
+        }
+    }
+}
+
+pub const MAX_WEBHOOKS_PER_BRAIN: usize = 10;
+pub const MIN_WEBHOOK_SECRET_CHARACTERS: usize = 16;
+pub const MAX_WEBHOOK_URL_CHARACTERS: usize = 2048;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreateWebhookRequest {
+    /// Where the events are posted, over http or https.
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    /// Signs every delivery. It is never shown again.
+    pub secret: String,
+    /// A global webhook hears about every brainfart, not only those of its brain. Only admins
+    /// can create one.
+    #[serde(default)]
+    pub global: bool,
+}
+
+impl CreateWebhookRequest {
+    pub fn validate(&self) -> bool {
+        (self.url.starts_with("http://") || self.url.starts_with("https://"))
+            && self.url.chars().count() <= MAX_WEBHOOK_URL_CHARACTERS
+            && !self.events.is_empty()
+            && self.secret.chars().count() >= MIN_WEBHOOK_SECRET_CHARACTERS
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct WebhookInformation {
+    pub id: Uuid,
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    pub global: bool,
+    pub createdate: DateTime<Local>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    /// Waiting for its first attempt or for a retry.
+    Pending,
+    Delivered,
+    /// Every attempt failed, there will be no more.
+    Failed,
+}
+
+impl Display for DeliveryStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeliveryStatus::Pending => write!(f, "pending"),
+            DeliveryStatus::Delivered => write!(f, "delivered"),
+            DeliveryStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+impl FromStr for DeliveryStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(DeliveryStatus::Pending),
+            "delivered" => Ok(DeliveryStatus::Delivered),
+            "failed" => Ok(DeliveryStatus::Failed),
+            _ => Err(format!("{} is not a delivery status", s)),
+        }
+    }
+}
+
+/// One event sent, or being sent, to a webhook.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct WebhookDeliveryInformation {
+    pub id: Uuid,
+    pub event: WebhookEvent,
+    pub status: DeliveryStatus,
+    pub attempts: i32,
+    /// The status the receiver answered the last attempt with, if it answered.
+    pub response_status: Option<i32>,
+    /// Why the last attempt failed.
+    pub error: Option<String>,
+    pub createdate: DateTime<Local>,
+    pub last_attempt_at: Option<DateTime<Local>>,
+    /// When the next attempt is due, while the delivery is pending.
+    pub next_attempt_at: Option<DateTime<Local>>,
+}
+
+/// The brainfart an event is about, as a webhook receives it.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct WebhookBrainfart {
+    pub id: Uuid,
+    pub title: String,
+    pub content: String,
+    pub birthdate: DateTime<Local>,
+    pub mastermind_name: String,
+    /// Set for rebrainfarts and quotes.
+    pub original_id: Option<Uuid>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct WebhookReaction {
+    pub kind: ReactionKind,
+    pub brainname: String,
+}
+
+/// The body of every delivery. It is signed with the secret of the webhook, see the
+/// `X-Brains-Signature` header.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct WebhookPayload {
+    pub event: WebhookEvent,
+    pub occurred_at: DateTime<Local>,
+    pub brainfart: WebhookBrainfart,
+    /// Set for `brainfart_reacted`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reaction: Option<WebhookReaction>,
 }
@@ -0,0 +1,136 @@
+//! The paths of the versioned API, shared so the server and the frontend cannot drift apart.
+//!
+//! Paths with parameters are written the way the server's router expects them, e.g.
+//! `/api/v1/brainfarts/:brainfart_id/reactions`. Use [`path`] to fill in the parameters.
+
+pub const API_V1: &str = "/api/v1";
+
+/// `POST` registers a brain.
+pub const BRAINS: &str = "/api/v1/brains";
+/// `POST` logs in as a brain.
+pub const SESSIONS: &str = "/api/v1/sessions";
+
+/// `GET` the feed, filtered by the query string, e.g. `?mode=bookmarks&order=hot`. `POST`
+/// creates a brainfart.
+pub const BRAINFARTS: &str = "/api/v1/brainfarts";
+/// `GET` one brainfart, which counts as viewing it.
+pub const BRAINFART: &str = "/api/v1/brainfarts/:brainfart_id";
+/// `POST` an explosion or implosion.
+pub const BRAINFART_REACTIONS: &str = "/api/v1/brainfarts/:brainfart_id/reactions";
+/// `PUT` bookmarks the brainfart, `DELETE` removes the bookmark.
+pub const BRAINFART_BOOKMARK: &str = "/api/v1/brainfarts/:brainfart_id/bookmark";
+pub const BRAINFART_REPORTS: &str = "/api/v1/brainfarts/:brainfart_id/reports";
+pub const BRAINFART_VOTES: &str = "/api/v1/brainfarts/:brainfart_id/votes";
+pub const BRAINFART_REBRAINFARTS: &str = "/api/v1/brainfarts/:brainfart_id/rebrainfarts";
+
+/// `GET` the brains you have muted or blocked, `POST` mutes or blocks one more.
+pub const RELATIONS: &str = "/api/v1/relations";
+/// `DELETE` unmutes or unblocks the brain.
+pub const RELATION: &str = "/api/v1/relations/:relation/:brainname";
+
+/// `POST` uploads files as multipart form data.
+pub const ATTACHMENTS: &str = "/api/v1/attachments";
+pub const ATTACHMENT: &str = "/api/v1/attachments/:attachment_id";
+pub const ATTACHMENT_THUMBNAIL: &str = "/api/v1/attachments/:attachment_id/thumbnail";
+
+/// `GET` with `?window=week|month|all`.
+pub const LEADERBOARD: &str = "/api/v1/leaderboard";
+
+/// `GET`, `PUT` and `DELETE` your draft.
+pub const DRAFT: &str = "/api/v1/draft";
+
+/// `GET` everything you have made, as an archive an admin can import into another instance.
+pub const EXPORT: &str = "/api/v1/export";
+/// `GET` your pending deletion, `PUT` asks for the brain to be deleted, `DELETE` calls it off.
+pub const ACCOUNT_DELETION: &str = "/api/v1/account/deletion";
+
+/// `GET` lists your webhooks, `POST` adds one.
+pub const WEBHOOKS: &str = "/api/v1/webhooks";
+/// `DELETE` removes the webhook.
+pub const WEBHOOK: &str = "/api/v1/webhooks/:webhook_id";
+/// `GET` the latest deliveries to the webhook.
+pub const WEBHOOK_DELIVERIES: &str = "/api/v1/webhooks/:webhook_id/deliveries";
+
+/// `GET` the feed as Atom or RSS, with `?format=atom|rss`.
+pub const FEED: &str = "/api/v1/feeds/brainfarts";
+/// `GET` the feed of one brain's brainfarts.
+pub const BRAIN_FEED: &str = "/api/v1/feeds/brains/:brainname";
+/// `GET` the feed of the brainfarts with a `#tag`.
+pub const TAG_FEED: &str = "/api/v1/feeds/tags/:tag";
+/// `POST` creates a token for reading the feeds with `?token=`, `DELETE` revokes them all.
+pub const FEED_TOKENS: &str = "/api/v1/feeds/tokens";
+
+pub const ADMIN_REPORTS: &str = "/api/v1/admin/reports";
+/// `PUT` hides the brainfart, `DELETE` restores it.
+pub const ADMIN_HIDDEN_BRAINFART: &str = "/api/v1/admin/brainfarts/:brainfart_id/hidden";
+/// `PUT` suspends the brain, `DELETE` lifts the suspension.
+pub const ADMIN_BRAIN_SUSPENSION: &str = "/api/v1/admin/brains/:brainname/suspension";
+pub const ADMIN_BRAIN_ROLE: &str = "/api/v1/admin/brains/:brainname/role";
+
+/// The OpenAPI document of everything above.
+pub const OPENAPI: &str = "/api/v1/openapi.json";
+/// A page to read and try the API in.
+pub const DOCS: &str = "/api/v1/docs";
+/// The scripts and styles of the docs page.
+pub const DOCS_FILE: &str = "/api/v1/docs/:file";
+
+/// Every route of the API, for checking that the OpenAPI document describes them all.
+pub const ALL: &[&str] = &[
+    BRAINS,
+    SESSIONS,
+    BRAINFARTS,
+    BRAINFART,
+    BRAINFART_REACTIONS,
+    BRAINFART_BOOKMARK,
+    BRAINFART_REPORTS,
+    BRAINFART_VOTES,
+    BRAINFART_REBRAINFARTS,
+    RELATIONS,
+    RELATION,
+    ATTACHMENTS,
+    ATTACHMENT,
+    ATTACHMENT_THUMBNAIL,
+    LEADERBOARD,
+    DRAFT,
+    EXPORT,
+    ACCOUNT_DELETION,
+    WEBHOOKS,
+    WEBHOOK,
+    WEBHOOK_DELIVERIES,
+    FEED,
+    BRAIN_FEED,
+    TAG_FEED,
+    FEED_TOKENS,
+    ADMIN_REPORTS,
+    ADMIN_HIDDEN_BRAINFART,
+    ADMIN_BRAIN_SUSPENSION,
+    ADMIN_BRAIN_ROLE,
+];
+
+/// Fills in the `:parameters` of the route in order, percent-encoding each value.
+///
+/// `path(BRAINFART_VOTES, &["42"])` gives `/api/v1/brainfarts/42/votes`.
+pub fn path(route: &str, values: &[&str]) -> String {
+    let mut values = values.iter();
+    route
+        .split('/')
+        .map(|segment| match segment.strip_prefix(':') {
+            Some(_) => encode(values.next().copied().unwrap_or_default()),
+            None => segment.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Percent-encodes a parameter of a path or a value of a query string.
+pub fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}